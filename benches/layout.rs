@@ -163,6 +163,34 @@ fn create_scaling_igr(node_count: usize, layout: &str) -> IntermediateGraph {
     IntermediateGraph::from_ast(ast).expect("Failed to create IGR")
 }
 
+/// A layered DAG of `layers * width` nodes, each feeding two nodes of the
+/// next layer, with no back edges
+fn create_layered_dag_igr(layers: usize, width: usize) -> IntermediateGraph {
+    let mut edsl = String::from("---\nlayout: dagre\n---\n\n");
+
+    for layer in 0..layers {
+        for i in 0..width {
+            edsl.push_str(&format!("n{layer}_{i}[Node {layer}.{i}]\n"));
+        }
+    }
+
+    edsl.push('\n');
+
+    for layer in 0..layers - 1 {
+        for i in 0..width {
+            edsl.push_str(&format!("n{layer}_{i} -> n{}_{}\n", layer + 1, i));
+            edsl.push_str(&format!(
+                "n{layer}_{i} -> n{}_{}\n",
+                layer + 1,
+                (i * 7 + 3) % width
+            ));
+        }
+    }
+
+    let ast = parse_edsl(&edsl).expect("Failed to parse layered DAG EDSL");
+    IntermediateGraph::from_ast(ast).expect("Failed to create IGR")
+}
+
 fn bench_layout_dagre_simple(c: &mut Criterion) {
     c.bench_function("layout_dagre_simple", |b| {
         b.iter(|| {
@@ -193,6 +221,22 @@ fn bench_layout_dagre_large(c: &mut Criterion) {
     });
 }
 
+fn bench_layout_dagre_large_dag(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout_dagre_large_dag");
+    group.sample_size(10);
+
+    // Just past dagre's 500 node threshold for fewer crossing passes
+    group.bench_function("nodes_520", |b| {
+        b.iter(|| {
+            let mut igr = create_layered_dag_igr(26, 20);
+            let manager = LayoutManager::new();
+            black_box(manager.layout(&mut igr))
+        })
+    });
+
+    group.finish();
+}
+
 fn bench_layout_force_simple(c: &mut Criterion) {
     c.bench_function("layout_force_simple", |b| {
         b.iter(|| {
@@ -289,6 +333,7 @@ criterion_group!(
     bench_layout_dagre_simple,
     bench_layout_dagre_medium,
     bench_layout_dagre_large,
+    bench_layout_dagre_large_dag,
     bench_layout_force_simple,
     bench_layout_force_medium,
     bench_layout_force_large,
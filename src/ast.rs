@@ -1,6 +1,6 @@
 // src/ast.rs
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::str::FromStr;
 
@@ -11,6 +11,12 @@ pub const MAX_SKETCHINESS: u8 = 4;
 pub const MIN_STROKE_WIDTH: f64 = 0.1;
 pub const MAX_STROKE_WIDTH: f64 = 20.0;
 
+/// Upper bound on force/stress simulation iterations; larger values are capped
+pub const MAX_LAYOUT_ITERATIONS: usize = 10_000;
+
+/// Stroke width of edges drawn with the `==>` token
+pub const THICK_ARROW_WIDTH: f64 = 4.0;
+
 /// Supported theme values
 pub const VALID_THEMES: &[&str] = &["light", "dark"];
 
@@ -20,6 +26,18 @@ pub const VALID_LAYOUTS: &[&str] = &["dagre", "force", "manual", "elk"];
 /// Supported font families
 pub const VALID_FONTS: &[&str] = &["Virgil", "Helvetica", "Cascadia"];
 
+/// Node shapes the generator can draw
+pub const VALID_SHAPES: &[&str] = &[
+    "rectangle",
+    "ellipse",
+    "diamond",
+    "cylinder",
+    "text",
+    "triangle",
+    "parallelogram",
+    "hexagon",
+];
+
 /// Global configuration settings for the EDSL document
 ///
 /// Controls overall rendering and layout behavior. All fields are optional
@@ -50,6 +68,64 @@ pub struct GlobalConfig {
     pub stroke_width: Option<f64>,
     /// Background color for the document
     pub background_color: Option<String>,
+    /// Default edge routing ("straight", "orthogonal", "curved", "auto")
+    pub routing: Option<RoutingType>,
+    /// Pull sparsely connected dagre layers closer together
+    pub compact_layers: Option<bool>,
+    /// After a force layout, swap nodes within components while that lowers
+    /// the number of edge crossings
+    pub reduce_crossings: Option<bool>,
+    /// Uniform zoom applied to all positions and sizes after layout (> 0)
+    pub scale: Option<f64>,
+    /// Draw the translucent container/group rectangles (default true); when
+    /// false, members are still grouped natively through `groupIds`
+    pub show_container_background: Option<bool>,
+    /// Derive element ids from DSL ids instead of random UUIDs, so
+    /// recompiling the same diagram yields the same ids
+    pub stable_ids: Option<bool>,
+    /// Pick white or black label text from the luminance of a node's
+    /// explicit background when it has no explicit text color
+    pub auto_contrast: Option<bool>,
+    /// Narrowest width, in pixels, an auto-sized node is given
+    pub min_node_width: Option<f64>,
+    /// Widest an auto-sized node grows; longer labels wrap onto more lines
+    pub max_node_width: Option<f64>,
+    /// Simulation steps for the force engine and ELK's force algorithm
+    /// (1-10000); fewer for quick previews, more for final renders
+    pub force_iterations: Option<usize>,
+    /// Simulation steps for ELK's stress algorithm (1-10000)
+    pub stress_iterations: Option<usize>,
+    /// Initial placement for the force engine ("circle" or "scc"); `scc`
+    /// starts each strongly connected component around its own center
+    pub seed: Option<ForceSeed>,
+    /// Add a legend of the semantic group types used, beside the diagram
+    pub legend: Option<bool>,
+    /// Prefix edge labels with their position in declaration order
+    /// (`1.`, `2.`, ...), as sequence diagrams number their messages
+    pub number_messages: Option<bool>,
+    /// Seed for random element ids and Excalidraw's per-element `seed` and
+    /// `versionNonce`; equal seeds give byte-identical output
+    pub random_seed: Option<u64>,
+    /// Shape of nodes that don't set one, instead of `rectangle`
+    pub default_shape: Option<String>,
+    /// Stroke width of edges that don't set one (0.1-20.0); thick `==>`
+    /// edges draw at twice this width
+    pub edge_width: Option<f64>,
+    /// `version` written to the Excalidraw file, instead of 2
+    pub file_version: Option<u32>,
+    /// `source` written to the Excalidraw file, instead of
+    /// `https://excalidraw-dsl.com`
+    pub file_source: Option<String>,
+    /// Fixed frame, written `1920x1080`, the finished diagram is centered
+    /// in; larger diagrams are scaled down to fit
+    pub canvas: Option<CanvasSize>,
+    /// Fill of a rectangle drawn behind the diagram covering the whole
+    /// `canvas`; without it no rectangle is drawn
+    pub canvas_background: Option<String>,
+    /// Front matter keys this version doesn't know, kept rather than
+    /// rejected so newer documents still compile
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 impl GlobalConfig {
@@ -121,10 +197,89 @@ impl GlobalConfig {
             }
         }
 
+        // Validate scale
+        if let Some(scale) = self.scale {
+            validate_scale(scale)?;
+        }
+
+        validate_shape("default_shape", self.default_shape.as_deref())?;
+        if let Some(width) = self.edge_width {
+            validate_edge_width(width)?;
+        }
+
+        // Validate node width clamps
+        validate_node_width("min_node_width", self.min_node_width)?;
+        validate_node_width("max_node_width", self.max_node_width)?;
+
+        // Validate simulation iteration counts
+        if let Some(iterations) = self.force_iterations {
+            validate_iterations("force_iterations", iterations)?;
+        }
+        if let Some(iterations) = self.stress_iterations {
+            validate_iterations("stress_iterations", iterations)?;
+        }
+
         Ok(())
     }
 }
 
+/// Check that a diagram scale factor is a positive, finite number
+pub fn validate_scale(scale: f64) -> crate::error::Result<()> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(crate::error::EDSLError::Validation {
+            message: format!("Scale must be greater than 0, got {scale}"),
+        });
+    }
+    Ok(())
+}
+
+/// Check that an optional shape name is one of `VALID_SHAPES`
+pub fn validate_shape(name: &str, shape: Option<&str>) -> crate::error::Result<()> {
+    match shape {
+        Some(shape) if !VALID_SHAPES.contains(&shape) => Err(crate::error::EDSLError::Validation {
+            message: format!(
+                "Invalid {name} '{shape}', must be one of: {}",
+                VALID_SHAPES.join(", ")
+            ),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check that a default edge width is within the stroke width range
+pub fn validate_edge_width(width: f64) -> crate::error::Result<()> {
+    if !(MIN_STROKE_WIDTH..=MAX_STROKE_WIDTH).contains(&width) {
+        return Err(crate::error::EDSLError::Validation {
+            message: format!(
+                "edge_width must be between {MIN_STROKE_WIDTH}-{MAX_STROKE_WIDTH}, got {width}"
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Check that an optional node width clamp is a positive, finite number
+pub fn validate_node_width(name: &str, width: Option<f64>) -> crate::error::Result<()> {
+    match width {
+        Some(width) if !(width.is_finite() && width > 0.0) => {
+            Err(crate::error::EDSLError::Validation {
+                message: format!("{name} must be greater than 0, got {width}"),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that a simulation iteration count is within 1-`MAX_LAYOUT_ITERATIONS`
+pub fn validate_iterations(name: &str, iterations: usize) -> crate::error::Result<()> {
+    if !(1..=MAX_LAYOUT_ITERATIONS).contains(&iterations) {
+        return Err(crate::error::EDSLError::Validation {
+            message: format!("{name} must be between 1-{MAX_LAYOUT_ITERATIONS}, got {iterations}"),
+        });
+    }
+    Ok(())
+}
+
 /// Builder for creating GlobalConfig instances
 #[derive(Debug, Default)]
 pub struct GlobalConfigBuilder {
@@ -134,6 +289,27 @@ pub struct GlobalConfigBuilder {
     sketchiness: Option<u8>,
     stroke_width: Option<f64>,
     background_color: Option<String>,
+    routing: Option<RoutingType>,
+    compact_layers: Option<bool>,
+    reduce_crossings: Option<bool>,
+    scale: Option<f64>,
+    show_container_background: Option<bool>,
+    stable_ids: Option<bool>,
+    auto_contrast: Option<bool>,
+    legend: Option<bool>,
+    number_messages: Option<bool>,
+    random_seed: Option<u64>,
+    default_shape: Option<String>,
+    edge_width: Option<f64>,
+    file_version: Option<u32>,
+    file_source: Option<String>,
+    canvas: Option<CanvasSize>,
+    canvas_background: Option<String>,
+    min_node_width: Option<f64>,
+    max_node_width: Option<f64>,
+    force_iterations: Option<usize>,
+    stress_iterations: Option<usize>,
+    seed: Option<ForceSeed>,
 }
 
 impl GlobalConfigBuilder {
@@ -185,6 +361,127 @@ impl GlobalConfigBuilder {
         self
     }
 
+    pub fn routing(mut self, routing: RoutingType) -> Self {
+        self.routing = Some(routing);
+        self
+    }
+
+    pub fn compact_layers(mut self, compact: bool) -> Self {
+        self.compact_layers = Some(compact);
+        self
+    }
+
+    /// Untangle force layouts by swapping nodes that cross fewer edges
+    pub fn reduce_crossings(mut self, enabled: bool) -> Self {
+        self.reduce_crossings = Some(enabled);
+        self
+    }
+
+    pub fn scale(mut self, scale: f64) -> crate::error::Result<Self> {
+        validate_scale(scale)?;
+        self.scale = Some(scale);
+        Ok(self)
+    }
+
+    pub fn show_container_background(mut self, show: bool) -> Self {
+        self.show_container_background = Some(show);
+        self
+    }
+
+    pub fn stable_ids(mut self, enabled: bool) -> Self {
+        self.stable_ids = Some(enabled);
+        self
+    }
+
+    pub fn auto_contrast(mut self, enabled: bool) -> Self {
+        self.auto_contrast = Some(enabled);
+        self
+    }
+
+    /// Add a legend explaining the semantic group colors
+    pub fn legend(mut self, enabled: bool) -> Self {
+        self.legend = Some(enabled);
+        self
+    }
+
+    /// Number edge labels in declaration order
+    pub fn number_messages(mut self, enabled: bool) -> Self {
+        self.number_messages = Some(enabled);
+        self
+    }
+
+    /// Make element ids and Excalidraw's randomness reproducible
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    pub fn default_shape(mut self, shape: &str) -> crate::error::Result<Self> {
+        validate_shape("default_shape", Some(shape))?;
+        self.default_shape = Some(shape.to_string());
+        Ok(self)
+    }
+
+    /// Default stroke width for edges without an explicit width
+    pub fn edge_width(mut self, width: f64) -> crate::error::Result<Self> {
+        validate_edge_width(width)?;
+        self.edge_width = Some(width);
+        Ok(self)
+    }
+
+    /// `version` recorded in generated Excalidraw files
+    pub fn file_version(mut self, version: u32) -> Self {
+        self.file_version = Some(version);
+        self
+    }
+
+    /// `source` recorded in generated Excalidraw files
+    pub fn file_source(mut self, source: impl Into<String>) -> Self {
+        self.file_source = Some(source.into());
+        self
+    }
+
+    /// Center the diagram in a `width` x `height` frame
+    pub fn canvas(mut self, width: f64, height: f64) -> crate::error::Result<Self> {
+        self.canvas = Some(CanvasSize::new(width, height)?);
+        Ok(self)
+    }
+
+    /// Draw a rectangle of this color covering the canvas
+    pub fn canvas_background<S: Into<String>>(mut self, color: S) -> Self {
+        self.canvas_background = Some(color.into());
+        self
+    }
+
+    pub fn min_node_width(mut self, width: f64) -> crate::error::Result<Self> {
+        validate_node_width("min_node_width", Some(width))?;
+        self.min_node_width = Some(width);
+        Ok(self)
+    }
+
+    pub fn max_node_width(mut self, width: f64) -> crate::error::Result<Self> {
+        validate_node_width("max_node_width", Some(width))?;
+        self.max_node_width = Some(width);
+        Ok(self)
+    }
+
+    pub fn force_iterations(mut self, iterations: usize) -> crate::error::Result<Self> {
+        validate_iterations("force_iterations", iterations)?;
+        self.force_iterations = Some(iterations);
+        Ok(self)
+    }
+
+    pub fn stress_iterations(mut self, iterations: usize) -> crate::error::Result<Self> {
+        validate_iterations("stress_iterations", iterations)?;
+        self.stress_iterations = Some(iterations);
+        Ok(self)
+    }
+
+    pub fn seed(mut self, seed: ForceSeed) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     pub fn build(self) -> GlobalConfig {
         GlobalConfig {
             theme: self.theme,
@@ -193,6 +490,28 @@ impl GlobalConfigBuilder {
             sketchiness: self.sketchiness,
             stroke_width: self.stroke_width,
             background_color: self.background_color,
+            routing: self.routing,
+            compact_layers: self.compact_layers,
+            reduce_crossings: self.reduce_crossings,
+            scale: self.scale,
+            show_container_background: self.show_container_background,
+            stable_ids: self.stable_ids,
+            auto_contrast: self.auto_contrast,
+            min_node_width: self.min_node_width,
+            max_node_width: self.max_node_width,
+            force_iterations: self.force_iterations,
+            stress_iterations: self.stress_iterations,
+            seed: self.seed,
+            legend: self.legend,
+            number_messages: self.number_messages,
+            random_seed: self.random_seed,
+            default_shape: self.default_shape,
+            edge_width: self.edge_width,
+            file_version: self.file_version,
+            file_source: self.file_source,
+            canvas: self.canvas,
+            canvas_background: self.canvas_background,
+            extra: BTreeMap::new(),
         }
     }
 }
@@ -201,6 +520,7 @@ impl GlobalConfigBuilder {
 pub struct ParsedDocument {
     pub config: GlobalConfig,
     pub component_types: HashMap<String, ComponentTypeDefinition>,
+    pub edge_types: HashMap<String, EdgeTypeDefinition>,
     pub templates: HashMap<String, TemplateDefinition>,
     pub diagram: Option<DiagramDefinition>,
     pub nodes: Vec<NodeDefinition>,
@@ -208,6 +528,40 @@ pub struct ParsedDocument {
     pub containers: Vec<ContainerDefinition>,
     pub groups: Vec<GroupDefinition>,
     pub connections: Vec<ConnectionDefinition>,
+    pub notes: Vec<NoteDefinition>,
+    pub lines: Vec<LineDefinition>,
+    pub hyperedges: Vec<HyperedgeDefinition>,
+}
+
+/// A `#` comment kept by `parse_edsl_lossless`, with what it annotates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// 1-based line of the `#`
+    pub line: usize,
+    /// 1-based column of the `#`, in characters
+    pub column: usize,
+    /// Everything after the `#`, without trailing whitespace
+    pub text: String,
+    /// Code precedes the comment on its line; otherwise it sits on its own
+    /// line before `owner`
+    pub trailing: bool,
+    /// Statement the comment belongs to; `None` past the last statement
+    pub owner: Option<CommentOwner>,
+    /// Style attribute of `owner` the comment belongs to
+    pub attribute: Option<String>,
+}
+
+/// Statement a comment is attached to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentOwner {
+    Node(String),
+    /// The first edge a statement declares
+    Edge {
+        source: String,
+        target: String,
+    },
+    /// A container by its `as` id, else its label
+    Container(String),
 }
 
 #[derive(Debug, Clone)]
@@ -217,6 +571,72 @@ pub struct ComponentTypeDefinition {
     pub style: StyleDefinition,
 }
 
+/// Named edge style declared with `edgetype Name { ... }`
+///
+/// Attributes are stored under their edge attribute names (`strokeColor`,
+/// `strokeWidth`, `strokeStyle`) and merged into every edge that references
+/// the type with `: @Name`.
+#[derive(Debug, Clone)]
+pub struct EdgeTypeDefinition {
+    pub name: String,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+/// Conditional node style from a `rules { match ... }` block
+///
+/// Nodes whose `attribute` compares to `value` under `operator` receive
+/// `attributes`, except those they set explicitly. Nodes without the
+/// attribute never match.
+#[derive(Debug, Clone)]
+pub struct StyleRule {
+    pub attribute: String,
+    pub operator: RuleOperator,
+    pub value: AttributeValue,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+/// Comparison used by a [`StyleRule`]; ordering operators only match numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOperator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl StyleRule {
+    /// Whether a node with these attributes matches the rule
+    pub fn matches(&self, attributes: &HashMap<String, AttributeValue>) -> bool {
+        let Some(actual) = attributes.get(&self.attribute) else {
+            return false;
+        };
+        if let (Some(actual), Some(expected)) = (actual.as_number(), self.value.as_number()) {
+            return match self.operator {
+                RuleOperator::Eq => actual == expected,
+                RuleOperator::Ne => actual != expected,
+                RuleOperator::Lt => actual < expected,
+                RuleOperator::Le => actual <= expected,
+                RuleOperator::Gt => actual > expected,
+                RuleOperator::Ge => actual >= expected,
+            };
+        }
+
+        // Identifiers, strings and colors compare by their text
+        let text = |value: &AttributeValue| match value {
+            AttributeValue::String(s) | AttributeValue::Color(s) => s.clone(),
+            AttributeValue::Number(n) => n.to_string(),
+            AttributeValue::Boolean(b) => b.to_string(),
+        };
+        match self.operator {
+            RuleOperator::Eq => text(actual) == text(&self.value),
+            RuleOperator::Ne => text(actual) != text(&self.value),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StyleDefinition {
     pub fill: Option<String>,
@@ -330,6 +750,34 @@ pub struct NodeDefinition {
     pub attributes: HashMap<String, AttributeValue>,
 }
 
+/// Free-floating annotation placed at absolute canvas coordinates
+#[derive(Debug, Clone)]
+pub struct NoteDefinition {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+/// Standalone line between two absolute canvas points, bound to nothing:
+/// `line from (100, 100) to (400, 100)`
+#[derive(Debug, Clone)]
+pub struct LineDefinition {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+/// Relationship among several nodes, drawn as a junction dot joined to each
+/// member: `bus [a, b, c]`
+#[derive(Debug, Clone)]
+pub struct HyperedgeDefinition {
+    /// Junction node id; generated when omitted
+    pub id: Option<String>,
+    pub members: Vec<String>,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
 #[derive(Debug, Clone)]
 pub struct EdgeDefinition {
     pub from: String,
@@ -338,6 +786,10 @@ pub struct EdgeDefinition {
     pub arrow_type: ArrowType,
     pub attributes: HashMap<String, AttributeValue>,
     pub style: Option<EdgeStyleDefinition>,
+    /// Absolute points the edge is routed through, from a `via` clause
+    pub waypoints: Vec<(f64, f64)>,
+    /// Byte offset in the source where the edge was declared, when parsed
+    pub position: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -368,6 +820,8 @@ impl EdgeChainDefinition {
                 arrow_type: self.arrow_type,
                 attributes: self.attributes.clone(),
                 style: self.style.clone(),
+                waypoints: Vec::new(),
+                position: None,
             });
         }
 
@@ -430,7 +884,14 @@ pub enum Statement {
     Diagram(DiagramDefinition),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Container attribute set by `lane "..." { ... }`, marking a swimlane
+pub const LANE_ATTRIBUTE: &str = "lane";
+
+/// Prefix of style-block keys that come from a nested `meta { ... }` block;
+/// property names can't contain `.`, so these never clash with styling keys
+pub const META_PREFIX: &str = "meta.";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AttributeValue {
     String(String),
     Number(f64),
@@ -513,10 +974,13 @@ impl fmt::Display for AttributeValue {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ArrowType {
-    SingleArrow, // ->
+    SingleArrow, // -> or -->
     Line,        // --
     DoubleArrow, // <->
     WavyArrow,   // ~>
+    DashedArrow, // -.->
+    DottedArrow, // ..>
+    ThickArrow,  // ==>
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -527,7 +991,8 @@ pub enum EdgeType {
     Dotted,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RoutingType {
     Straight,
     Orthogonal,
@@ -535,6 +1000,81 @@ pub enum RoutingType {
     Auto,
 }
 
+/// How the force engine places nodes before simulating
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForceSeed {
+    /// All nodes evenly spaced on one circle
+    Circle,
+    /// Each strongly connected component on its own circle, with the
+    /// components spread around a larger one
+    Scc,
+}
+
+/// Size of the fixed frame set with `canvas: WIDTHxHEIGHT`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CanvasSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl CanvasSize {
+    pub fn new(width: f64, height: f64) -> crate::error::Result<Self> {
+        if !(width.is_finite() && width > 0.0 && height.is_finite() && height > 0.0) {
+            return Err(crate::error::EDSLError::Validation {
+                message: format!("Canvas size must be greater than 0, got {width}x{height}"),
+            });
+        }
+        Ok(CanvasSize { width, height })
+    }
+}
+
+impl FromStr for CanvasSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid canvas size '{s}', expected WIDTHxHEIGHT");
+        let (width, height) = s.split_once(['x', 'X']).ok_or_else(invalid)?;
+        let width = width.trim().parse().map_err(|_| invalid())?;
+        let height = height.trim().parse().map_err(|_| invalid())?;
+        CanvasSize::new(width, height).map_err(|e| e.to_string())
+    }
+}
+
+impl TryFrom<String> for CanvasSize {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CanvasSize> for String {
+    fn from(canvas: CanvasSize) -> Self {
+        format!("{}x{}", canvas.width, canvas.height)
+    }
+}
+
+/// Side of a node box where an edge attaches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Direction a container's contents flow in, set with `direction:`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StrokeStyle {
     Solid,
@@ -547,10 +1087,13 @@ impl FromStr for ArrowType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "->" => Ok(ArrowType::SingleArrow),
+            "->" | "-->" => Ok(ArrowType::SingleArrow),
             "--" => Ok(ArrowType::Line),
             "<->" => Ok(ArrowType::DoubleArrow),
             "~>" => Ok(ArrowType::WavyArrow),
+            "-.->" => Ok(ArrowType::DashedArrow),
+            "..>" => Ok(ArrowType::DottedArrow),
+            "==>" => Ok(ArrowType::ThickArrow),
             _ => Err(()),
         }
     }
@@ -563,6 +1106,83 @@ impl ArrowType {
             ArrowType::Line => "line",
             ArrowType::DoubleArrow => "arrow",
             ArrowType::WavyArrow => "arrow",
+            ArrowType::DashedArrow => "arrow",
+            ArrowType::DottedArrow => "arrow",
+            ArrowType::ThickArrow => "arrow",
+        }
+    }
+
+    /// Stroke style implied by the arrow token, used when no `strokeStyle` is set
+    pub fn default_stroke_style(&self) -> Option<StrokeStyle> {
+        match self {
+            ArrowType::DashedArrow => Some(StrokeStyle::Dashed),
+            ArrowType::DottedArrow => Some(StrokeStyle::Dotted),
+            _ => None,
+        }
+    }
+
+    /// Stroke width implied by the arrow token, used when no width is set
+    pub fn default_stroke_width(&self) -> Option<f64> {
+        match self {
+            ArrowType::ThickArrow => Some(THICK_ARROW_WIDTH),
+            _ => None,
+        }
+    }
+
+    /// Stroke width for an edge without an explicit width, given the
+    /// document's `edge_width` default; thick arrows draw at twice it
+    pub fn edge_stroke_width(&self, edge_width: Option<f64>) -> Option<f64> {
+        match (self, edge_width) {
+            (ArrowType::ThickArrow, Some(width)) => Some(width * 2.0),
+            (_, Some(width)) => Some(width),
+            (_, None) => self.default_stroke_width(),
+        }
+    }
+
+    /// Whether the token draws an arrowhead at the target end by default
+    pub fn has_end_arrowhead(&self) -> bool {
+        !matches!(self, ArrowType::Line | ArrowType::WavyArrow)
+    }
+}
+
+impl FromStr for RoutingType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "straight" => Ok(RoutingType::Straight),
+            "orthogonal" => Ok(RoutingType::Orthogonal),
+            "curved" => Ok(RoutingType::Curved),
+            "auto" => Ok(RoutingType::Auto),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for PortSide {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(PortSide::Top),
+            "bottom" => Ok(PortSide::Bottom),
+            "left" => Ok(PortSide::Left),
+            "right" => Ok(PortSide::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FromStr for FlowDirection {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "right" | "lr" => Ok(FlowDirection::Right),
+            "down" | "tb" => Ok(FlowDirection::Down),
+            "left" | "rl" => Ok(FlowDirection::Left),
+            "up" | "bt" => Ok(FlowDirection::Up),
+            _ => Err(()),
         }
     }
 }
@@ -629,6 +1249,12 @@ pub enum ArrowheadType {
     Triangle,
     Dot,
     Diamond,
+    // Crow's-foot (ER cardinality) markers, drawn as extra line elements
+    One,
+    Many,
+    ZeroOrOne,
+    OneOrMany,
+    ZeroOrMany,
 }
 
 impl FromStr for ArrowheadType {
@@ -640,6 +1266,11 @@ impl FromStr for ArrowheadType {
             "triangle" => Ok(ArrowheadType::Triangle),
             "dot" => Ok(ArrowheadType::Dot),
             "diamond" => Ok(ArrowheadType::Diamond),
+            "one" => Ok(ArrowheadType::One),
+            "many" => Ok(ArrowheadType::Many),
+            "zero-or-one" => Ok(ArrowheadType::ZeroOrOne),
+            "one-or-many" => Ok(ArrowheadType::OneOrMany),
+            "zero-or-many" => Ok(ArrowheadType::ZeroOrMany),
             _ => Err(()),
         }
     }
@@ -652,6 +1283,23 @@ impl ArrowheadType {
             ArrowheadType::Triangle => Some("triangle"),
             ArrowheadType::Dot => Some("dot"),
             ArrowheadType::Diamond => Some("diamond"),
+            ArrowheadType::One
+            | ArrowheadType::Many
+            | ArrowheadType::ZeroOrOne
+            | ArrowheadType::OneOrMany
+            | ArrowheadType::ZeroOrMany => None,
         }
     }
+
+    /// Whether this arrowhead is drawn as crow's-foot decorator elements
+    pub fn is_crows_foot(&self) -> bool {
+        matches!(
+            self,
+            ArrowheadType::One
+                | ArrowheadType::Many
+                | ArrowheadType::ZeroOrOne
+                | ArrowheadType::OneOrMany
+                | ArrowheadType::ZeroOrMany
+        )
+    }
 }
@@ -0,0 +1,222 @@
+// src/dot.rs
+//! Graphviz DOT export
+//!
+//! Walks an `IntermediateGraph` and emits a `digraph`. Layout is not needed:
+//! Graphviz positions the nodes itself. Containers become `cluster`
+//! subgraphs, nested as in the source, and a container referenced by an
+//! edge is represented by an invisible point inside its cluster.
+
+use crate::ast::{ArrowType, StrokeStyle};
+use crate::error::Result;
+use crate::graphml::GraphmlGenerator;
+use crate::igr::{ExcalidrawAttributes, IntermediateGraph};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub struct DotGenerator;
+
+impl DotGenerator {
+    /// Generate a DOT `digraph` for a graph
+    ///
+    /// # Examples
+    /// ```rust
+    /// use excalidraw_dsl::dot::DotGenerator;
+    /// use excalidraw_dsl::igr::IntermediateGraph;
+    ///
+    /// let document = excalidraw_dsl::parser::parse_edsl("a[A]\nb[B]\na -> b").unwrap();
+    /// let igr = IntermediateGraph::from_ast(document).unwrap();
+    /// let dot = DotGenerator::generate(&igr).unwrap();
+    /// assert!(dot.contains("\"a\" -> \"b\";"));
+    /// ```
+    pub fn generate(igr: &IntermediateGraph) -> Result<String> {
+        let owners = GraphmlGenerator::innermost_containers(igr);
+
+        let mut out = String::from("digraph G {\n  node [shape=box];\n");
+        Self::write_members(&mut out, igr, &owners, None, 1);
+
+        for edge in igr.graph.edge_references() {
+            let edge_data = edge.weight();
+            let mut options = Vec::new();
+            if let Some(ref label) = edge_data.label {
+                options.push(format!("label={}", quote(label)));
+            }
+            match edge_data.arrow_type {
+                ArrowType::Line => options.push("dir=none".to_string()),
+                ArrowType::DoubleArrow => options.push("dir=both".to_string()),
+                _ => {}
+            }
+            let mut attributes = edge_data.attributes.clone();
+            attributes.stroke_style = attributes
+                .stroke_style
+                .or_else(|| edge_data.arrow_type.default_stroke_style());
+            options.extend(Self::style_options(&attributes));
+
+            let _ = writeln!(
+                out,
+                "  {} -> {}{};",
+                quote(&igr.graph[edge.source()].id),
+                quote(&igr.graph[edge.target()].id),
+                Self::option_list(&options)
+            );
+        }
+
+        out.push_str("}\n");
+        Ok(out)
+    }
+
+    /// Write the clusters and nodes directly inside `parent` (`None` for the
+    /// top level), recursing into nested containers
+    fn write_members(
+        out: &mut String,
+        igr: &IntermediateGraph,
+        owners: &HashMap<NodeIndex, usize>,
+        parent: Option<usize>,
+        depth: usize,
+    ) {
+        let indent = "  ".repeat(depth);
+        for (idx, container) in igr.containers.iter().enumerate() {
+            if container.parent_container != parent {
+                continue;
+            }
+            let _ = writeln!(out, "{indent}subgraph cluster_{idx} {{");
+            if let Some(ref label) = container.label {
+                let _ = writeln!(out, "{indent}  label={};", quote(label));
+            }
+            for option in Self::style_options(&container.attributes) {
+                let _ = writeln!(out, "{indent}  {option};");
+            }
+            Self::write_members(out, igr, owners, Some(idx), depth + 1);
+            // The container's own node, for edges that reference it
+            if let Some((_, node)) = container
+                .id
+                .as_deref()
+                .and_then(|id| igr.get_node_by_id(id))
+            {
+                let _ = writeln!(
+                    out,
+                    "{indent}  {} [shape=point, style=invis];",
+                    quote(&node.id)
+                );
+            }
+            let _ = writeln!(out, "{indent}}}");
+        }
+
+        for idx in igr.graph.node_indices() {
+            let node = &igr.graph[idx];
+            if node.is_virtual_container || owners.get(&idx).copied() != parent {
+                continue;
+            }
+            let mut options = vec![format!("label={}", quote(&node.label))];
+            if let Some(shape) = Self::shape(node.attributes.shape.as_deref()) {
+                options.push(format!("shape={shape}"));
+            }
+            options.extend(Self::style_options(&node.attributes));
+            let _ = writeln!(
+                out,
+                "{indent}{}{};",
+                quote(&node.id),
+                Self::option_list(&options)
+            );
+        }
+    }
+
+    fn shape(shape: Option<&str>) -> Option<&'static str> {
+        match shape? {
+            "ellipse" => Some("ellipse"),
+            "circle" => Some("circle"),
+            "diamond" => Some("diamond"),
+            "cylinder" => Some("cylinder"),
+            "hexagon" => Some("hexagon"),
+            "triangle" => Some("triangle"),
+            "parallelogram" => Some("parallelogram"),
+            "text" => Some("plaintext"),
+            _ => None,
+        }
+    }
+
+    fn style_options(attributes: &ExcalidrawAttributes) -> Vec<String> {
+        let mut options = Vec::new();
+        let mut styles = Vec::new();
+        if let Some(ref color) = attributes.stroke_color {
+            options.push(format!("color={}", quote(color)));
+        }
+        if let Some(ref color) = attributes.background_color {
+            if color != "transparent" {
+                options.push(format!("fillcolor={}", quote(color)));
+                styles.push("filled");
+            }
+        }
+        if let Some(ref color) = attributes.text_color {
+            options.push(format!("fontcolor={}", quote(color)));
+        }
+        match attributes.stroke_style {
+            Some(StrokeStyle::Dashed) => styles.push("dashed"),
+            Some(StrokeStyle::Dotted) => styles.push("dotted"),
+            _ => {}
+        }
+        if attributes.rounded.is_some() {
+            styles.push("rounded");
+        }
+        if !styles.is_empty() {
+            options.push(format!("style={}", quote(&styles.join(","))));
+        }
+        if let Some(width) = attributes.stroke_width {
+            options.push(format!("penwidth={width}"));
+        }
+        options
+    }
+
+    fn option_list(options: &[String]) -> String {
+        if options.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", options.join(", "))
+        }
+    }
+}
+
+/// DOT double-quoted string, with newlines as `\n` escapes
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_dot() {
+        let input = r##"
+container "Backend" as backend {
+  api[API] { shape: ellipse; }
+  db[Database] { backgroundColor: "#a5d8ff"; }
+}
+user[User]
+user -> api: calls
+api -- db
+"##;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let dot = DotGenerator::generate(&igr).unwrap();
+
+        assert!(dot.starts_with("digraph G {"));
+        assert!(dot.contains("subgraph cluster_0 {"));
+        assert!(dot.contains("\"api\" [label=\"API\", shape=ellipse];"));
+        assert!(dot.contains("fillcolor=\"#a5d8ff\", style=\"filled\""));
+        assert!(dot.contains("\"user\" -> \"api\" [label=\"calls\"];"));
+        assert!(dot.contains("\"api\" -> \"db\" [dir=none];"));
+    }
+}
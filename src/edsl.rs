@@ -0,0 +1,1134 @@
+// src/edsl.rs
+//! Import Excalidraw files into EDSL documents, and export graphs as EDSL
+//! source
+//!
+//! Import reads shapes with bound text as nodes, arrows and lines bound at
+//! both ends as edges, and frames or container rectangles as containers.
+//! Export writes a graph back out in a canonical layout: front matter,
+//! nodes, then containers and groups with their members, then edges, notes
+//! and lines, each with its style block. Together they let a drawing be
+//! edited as source again.
+
+use crate::ast::{
+    ArrowType, AttributeValue, Comment, CommentOwner, ContainerDefinition, EdgeDefinition,
+    GlobalConfig, GroupType, NodeDefinition, ParsedDocument, Statement, META_PREFIX,
+};
+use crate::error::{EDSLError, Result};
+use crate::generator::DEFAULT_STROKE_COLOR;
+use crate::igr::{ExcalidrawAttributes, GroupData, IntermediateGraph};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Container colors the generator uses when none is given; not worth
+/// repeating in source
+const DEFAULT_CONTAINER_STROKE: &str = "#868e96";
+const DEFAULT_CONTAINER_BACKGROUND: &str = "#f8f9fa";
+
+/// A shape or frame read from the Excalidraw file
+struct Shape<'a> {
+    element: &'a Value,
+    id: String,
+    label: Option<String>,
+}
+
+impl Shape<'_> {
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        let number = |key: &str| self.element[key].as_f64().unwrap_or(0.0);
+        let (x, y) = (number("x"), number("y"));
+        (x, y, x + number("width"), y + number("height"))
+    }
+
+    fn contains(&self, other: &Shape) -> bool {
+        let (x1, y1, x2, y2) = self.bounds();
+        let (ox1, oy1, ox2, oy2) = other.bounds();
+        x1 <= ox1 && y1 <= oy1 && ox2 <= x2 && oy2 <= y2 && self.area() > other.area()
+    }
+
+    fn area(&self) -> f64 {
+        let (x1, y1, x2, y2) = self.bounds();
+        (x2 - x1) * (y2 - y1)
+    }
+}
+
+/// Parse an Excalidraw file (or a bare elements array) into a `ParsedDocument`.
+///
+/// Rectangles, ellipses and diamonds that carry a bound label or are bound
+/// to an arrow become nodes. Frames, and rectangles the generator marked as
+/// containers, become containers holding the nodes inside them; frames use
+/// their members' `frameId`, rectangles use geometry. Arrows and lines bound
+/// at both ends become edges. Ids are derived from labels.
+///
+/// # Example
+///
+/// ```rust
+/// use excalidraw_dsl::edsl::parse_excalidraw;
+///
+/// let json = excalidraw_dsl::EDSLCompiler::new().compile("a[A]\nb[B]\na -> b").unwrap();
+/// let document = parse_excalidraw(&json).unwrap();
+/// assert_eq!(document.nodes.len(), 2);
+/// assert_eq!(document.edges.len(), 1);
+/// ```
+pub fn parse_excalidraw(json: &str) -> Result<ParsedDocument> {
+    let value: Value = serde_json::from_str(json)?;
+    let elements = value
+        .as_array()
+        .or_else(|| value["elements"].as_array())
+        .ok_or_else(|| EDSLError::Validation {
+            message: "Excalidraw file has no elements array".to_string(),
+        })?;
+    let elements: Vec<&Value> = elements
+        .iter()
+        .filter(|e| !e["isDeleted"].as_bool().unwrap_or(false))
+        .collect();
+    let element_type = |e: &Value| e["type"].as_str().unwrap_or_default().to_string();
+    let element_id = |e: &Value| e["id"].as_str().unwrap_or_default().to_string();
+
+    // Bound text, by the element it belongs to
+    let mut texts: HashMap<String, Vec<&Value>> = HashMap::new();
+    for element in &elements {
+        if let (Some("text"), Some(container)) =
+            (element["type"].as_str(), element["containerId"].as_str())
+        {
+            texts
+                .entry(container.to_string())
+                .or_default()
+                .push(element);
+        }
+    }
+    let label_of = |element: &Value| -> Option<&Value> {
+        let candidates = texts.get(element["id"].as_str()?)?;
+        // Icons are bound ahead of the label
+        let listed = element["boundElements"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|bound| bound["id"].as_str())
+            .filter_map(|id| candidates.iter().find(|t| t["id"].as_str() == Some(id)))
+            .next_back();
+        listed.or(candidates.first()).copied()
+    };
+
+    let bound_ends: HashSet<&str> = elements
+        .iter()
+        .flat_map(|e| [&e["startBinding"], &e["endBinding"]])
+        .filter_map(|binding| binding["elementId"].as_str())
+        .collect();
+
+    let mut ids = IdAllocator::default();
+    let mut containers: Vec<Shape> = Vec::new();
+    let mut nodes: Vec<Shape> = Vec::new();
+    for &element in &elements {
+        let kind = element_type(element);
+        let is_container = kind == "frame" || element["isContainer"].as_bool() == Some(true);
+        let is_shape = matches!(kind.as_str(), "rectangle" | "ellipse" | "diamond");
+        let label = if kind == "frame" {
+            element["name"].as_str().map(str::to_string)
+        } else {
+            label_of(element).and_then(|t| t["text"].as_str().map(str::to_string))
+        };
+        if is_container {
+            containers.push(Shape {
+                element,
+                id: ids.allocate(label.as_deref(), "container"),
+                label,
+            });
+        } else if is_shape && (label.is_some() || bound_ends.contains(element_id(element).as_str()))
+        {
+            nodes.push(Shape {
+                element,
+                id: ids.allocate(label.as_deref(), "node"),
+                label,
+            });
+        }
+    }
+
+    // Innermost container of each shape: its frame, else the smallest
+    // container rectangle around it
+    let parent_of = |shape: &Shape| -> Option<usize> {
+        if let Some(frame) = shape.element["frameId"].as_str() {
+            if let Some(idx) = containers
+                .iter()
+                .position(|c| element_id(c.element) == frame)
+            {
+                return Some(idx);
+            }
+        }
+        containers
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| element_type(c.element) != "frame" && c.contains(shape))
+            .min_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()))
+            .map(|(idx, _)| idx)
+    };
+    let node_parents: Vec<Option<usize>> = nodes.iter().map(parent_of).collect();
+    let container_parents: Vec<Option<usize>> = containers.iter().map(parent_of).collect();
+
+    let element_names: HashMap<String, &str> = nodes
+        .iter()
+        .chain(containers.iter())
+        .map(|shape| (element_id(shape.element), shape.id.as_str()))
+        .collect();
+    let mut edges = Vec::new();
+    for &element in &elements {
+        if !matches!(element_type(element).as_str(), "arrow" | "line") {
+            continue;
+        }
+        let end = |key: &str| {
+            element[key]["elementId"]
+                .as_str()
+                .and_then(|id| element_names.get(id).copied())
+        };
+        let (Some(start), Some(end)) = (end("startBinding"), end("endBinding")) else {
+            continue;
+        };
+        edges.push(import_edge(
+            element,
+            start,
+            end,
+            label_of(element).and_then(|t| t["text"].as_str()),
+        ));
+    }
+
+    let node_definitions: Vec<NodeDefinition> = nodes
+        .iter()
+        .map(|shape| NodeDefinition {
+            id: shape.id.clone(),
+            label: shape.label.clone(),
+            component_type: None,
+            attributes: import_node_attributes(shape.element, label_of(shape.element)),
+        })
+        .collect();
+
+    // Assemble containers innermost first, so each can be moved into its parent
+    let mut definitions: Vec<Option<ContainerDefinition>> = containers
+        .iter()
+        .enumerate()
+        .map(|(idx, shape)| {
+            let members: Vec<&NodeDefinition> = node_definitions
+                .iter()
+                .zip(&node_parents)
+                .filter(|(_, parent)| **parent == Some(idx))
+                .map(|(node, _)| node)
+                .collect();
+            Some(ContainerDefinition {
+                id: Some(shape.id.clone()),
+                label: shape.label.clone(),
+                children: members.iter().map(|node| node.id.clone()).collect(),
+                attributes: import_container_attributes(shape.element),
+                internal_statements: members
+                    .into_iter()
+                    .map(|node| Statement::Node(node.clone()))
+                    .collect(),
+            })
+        })
+        .collect();
+    let depth = |mut idx: usize| {
+        let mut depth = 0;
+        while let Some(parent) = container_parents[idx] {
+            depth += 1;
+            idx = parent;
+        }
+        depth
+    };
+    let mut order: Vec<usize> = (0..containers.len()).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(depth(idx)));
+    let mut top_level = Vec::new();
+    for idx in order {
+        let Some(definition) = definitions[idx].take() else {
+            continue;
+        };
+        match container_parents[idx].and_then(|parent| definitions[parent].as_mut()) {
+            Some(parent) => parent
+                .internal_statements
+                .push(Statement::Container(definition)),
+            None => top_level.push((idx, definition)),
+        }
+    }
+    top_level.sort_by_key(|(idx, _)| *idx);
+
+    Ok(ParsedDocument {
+        config: GlobalConfig::default(),
+        component_types: HashMap::new(),
+        edge_types: HashMap::new(),
+        templates: HashMap::new(),
+        diagram: None,
+        nodes: node_definitions
+            .into_iter()
+            .zip(&node_parents)
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(node, _)| node)
+            .collect(),
+        edges,
+        containers: top_level
+            .into_iter()
+            .map(|(_, definition)| definition)
+            .collect(),
+        groups: vec![],
+        connections: vec![],
+        notes: vec![],
+        lines: vec![],
+        hyperedges: vec![],
+    })
+}
+
+fn import_edge(element: &Value, start: &str, end: &str, label: Option<&str>) -> EdgeDefinition {
+    let has_head = |key: &str| element[key].as_str().is_some_and(|head| head != "none");
+    let (start_head, end_head) = if element["type"] == "line" {
+        (false, false)
+    } else {
+        (has_head("startArrowhead"), has_head("endArrowhead"))
+    };
+    let (from, to, arrow_type) = match (start_head, end_head) {
+        (true, true) => (start, end, ArrowType::DoubleArrow),
+        (true, false) => (end, start, ArrowType::SingleArrow),
+        (false, true) => (start, end, ArrowType::SingleArrow),
+        (false, false) => (start, end, ArrowType::Line),
+    };
+
+    let mut attributes = HashMap::new();
+    import_stroke(element, DEFAULT_STROKE_COLOR, &mut attributes);
+    EdgeDefinition {
+        from: from.to_string(),
+        to: to.to_string(),
+        label: label.map(str::to_string),
+        arrow_type,
+        attributes,
+        style: None,
+        waypoints: Vec::new(),
+        position: None,
+    }
+}
+
+fn import_node_attributes(
+    element: &Value,
+    label: Option<&Value>,
+) -> HashMap<String, AttributeValue> {
+    let mut attributes = HashMap::new();
+    if let Some(shape @ ("ellipse" | "diamond")) = element["type"].as_str() {
+        attributes.insert(
+            "shape".to_string(),
+            AttributeValue::String(shape.to_string()),
+        );
+    }
+    import_stroke(element, DEFAULT_STROKE_COLOR, &mut attributes);
+    if let Some(color) = element["backgroundColor"]
+        .as_str()
+        .filter(|color| *color != "transparent")
+    {
+        attributes.insert(
+            "backgroundColor".to_string(),
+            AttributeValue::String(color.to_string()),
+        );
+    }
+    if let Some(color) = label
+        .and_then(|text| text["strokeColor"].as_str())
+        .filter(|color| *color != DEFAULT_STROKE_COLOR)
+    {
+        attributes.insert(
+            "color".to_string(),
+            AttributeValue::String(color.to_string()),
+        );
+    }
+    for (key, value) in element["customData"].as_object().into_iter().flatten() {
+        let value = match value {
+            Value::String(s) => AttributeValue::String(s.clone()),
+            Value::Number(n) => AttributeValue::Number(n.as_f64().unwrap_or_default()),
+            Value::Bool(b) => AttributeValue::Boolean(*b),
+            _ => continue,
+        };
+        if key == "tooltip" {
+            attributes.insert(key.clone(), value);
+        } else {
+            attributes.insert(format!("{META_PREFIX}{key}"), value);
+        }
+    }
+    attributes
+}
+
+fn import_container_attributes(element: &Value) -> HashMap<String, AttributeValue> {
+    let mut attributes = HashMap::new();
+    if element["type"] == "frame" {
+        return attributes;
+    }
+    import_stroke(element, DEFAULT_CONTAINER_STROKE, &mut attributes);
+    if let Some(color) = element["backgroundColor"]
+        .as_str()
+        .filter(|color| *color != DEFAULT_CONTAINER_BACKGROUND)
+    {
+        attributes.insert(
+            "backgroundColor".to_string(),
+            AttributeValue::String(color.to_string()),
+        );
+    }
+    attributes
+}
+
+/// Stroke color and style, where they differ from the defaults
+fn import_stroke(
+    element: &Value,
+    default_color: &str,
+    attributes: &mut HashMap<String, AttributeValue>,
+) {
+    if let Some(color) = element["strokeColor"]
+        .as_str()
+        .filter(|color| *color != default_color)
+    {
+        attributes.insert(
+            "strokeColor".to_string(),
+            AttributeValue::String(color.to_string()),
+        );
+    }
+    if let Some(style @ ("dashed" | "dotted")) = element["strokeStyle"].as_str() {
+        attributes.insert(
+            "strokeStyle".to_string(),
+            AttributeValue::String(style.to_string()),
+        );
+    }
+}
+
+/// Hands out unique DSL ids derived from labels
+#[derive(Default)]
+struct IdAllocator {
+    used: HashSet<String>,
+}
+
+impl IdAllocator {
+    fn allocate(&mut self, label: Option<&str>, fallback: &str) -> String {
+        let mut base: String = label
+            .and_then(|label| label.lines().next())
+            .unwrap_or_default()
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        while base.contains("__") {
+            base = base.replace("__", "_");
+        }
+        let mut base = base.trim_matches('_').to_string();
+        if base.is_empty() {
+            base = fallback.to_string();
+        }
+
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while !self.used.insert(id.clone()) {
+            id = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        id
+    }
+}
+
+/// A container or group of the graph, by index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cluster {
+    Container(usize),
+    Group(usize),
+}
+
+impl Cluster {
+    /// The cluster this one is declared in. Containers nested in a group
+    /// are only listed in the group's `nested_containers`.
+    fn parent(self, igr: &IntermediateGraph) -> Option<Cluster> {
+        match self {
+            Cluster::Container(idx) => igr.containers[idx]
+                .parent_container
+                .map(Cluster::Container)
+                .or_else(|| {
+                    igr.groups
+                        .iter()
+                        .position(|group| group.nested_containers.contains(&idx))
+                        .map(Cluster::Group)
+                }),
+            Cluster::Group(idx) => {
+                let group = &igr.groups[idx];
+                group
+                    .parent_group
+                    .map(Cluster::Group)
+                    .or(group.parent_container.map(Cluster::Container))
+            }
+        }
+    }
+
+    fn depth(self, igr: &IntermediateGraph) -> usize {
+        let mut depth = 0;
+        let mut cluster = self;
+        while let Some(parent) = cluster.parent(igr) {
+            depth += 1;
+            cluster = parent;
+        }
+        depth
+    }
+}
+
+/// Innermost written cluster holding each node
+fn cluster_owners(igr: &IntermediateGraph) -> HashMap<NodeIndex, Cluster> {
+    let containers = igr
+        .containers
+        .iter()
+        .enumerate()
+        .map(|(idx, container)| (Cluster::Container(idx), &container.children));
+    let groups = igr
+        .groups
+        .iter()
+        .enumerate()
+        .filter(|(_, group)| !is_attribute_group(igr, group))
+        .map(|(idx, group)| (Cluster::Group(idx), &group.children));
+
+    let mut owners: HashMap<NodeIndex, Cluster> = HashMap::new();
+    for (cluster, children) in containers.chain(groups) {
+        for &child in children {
+            match owners.get(&child) {
+                Some(current) if current.depth(igr) >= cluster.depth(igr) => {}
+                _ => {
+                    owners.insert(child, cluster);
+                }
+            }
+        }
+    }
+    owners
+}
+
+/// Whether `group` only exists because its members set the `group`
+/// attribute, which recreates it, so it isn't written as a block
+fn is_attribute_group(igr: &IntermediateGraph, group: &GroupData) -> bool {
+    group.group_type == GroupType::BasicGroup
+        && group.label.as_deref() == Some(group.id.as_str())
+        && group.parent_group.is_none()
+        && group.parent_container.is_none()
+        && group.nested_groups.is_empty()
+        && group.nested_containers.is_empty()
+        && EdslGenerator::attributes(&group.attributes).is_empty()
+        && group
+            .children
+            .iter()
+            .all(|&idx| igr.graph[idx].attributes.group.as_deref() == Some(group.id.as_str()))
+}
+
+pub struct EdslGenerator;
+
+impl EdslGenerator {
+    /// Generate EDSL source for a graph; layout is not needed
+    ///
+    /// The front matter comes first, then nodes, then containers and groups
+    /// with their members nested inside, then every edge, note and line.
+    /// Style attributes are written in a fixed order, so the output doubles
+    /// as a canonical formatting of the source. Templates, component types,
+    /// edge types and rules are written out as the attributes they applied.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use excalidraw_dsl::edsl::EdslGenerator;
+    /// use excalidraw_dsl::igr::IntermediateGraph;
+    ///
+    /// let document = excalidraw_dsl::parser::parse_edsl("a[Start]\nb\na -> b").unwrap();
+    /// let igr = IntermediateGraph::from_ast(document).unwrap();
+    /// let edsl = EdslGenerator::generate(&igr).unwrap();
+    /// assert_eq!(edsl, "a[Start]\nb\n\na -> b\n");
+    /// ```
+    pub fn generate(igr: &IntermediateGraph) -> Result<String> {
+        Self::generate_with_comments(igr, &[])
+    }
+
+    /// Generate EDSL source like [`generate`](Self::generate), keeping the
+    /// comments returned by `parse_edsl_lossless`
+    ///
+    /// A comment on a style attribute stays beside that attribute, with the
+    /// style block written one attribute per line; other comments stay
+    /// before or after their statement. Comments whose statement isn't
+    /// written out, such as those in component types, end the output.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use excalidraw_dsl::edsl::EdslGenerator;
+    /// use excalidraw_dsl::igr::IntermediateGraph;
+    /// use excalidraw_dsl::parser::parse_edsl_lossless;
+    ///
+    /// let (document, comments) = parse_edsl_lossless("# entry\na[Start]").unwrap();
+    /// let igr = IntermediateGraph::from_ast(document).unwrap();
+    /// let edsl = EdslGenerator::generate_with_comments(&igr, &comments).unwrap();
+    /// assert_eq!(edsl, "# entry\na[Start]\n");
+    /// ```
+    pub fn generate_with_comments(igr: &IntermediateGraph, comments: &[Comment]) -> Result<String> {
+        let mut pending = PendingComments(comments.iter().map(Some).collect());
+        let owners = cluster_owners(igr);
+        let mut out = String::new();
+        Self::write_members(&mut out, igr, &owners, None, 0, &mut pending);
+
+        if igr.graph.edge_count() > 0 && !out.is_empty() {
+            out.push('\n');
+        }
+        for edge in igr.graph.edge_references() {
+            let edge_data = edge.weight();
+            let (source, target) = (&igr.graph[edge.source()].id, &igr.graph[edge.target()].id);
+            let mut head = format!("{source} {} {target}", arrow(edge_data.arrow_type));
+            if !edge_data.waypoints.is_empty() {
+                let points: Vec<String> = edge_data
+                    .waypoints
+                    .iter()
+                    .map(|(x, y)| format!("({x}, {y})"))
+                    .collect();
+                let _ = write!(head, " via {}", points.join(", "));
+            }
+            if let Some(ref label) = edge_data.label {
+                let _ = write!(head, ": {}", quote(label));
+            }
+            let mut style = Self::attributes(&edge_data.attributes);
+            if let Some(routing) = edge_data.routing_type {
+                style.push(("routing", keyword(&format!("{routing:?}").to_lowercase())));
+            }
+            let comments = pending.take(&CommentOwner::Edge {
+                source: source.clone(),
+                target: target.clone(),
+            });
+            write_statement(&mut out, "", &head, &style, &[], comments);
+        }
+
+        if (!igr.notes.is_empty() || !igr.lines.is_empty()) && !out.is_empty() {
+            out.push('\n');
+        }
+        for note in &igr.notes {
+            let head = format!("note {} at ({}, {})", quote(&note.text), note.x, note.y);
+            let style = Self::attributes(&note.attributes);
+            write_statement(&mut out, "", &head, &style, &[], Vec::new());
+        }
+        for line in &igr.lines {
+            let ((x1, y1), (x2, y2)) = (line.from, line.to);
+            let head = format!("line from ({x1}, {y1}) to ({x2}, {y2})");
+            let style = Self::attributes(&line.attributes);
+            write_statement(&mut out, "", &head, &style, &[], Vec::new());
+        }
+
+        for comment in pending.0.into_iter().flatten() {
+            let _ = writeln!(out, "#{}", comment.text);
+        }
+        Ok(Self::front_matter(&igr.global_config, !out.is_empty())? + &out)
+    }
+
+    /// `---` block with the config fields that are set, or nothing when
+    /// none are; `separate` adds a blank line before the statements
+    fn front_matter(config: &GlobalConfig, separate: bool) -> Result<String> {
+        let serde_yaml::Value::Mapping(fields) = serde_yaml::to_value(config)? else {
+            return Ok(String::new());
+        };
+        let set: serde_yaml::Mapping = fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .collect();
+        if set.is_empty() {
+            return Ok(String::new());
+        }
+        let mut block = format!("---\n{}---\n", serde_yaml::to_string(&set)?);
+        if separate {
+            block.push('\n');
+        }
+        Ok(block)
+    }
+
+    /// Write the nodes, containers and groups directly inside `parent`
+    /// (`None` for the top level), recursing into nested clusters
+    fn write_members(
+        out: &mut String,
+        igr: &IntermediateGraph,
+        owners: &HashMap<NodeIndex, Cluster>,
+        parent: Option<Cluster>,
+        depth: usize,
+        pending: &mut PendingComments,
+    ) {
+        let indent = "  ".repeat(depth);
+        for idx in igr.graph.node_indices() {
+            let node = &igr.graph[idx];
+            if node.is_virtual_container || owners.get(&idx).copied() != parent {
+                continue;
+            }
+            let mut head = node.id.clone();
+            if node.label != node.id {
+                let _ = write!(head, "[{}]", escape_label(&node.label));
+            }
+            let metadata: Vec<(&str, String)> = node
+                .metadata
+                .iter()
+                .map(|(key, value)| (key.as_str(), attribute_value(value)))
+                .collect();
+            let comments = pending.take(&CommentOwner::Node(node.id.clone()));
+            write_statement(
+                out,
+                &indent,
+                &head,
+                &Self::attributes(&node.attributes),
+                &metadata,
+                comments,
+            );
+        }
+
+        for (idx, container) in igr.containers.iter().enumerate() {
+            let cluster = Cluster::Container(idx);
+            if cluster.parent(igr) != parent {
+                continue;
+            }
+            let style = Self::attributes(&container.attributes);
+            let comments = match container.id.as_ref().or(container.label.as_ref()) {
+                Some(key) => pending.take(&CommentOwner::Container(key.clone())),
+                None => Vec::new(),
+            };
+            let (style_comments, comments) = split_comments(&style, &[], comments);
+
+            for comment in comments.iter().filter(|comment| !comment.trailing) {
+                let _ = writeln!(out, "{indent}#{}", comment.text);
+            }
+            let keyword = if container.is_lane {
+                "lane"
+            } else {
+                "container"
+            };
+            let _ = write!(out, "{indent}{keyword}");
+            if let Some(ref label) = container.label {
+                let _ = write!(out, " {}", quote(label));
+            }
+            if let Some(ref id) = container.id {
+                let _ = write!(out, " as {id}");
+            }
+            out.push_str(" {");
+            for comment in comments.iter().filter(|comment| comment.trailing) {
+                let _ = write!(out, " #{}", comment.text);
+            }
+            out.push('\n');
+            if !style.is_empty() {
+                write_statement(
+                    out,
+                    &format!("{indent}  "),
+                    "style:",
+                    &style,
+                    &[],
+                    style_comments,
+                );
+            }
+            Self::write_members(out, igr, owners, Some(cluster), depth + 1, pending);
+            let _ = writeln!(out, "{indent}}}");
+        }
+
+        for (idx, group) in igr.groups.iter().enumerate() {
+            let cluster = Cluster::Group(idx);
+            if is_attribute_group(igr, group) || cluster.parent(igr) != parent {
+                continue;
+            }
+            let keyword = match group.group_type {
+                GroupType::BasicGroup => "group",
+                GroupType::FlowGroup => "flow",
+                GroupType::SemanticGroup(ref group_type) => group_type.as_str(),
+            };
+            let label = group.label.as_deref().unwrap_or(&group.id);
+            let _ = write!(out, "{indent}{keyword} {}", quote(label));
+            // Without `as`, the id is derived from the label
+            if group.id != label.to_lowercase().replace(' ', "_") {
+                let _ = write!(out, " as {}", group.id);
+            }
+            out.push_str(" {\n");
+            let style = Self::attributes(&group.attributes);
+            if !style.is_empty() {
+                write_statement(
+                    out,
+                    &format!("{indent}  "),
+                    "style:",
+                    &style,
+                    &[],
+                    Vec::new(),
+                );
+            }
+            Self::write_members(out, igr, owners, Some(cluster), depth + 1, pending);
+            let _ = writeln!(out, "{indent}}}");
+        }
+    }
+
+    /// DSL attributes for the explicitly set fields, in canonical order
+    fn attributes(attributes: &ExcalidrawAttributes) -> Vec<(&'static str, String)> {
+        let mut style = Vec::new();
+        if let Some(ref shape) = attributes.shape {
+            style.push(("shape", keyword(shape)));
+        }
+        let mut text = |name: &'static str, value: &Option<String>| {
+            if let Some(value) = value {
+                style.push((name, quote(value)));
+            }
+        };
+        text("strokeColor", &attributes.stroke_color);
+        text("backgroundColor", &attributes.background_color);
+        text("color", &attributes.text_color);
+        text("labelColor", &attributes.label_color);
+        text("labelBackground", &attributes.label_background);
+        text("font", &attributes.font);
+        text("icon", &attributes.icon);
+        text("tooltip", &attributes.tooltip);
+        text("group", &attributes.group);
+        if attributes.show_label == Some(false) {
+            style.push(("showLabel", "false".to_string()));
+        }
+        if attributes.shadow == Some(true) {
+            style.push(("shadow", "true".to_string()));
+        }
+
+        if let Some(stroke_style) = attributes.stroke_style {
+            style.push((
+                "strokeStyle",
+                stroke_style.to_excalidraw_style().to_string(),
+            ));
+        }
+        if let Some(ref fill) = attributes.fill_style {
+            style.push(("fill", fill.to_excalidraw_style().to_string()));
+        }
+        let numbers = [
+            ("width", attributes.width),
+            ("height", attributes.height),
+            ("strokeWidth", attributes.stroke_width),
+            ("fontSize", attributes.font_size),
+            ("rounded", attributes.rounded),
+            ("angle", attributes.angle),
+            ("curvature", attributes.curvature),
+            ("roughness", attributes.roughness.map(f64::from)),
+        ];
+        for (name, value) in numbers {
+            // The grammar has no negative attribute numbers
+            if let Some(value) = value.filter(|value| *value >= 0.0) {
+                style.push((name, value.to_string()));
+            }
+        }
+        style
+    }
+}
+
+/// Comments not yet written out; each is taken once
+struct PendingComments<'a>(Vec<Option<&'a Comment>>);
+
+impl<'a> PendingComments<'a> {
+    /// Remove and return the comments attached to `owner`
+    fn take(&mut self, owner: &CommentOwner) -> Vec<&'a Comment> {
+        self.0
+            .iter_mut()
+            .filter(|slot| slot.is_some_and(|comment| comment.owner.as_ref() == Some(owner)))
+            .filter_map(Option::take)
+            .collect()
+    }
+}
+
+/// Split a statement's comments into those on attributes it writes and the
+/// rest, which belong to the statement itself
+fn split_comments<'a>(
+    style: &[(&str, String)],
+    metadata: &[(&str, String)],
+    comments: Vec<&'a Comment>,
+) -> (Vec<&'a Comment>, Vec<&'a Comment>) {
+    comments.into_iter().partition(|comment| {
+        comment.attribute.as_deref().is_some_and(|attribute| {
+            style
+                .iter()
+                .chain(metadata)
+                .any(|(name, _)| *name == attribute)
+        })
+    })
+}
+
+/// One statement: its own comments around `head` and the style block, which
+/// is written one attribute per line when attributes carry comments
+fn write_statement(
+    out: &mut String,
+    indent: &str,
+    head: &str,
+    style: &[(&str, String)],
+    metadata: &[(&str, String)],
+    comments: Vec<&Comment>,
+) {
+    let (attribute_comments, comments) = split_comments(style, metadata, comments);
+    for comment in comments.iter().filter(|comment| !comment.trailing) {
+        let _ = writeln!(out, "{indent}#{}", comment.text);
+    }
+    let _ = write!(out, "{indent}{head}");
+    if attribute_comments.is_empty() {
+        out.push_str(&style_block(style, metadata));
+    } else {
+        let inner = format!("{indent}  ");
+        out.push_str(" {\n");
+        write_commented_attributes(out, &inner, style, &attribute_comments);
+        if !metadata.is_empty() {
+            // A comment goes with the style attribute when both have its name
+            let meta_comments: Vec<&Comment> = attribute_comments
+                .into_iter()
+                .filter(|comment| {
+                    !style
+                        .iter()
+                        .any(|(name, _)| comment.attribute.as_deref() == Some(*name))
+                })
+                .collect();
+            let _ = writeln!(out, "{inner}meta {{");
+            write_commented_attributes(out, &format!("{inner}  "), metadata, &meta_comments);
+            let _ = writeln!(out, "{inner}}}");
+        }
+        let _ = write!(out, "{indent}}}");
+    }
+    for comment in comments.iter().filter(|comment| comment.trailing) {
+        let _ = write!(out, " #{}", comment.text);
+    }
+    out.push('\n');
+}
+
+/// `name: value;` lines, each with the comments on that attribute
+fn write_commented_attributes(
+    out: &mut String,
+    indent: &str,
+    attributes: &[(&str, String)],
+    comments: &[&Comment],
+) {
+    for (name, value) in attributes {
+        let on_attribute = || {
+            comments
+                .iter()
+                .filter(move |comment| comment.attribute.as_deref() == Some(*name))
+        };
+        for comment in on_attribute().filter(|comment| !comment.trailing) {
+            let _ = writeln!(out, "{indent}#{}", comment.text);
+        }
+        let _ = write!(out, "{indent}{name}: {value};");
+        for comment in on_attribute().filter(|comment| comment.trailing) {
+            let _ = write!(out, " #{}", comment.text);
+        }
+        out.push('\n');
+    }
+}
+
+fn arrow(arrow_type: ArrowType) -> &'static str {
+    match arrow_type {
+        ArrowType::SingleArrow => "->",
+        ArrowType::Line => "--",
+        ArrowType::DoubleArrow => "<->",
+        ArrowType::WavyArrow => "~>",
+        ArrowType::DashedArrow => "-.->",
+        ArrowType::DottedArrow => "..>",
+        ArrowType::ThickArrow => "==>",
+    }
+}
+
+/// ` { name: value; ... }`, with a `meta` block when there is metadata, or
+/// nothing when both are empty
+fn style_block(style: &[(&str, String)], metadata: &[(&str, String)]) -> String {
+    if style.is_empty() && metadata.is_empty() {
+        return String::new();
+    }
+    let mut block = String::from(" {");
+    for (name, value) in style {
+        let _ = write!(block, " {name}: {value};");
+    }
+    if !metadata.is_empty() {
+        block.push_str(" meta {");
+        for (name, value) in metadata {
+            let _ = write!(block, " {name}: {value};");
+        }
+        block.push_str(" }");
+    }
+    block.push_str(" }");
+    block
+}
+
+fn attribute_value(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::String(s) | AttributeValue::Color(s) => quote(s),
+        AttributeValue::Number(n) => n.to_string(),
+        AttributeValue::Boolean(b) => b.to_string(),
+    }
+}
+
+/// Bare identifier such as a shape name, quoted only when the grammar
+/// would read it as something else
+fn keyword(value: &str) -> String {
+    let bare = value.split('-').all(|part| {
+        !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    let ambiguous =
+        value == "true" || value == "false" || value.starts_with(|c: char| c.is_ascii_digit());
+    if bare && !ambiguous {
+        value.to_string()
+    } else {
+        quote(value)
+    }
+}
+
+/// EDSL string literal
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Node label text for `id[...]`
+fn escape_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '[' | ']' | '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_through_excalidraw() {
+        let input = r##"
+user[User]
+container "Backend" as backend {
+  api[API Server] { shape: ellipse; backgroundColor: "#a5d8ff"; }
+  db[Database] { strokeColor: "#c92a2a"; }
+}
+user -> api: "calls \"v2\""
+api -- db
+api -> backend
+"##;
+        let mut compiler = crate::EDSLCompiler::new();
+        let original = compiler.build_igr(input).unwrap();
+        let json = compiler.compile(input).unwrap();
+
+        let document = parse_excalidraw(&json).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let edsl = EdslGenerator::generate(&igr).unwrap();
+        assert!(edsl.contains("container \"Backend\" as backend {"));
+        assert!(edsl.contains(
+            "  api_server[API Server] { shape: ellipse; backgroundColor: \"#a5d8ff\"; }"
+        ));
+        assert!(edsl.contains("user -> api_server: \"calls \\\"v2\\\"\""));
+
+        // The regenerated source compiles back to an equivalent graph
+        let reparsed = compiler.build_igr(&edsl).unwrap();
+        let count = |igr: &IntermediateGraph| {
+            let nodes = igr
+                .graph
+                .node_weights()
+                .filter(|node| !node.is_virtual_container)
+                .count();
+            (nodes, igr.graph.edge_count(), igr.containers.len())
+        };
+        assert_eq!(count(&reparsed), count(&original));
+        assert_eq!(count(&reparsed), (3, 3, 1));
+        let (_, db) = reparsed.get_node_by_id("database").unwrap();
+        assert_eq!(db.attributes.stroke_color.as_deref(), Some("#c92a2a"));
+        compiler.compile(&edsl).unwrap();
+    }
+
+    #[test]
+    fn test_format_keeps_attribute_comments() {
+        let input = r##"
+# The public entry point
+web[Web] {
+  backgroundColor: "#a5d8ff"; # light blue
+  # brand color
+  strokeColor: "#1971c2";
+  shape: ellipse; # round
+}
+db[Database] # storage
+web -> db { strokeWidth: 2; } # main path
+"##;
+        let (document, comments) = crate::parser::parse_edsl_lossless(input).unwrap();
+        assert_eq!(comments.len(), 6);
+        assert_eq!((comments[1].line, comments[1].column), (4, 31));
+
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let edsl = EdslGenerator::generate_with_comments(&igr, &comments).unwrap();
+        assert_eq!(
+            edsl,
+            r##"# The public entry point
+web[Web] {
+  shape: ellipse; # round
+  # brand color
+  strokeColor: "#1971c2";
+  backgroundColor: "#a5d8ff"; # light blue
+}
+db[Database] # storage
+
+web -> db { strokeWidth: 2; } # main path
+"##
+        );
+
+        // Formatting the output again changes nothing
+        let (document, comments) = crate::parser::parse_edsl_lossless(&edsl).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        assert_eq!(
+            EdslGenerator::generate_with_comments(&igr, &comments).unwrap(),
+            edsl
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_the_whole_document() {
+        let input = r##"---
+theme: dark
+stable_ids: true
+random_seed: 7
+---
+# entry
+c[C]
+x[X] { group: "pool"; }
+container "Box" as box {
+  e[E]
+  service "Api Layer" as api {
+    d[D]
+  }
+}
+group "Team" {
+  a[A]
+  b[B] { strokeColor: "#ff0000"; }
+}
+a -> b
+b -> c
+c -> d
+note "Needs review" at (-10.5, 20) { backgroundColor: "#fff3bf"; }
+line from (0, 0) to (100, 0)
+"##;
+        let format = |source: &str| {
+            let (document, comments) = crate::parser::parse_edsl_lossless(source).unwrap();
+            let igr = IntermediateGraph::from_ast(document).unwrap();
+            EdslGenerator::generate_with_comments(&igr, &comments).unwrap()
+        };
+        let edsl = format(input);
+        assert!(edsl.starts_with("---\ntheme: dark\n"), "{edsl}");
+        assert!(edsl.contains("  service \"Api Layer\" as api {\n    d[D]\n  }"));
+        assert!(edsl.contains("group \"Team\" {\n  a[A]\n"));
+        assert!(!edsl.contains("group \"pool\""));
+        assert!(edsl.contains("note \"Needs review\" at (-10.5, 20)"));
+        assert!(edsl.contains("line from (0, 0) to (100, 0)"));
+        assert_eq!(format(&edsl), edsl);
+
+        // The formatted source draws exactly the same diagram
+        let mut compiler = crate::EDSLCompiler::new();
+        assert_eq!(
+            compiler.compile(&edsl).unwrap(),
+            compiler.compile(input).unwrap()
+        );
+    }
+}
@@ -1,6 +1,8 @@
 // src/error.rs
 
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Extended error context for better debugging
@@ -90,7 +92,65 @@ pub enum EDSLError {
     Configuration { message: String },
 }
 
+/// Machine-readable description of an error, used by `--error-format json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// Error category: parse, validation, configuration, layout, generate,
+    /// io, serialization or llm
+    pub kind: String,
+    pub message: String,
+    /// 1-based source line, when known
+    pub line: Option<usize>,
+    /// 1-based source column, when known
+    pub column: Option<usize>,
+}
+
 impl EDSLError {
+    /// Short category name for this error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EDSLError::Parse(_) => "parse",
+            EDSLError::Build(_) | EDSLError::Validation { .. } => "validation",
+            EDSLError::Configuration { .. } => "configuration",
+            EDSLError::Layout(_) => "layout",
+            EDSLError::Generator(_) => "generate",
+            #[cfg(feature = "llm")]
+            EDSLError::LLM(_) => "llm",
+            EDSLError::Io(_) => "io",
+            EDSLError::Json(_) | EDSLError::Yaml(_) => "serialization",
+        }
+    }
+
+    /// Source location (line, column) of the error, when known
+    pub fn location(&self) -> (Option<usize>, Option<usize>) {
+        match self {
+            EDSLError::Parse(ParseError::Syntax { line, .. }) if *line > 0 => (Some(*line), None),
+            EDSLError::Parse(ParseError::InvalidConfig { line, .. }) => (*line, None),
+            EDSLError::Parse(ParseError::PestError(e)) => match e.line_col {
+                pest::error::LineColLocation::Pos((line, column))
+                | pest::error::LineColLocation::Span((line, column), _) => {
+                    (Some(line), Some(column))
+                }
+            },
+            EDSLError::Yaml(e) => match e.location() {
+                Some(location) => (Some(location.line()), Some(location.column())),
+                None => (None, None),
+            },
+            _ => (None, None),
+        }
+    }
+
+    /// Serializable summary of this error
+    pub fn to_report(&self) -> ErrorReport {
+        let (line, column) = self.location();
+        ErrorReport {
+            kind: self.kind().to_string(),
+            message: self.to_string(),
+            line,
+            column,
+        }
+    }
+
     /// Add context to any error type
     pub fn with_context<F>(self, f: F) -> ContextualError
     where
@@ -138,13 +198,28 @@ fn format_context(ctx: &ErrorContext) -> String {
     parts.join("\n  ")
 }
 
+fn config_location(line: &Option<usize>, key: &Option<String>) -> String {
+    match (line, key) {
+        (Some(line), Some(key)) => format!(" at line {line} (`{key}`)"),
+        (Some(line), None) => format!(" at line {line}"),
+        (None, Some(key)) => format!(" (`{key}`)"),
+        (None, None) => String::new(),
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("Syntax error at line {line}: {message}")]
     Syntax { line: usize, message: String },
 
-    #[error("Invalid YAML configuration: {0}")]
-    InvalidConfig(String),
+    /// Front matter that `serde_yaml` rejected; `line` is the source line
+    /// and `key` the top-level key on it, when the error could be located
+    #[error("Invalid YAML configuration{}: {message}", config_location(.line, .key))]
+    InvalidConfig {
+        line: Option<usize>,
+        key: Option<String>,
+        message: String,
+    },
 
     #[error("Pest parsing failed: {0}")]
     PestError(#[from] Box<pest::error::Error<crate::parser::Rule>>),
@@ -185,8 +260,8 @@ pub enum LayoutError {
     #[error("Invalid graph structure for layout")]
     InvalidGraph,
 
-    #[error("Layout timeout")]
-    Timeout,
+    #[error("Layout engine '{engine}' timed out after {elapsed:?}")]
+    Timeout { engine: String, elapsed: Duration },
 }
 
 #[derive(Error, Debug)]
@@ -258,7 +333,7 @@ impl RecoverableError for ParseError {
                     instructions: "Check syntax and ensure proper formatting".to_string(),
                 },
             ],
-            ParseError::InvalidConfig(_) => vec![
+            ParseError::InvalidConfig { .. } => vec![
                 RecoveryStrategy::Default("Use default configuration".to_string()),
                 RecoveryStrategy::Manual {
                     instructions: "Verify YAML configuration format".to_string(),
@@ -282,7 +357,7 @@ impl RecoverableError for LayoutError {
                 },
                 RecoveryStrategy::Alternative("Use manual positioning".to_string()),
             ],
-            LayoutError::Timeout => vec![
+            LayoutError::Timeout { .. } => vec![
                 RecoveryStrategy::Retry {
                     suggestion: "Increase timeout or simplify graph".to_string(),
                 },
@@ -79,9 +79,13 @@ impl DiagramBuilder {
             containers: self.containers,
             groups: self.groups,
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             connections: Vec::new(),
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let _compiler = EDSLCompiler::new();
@@ -102,9 +106,13 @@ impl DiagramBuilder {
             containers: self.containers,
             groups: self.groups,
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             connections: Vec::new(),
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         }
     }
 }
@@ -185,6 +193,8 @@ impl EdgeBuilder {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: HashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             },
         }
     }
@@ -1,20 +1,37 @@
 // src/generator.rs
-use crate::ast::{ArrowType, ArrowheadType, FillStyle, GroupType, StrokeStyle};
-use crate::error::{GeneratorError, Result};
-use crate::igr::{ContainerData, EdgeData, GroupData, IntermediateGraph, NodeData};
-use crate::routing::EdgeRouter;
+use crate::ast::{
+    ArrowType, ArrowheadType, AttributeValue, CanvasSize, FillStyle, GroupType, PortSide,
+    StrokeStyle,
+};
+use crate::error::{EDSLError, GeneratorError, Result};
+use crate::igr::{
+    ContainerData, EdgeData, ExcalidrawAttributes, GroupData, IntermediateGraph, LineData,
+    NodeData, NoteData, NOTE_PADDING,
+};
+use crate::routing::{relative_point, ContainerContext, EdgeRouter};
+use crate::stable_hash;
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
+use petgraph::graph::NodeIndex;
 use petgraph::visit::{EdgeRef, IntoNodeReferences};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
 // String constants to avoid repeated allocations
 const EXCALIDRAW_TYPE: &str = "excalidraw";
+const EXCALIDRAW_VERSION: u32 = 2;
 const EXCALIDRAW_SOURCE: &str = "https://excalidraw-dsl.com";
 const DEFAULT_BACKGROUND_COLOR: &str = "#ffffff";
-const DEFAULT_STROKE_COLOR: &str = "#000000";
+pub(crate) const DEFAULT_STROKE_COLOR: &str = "#000000";
+const DARK_BACKGROUND_COLOR: &str = "#121212";
+const DARK_STROKE_COLOR: &str = "#e0e0e0";
+const CONTRAST_LIGHT_TEXT: &str = "#ffffff";
+const CONTRAST_DARK_TEXT: &str = "#1e1e1e";
+/// Background luminance below which white text contrasts better than black
+const CONTRAST_LUMINANCE_THRESHOLD: f64 = 0.179;
 const DEFAULT_FILL_STYLE: &str = "solid";
 const DEFAULT_STROKE_STYLE: &str = "solid";
 const TEXT_ALIGN_CENTER: &str = "center";
@@ -26,6 +43,24 @@ const ELEMENT_TYPE_ELLIPSE: &str = "ellipse";
 const ELEMENT_TYPE_DIAMOND: &str = "diamond";
 const ELEMENT_TYPE_ARROW: &str = "arrow";
 const ELEMENT_TYPE_TEXT: &str = "text";
+const ELEMENT_TYPE_LINE: &str = "line";
+const ICON_PADDING: f64 = 8.0;
+const CROWS_FOOT_SIZE: f64 = 14.0;
+const LABEL_BACKGROUND_PADDING: f64 = 4.0;
+const SHADOW_COLOR: &str = "#000000";
+const SHADOW_OFFSET: f64 = 6.0;
+/// How far the shadow extends past each side of its shape, a stand-in for blur
+const SHADOW_SPREAD: f64 = 2.0;
+const SHADOW_OPACITY: i32 = 20;
+const LEGEND_MARGIN: f64 = 40.0;
+const LEGEND_PADDING: f64 = 12.0;
+const LEGEND_SWATCH_WIDTH: f64 = 28.0;
+const LEGEND_SWATCH_HEIGHT: f64 = 18.0;
+const LEGEND_ROW_GAP: f64 = 8.0;
+const LEGEND_FONT_SIZE: f64 = 16.0;
+
+/// Default cap on the number of elements a single diagram may generate
+pub const DEFAULT_MAX_ELEMENTS: usize = 20_000;
 
 /// String interning pool for reducing memory allocations
 static STRING_POOL: Lazy<DashMap<String, Arc<str>>> = Lazy::new(DashMap::new);
@@ -79,6 +114,20 @@ fn intern_opt_string(s: Option<&String>) -> Option<String> {
     s.map(|s| intern_string(s).to_string())
 }
 
+/// Width and height spanned by a linear element's relative points
+fn points_extent(points: &[[i32; 2]]) -> (i32, i32) {
+    let (min_x, max_x) = points.iter().fold((i32::MAX, i32::MIN), |(lo, hi), p| {
+        (lo.min(p[0]), hi.max(p[0]))
+    });
+    let (min_y, max_y) = points.iter().fold((i32::MAX, i32::MIN), |(lo, hi), p| {
+        (lo.min(p[1]), hi.max(p[1]))
+    });
+    (
+        max_x.saturating_sub(min_x).max(0),
+        max_y.saturating_sub(min_y).max(0),
+    )
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExcalidrawFile {
     pub r#type: String,
@@ -98,7 +147,7 @@ pub struct AppState {
     pub view_background_color: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExcalidrawElementSkeleton {
     pub r#type: String,
     pub id: String,
@@ -106,7 +155,8 @@ pub struct ExcalidrawElementSkeleton {
     pub y: i32,
     pub width: i32,
     pub height: i32,
-    pub angle: i32,
+    /// Rotation in radians, clockwise around the element's center
+    pub angle: f64,
     #[serde(rename = "strokeColor")]
     pub stroke_color: String,
     #[serde(rename = "backgroundColor")]
@@ -167,9 +217,12 @@ pub struct ExcalidrawElementSkeleton {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "isContainer")]
     pub is_container: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "customData")]
+    pub custom_data: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElementBinding {
     #[serde(rename = "elementId")]
     pub element_id: String,
@@ -177,6 +230,154 @@ pub struct ElementBinding {
     pub gap: i32,
 }
 
+/// Excalidraw `groupIds` for elements that belong to DSL groups
+#[derive(Default)]
+struct GroupIdStacks {
+    groups: Vec<Vec<String>>,
+    nodes: std::collections::HashMap<NodeIndex, Vec<String>>,
+    containers: std::collections::HashMap<usize, Vec<String>>,
+}
+
+/// Walks the group/container hierarchy to build `GroupIdStacks`
+struct GroupStackResolver<'a> {
+    igr: &'a IntermediateGraph,
+    group_ids: Vec<String>,
+    container_ids: Vec<Option<String>>,
+    enclosing_group: std::collections::HashMap<usize, usize>,
+}
+
+impl GroupStackResolver<'_> {
+    fn group(&self, group_idx: usize) -> Vec<String> {
+        let group = &self.igr.groups[group_idx];
+        let mut stack = vec![self.group_ids[group_idx].clone()];
+        if let Some(parent) = group.parent_group {
+            stack.extend(self.group(parent));
+        } else if let Some(parent) = group.parent_container {
+            stack.extend(self.container(parent));
+        }
+        stack
+    }
+
+    fn container(&self, container_idx: usize) -> Vec<String> {
+        let container = &self.igr.containers[container_idx];
+        let mut stack: Vec<String> = self.container_ids[container_idx].iter().cloned().collect();
+        if let Some(parent) = container.parent_container {
+            stack.extend(self.container(parent));
+        } else if let Some(&group_idx) = self.enclosing_group.get(&container_idx) {
+            stack.extend(self.group(group_idx));
+        }
+        stack
+    }
+}
+
+impl GroupIdStacks {
+    /// Groups shared by both endpoints of an edge: the common tail of the stacks
+    fn shared(&self, source: NodeIndex, target: NodeIndex) -> Vec<String> {
+        let empty = Vec::new();
+        let source = self.nodes.get(&source).unwrap_or(&empty);
+        let target = self.nodes.get(&target).unwrap_or(&empty);
+        let common = source
+            .iter()
+            .rev()
+            .zip(target.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        source[source.len() - common..].to_vec()
+    }
+}
+
+/// Containers enclosing each node, used to route edges across container
+/// boundaries
+struct ContainerChains {
+    /// Every container holding a node, directly or through nesting
+    nodes: std::collections::HashMap<NodeIndex, std::collections::HashSet<usize>>,
+    /// Container drawn for each container virtual node
+    own: std::collections::HashMap<NodeIndex, usize>,
+}
+
+impl ContainerChains {
+    fn new(igr: &IntermediateGraph) -> Self {
+        let mut chains = Self {
+            nodes: std::collections::HashMap::new(),
+            own: std::collections::HashMap::new(),
+        };
+        for (idx, container) in igr.containers.iter().enumerate() {
+            let ancestors = Self::ancestors(igr, idx);
+            for &child in &container.children {
+                chains
+                    .nodes
+                    .entry(child)
+                    .or_default()
+                    .extend(ancestors.iter().copied());
+            }
+        }
+        for (node_idx, node) in igr.graph.node_references() {
+            if !node.is_virtual_container {
+                continue;
+            }
+            if let Some(&container_idx) = igr.container_map.get(&node.id) {
+                chains.own.insert(node_idx, container_idx);
+                // A container sits inside its parents, not inside itself
+                let mut ancestors = Self::ancestors(igr, container_idx);
+                ancestors.remove(0);
+                let chain = chains.nodes.entry(node_idx).or_default();
+                chain.extend(ancestors);
+                chain.remove(&container_idx);
+            }
+        }
+        chains
+    }
+
+    /// A container followed by its parents, innermost first
+    fn ancestors(igr: &IntermediateGraph, mut idx: usize) -> Vec<usize> {
+        let mut ancestors = vec![idx];
+        while let Some(parent) = igr.containers[idx].parent_container {
+            ancestors.push(parent);
+            idx = parent;
+        }
+        ancestors
+    }
+
+    /// Split the laid-out containers into those an edge leaves, enters and
+    /// passes by. Containers drawn for an endpoint, and their contents, are
+    /// left out.
+    fn context(
+        &self,
+        igr: &IntermediateGraph,
+        source: NodeIndex,
+        target: NodeIndex,
+    ) -> ContainerContext {
+        let empty = std::collections::HashSet::new();
+        let source_chain = self.nodes.get(&source).unwrap_or(&empty);
+        let target_chain = self.nodes.get(&target).unwrap_or(&empty);
+        let endpoints: Vec<usize> = [source, target]
+            .iter()
+            .filter_map(|node| self.own.get(node).copied())
+            .collect();
+
+        let mut context = ContainerContext::default();
+        for (idx, container) in igr.containers.iter().enumerate() {
+            let Some(bounds) = &container.bounds else {
+                continue;
+            };
+            match (source_chain.contains(&idx), target_chain.contains(&idx)) {
+                (true, false) => context.exits.push(bounds.clone()),
+                (false, true) => context.entries.push(bounds.clone()),
+                (true, true) => {}
+                (false, false) => {
+                    let inside_endpoint = Self::ancestors(igr, idx)
+                        .iter()
+                        .any(|ancestor| endpoints.contains(ancestor));
+                    if !inside_endpoint {
+                        context.others.push(bounds.clone());
+                    }
+                }
+            }
+        }
+        context
+    }
+}
+
 /// Generator for converting intermediate graph representation to Excalidraw format
 ///
 /// The ExcalidrawGenerator is responsible for the final step in the EDSL compilation
@@ -270,6 +471,72 @@ impl ExcalidrawGenerator {
         }
     }
 
+    /// Compute Excalidraw `groupIds` stacks for DSL group members.
+    ///
+    /// Each group gets one Excalidraw group id. A member's stack lists the id of
+    /// its innermost group first, followed by the ids of the enclosing groups, so
+    /// nested groups select and move together as they do in the editor. With
+    /// `include_containers`, containers get their own group id as well.
+    fn group_id_stacks(igr: &IntermediateGraph, include_containers: bool) -> GroupIdStacks {
+        let resolver = GroupStackResolver {
+            igr,
+            group_ids: igr
+                .groups
+                .iter()
+                .map(|_| format!("group_{}", Uuid::new_v4()))
+                .collect(),
+            container_ids: igr
+                .containers
+                .iter()
+                .map(|_| include_containers.then(|| format!("group_{}", Uuid::new_v4())))
+                .collect(),
+            enclosing_group: igr
+                .groups
+                .iter()
+                .enumerate()
+                .flat_map(|(group_idx, group)| {
+                    group
+                        .nested_containers
+                        .iter()
+                        .map(move |&container_idx| (container_idx, group_idx))
+                })
+                .collect(),
+        };
+
+        let mut stacks = GroupIdStacks {
+            groups: (0..igr.groups.len()).map(|g| resolver.group(g)).collect(),
+            ..Default::default()
+        };
+
+        for (group_idx, group) in igr.groups.iter().enumerate() {
+            for &node_idx in &group.children {
+                Self::assign_deepest(&mut stacks.nodes, node_idx, &stacks.groups[group_idx]);
+            }
+        }
+        for (container_idx, container) in igr.containers.iter().enumerate() {
+            let stack = resolver.container(container_idx);
+            for &node_idx in &container.children {
+                Self::assign_deepest(&mut stacks.nodes, node_idx, &stack);
+            }
+            if !stack.is_empty() {
+                stacks.containers.insert(container_idx, stack);
+            }
+        }
+
+        stacks
+    }
+
+    fn assign_deepest(
+        map: &mut std::collections::HashMap<NodeIndex, Vec<String>>,
+        key: NodeIndex,
+        stack: &[String],
+    ) {
+        let entry = map.entry(key).or_default();
+        if stack.len() > entry.len() {
+            *entry = stack.to_vec();
+        }
+    }
+
     /// Generate a complete Excalidraw file from an intermediate graph
     ///
     /// This is the main entry point for the generator. It creates a complete
@@ -291,26 +558,91 @@ impl ExcalidrawGenerator {
     /// let file = ExcalidrawGenerator::generate_file(&igr).unwrap();
     /// ```
     pub fn generate_file(igr: &IntermediateGraph) -> Result<ExcalidrawFile> {
-        let elements = Self::generate(igr)?;
+        Self::generate_file_with_limit(igr, DEFAULT_MAX_ELEMENTS)
+    }
+
+    /// Generate a complete Excalidraw file with a cap on the number of elements
+    pub fn generate_file_with_limit(
+        igr: &IntermediateGraph,
+        max_elements: usize,
+    ) -> Result<ExcalidrawFile> {
+        let elements = Self::generate_with_limit(igr, max_elements)?;
 
         Ok(ExcalidrawFile {
             r#type: istr!(EXCALIDRAW_TYPE),
-            version: 2,
-            source: istr!(EXCALIDRAW_SOURCE),
+            version: igr.global_config.file_version.unwrap_or(EXCALIDRAW_VERSION),
+            source: istr!(igr
+                .global_config
+                .file_source
+                .as_deref()
+                .unwrap_or(EXCALIDRAW_SOURCE)),
             elements,
             app_state: AppState {
                 grid_size: None,
-                view_background_color: istr!(DEFAULT_BACKGROUND_COLOR),
+                view_background_color: if Self::is_dark_theme(igr) {
+                    istr!(DARK_BACKGROUND_COLOR)
+                } else {
+                    istr!(DEFAULT_BACKGROUND_COLOR)
+                },
             },
             files: serde_json::json!({}),
         })
     }
 
     pub fn generate(igr: &IntermediateGraph) -> Result<Vec<ExcalidrawElementSkeleton>> {
+        Self::generate_with_limit(igr, DEFAULT_MAX_ELEMENTS)
+    }
+
+    /// Generate elements, failing with `EDSLError::Validation` if more than
+    /// `max_elements` would be produced
+    pub fn generate_with_limit(
+        igr: &IntermediateGraph,
+        max_elements: usize,
+    ) -> Result<Vec<ExcalidrawElementSkeleton>> {
+        // Every node and edge produces at least one element, so fail fast
+        // before doing any work on obviously oversized graphs
+        let minimum_elements = igr.graph.edge_count()
+            + igr
+                .graph
+                .node_weights()
+                .filter(|node| !node.is_virtual_container)
+                .count();
+        Self::check_element_limit(minimum_elements, max_elements)?;
+
+        let auto_contrast = igr.global_config.auto_contrast.unwrap_or(false);
         let mut elements = Vec::new();
         let mut node_id_map = std::collections::HashMap::new();
         let mut node_element_indices = std::collections::HashMap::new();
         let mut element_indices = std::collections::HashMap::new(); // Track all element indices
+        let mut z_orders = std::collections::HashMap::new(); // Element ID -> explicit z value
+
+        // Without backgrounds, containers become native groups instead
+        let show_backgrounds = igr.global_config.show_container_background.unwrap_or(true);
+        let group_stacks = Self::group_id_stacks(igr, !show_backgrounds);
+        let container_chains = ContainerChains::new(igr);
+
+        // Semantic keys (element or group id -> key) for stable ids
+        let mut stable_keys = std::collections::HashMap::new();
+        for (group, stack) in igr.groups.iter().zip(&group_stacks.groups) {
+            stable_keys.insert(stack[0].clone(), format!("groupid:{}", group.id));
+        }
+        if !show_backgrounds {
+            for (&container_idx, stack) in &group_stacks.containers {
+                let key = Self::container_key(igr, container_idx);
+                stable_keys.insert(stack[0].clone(), format!("groupid:{key}"));
+            }
+        }
+
+        // The canvas background sits behind everything, whatever the z values
+        if let (Some(canvas), Some(color)) = (
+            igr.global_config.canvas,
+            igr.global_config.canvas_background.as_deref(),
+        ) {
+            let background = Self::generate_canvas_background(canvas, color)?;
+            stable_keys.insert(background.id.clone(), "canvas".to_string());
+            z_orders.insert(background.id.clone(), i32::MIN);
+            elements.push(background);
+        }
 
         // Generate group elements first (visual grouping rectangles) in depth-first order
         let group_order = Self::get_group_render_order(&igr.groups);
@@ -318,38 +650,49 @@ impl ExcalidrawGenerator {
             let group = &igr.groups[group_idx];
             if let Some(mut group_element) = Self::generate_group(group)? {
                 let group_id = group_element.id.clone();
+                group_element.group_ids = group_stacks.groups[group_idx].clone();
+                if let Some(z) = group.attributes.z_index {
+                    z_orders.insert(group_id.clone(), z);
+                }
+
+                // Groups used as edge endpoints bind like containers
+                node_id_map.insert(group.id.clone(), group_id.clone());
+                stable_keys.insert(group_id.clone(), format!("group:{}", group.id));
+                if show_backgrounds {
+                    element_indices.insert(group_id.clone(), elements.len());
+                }
 
                 // Generate text element for group if it has a label
-                if let Some(label) = &group.label {
+                let mut text_element = None;
+                if let (Some(label), Some(bounds)) = (&group.label, &group.bounds) {
                     if !label.is_empty() {
-                        if let Some(bounds) = &group.bounds {
-                            let text_element = Self::generate_container_text_element(
-                                label,
-                                bounds.x + 10.0, // 10px padding from left edge
-                                bounds.y + 10.0, // 10px padding from top edge
-                                &group_id,
-                                group.attributes.font_size.unwrap_or(16.0),
-                                &group.attributes.font,
-                                &group.attributes.text_color,
-                            )?;
-
-                            // Add reference to text element in the group's boundElements
-                            group_element.bound_elements.push(serde_json::json!({
-                                "id": text_element.id.clone(),
-                                "type": ELEMENT_TYPE_TEXT
-                            }));
-
-                            elements.push(group_element);
-                            elements.push(text_element);
-                        } else {
-                            elements.push(group_element);
-                        }
-                    } else {
-                        elements.push(group_element);
+                        let mut text = Self::generate_container_text_element(
+                            label,
+                            bounds.x + 10.0, // 10px padding from left edge
+                            bounds.y + 10.0, // 10px padding from top edge
+                            &group_id,
+                            group.attributes.font_size.unwrap_or(16.0),
+                            &group.attributes.font,
+                            &group.attributes.text_color,
+                        )?;
+                        text.group_ids = group_element.group_ids.clone();
+
+                        // Add reference to text element in the group's boundElements
+                        group_element.bound_elements.push(serde_json::json!({
+                            "id": text.id.clone(),
+                            "type": ELEMENT_TYPE_TEXT
+                        }));
+                        stable_keys.insert(text.id.clone(), format!("group:{}:label", group.id));
+                        text_element = Some(text);
                     }
-                } else {
-                    elements.push(group_element);
                 }
+
+                Self::push_cluster_elements(
+                    &mut elements,
+                    group_element,
+                    text_element,
+                    show_backgrounds,
+                );
             }
         }
 
@@ -359,86 +702,185 @@ impl ExcalidrawGenerator {
             let container = &igr.containers[container_idx];
             if let Some(mut container_element) = Self::generate_container(container)? {
                 let container_element_id = container_element.id.clone();
+                if let Some(stack) = group_stacks.containers.get(&container_idx) {
+                    container_element.group_ids = stack.clone();
+                }
+                if let Some(z) = container.attributes.z_index {
+                    z_orders.insert(container_element_id.clone(), z);
+                }
 
                 // Map container ID to element ID for edge connections
                 if let Some(ref container_id) = container.id {
                     node_id_map.insert(container_id.clone(), container_element_id.clone());
                 }
+                stable_keys.insert(
+                    container_element_id.clone(),
+                    Self::container_key(igr, container_idx),
+                );
 
                 // Track the container element index before adding to elements
-                let container_index = elements.len();
-                element_indices.insert(container_element_id.clone(), container_index);
+                if show_backgrounds {
+                    element_indices.insert(container_element_id.clone(), elements.len());
+                }
 
                 // Generate text element for container if it has a label
-                if let Some(label) = &container.label {
+                let mut text_element = None;
+                if let (Some(label), Some(bounds)) = (&container.label, &container.bounds) {
                     if !label.is_empty() {
-                        if let Some(bounds) = &container.bounds {
-                            let text_element = Self::generate_container_text_element(
-                                label,
-                                bounds.x + 10.0, // 10px padding from left edge
-                                bounds.y + 10.0, // 10px padding from top edge
-                                &container_element_id,
-                                container.attributes.font_size.unwrap_or(16.0),
-                                &container.attributes.font,
-                                &container.attributes.text_color,
-                            )?;
-
-                            // Add reference to text element in the container's boundElements
-                            container_element.bound_elements.push(serde_json::json!({
-                                "id": text_element.id.clone(),
-                                "type": ELEMENT_TYPE_TEXT
-                            }));
-
-                            elements.push(container_element);
-                            elements.push(text_element);
-                        } else {
-                            elements.push(container_element);
-                        }
-                    } else {
-                        elements.push(container_element);
+                        let mut text = Self::generate_container_text_element(
+                            label,
+                            bounds.x + 10.0, // 10px padding from left edge
+                            bounds.y + 10.0, // 10px padding from top edge
+                            &container_element_id,
+                            container.attributes.font_size.unwrap_or(16.0),
+                            &container.attributes.font,
+                            &container.attributes.text_color,
+                        )?;
+                        text.group_ids = container_element.group_ids.clone();
+
+                        // Add reference to text element in the container's boundElements
+                        container_element.bound_elements.push(serde_json::json!({
+                            "id": text.id.clone(),
+                            "type": ELEMENT_TYPE_TEXT
+                        }));
+                        stable_keys.insert(
+                            text.id.clone(),
+                            format!("{}:label", Self::container_key(igr, container_idx)),
+                        );
+                        text_element = Some(text);
                     }
-                } else {
-                    elements.push(container_element);
                 }
+
+                Self::push_cluster_elements(
+                    &mut elements,
+                    container_element,
+                    text_element,
+                    show_backgrounds,
+                );
             }
         }
 
         // Generate node elements (skip virtual container nodes)
-        for (_, node_data) in igr.graph.node_references() {
+        for (node_idx, node_data) in igr.graph.node_references() {
             // Skip virtual container nodes - they're only for routing connections
             if node_data.is_virtual_container {
                 continue;
             }
             let element_id = format!("node_{}", Uuid::new_v4());
             let mut element = Self::generate_node(node_data, &element_id)?;
+            if let Some(stack) = group_stacks.nodes.get(&node_idx) {
+                element.group_ids = stack.clone();
+            }
             node_id_map.insert(node_data.id.clone(), element_id.clone());
+            stable_keys.insert(element_id.clone(), format!("node:{}", node_data.id));
+            if let Some(z) = node_data.attributes.z_index {
+                z_orders.insert(element_id.clone(), z);
+            }
 
             // Remove text from shape element (it will be a separate element)
             let label = element.text.take();
 
+            // Polygons are drawn as lines, which Excalidraw can't bind text
+            // or arrows to
+            let bindable = element.r#type != ELEMENT_TYPE_LINE;
+
+            // Excalidraw binds one text per shape, so an icon, and the label
+            // of an unbindable shape, is free text kept with its node by an
+            // innermost group of their own
+            if node_data.attributes.icon.is_some() || !bindable {
+                let node_group = format!("nodegroup_{}", Uuid::new_v4());
+                stable_keys.insert(node_group.clone(), format!("node:{}:group", node_data.id));
+                element.group_ids.insert(0, node_group);
+            }
+
+            // Pushed first so it renders behind the node
+            if node_data.attributes.shadow == Some(true)
+                && node_data.attributes.shape.as_deref() != Some("text")
+            {
+                let mut shadow = Self::generate_shadow(node_data)?;
+                shadow.group_ids = element.group_ids.clone();
+                stable_keys.insert(shadow.id.clone(), format!("node:{}:shadow", node_data.id));
+                if let Some(z) = node_data.attributes.z_index {
+                    z_orders.insert(shadow.id.clone(), z);
+                }
+                elements.push(shadow);
+            }
+
             // Track the actual index where this node element is pushed
             let node_index = elements.len();
             node_element_indices.insert(element_id.clone(), node_index);
-            element_indices.insert(element_id.clone(), node_index);
+            if bindable {
+                element_indices.insert(element_id.clone(), node_index);
+            }
+
+            // Generate icon element at the left edge, shifting the label to the right
+            let font_size = node_data.attributes.font_size.unwrap_or(20.0);
+            let mut label_x = node_data.x;
+            let mut icon_element = None;
+            if let Some(ref icon) = node_data.attributes.icon {
+                let icon_size = font_size * 1.2;
+                // Without a label the icon is centered instead
+                let offset = if node_data.shows_label() {
+                    -node_data.width / 2.0 + ICON_PADDING + icon_size / 2.0
+                } else {
+                    0.0
+                };
+                // A turned shape carries its icon round its center with it
+                let (sin, cos) = element.angle.sin_cos();
+                let mut icon_text = Self::generate_text_element(
+                    icon,
+                    node_data.x + offset * cos,
+                    node_data.y + offset * sin,
+                    &element_id,
+                    icon_size,
+                    &None,
+                    &None,
+                )?;
+                icon_text.container_id = None;
+                icon_text.angle = element.angle;
+                icon_text.group_ids = element.group_ids.clone();
+                stable_keys.insert(icon_text.id.clone(), format!("node:{}:icon", node_data.id));
+                if let Some(z) = node_data.attributes.z_index {
+                    z_orders.insert(icon_text.id.clone(), z);
+                }
+                label_x += (icon_size + ICON_PADDING) / 2.0;
+                icon_element = Some(icon_text);
+            }
 
             // Generate separate text element for node label
             if let Some(label) = label {
                 if !label.is_empty() {
-                    let text_element = Self::generate_text_element(
+                    let text_color = if auto_contrast {
+                        Self::contrast_text_color(&node_data.attributes)
+                    } else {
+                        node_data.attributes.text_color.clone()
+                    };
+                    let mut text_element = Self::generate_text_element(
                         &label,
-                        node_data.x,
+                        label_x,
                         node_data.y,
                         &element_id,
-                        node_data.attributes.font_size.unwrap_or(20.0),
+                        font_size,
                         &node_data.attributes.font,
-                        &node_data.attributes.text_color,
+                        &text_color,
                     )?;
-
-                    // Add reference to text element in the shape's boundElements
-                    element.bound_elements.push(serde_json::json!({
-                        "id": text_element.id.clone(),
-                        "type": ELEMENT_TYPE_TEXT
-                    }));
+                    text_element.group_ids = element.group_ids.clone();
+                    // Bound text turns with its shape
+                    text_element.angle = element.angle;
+
+                    if bindable {
+                        // Add reference to text element in the shape's boundElements
+                        element.bound_elements.push(serde_json::json!({
+                            "id": text_element.id.clone(),
+                            "type": ELEMENT_TYPE_TEXT
+                        }));
+                    } else {
+                        text_element.container_id = None;
+                        stable_keys.insert(
+                            text_element.id.clone(),
+                            format!("node:{}:text", node_data.id),
+                        );
+                    }
 
                     elements.push(element);
                     elements.push(text_element);
@@ -448,6 +890,9 @@ impl ExcalidrawGenerator {
             } else {
                 elements.push(element);
             }
+            if let Some(icon_element) = icon_element {
+                elements.push(icon_element);
+            }
         }
 
         // Generate edge elements and update node boundElements
@@ -456,28 +901,89 @@ impl ExcalidrawGenerator {
             let target_node = &igr.graph[edge_ref.target()];
             let edge_data = edge_ref.weight();
 
-            let source_element_id = node_id_map.get(&source_node.id).ok_or_else(|| {
-                GeneratorError::GenerationFailed(format!(
-                    "Source node {} not found in node map",
-                    source_node.id
-                ))
-            })?;
-            let target_element_id = node_id_map.get(&target_node.id).ok_or_else(|| {
-                GeneratorError::GenerationFailed(format!(
-                    "Target node {} not found in node map",
-                    target_node.id
-                ))
-            })?;
-
-            let edge_element = Self::generate_edge(
+            let source_element_id = Self::endpoint_element_id(&node_id_map, source_node, "Source")?;
+            let target_element_id = Self::endpoint_element_id(&node_id_map, target_node, "Target")?;
+
+            let mut edge_element = Self::generate_edge(
                 edge_data,
                 source_node,
                 target_node,
                 source_element_id,
                 target_element_id,
+                &container_chains.context(igr, edge_ref.source(), edge_ref.target()),
+                igr.global_config.edge_width,
             )?;
+            // Hidden container rectangles and polygon lines can't be bound to
+            if !element_indices.contains_key(source_element_id) {
+                edge_element.start_binding = None;
+            }
+            if !element_indices.contains_key(target_element_id) {
+                edge_element.end_binding = None;
+            }
+            let mut decorators = Self::generate_crows_foot_decorators(&mut edge_element, edge_data);
+
+            let edge_key = format!("edge:{}->{}", source_node.id, target_node.id);
+            stable_keys.insert(edge_element.id.clone(), edge_key.clone());
+            for decorator in &decorators {
+                stable_keys.insert(decorator.id.clone(), format!("{edge_key}:decorator"));
+                if let Some(crows_foot) = decorator.group_ids.first() {
+                    stable_keys.insert(crows_foot.clone(), format!("{edge_key}:crowsfoot"));
+                }
+            }
+
+            // Edges inside a group move with it; decorator groups stay innermost
+            let shared_groups = group_stacks.shared(edge_ref.source(), edge_ref.target());
+            edge_element.group_ids.extend(shared_groups.iter().cloned());
+            for decorator in &mut decorators {
+                decorator.group_ids.extend(shared_groups.iter().cloned());
+            }
+
+            // Edge labels are separate text elements bound to the arrow
+            let mut label_background = None;
+            let label_element = match edge_element.text.take() {
+                Some(label) if !label.is_empty() => {
+                    let (label_x, label_y) = Self::routed_midpoint(&edge_element);
+                    let attributes = &edge_data.attributes;
+                    let mut text_element = Self::generate_text_element(
+                        &label,
+                        label_x,
+                        label_y,
+                        &edge_element.id,
+                        attributes.font_size.unwrap_or(16.0),
+                        &attributes.font,
+                        &attributes
+                            .label_color
+                            .clone()
+                            .or(attributes.text_color.clone()),
+                    )?;
+                    text_element.group_ids = edge_element.group_ids.clone();
+                    edge_element.bound_elements.push(serde_json::json!({
+                        "id": text_element.id.clone(),
+                        "type": ELEMENT_TYPE_TEXT
+                    }));
+                    stable_keys.insert(text_element.id.clone(), format!("{edge_key}:label"));
+
+                    if let Some(ref color) = attributes.label_background {
+                        let mut background = Self::generate_label_background(&text_element, color)?;
+                        background.group_ids = edge_element.group_ids.clone();
+                        stable_keys.insert(
+                            background.id.clone(),
+                            format!("{edge_key}:label_background"),
+                        );
+                        label_background = Some(background);
+                    }
+                    Some(text_element)
+                }
+                _ => None,
+            };
 
             let edge_id = edge_element.id.clone();
+            if let Some(z) = edge_data.attributes.z_index {
+                z_orders.insert(edge_id.clone(), z);
+                for decorator in &decorators {
+                    z_orders.insert(decorator.id.clone(), z);
+                }
+            }
 
             // Update source element's boundElements to include this edge (works for both nodes and containers)
             if let Some(&source_index) = element_indices.get(source_element_id) {
@@ -500,78 +1006,595 @@ impl ExcalidrawGenerator {
             }
 
             elements.push(edge_element);
+            elements.extend(label_background);
+            elements.extend(label_element);
+            elements.extend(decorators);
+        }
+
+        // Notes are free-floating and don't take part in layout
+        for (note_idx, note) in igr.notes.iter().enumerate() {
+            let note_elements = Self::generate_note(note)?;
+            stable_keys.insert(note_elements[0].id.clone(), format!("note:{note_idx}"));
+            if let Some(z) = note.attributes.z_index {
+                for element in &note_elements {
+                    z_orders.insert(element.id.clone(), z);
+                }
+            }
+            elements.extend(note_elements);
+        }
+
+        // Standalone lines, like notes, bypass layout and bind to nothing
+        for (line_idx, line) in igr.lines.iter().enumerate() {
+            let line_element = Self::generate_line(line)?;
+            stable_keys.insert(line_element.id.clone(), format!("line:{line_idx}"));
+            if let Some(z) = line.attributes.z_index {
+                z_orders.insert(line_element.id.clone(), z);
+            }
+            elements.push(line_element);
+        }
+
+        if igr.global_config.legend.unwrap_or(false) {
+            let legend = Self::generate_legend(igr)?;
+            for (idx, element) in legend.iter().enumerate() {
+                stable_keys.insert(element.id.clone(), format!("legend:{idx}"));
+            }
+            if let Some(group_id) = legend.first().and_then(|e| e.group_ids.first()) {
+                stable_keys.insert(group_id.clone(), "legend".to_string());
+            }
+            elements.extend(legend);
+        }
+
+        if igr.global_config.stable_ids.unwrap_or(false) {
+            Self::stabilize_ids(&mut elements, &mut z_orders, &stable_keys);
+        } else if let Some(seed) = igr.global_config.random_seed {
+            Self::seed_ids(&mut elements, &mut z_orders, seed);
+        }
+        if let Some(seed) = igr.global_config.random_seed {
+            Self::seed_randomness(&mut elements, seed);
+        }
+        Self::normalize_bound_elements(&mut elements);
+
+        // Default black strokes and text would vanish on a dark canvas
+        if Self::is_dark_theme(igr) {
+            for element in &mut elements {
+                if element.stroke_color == DEFAULT_STROKE_COLOR {
+                    element.stroke_color = DARK_STROKE_COLOR.to_string();
+                }
+            }
         }
 
+        Self::check_element_limit(elements.len(), max_elements)?;
+        Self::apply_z_order(&mut elements, &z_orders);
+
         Ok(elements)
     }
 
-    fn generate_node(node_data: &NodeData, element_id: &str) -> Result<ExcalidrawElementSkeleton> {
-        let shape_type = match node_data.attributes.shape.as_deref() {
-            Some("rectangle") | None => ELEMENT_TYPE_RECTANGLE,
-            Some("ellipse") => ELEMENT_TYPE_ELLIPSE,
-            Some("diamond") => ELEMENT_TYPE_DIAMOND,
-            Some("cylinder") => ELEMENT_TYPE_ELLIPSE, // Approximate with ellipse for now
-            Some("text") => ELEMENT_TYPE_TEXT,
-            shape => {
-                return Err(GeneratorError::InvalidElementType(
-                    shape.unwrap_or("unknown").to_string(),
-                )
-                .into())
+    fn container_key(igr: &IntermediateGraph, container_idx: usize) -> String {
+        match &igr.containers[container_idx].id {
+            Some(id) => format!("container:{id}"),
+            None => format!("container:#{container_idx}"),
+        }
+    }
+
+    /// Replace random element and group ids with hashes of their semantic
+    /// keys (DSL ids, edge endpoints), so recompiling yields the same ids.
+    /// Elements without a key, like labels, derive one from their container,
+    /// or failing that from their type and position.
+    fn stabilize_ids(
+        elements: &mut [ExcalidrawElementSkeleton],
+        z_orders: &mut std::collections::HashMap<String, i32>,
+        stable_keys: &std::collections::HashMap<String, String>,
+    ) {
+        let mut used = std::collections::HashSet::new();
+        let mut mapping = std::collections::HashMap::new();
+        let mut stable_id = |old_id: &str, key: &str| {
+            let prefix = old_id.split('_').next().unwrap_or("element");
+            let mut suffix = 0;
+            loop {
+                let id = format!("{prefix}_{:016x}", stable_hash::hash(&(key, suffix)));
+                // Repeated keys, like parallel edges, get the next suffix
+                if used.insert(id.clone()) {
+                    return id;
+                }
+                suffix += 1;
             }
         };
 
-        // Validate coordinates
-        if !node_data.x.is_finite() || !node_data.y.is_finite() {
+        for element in elements.iter() {
+            let key = stable_keys.get(&element.id).cloned().or_else(|| {
+                let owner = element.container_id.as_ref()?;
+                Some(format!("{}:text", stable_keys.get(owner)?))
+            });
+            let key =
+                key.unwrap_or_else(|| format!("{}@{},{}", element.r#type, element.x, element.y));
+            mapping.insert(element.id.clone(), stable_id(&element.id, &key));
+            for (depth, group_id) in element.group_ids.iter().enumerate() {
+                if !mapping.contains_key(group_id) {
+                    let group_key = stable_keys
+                        .get(group_id)
+                        .cloned()
+                        .unwrap_or_else(|| format!("{key}:group{depth}"));
+                    mapping.insert(group_id.clone(), stable_id(group_id, &group_key));
+                }
+            }
+        }
+
+        Self::remap_ids(elements, z_orders, &mapping);
+    }
+
+    /// Replace random element and group ids with ones drawn from an RNG
+    /// seeded with `seed`, in element order
+    fn seed_ids(
+        elements: &mut [ExcalidrawElementSkeleton],
+        z_orders: &mut std::collections::HashMap<String, i32>,
+        seed: u64,
+    ) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut mapping = std::collections::HashMap::new();
+        let mut seeded_id = |old_id: &str| {
+            let prefix = old_id.split('_').next().unwrap_or("element");
+            format!("{prefix}_{:016x}", rng.random::<u64>())
+        };
+        for element in elements.iter() {
+            mapping.insert(element.id.clone(), seeded_id(&element.id));
+            for group_id in &element.group_ids {
+                if !mapping.contains_key(group_id) {
+                    mapping.insert(group_id.clone(), seeded_id(group_id));
+                }
+            }
+        }
+
+        Self::remap_ids(elements, z_orders, &mapping);
+    }
+
+    /// Draw every element's `seed` and `versionNonce` from an RNG seeded with
+    /// `seed`, and zero `updated`, so the output doesn't vary between runs
+    fn seed_randomness(elements: &mut [ExcalidrawElementSkeleton], seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for element in elements {
+            element.seed = rng.random::<i32>().abs();
+            element.version_nonce = rng.random::<i32>().abs();
+            element.updated = 0;
+        }
+    }
+
+    /// Rewrite element ids, and every reference to them, through `mapping`
+    fn remap_ids(
+        elements: &mut [ExcalidrawElementSkeleton],
+        z_orders: &mut std::collections::HashMap<String, i32>,
+        mapping: &std::collections::HashMap<String, String>,
+    ) {
+        let remap = |id: &mut String| {
+            if let Some(stable) = mapping.get(id.as_str()) {
+                *id = stable.clone();
+            }
+        };
+        for element in elements.iter_mut() {
+            remap(&mut element.id);
+            element.container_id.as_mut().map(remap);
+            element.group_ids.iter_mut().for_each(remap);
+            for binding in [&mut element.start_binding, &mut element.end_binding]
+                .into_iter()
+                .flatten()
+            {
+                remap(&mut binding.element_id);
+            }
+            for bound in &mut element.bound_elements {
+                if let Some(stable) = bound["id"].as_str().and_then(|id| mapping.get(id)) {
+                    bound["id"] = serde_json::Value::String(stable.clone());
+                }
+            }
+        }
+        *z_orders = z_orders
+            .drain()
+            .map(|(id, z)| (mapping.get(&id).cloned().unwrap_or(id), z))
+            .collect();
+    }
+
+    /// Drop repeated `boundElements` entries, such as the two ends of a
+    /// self-loop, and order them text first, then by id, so the output
+    /// doesn't depend on edge iteration order
+    fn normalize_bound_elements(elements: &mut [ExcalidrawElementSkeleton]) {
+        for element in elements {
+            let bound = &mut element.bound_elements;
+            bound.sort_by(|a, b| {
+                let key = |entry: &serde_json::Value| {
+                    (
+                        entry["type"].as_str() != Some(ELEMENT_TYPE_TEXT),
+                        entry["id"].as_str().unwrap_or_default().to_string(),
+                    )
+                };
+                key(a).cmp(&key(b))
+            });
+            bound.dedup_by(|a, b| a["id"] == b["id"]);
+        }
+    }
+
+    fn is_dark_theme(igr: &IntermediateGraph) -> bool {
+        igr.global_config.theme.as_deref() == Some("dark")
+    }
+
+    /// Label color for `auto_contrast`: an explicit text color wins, otherwise
+    /// white on dark backgrounds and black on light ones
+    fn contrast_text_color(attributes: &ExcalidrawAttributes) -> Option<String> {
+        if attributes.text_color.is_some() {
+            return attributes.text_color.clone();
+        }
+        let luminance = attributes
+            .background_color
+            .as_deref()
+            .and_then(relative_luminance)?;
+        let color = if luminance < CONTRAST_LUMINANCE_THRESHOLD {
+            CONTRAST_LIGHT_TEXT
+        } else {
+            CONTRAST_DARK_TEXT
+        };
+        Some(color.to_string())
+    }
+
+    /// Label text and background colors a node renders with, after theming
+    /// and `auto_contrast`; a node without a fill shows the canvas behind it
+    pub(crate) fn label_colors(
+        igr: &IntermediateGraph,
+        attributes: &ExcalidrawAttributes,
+    ) -> (String, String) {
+        let dark = Self::is_dark_theme(igr);
+        let text_color = if igr.global_config.auto_contrast.unwrap_or(false) {
+            Self::contrast_text_color(attributes)
+        } else {
+            attributes.text_color.clone()
+        };
+        let text_color = match text_color.as_deref() {
+            None | Some(DEFAULT_STROKE_COLOR) if dark => DARK_STROKE_COLOR.to_string(),
+            None => DEFAULT_STROKE_COLOR.to_string(),
+            Some(color) => color.to_string(),
+        };
+        let background = match attributes.background_color.as_deref() {
+            Some(color) if color != "transparent" => color.to_string(),
+            _ if dark => DARK_BACKGROUND_COLOR.to_string(),
+            _ => DEFAULT_BACKGROUND_COLOR.to_string(),
+        };
+        (text_color, background)
+    }
+
+    /// Generate a note anchored at its top-left corner: plain text, or a
+    /// sticky rectangle with the text bound inside when a background is set
+    fn generate_note(note: &NoteData) -> Result<Vec<ExcalidrawElementSkeleton>> {
+        if !note.x.is_finite() || !note.y.is_finite() {
             return Err(GeneratorError::InvalidCoordinate {
-                x: node_data.x,
-                y: node_data.y,
+                x: note.x,
+                y: note.y,
             }
             .into());
         }
 
+        let attributes = &note.attributes;
+        let font_size = attributes.font_size.unwrap_or(20.0);
+        let (text_width, text_height) = Self::calculate_text_dimensions(
+            &note.text,
+            font_size,
+            Self::convert_font_family(&attributes.font),
+        );
+
+        if attributes.background_color.is_none() {
+            let mut text = Self::generate_text_element(
+                &note.text,
+                note.x + text_width as f64 / 2.0,
+                note.y + text_height as f64 / 2.0,
+                "",
+                font_size,
+                &attributes.font,
+                &attributes.text_color,
+            )?;
+            text.id = format!("note_{}", Uuid::new_v4());
+            text.container_id = None;
+            text.text_align = Some(TEXT_ALIGN_LEFT.to_string());
+            text.vertical_align = Some(VERTICAL_ALIGN_TOP.to_string());
+            return Ok(vec![text]);
+        }
+
+        let width = attributes
+            .width
+            .unwrap_or(text_width as f64 + NOTE_PADDING * 2.0);
+        let height = attributes
+            .height
+            .unwrap_or(text_height as f64 + NOTE_PADDING * 2.0);
+        let sticky = NodeData {
+            id: String::new(),
+            label: note.text.clone(),
+            attributes: ExcalidrawAttributes {
+                shape: Some("rectangle".to_string()),
+                ..attributes.clone()
+            },
+            x: note.x + width / 2.0,
+            y: note.y + height / 2.0,
+            width,
+            height,
+            is_virtual_container: false,
+            metadata: Default::default(),
+        };
+        let element_id = format!("note_{}", Uuid::new_v4());
+        let mut rectangle = Self::generate_node(&sticky, &element_id)?;
+        rectangle.text = None;
+
+        let text = Self::generate_text_element(
+            &note.text,
+            sticky.x,
+            sticky.y,
+            &element_id,
+            font_size,
+            &attributes.font,
+            &attributes.text_color,
+        )?;
+        rectangle.bound_elements.push(serde_json::json!({
+            "id": text.id.clone(),
+            "type": ELEMENT_TYPE_TEXT
+        }));
+        Ok(vec![rectangle, text])
+    }
+
+    /// Generate an unbound `line` element between two absolute points
+    fn generate_line(line: &LineData) -> Result<ExcalidrawElementSkeleton> {
+        for &(x, y) in [&line.from, &line.to] {
+            if !x.is_finite() || !y.is_finite() {
+                return Err(GeneratorError::InvalidCoordinate { x, y }.into());
+            }
+        }
+
+        let attributes = &line.attributes;
+        let dx = (line.to.0 - line.from.0).round() as i32;
+        let dy = (line.to.1 - line.from.1).round() as i32;
         Ok(ExcalidrawElementSkeleton {
-            r#type: shape_type.to_string(),
-            id: element_id.to_string(),
-            x: (node_data.x - node_data.width / 2.0).round() as i32,
-            y: (node_data.y - node_data.height / 2.0).round() as i32,
-            width: node_data.width.round() as i32,
-            height: node_data.height.round() as i32,
-            angle: 0,
-            stroke_color: node_data
-                .attributes
+            r#type: ELEMENT_TYPE_LINE.to_string(),
+            id: format!("line_{}", Uuid::new_v4()),
+            x: line.from.0.round() as i32,
+            y: line.from.1.round() as i32,
+            width: dx.abs(),
+            height: dy.abs(),
+            angle: 0.0,
+            stroke_color: attributes
                 .stroke_color
                 .clone()
                 .unwrap_or_else(|| DEFAULT_STROKE_COLOR.to_string()),
-            background_color: node_data
-                .attributes
-                .background_color
-                .clone()
-                .unwrap_or_else(|| "transparent".to_string()),
-            fill_style: Self::convert_fill_style(&node_data.attributes.fill_style),
-            stroke_width: node_data.attributes.stroke_width.unwrap_or(2.0).round() as i32,
-            stroke_style: Self::convert_stroke_style(&node_data.attributes.stroke_style),
-            roughness: node_data.attributes.roughness.unwrap_or(0),
+            background_color: "transparent".to_string(),
+            fill_style: DEFAULT_FILL_STYLE.to_string(),
+            stroke_width: attributes.stroke_width.unwrap_or(2.0).round() as i32,
+            stroke_style: Self::convert_stroke_style(&attributes.stroke_style),
+            roughness: attributes.roughness.unwrap_or(0),
             opacity: 100,
-            text: if node_data.label.is_empty() {
-                None
-            } else {
-                Some(node_data.label.clone())
-            },
-            font_size: node_data.attributes.font_size.unwrap_or(20.0).round() as i32,
-            font_family: Self::convert_font_family(&node_data.attributes.font),
+            text: None,
+            font_size: 16,
+            font_family: 1,
             start_binding: None,
             end_binding: None,
             start_arrowhead: None,
             end_arrowhead: None,
-            points: None,
+            points: Some(vec![[0, 0], [dx, dy]]),
             seed: rand::random::<i32>().abs(),
             version: 1,
             version_nonce: rand::random::<i32>().abs(),
             is_deleted: false,
             group_ids: vec![],
             frame_id: None,
-            roundness: if shape_type == ELEMENT_TYPE_RECTANGLE {
-                if let Some(rounded) = node_data.attributes.rounded {
+            roundness: None,
+            bound_elements: vec![],
+            updated: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
+                .as_millis() as u64,
+            link: None,
+            locked: false,
+            container_id: None,
+            text_align: None,
+            vertical_align: None,
+            is_container: None,
+            custom_data: None,
+        })
+    }
+
+    /// Element id an edge endpoint binds to. Clusters drawn without a
+    /// rectangle keep their node id, which leaves that end unbound.
+    fn endpoint_element_id<'a>(
+        node_id_map: &'a std::collections::HashMap<String, String>,
+        node: &'a NodeData,
+        role: &str,
+    ) -> Result<&'a str> {
+        match node_id_map.get(&node.id) {
+            Some(element_id) => Ok(element_id),
+            None if node.is_virtual_container => Ok(&node.id),
+            None => Err(GeneratorError::GenerationFailed(format!(
+                "{role} node {} not found in node map",
+                node.id
+            ))
+            .into()),
+        }
+    }
+
+    /// Push a container/group rectangle and its label. With backgrounds hidden
+    /// only the label is kept, as free text grouped with the members.
+    fn push_cluster_elements(
+        elements: &mut Vec<ExcalidrawElementSkeleton>,
+        rectangle: ExcalidrawElementSkeleton,
+        label: Option<ExcalidrawElementSkeleton>,
+        show_background: bool,
+    ) {
+        if show_background {
+            elements.push(rectangle);
+            elements.extend(label);
+        } else if let Some(mut label) = label {
+            label.container_id = None;
+            elements.push(label);
+        }
+    }
+
+    fn check_element_limit(count: usize, max_elements: usize) -> Result<()> {
+        if count > max_elements {
+            return Err(EDSLError::Validation {
+                message: format!(
+                    "Diagram produces {count} elements, exceeding the limit of {max_elements}"
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reorder elements by their `z` attribute so higher values render on top.
+    ///
+    /// Excalidraw stacks elements in array order. Elements without an explicit
+    /// `z` default to 0, and the sort is stable so the natural order (groups and
+    /// containers behind nodes, nodes behind edges) is kept within equal z.
+    /// Bound text elements inherit the z of their container.
+    fn apply_z_order(
+        elements: &mut [ExcalidrawElementSkeleton],
+        z_orders: &std::collections::HashMap<String, i32>,
+    ) {
+        if z_orders.is_empty() {
+            return;
+        }
+
+        elements.sort_by_key(|element| {
+            z_orders
+                .get(&element.id)
+                .or_else(|| {
+                    element
+                        .container_id
+                        .as_ref()
+                        .and_then(|container_id| z_orders.get(container_id))
+                })
+                .copied()
+                .unwrap_or(0)
+        });
+    }
+
+    /// Rectangle covering the fixed canvas, from the origin to its size
+    fn generate_canvas_background(
+        canvas: CanvasSize,
+        color: &str,
+    ) -> Result<ExcalidrawElementSkeleton> {
+        let node = NodeData {
+            id: String::new(),
+            label: String::new(),
+            attributes: ExcalidrawAttributes {
+                shape: Some("rectangle".to_string()),
+                stroke_color: Some("transparent".to_string()),
+                background_color: Some(color.to_string()),
+                fill_style: Some(FillStyle::Solid),
+                roughness: Some(0),
+                ..Default::default()
+            },
+            x: canvas.width / 2.0,
+            y: canvas.height / 2.0,
+            width: canvas.width,
+            height: canvas.height,
+            is_virtual_container: false,
+            metadata: Default::default(),
+        };
+        let mut element = Self::generate_node(&node, &format!("canvas_{}", Uuid::new_v4()))?;
+        element.text = None;
+        Ok(element)
+    }
+
+    /// Excalidraw has no shadows, so draw an offset, slightly larger and
+    /// translucent copy of the node's shape instead
+    fn generate_shadow(node_data: &NodeData) -> Result<ExcalidrawElementSkeleton> {
+        let shadow = NodeData {
+            id: node_data.id.clone(),
+            label: String::new(),
+            attributes: ExcalidrawAttributes {
+                shape: node_data.attributes.shape.clone(),
+                stroke_color: Some("transparent".to_string()),
+                background_color: Some(SHADOW_COLOR.to_string()),
+                fill_style: Some(FillStyle::Solid),
+                rounded: node_data.attributes.rounded,
+                angle: node_data.attributes.angle,
+                roughness: Some(0),
+                ..Default::default()
+            },
+            x: node_data.x + SHADOW_OFFSET,
+            y: node_data.y + SHADOW_OFFSET,
+            width: node_data.width + 2.0 * SHADOW_SPREAD,
+            height: node_data.height + 2.0 * SHADOW_SPREAD,
+            is_virtual_container: false,
+            metadata: Default::default(),
+        };
+        let mut element = Self::generate_node(&shadow, &format!("shadow_{}", Uuid::new_v4()))?;
+        element.text = None;
+        element.opacity = SHADOW_OPACITY;
+        Ok(element)
+    }
+
+    fn generate_node(node_data: &NodeData, element_id: &str) -> Result<ExcalidrawElementSkeleton> {
+        let shape_type = match node_data.attributes.shape.as_deref() {
+            Some("rectangle") | None => ELEMENT_TYPE_RECTANGLE,
+            Some("ellipse") => ELEMENT_TYPE_ELLIPSE,
+            Some("diamond") => ELEMENT_TYPE_DIAMOND,
+            Some("cylinder") => ELEMENT_TYPE_ELLIPSE, // Approximate with ellipse for now
+            Some("text") => ELEMENT_TYPE_TEXT,
+            Some("triangle") | Some("parallelogram") | Some("hexagon") => ELEMENT_TYPE_LINE,
+            shape => {
+                return Err(GeneratorError::InvalidElementType(
+                    shape.unwrap_or("unknown").to_string(),
+                )
+                .into())
+            }
+        };
+
+        // Validate coordinates
+        if !node_data.x.is_finite() || !node_data.y.is_finite() {
+            return Err(GeneratorError::InvalidCoordinate {
+                x: node_data.x,
+                y: node_data.y,
+            }
+            .into());
+        }
+
+        Ok(ExcalidrawElementSkeleton {
+            r#type: shape_type.to_string(),
+            id: element_id.to_string(),
+            x: (node_data.x - node_data.width / 2.0).round() as i32,
+            y: (node_data.y - node_data.height / 2.0).round() as i32,
+            width: node_data.width.round() as i32,
+            height: node_data.height.round() as i32,
+            angle: node_data.attributes.angle.map_or(0.0, f64::to_radians),
+            stroke_color: node_data
+                .attributes
+                .stroke_color
+                .clone()
+                .unwrap_or_else(|| DEFAULT_STROKE_COLOR.to_string()),
+            background_color: node_data
+                .attributes
+                .background_color
+                .clone()
+                .unwrap_or_else(|| "transparent".to_string()),
+            fill_style: Self::convert_fill_style(&node_data.attributes.fill_style),
+            stroke_width: node_data.attributes.stroke_width.unwrap_or(2.0).round() as i32,
+            stroke_style: Self::convert_stroke_style(&node_data.attributes.stroke_style),
+            roughness: node_data.attributes.roughness.unwrap_or(0),
+            opacity: 100,
+            text: if node_data.shows_label() {
+                Some(node_data.label.clone())
+            } else {
+                None
+            },
+            font_size: node_data.attributes.font_size.unwrap_or(20.0).round() as i32,
+            font_family: Self::convert_font_family(&node_data.attributes.font),
+            start_binding: None,
+            end_binding: None,
+            start_arrowhead: None,
+            end_arrowhead: None,
+            points: node_data
+                .attributes
+                .shape
+                .as_deref()
+                .and_then(|shape| Self::polygon_points(shape, node_data.width, node_data.height)),
+            seed: rand::random::<i32>().abs(),
+            version: 1,
+            version_nonce: rand::random::<i32>().abs(),
+            is_deleted: false,
+            group_ids: vec![],
+            frame_id: None,
+            roundness: if shape_type == ELEMENT_TYPE_RECTANGLE {
+                if let Some(rounded) = node_data.attributes.rounded {
                     // Convert rounded value to Excalidraw format
                     // Excalidraw uses a radius value for rounded corners
                     Some(serde_json::json!({"type": 3, "value": rounded}))
@@ -594,19 +1617,103 @@ impl ExcalidrawGenerator {
             text_align: None,
             vertical_align: None,
             is_container: None,
+            custom_data: Self::custom_data(&node_data.metadata, &node_data.attributes),
         })
     }
 
+    /// `customData` for an element: its `meta` entries plus any `tooltip`.
+    /// A `tooltip` key in `meta` is more specific and wins.
+    fn custom_data(
+        metadata: &std::collections::BTreeMap<String, AttributeValue>,
+        attributes: &ExcalidrawAttributes,
+    ) -> Option<serde_json::Map<String, serde_json::Value>> {
+        if metadata.is_empty() && attributes.tooltip.is_none() {
+            return None;
+        }
+        let mut entries: serde_json::Map<String, serde_json::Value> = metadata
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    AttributeValue::String(text) | AttributeValue::Color(text) => {
+                        serde_json::Value::from(text.as_str())
+                    }
+                    AttributeValue::Number(number) => serde_json::Value::from(*number),
+                    AttributeValue::Boolean(flag) => serde_json::Value::from(*flag),
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        if let Some(ref tooltip) = attributes.tooltip {
+            entries
+                .entry("tooltip")
+                .or_insert_with(|| serde_json::Value::from(tooltip.as_str()));
+        }
+        Some(entries)
+    }
+
+    /// Compute the closed outline for polygon shapes that Excalidraw has no
+    /// native element for. Points are relative to the node's top-left corner
+    /// and the last point repeats the first to close the shape.
+    fn polygon_points(shape: &str, width: f64, height: f64) -> Option<Vec<[i32; 2]>> {
+        let w = width.round() as i32;
+        let h = height.round() as i32;
+
+        let points = match shape {
+            "triangle" => vec![[w / 2, 0], [w, h], [0, h], [w / 2, 0]],
+            "parallelogram" => {
+                let skew = (width * 0.2).round() as i32;
+                vec![[skew, 0], [w, 0], [w - skew, h], [0, h], [skew, 0]]
+            }
+            "hexagon" => {
+                let inset = (width * 0.25).round() as i32;
+                vec![
+                    [inset, 0],
+                    [w - inset, 0],
+                    [w, h / 2],
+                    [w - inset, h],
+                    [inset, h],
+                    [0, h / 2],
+                    [inset, 0],
+                ]
+            }
+            _ => return None,
+        };
+
+        Some(points)
+    }
+
     fn generate_edge(
         edge_data: &EdgeData,
         source_node: &NodeData,
         target_node: &NodeData,
         source_element_id: &str,
         target_element_id: &str,
+        containers: &ContainerContext,
+        edge_width: Option<f64>,
     ) -> Result<ExcalidrawElementSkeleton> {
-        // Calculate connection points
-        let start_point = Self::calculate_connection_point(source_node, target_node, true);
-        let end_point = Self::calculate_connection_point(target_node, source_node, false);
+        // Calculate connection points, aimed at the nearest waypoint if any
+        let first_hop = edge_data
+            .waypoints
+            .first()
+            .copied()
+            .unwrap_or((target_node.x, target_node.y));
+        let last_hop = edge_data
+            .waypoints
+            .last()
+            .copied()
+            .unwrap_or((source_node.x, source_node.y));
+        let start_point = Self::calculate_connection_point(
+            source_node,
+            first_hop,
+            true,
+            edge_data.attributes.start_port,
+        );
+        let end_point = Self::calculate_connection_point(
+            target_node,
+            last_hop,
+            false,
+            edge_data.attributes.end_port,
+        );
 
         let element_type = edge_data.arrow_type.to_excalidraw_type();
 
@@ -623,14 +1730,35 @@ impl ExcalidrawGenerator {
             .into());
         }
 
+        // Points are relative to the arrow's origin, the rounded start point
+        let points = if edge_data.waypoints.is_empty() {
+            EdgeRouter::route_edge_with_containers(
+                start_point,
+                end_point,
+                source_node,
+                target_node,
+                edge_data.routing_type,
+                edge_data.attributes.curvature,
+                containers,
+            )
+        } else {
+            EdgeRouter::route_through_waypoints(
+                start_point,
+                end_point,
+                &edge_data.waypoints,
+                edge_data.routing_type,
+            )
+        };
+        let (width, height) = points_extent(&points);
+
         Ok(ExcalidrawElementSkeleton {
             r#type: element_type.to_string(),
             id: format!("edge_{}", Uuid::new_v4()),
             x: start_point.0.round() as i32,
             y: start_point.1.round() as i32,
-            width: (end_point.0 - start_point.0).round() as i32,
-            height: (end_point.1 - start_point.1).round() as i32,
-            angle: 0,
+            width,
+            height,
+            angle: 0.0,
             stroke_color: edge_data
                 .attributes
                 .stroke_color
@@ -638,8 +1766,18 @@ impl ExcalidrawGenerator {
                 .unwrap_or_else(|| DEFAULT_STROKE_COLOR.to_string()),
             background_color: "transparent".to_string(),
             fill_style: DEFAULT_FILL_STYLE.to_string(),
-            stroke_width: edge_data.attributes.stroke_width.unwrap_or(2.0).round() as i32,
-            stroke_style: Self::convert_stroke_style(&edge_data.attributes.stroke_style),
+            stroke_width: edge_data
+                .attributes
+                .stroke_width
+                .or_else(|| edge_data.arrow_type.edge_stroke_width(edge_width))
+                .unwrap_or(2.0)
+                .round() as i32,
+            stroke_style: Self::convert_stroke_style(
+                &edge_data
+                    .attributes
+                    .stroke_style
+                    .or_else(|| edge_data.arrow_type.default_stroke_style()),
+            ),
             roughness: edge_data.attributes.roughness.unwrap_or(0),
             opacity: 100,
             text: edge_data.label.clone(),
@@ -655,25 +1793,23 @@ impl ExcalidrawGenerator {
                 focus: 0,
                 gap: 1,
             }),
-            start_arrowhead: Self::convert_arrowhead(&edge_data.attributes.start_arrowhead)
-                .or_else(|| match edge_data.arrow_type {
-                    ArrowType::DoubleArrow => Some(ELEMENT_TYPE_ARROW.to_string()),
-                    _ => None,
-                }),
-            end_arrowhead: Self::convert_arrowhead(&edge_data.attributes.end_arrowhead).or_else(
-                || match edge_data.arrow_type {
-                    ArrowType::SingleArrow => Some(ELEMENT_TYPE_ARROW.to_string()),
+            // An explicit arrowhead (including `none` and crow's-foot markers)
+            // replaces the arrow type's default head
+            start_arrowhead: match edge_data.attributes.start_arrowhead {
+                Some(_) => Self::convert_arrowhead(&edge_data.attributes.start_arrowhead),
+                None => match edge_data.arrow_type {
                     ArrowType::DoubleArrow => Some(ELEMENT_TYPE_ARROW.to_string()),
                     _ => None,
                 },
-            ),
-            points: Some(EdgeRouter::route_edge(
-                start_point,
-                end_point,
-                source_node,
-                target_node,
-                edge_data.routing_type,
-            )),
+            },
+            end_arrowhead: match edge_data.attributes.end_arrowhead {
+                Some(_) => Self::convert_arrowhead(&edge_data.attributes.end_arrowhead),
+                None => edge_data
+                    .arrow_type
+                    .has_end_arrowhead()
+                    .then(|| ELEMENT_TYPE_ARROW.to_string()),
+            },
+            points: Some(points),
             seed: rand::random::<i32>().abs(),
             version: 1,
             version_nonce: rand::random::<i32>().abs(),
@@ -692,9 +1828,359 @@ impl ExcalidrawGenerator {
             text_align: None,
             vertical_align: None,
             is_container: None,
+            custom_data: Self::custom_data(&Default::default(), &edge_data.attributes),
         })
     }
 
+    /// Absolute point halfway along an edge's routed path
+    fn routed_midpoint(edge: &ExcalidrawElementSkeleton) -> (f64, f64) {
+        let points: Vec<(f64, f64)> = match edge.points {
+            Some(ref points) if !points.is_empty() => points
+                .iter()
+                .map(|p| ((edge.x + p[0]) as f64, (edge.y + p[1]) as f64))
+                .collect(),
+            _ => vec![(edge.x as f64, edge.y as f64)],
+        };
+        let segment_length = |a: (f64, f64), b: (f64, f64)| (b.0 - a.0).hypot(b.1 - a.1);
+
+        let total: f64 = points
+            .windows(2)
+            .map(|pair| segment_length(pair[0], pair[1]))
+            .sum();
+        let mut remaining = total / 2.0;
+        for pair in points.windows(2) {
+            let length = segment_length(pair[0], pair[1]);
+            if length > 0.0 && remaining <= length {
+                let t = remaining / length;
+                return (
+                    pair[0].0 + (pair[1].0 - pair[0].0) * t,
+                    pair[0].1 + (pair[1].1 - pair[0].1) * t,
+                );
+            }
+            remaining -= length;
+        }
+        points[points.len() - 1]
+    }
+
+    /// Draw crow's-foot cardinality markers for an edge's start/end arrowheads.
+    ///
+    /// Markers are line/ellipse elements placed along the final segment at each
+    /// end. They share a group with the edge so they move together in the editor.
+    fn generate_crows_foot_decorators(
+        edge: &mut ExcalidrawElementSkeleton,
+        edge_data: &EdgeData,
+    ) -> Vec<ExcalidrawElementSkeleton> {
+        let points = match edge.points {
+            Some(ref points) if points.len() >= 2 => points.clone(),
+            _ => return Vec::new(),
+        };
+        let absolute = |p: [i32; 2]| ((edge.x + p[0]) as f64, (edge.y + p[1]) as f64);
+
+        let ends = [
+            (
+                &edge_data.attributes.start_arrowhead,
+                absolute(points[0]),
+                absolute(points[1]),
+            ),
+            (
+                &edge_data.attributes.end_arrowhead,
+                absolute(points[points.len() - 1]),
+                absolute(points[points.len() - 2]),
+            ),
+        ];
+
+        let mut decorators = Vec::new();
+        for (arrowhead, tip, previous) in ends {
+            let Some(arrowhead) = arrowhead.as_ref().filter(|a| a.is_crows_foot()) else {
+                continue;
+            };
+
+            // Unit vector pointing from the edge into the node, and its normal
+            let (dx, dy) = (tip.0 - previous.0, tip.1 - previous.1);
+            let length = (dx * dx + dy * dy).sqrt();
+            if length < f64::EPSILON {
+                continue;
+            }
+            let (ux, uy) = (dx / length, dy / length);
+            let (nx, ny) = (-uy, ux);
+            let along = |d: f64| (tip.0 - ux * d, tip.1 - uy * d);
+            let bar = |d: f64| {
+                let (cx, cy) = along(d);
+                vec![
+                    (
+                        cx + nx * CROWS_FOOT_SIZE / 2.0,
+                        cy + ny * CROWS_FOOT_SIZE / 2.0,
+                    ),
+                    (
+                        cx - nx * CROWS_FOOT_SIZE / 2.0,
+                        cy - ny * CROWS_FOOT_SIZE / 2.0,
+                    ),
+                ]
+            };
+            let foot = || {
+                vec![
+                    (
+                        tip.0 + nx * CROWS_FOOT_SIZE / 2.0,
+                        tip.1 + ny * CROWS_FOOT_SIZE / 2.0,
+                    ),
+                    along(CROWS_FOOT_SIZE),
+                    (
+                        tip.0 - nx * CROWS_FOOT_SIZE / 2.0,
+                        tip.1 - ny * CROWS_FOOT_SIZE / 2.0,
+                    ),
+                ]
+            };
+
+            let line = |points: Vec<(f64, f64)>| Self::generate_decorator_line(edge, &points);
+            let circle = |d: f64| Self::generate_decorator_circle(edge, along(d));
+            match arrowhead {
+                ArrowheadType::One => {
+                    decorators.push(line(bar(CROWS_FOOT_SIZE * 0.6)));
+                    decorators.push(line(bar(CROWS_FOOT_SIZE)));
+                }
+                ArrowheadType::Many => decorators.push(line(foot())),
+                ArrowheadType::ZeroOrOne => {
+                    decorators.push(line(bar(CROWS_FOOT_SIZE * 0.6)));
+                    decorators.push(circle(CROWS_FOOT_SIZE * 1.5));
+                }
+                ArrowheadType::OneOrMany => {
+                    decorators.push(line(foot()));
+                    decorators.push(line(bar(CROWS_FOOT_SIZE * 1.4)));
+                }
+                ArrowheadType::ZeroOrMany => {
+                    decorators.push(line(foot()));
+                    decorators.push(circle(CROWS_FOOT_SIZE * 1.8));
+                }
+                _ => {}
+            }
+        }
+
+        if !decorators.is_empty() {
+            let group_id = format!("crowsfoot_{}", Uuid::new_v4());
+            edge.group_ids.push(group_id.clone());
+            for decorator in &mut decorators {
+                decorator.group_ids.push(group_id.clone());
+            }
+        }
+
+        decorators
+    }
+
+    fn generate_decorator_line(
+        edge: &ExcalidrawElementSkeleton,
+        points: &[(f64, f64)],
+    ) -> ExcalidrawElementSkeleton {
+        let (origin_x, origin_y) = points[0];
+        let relative: Vec<[i32; 2]> = points
+            .iter()
+            .map(|&point| relative_point(points[0], point))
+            .collect();
+        let (width, height) = points_extent(&relative);
+
+        ExcalidrawElementSkeleton {
+            r#type: ELEMENT_TYPE_LINE.to_string(),
+            id: format!("decorator_{}", Uuid::new_v4()),
+            x: origin_x.round() as i32,
+            y: origin_y.round() as i32,
+            width,
+            height,
+            points: Some(relative),
+            roundness: None,
+            start_binding: None,
+            end_binding: None,
+            start_arrowhead: None,
+            end_arrowhead: None,
+            text: None,
+            stroke_style: DEFAULT_STROKE_STYLE.to_string(),
+            seed: rand::random::<i32>().abs(),
+            version_nonce: rand::random::<i32>().abs(),
+            ..edge.clone()
+        }
+    }
+
+    fn generate_decorator_circle(
+        edge: &ExcalidrawElementSkeleton,
+        center: (f64, f64),
+    ) -> ExcalidrawElementSkeleton {
+        let radius = CROWS_FOOT_SIZE / 3.0;
+        ExcalidrawElementSkeleton {
+            r#type: ELEMENT_TYPE_ELLIPSE.to_string(),
+            id: format!("decorator_{}", Uuid::new_v4()),
+            x: (center.0 - radius).round() as i32,
+            y: (center.1 - radius).round() as i32,
+            width: (radius * 2.0).round() as i32,
+            height: (radius * 2.0).round() as i32,
+            background_color: DEFAULT_BACKGROUND_COLOR.to_string(),
+            points: None,
+            roundness: None,
+            start_binding: None,
+            end_binding: None,
+            start_arrowhead: None,
+            end_arrowhead: None,
+            text: None,
+            stroke_style: DEFAULT_STROKE_STYLE.to_string(),
+            seed: rand::random::<i32>().abs(),
+            version_nonce: rand::random::<i32>().abs(),
+            ..edge.clone()
+        }
+    }
+
+    /// Default stroke and background colors of a semantic group type
+    fn semantic_group_colors(group_type: &str) -> (&'static str, &'static str) {
+        match group_type {
+            "service" => ("#8b5cf6", "#f3e8ff"),
+            "layer" => ("#f59e0b", "#fef3c7"),
+            "component" => ("#10b981", "#d1fae5"),
+            "subsystem" => ("#ef4444", "#fee2e2"),
+            "zone" => ("#06b6d4", "#cffafe"),
+            "cluster" => ("#ec4899", "#fce7f3"),
+            _ => ("#6b7280", "#f3f4f6"),
+        }
+    }
+
+    /// Borderless box filled with `color`, padded around a label's text
+    fn generate_label_background(
+        text: &ExcalidrawElementSkeleton,
+        color: &str,
+    ) -> Result<ExcalidrawElementSkeleton> {
+        let width = text.width as f64 + LABEL_BACKGROUND_PADDING * 2.0;
+        let height = text.height as f64 + LABEL_BACKGROUND_PADDING * 2.0;
+        let node = NodeData {
+            id: String::new(),
+            label: String::new(),
+            attributes: ExcalidrawAttributes {
+                shape: Some("rectangle".to_string()),
+                stroke_color: Some("transparent".to_string()),
+                background_color: Some(color.to_string()),
+                fill_style: Some(FillStyle::Solid),
+                roughness: Some(0),
+                ..Default::default()
+            },
+            x: text.x as f64 + text.width as f64 / 2.0,
+            y: text.y as f64 + text.height as f64 / 2.0,
+            width,
+            height,
+            is_virtual_container: false,
+            metadata: Default::default(),
+        };
+        let mut element = Self::generate_node(&node, &format!("label_bg_{}", Uuid::new_v4()))?;
+        element.text = None;
+        Ok(element)
+    }
+
+    /// A legend box to the right of the diagram with one color swatch and
+    /// name per semantic group type in use, in order of first use. Returns
+    /// no elements when the diagram has no semantic groups.
+    fn generate_legend(igr: &IntermediateGraph) -> Result<Vec<ExcalidrawElementSkeleton>> {
+        // Each type is shown in the colors of its first group
+        let mut group_types: Vec<(&str, &GroupData)> = Vec::new();
+        for group in &igr.groups {
+            if let GroupType::SemanticGroup(group_type) = &group.group_type {
+                if !group_types.iter().any(|(name, _)| name == group_type) {
+                    group_types.push((group_type, group));
+                }
+            }
+        }
+        if group_types.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Right edge and top of everything laid out
+        let bounds = igr.bounding_box();
+        let (right, top) = (bounds.x + bounds.width, bounds.y);
+
+        let font_family = Self::convert_font_family(&None);
+        let label_width = group_types
+            .iter()
+            .map(|(name, _)| Self::calculate_text_dimensions(name, LEGEND_FONT_SIZE, font_family).0)
+            .max()
+            .unwrap_or(0) as f64;
+        let row_height = LEGEND_SWATCH_HEIGHT.max(LEGEND_FONT_SIZE * 1.25);
+        let x = right + LEGEND_MARGIN;
+        let width = LEGEND_PADDING * 3.0 + LEGEND_SWATCH_WIDTH + label_width;
+        let height = LEGEND_PADDING * 2.0
+            + group_types.len() as f64 * row_height
+            + (group_types.len() - 1) as f64 * LEGEND_ROW_GAP;
+
+        let rectangle = |id: String, x: f64, y: f64, w: f64, h: f64, stroke: &str, bg: &str| {
+            let node = NodeData {
+                id: String::new(),
+                label: String::new(),
+                attributes: ExcalidrawAttributes {
+                    shape: Some("rectangle".to_string()),
+                    stroke_color: Some(stroke.to_string()),
+                    background_color: Some(bg.to_string()),
+                    roughness: Some(0),
+                    ..Default::default()
+                },
+                x: x + w / 2.0,
+                y: y + h / 2.0,
+                width: w,
+                height: h,
+                is_virtual_container: false,
+                metadata: Default::default(),
+            };
+            let mut element = Self::generate_node(&node, &id)?;
+            element.text = None;
+            Ok::<_, EDSLError>(element)
+        };
+
+        let group_id = format!("legend_{}", Uuid::new_v4());
+        let mut elements = vec![rectangle(
+            format!("legend_{}", Uuid::new_v4()),
+            x,
+            top,
+            width,
+            height,
+            DEFAULT_STROKE_COLOR,
+            "transparent",
+        )?];
+        for (row, &(group_type, group)) in group_types.iter().enumerate() {
+            let row_y = top + LEGEND_PADDING + row as f64 * (row_height + LEGEND_ROW_GAP);
+            let (default_stroke, default_bg) = Self::semantic_group_colors(group_type);
+            let stroke = group
+                .attributes
+                .stroke_color
+                .as_deref()
+                .unwrap_or(default_stroke);
+            let background = group
+                .attributes
+                .background_color
+                .as_deref()
+                .unwrap_or(default_bg);
+            elements.push(rectangle(
+                format!("legend_{}", Uuid::new_v4()),
+                x + LEGEND_PADDING,
+                row_y + (row_height - LEGEND_SWATCH_HEIGHT) / 2.0,
+                LEGEND_SWATCH_WIDTH,
+                LEGEND_SWATCH_HEIGHT,
+                stroke,
+                background,
+            )?);
+
+            let (text_width, _) =
+                Self::calculate_text_dimensions(group_type, LEGEND_FONT_SIZE, font_family);
+            let mut text = Self::generate_text_element(
+                group_type,
+                x + LEGEND_PADDING * 2.0 + LEGEND_SWATCH_WIDTH + text_width as f64 / 2.0,
+                row_y + row_height / 2.0,
+                "",
+                LEGEND_FONT_SIZE,
+                &None,
+                &None,
+            )?;
+            text.id = format!("legend_{}", Uuid::new_v4());
+            text.container_id = None;
+            text.text_align = Some(TEXT_ALIGN_LEFT.to_string());
+            elements.push(text);
+        }
+
+        for element in &mut elements {
+            element.group_ids = vec![group_id.clone()];
+        }
+        Ok(elements)
+    }
+
     fn generate_group(group: &GroupData) -> Result<Option<ExcalidrawElementSkeleton>> {
         let bounds = match &group.bounds {
             Some(bounds) => bounds,
@@ -714,7 +2200,7 @@ impl ExcalidrawGenerator {
                     .background_color
                     .clone()
                     .unwrap_or_else(|| "#dbeafe".to_string()),
-                group.attributes.stroke_style.unwrap_or(StrokeStyle::Dashed),
+                StrokeStyle::Dashed,
                 group.attributes.stroke_width.unwrap_or(2.0),
             ),
             GroupType::BasicGroup => (
@@ -728,20 +2214,11 @@ impl ExcalidrawGenerator {
                     .background_color
                     .clone()
                     .unwrap_or_else(|| "#f3f4f6".to_string()),
-                group.attributes.stroke_style.unwrap_or(StrokeStyle::Solid),
+                StrokeStyle::Solid,
                 group.attributes.stroke_width.unwrap_or(1.0),
             ),
             GroupType::SemanticGroup(group_type) => {
-                // Different colors for different semantic types
-                let (default_stroke, default_bg) = match group_type.as_str() {
-                    "service" => ("#8b5cf6", "#f3e8ff"),
-                    "layer" => ("#f59e0b", "#fef3c7"),
-                    "component" => ("#10b981", "#d1fae5"),
-                    "subsystem" => ("#ef4444", "#fee2e2"),
-                    "zone" => ("#06b6d4", "#cffafe"),
-                    "cluster" => ("#ec4899", "#fce7f3"),
-                    _ => ("#6b7280", "#f3f4f6"),
-                };
+                let (default_stroke, default_bg) = Self::semantic_group_colors(group_type);
                 (
                     group
                         .attributes
@@ -753,12 +2230,15 @@ impl ExcalidrawGenerator {
                         .background_color
                         .clone()
                         .unwrap_or_else(|| default_bg.to_string()),
-                    group.attributes.stroke_style.unwrap_or(StrokeStyle::Solid),
+                    StrokeStyle::Solid,
                     group.attributes.stroke_width.unwrap_or(2.0),
                 )
             }
         };
 
+        // An explicit strokeStyle always wins over the group type's default
+        let stroke_style = group.attributes.stroke_style.or(Some(stroke_style));
+
         Ok(Some(ExcalidrawElementSkeleton {
             r#type: ELEMENT_TYPE_RECTANGLE.to_string(),
             id: format!("group_{}", Uuid::new_v4()),
@@ -766,12 +2246,12 @@ impl ExcalidrawGenerator {
             y: bounds.y.round() as i32,
             width: bounds.width.round() as i32,
             height: bounds.height.round() as i32,
-            angle: 0,
+            angle: 0.0,
             stroke_color,
             background_color,
             fill_style: Self::convert_fill_style(&group.attributes.fill_style),
             stroke_width: stroke_width.round() as i32,
-            stroke_style: Self::convert_stroke_style(&Some(stroke_style)),
+            stroke_style: Self::convert_stroke_style(&stroke_style),
             roughness: group.attributes.roughness.unwrap_or(0),
             opacity: 30, // Semi-transparent background for groups
             text: None,  // Text will be a separate element
@@ -800,6 +2280,7 @@ impl ExcalidrawGenerator {
             text_align: None,
             vertical_align: None,
             is_container: Some(true),
+            custom_data: None,
         }))
     }
 
@@ -816,7 +2297,7 @@ impl ExcalidrawGenerator {
             y: bounds.y.round() as i32,
             width: bounds.width.round() as i32,
             height: bounds.height.round() as i32,
-            angle: 0,
+            angle: 0.0,
             stroke_color: container
                 .attributes
                 .stroke_color
@@ -858,6 +2339,7 @@ impl ExcalidrawGenerator {
             text_align: None,
             vertical_align: None,
             is_container: Some(true),
+            custom_data: None,
         }))
     }
 
@@ -871,7 +2353,7 @@ impl ExcalidrawGenerator {
     fn convert_stroke_style(stroke_style: &Option<StrokeStyle>) -> String {
         match stroke_style {
             Some(style) => style.to_excalidraw_style().to_string(),
-            None => DEFAULT_FILL_STYLE.to_string(),
+            None => DEFAULT_STROKE_STYLE.to_string(),
         }
     }
 
@@ -902,29 +2384,35 @@ impl ExcalidrawGenerator {
             _ => 0.6,
         };
 
-        // Improved character width calculation with better handling for common characters
-        let effective_length = text
-            .chars()
-            .map(|c| {
-                match c {
-                    // Narrow characters
-                    'i' | 'l' | '.' | '!' | '|' | '\'' | '`' | 'I' | 'j' | 'f' | 't' => 0.4,
-                    // Wide characters
-                    'w' | 'm' | 'W' | 'M' | '@' | '%' | '#' => 1.4,
-                    // Uppercase letters (generally wider)
-                    'A'..='Z' => 1.15,
-                    // Space (reduced to save space)
-                    ' ' => 0.35,
-                    // Numbers and common punctuation
-                    '0'..='9' | '(' | ')' | '[' | ']' | '{' | '}' | '-' | '_' | '=' | '+' => 0.9,
-                    // Default for most lowercase and other characters
-                    _ => 1.0,
-                }
-            })
-            .sum::<f64>();
+        // Improved character width calculation with better handling for common characters;
+        // multi-line text is as wide as its longest line
+        let line_length = |line: &str| {
+            line.chars()
+                .map(|c| {
+                    match c {
+                        // Narrow characters
+                        'i' | 'l' | '.' | '!' | '|' | '\'' | '`' | 'I' | 'j' | 'f' | 't' => 0.4,
+                        // Wide characters
+                        'w' | 'm' | 'W' | 'M' | '@' | '%' | '#' => 1.4,
+                        // Uppercase letters (generally wider)
+                        'A'..='Z' => 1.15,
+                        // Space (reduced to save space)
+                        ' ' => 0.35,
+                        // Numbers and common punctuation
+                        '0'..='9' | '(' | ')' | '[' | ']' | '{' | '}' | '-' | '_' | '=' | '+' => {
+                            0.9
+                        }
+                        // Default for most lowercase and other characters
+                        _ => 1.0,
+                    }
+                })
+                .sum::<f64>()
+        };
+        let effective_length = text.lines().map(line_length).fold(0.0, f64::max);
+        let line_count = text.lines().count().max(1);
 
         let text_width = (effective_length * font_size * char_width_multiplier).round() as i32;
-        let text_height = (font_size * 1.3).round() as i32; // Slightly more height for better appearance
+        let text_height = (font_size * 1.3 * line_count as f64).round() as i32; // Slightly more height for better appearance
 
         (text_width, text_height)
     }
@@ -953,7 +2441,7 @@ impl ExcalidrawGenerator {
             y: text_y,
             width: text_width,
             height: text_height,
-            angle: 0,
+            angle: 0.0,
             stroke_color: text_color
                 .clone()
                 .unwrap_or_else(|| DEFAULT_STROKE_COLOR.to_string()),
@@ -989,6 +2477,7 @@ impl ExcalidrawGenerator {
             text_align: Some(TEXT_ALIGN_LEFT.to_string()),
             vertical_align: Some(VERTICAL_ALIGN_TOP.to_string()),
             is_container: None,
+            custom_data: None,
         })
     }
 
@@ -1016,7 +2505,7 @@ impl ExcalidrawGenerator {
             y: text_y,
             width: text_width,
             height: text_height,
-            angle: 0,
+            angle: 0.0,
             stroke_color: text_color
                 .clone()
                 .unwrap_or_else(|| DEFAULT_STROKE_COLOR.to_string()),
@@ -1052,21 +2541,34 @@ impl ExcalidrawGenerator {
             text_align: Some(TEXT_ALIGN_CENTER.to_string()),
             vertical_align: Some(VERTICAL_ALIGN_MIDDLE.to_string()),
             is_container: None,
+            custom_data: None,
         })
     }
 
     fn calculate_connection_point(
         from_node: &NodeData,
-        to_node: &NodeData,
+        toward: (f64, f64),
         _is_start: bool,
+        port: Option<PortSide>,
     ) -> (f64, f64) {
+        // An explicit port attaches at the midpoint of that side of the box
+        if let Some(side) = port {
+            let half_width = from_node.width / 2.0;
+            let half_height = from_node.height / 2.0;
+            return match side {
+                PortSide::Top => (from_node.x, from_node.y - half_height),
+                PortSide::Bottom => (from_node.x, from_node.y + half_height),
+                PortSide::Left => (from_node.x - half_width, from_node.y),
+                PortSide::Right => (from_node.x + half_width, from_node.y),
+            };
+        }
+
         // Simple edge connection calculation
         // Calculate the direction from center to center
         let center_x = from_node.x;
         let center_y = from_node.y;
 
-        let target_center_x = to_node.x;
-        let target_center_y = to_node.y;
+        let (target_center_x, target_center_y) = toward;
 
         let dx = target_center_x - center_x;
         let dy = target_center_y - center_y;
@@ -1104,6 +2606,34 @@ impl ExcalidrawGenerator {
     }
 }
 
+/// WCAG relative luminance (0 = black, 1 = white) of a `#rgb` or `#rrggbb`
+/// color; other color formats are not understood
+fn relative_luminance(color: &str) -> Option<f64> {
+    let hex = color.strip_prefix('#')?;
+    let expanded: String = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| -> Option<f64> {
+        let value = u8::from_str_radix(expanded.get(i..i + 2)?, 16).ok()? as f64 / 255.0;
+        Some(if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        })
+    };
+    Some(0.2126 * channel(0)? + 0.7152 * channel(2)? + 0.0722 * channel(4)?)
+}
+
+/// WCAG contrast ratio (1 to 21) between two colors understood by
+/// [`relative_luminance`]
+pub(crate) fn contrast_ratio(foreground: &str, background: &str) -> Option<f64> {
+    let a = relative_luminance(foreground)?;
+    let b = relative_luminance(background)?;
+    Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1122,6 +2652,7 @@ mod tests {
             width: 120.0,
             height: 60.0,
             is_virtual_container: false,
+            metadata: Default::default(),
         };
 
         let result = ExcalidrawGenerator::generate_node(&node_data, "test_id").unwrap();
@@ -1134,11 +2665,116 @@ mod tests {
         assert_eq!(result.height, 60);
     }
 
-    #[test]
-    fn test_generate_from_igr() {
-        let document = ParsedDocument {
-            config: GlobalConfig::default(),
-            component_types: HashMap::new(),
+    fn polygon_node(shape: &str) -> NodeData {
+        NodeData {
+            id: shape.to_string(),
+            label: "Step".to_string(),
+            attributes: ExcalidrawAttributes {
+                shape: Some(shape.to_string()),
+                ..Default::default()
+            },
+            x: 100.0,
+            y: 100.0,
+            width: 120.0,
+            height: 60.0,
+            is_virtual_container: false,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_generate_triangle_node() {
+        let element =
+            ExcalidrawGenerator::generate_node(&polygon_node("triangle"), "node_1").unwrap();
+
+        assert_eq!(element.r#type, ELEMENT_TYPE_LINE);
+        let points = element.points.unwrap();
+        assert_eq!(points.len(), 4);
+        assert_eq!(points.first(), points.last());
+        assert_eq!(points[0], [60, 0]);
+    }
+
+    #[test]
+    fn test_generate_parallelogram_node() {
+        let element =
+            ExcalidrawGenerator::generate_node(&polygon_node("parallelogram"), "node_1").unwrap();
+
+        assert_eq!(element.r#type, ELEMENT_TYPE_LINE);
+        let points = element.points.unwrap();
+        assert_eq!(points.len(), 5);
+        assert_eq!(points.first(), points.last());
+    }
+
+    #[test]
+    fn test_generate_hexagon_node() {
+        let element =
+            ExcalidrawGenerator::generate_node(&polygon_node("hexagon"), "node_1").unwrap();
+
+        assert_eq!(element.r#type, ELEMENT_TYPE_LINE);
+        let points = element.points.unwrap();
+        assert_eq!(points.len(), 7);
+        assert_eq!(points.first(), points.last());
+        // Label stays centered on the node box
+        assert_eq!(element.x, 40);
+        assert_eq!(element.y, 70);
+        assert_eq!(element.text, Some("Step".to_string()));
+    }
+
+    #[test]
+    fn test_stabilize_ids_without_keys_is_deterministic() {
+        let igr = crate::parse_and_layout("a[A]\nb[B]\na -> b\n", "dagre").unwrap();
+        // With no semantic keys at all, ids come from type and position
+        let ids = || {
+            let mut elements = ExcalidrawGenerator::generate(&igr).unwrap();
+            ExcalidrawGenerator::stabilize_ids(
+                &mut elements,
+                &mut std::collections::HashMap::new(),
+                &std::collections::HashMap::new(),
+            );
+            elements
+                .into_iter()
+                .map(|e| (e.id, e.group_ids))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(), ids());
+    }
+
+    #[test]
+    fn test_polygon_nodes_bind_no_text_or_arrows() {
+        let input = "a[Start] { shape: triangle; }\nb[End]\na -> b\n";
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let polygon = elements
+            .iter()
+            .find(|e| e.r#type == ELEMENT_TYPE_LINE)
+            .unwrap();
+        assert!(polygon.bound_elements.is_empty());
+
+        // The label is free text grouped with the outline
+        let label = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("Start"))
+            .unwrap();
+        assert_eq!(label.container_id, None);
+        assert_eq!(polygon.group_ids.len(), 1);
+        assert_eq!(label.group_ids, polygon.group_ids);
+
+        // The arrow binds only to the rectangle
+        let arrow = elements
+            .iter()
+            .find(|e| e.r#type == ELEMENT_TYPE_ARROW)
+            .unwrap();
+        assert!(arrow.start_binding.is_none());
+        assert!(arrow.end_binding.is_some());
+    }
+
+    #[test]
+    fn test_generate_from_igr() {
+        let document = ParsedDocument {
+            config: GlobalConfig::default(),
+            component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -1162,10 +2798,15 @@ mod tests {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: HashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             }],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let mut igr = IntermediateGraph::from_ast(document).unwrap();
@@ -1178,8 +2819,8 @@ mod tests {
 
         let elements = ExcalidrawGenerator::generate(&igr).unwrap();
 
-        // Should have 2 nodes + 2 text elements + 1 edge = 5 elements
-        assert_eq!(elements.len(), 5);
+        // Should have 2 nodes + 3 text elements + 1 edge = 6 elements
+        assert_eq!(elements.len(), 6);
 
         // Check node elements
         let node_elements: Vec<_> = elements
@@ -1196,12 +2837,975 @@ mod tests {
         assert_eq!(edge_elements.len(), 1);
 
         let edge = &edge_elements[0];
-        assert_eq!(edge.text, Some("Edge".to_string()));
+        assert_eq!(edge.text, None);
         assert!(edge.start_binding.is_some());
         assert!(edge.end_binding.is_some());
         assert_eq!(edge.end_arrowhead, Some(ELEMENT_TYPE_ARROW.to_string()));
     }
 
+    #[test]
+    fn test_auto_contrast_label_colors() {
+        let input = r##"---
+auto_contrast: true
+---
+dark[Dark] { backgroundColor: "#1e3a8a"; }
+light[Light] { backgroundColor: "#fef9c3"; }
+short[Short] { backgroundColor: "#000"; }
+explicit[Explicit] { backgroundColor: "#1e3a8a"; color: "#facc15"; }
+plain[Plain]
+"##;
+        let label_color = |input: &str, label: &str| {
+            let document = crate::parser::parse_edsl(input).unwrap();
+            let igr = IntermediateGraph::from_ast(document).unwrap();
+            let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+            elements
+                .iter()
+                .find(|e| e.r#type == ELEMENT_TYPE_TEXT && e.text.as_deref() == Some(label))
+                .unwrap()
+                .stroke_color
+                .clone()
+        };
+
+        assert_eq!(label_color(input, "Dark"), CONTRAST_LIGHT_TEXT);
+        assert_eq!(label_color(input, "Light"), CONTRAST_DARK_TEXT);
+        assert_eq!(label_color(input, "Short"), CONTRAST_LIGHT_TEXT);
+        assert_eq!(label_color(input, "Explicit"), "#facc15");
+        assert_eq!(label_color(input, "Plain"), DEFAULT_STROKE_COLOR);
+
+        // Off by default
+        let input = input.replace("auto_contrast: true", "auto_contrast: false");
+        assert_eq!(label_color(&input, "Dark"), DEFAULT_STROKE_COLOR);
+    }
+
+    #[test]
+    fn test_relative_luminance() {
+        assert_eq!(relative_luminance("#000000"), Some(0.0));
+        assert!((relative_luminance("#ffffff").unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(relative_luminance("#fff"), relative_luminance("#ffffff"));
+        assert!(relative_luminance("#ff0000").unwrap() < relative_luminance("#00ff00").unwrap());
+        assert_eq!(relative_luminance("transparent"), None);
+        assert_eq!(relative_luminance("#12345"), None);
+        assert_eq!(relative_luminance("#gggggg"), None);
+
+        assert!((contrast_ratio("#000", "#fff").unwrap() - 21.0).abs() < 1e-9);
+        assert_eq!(
+            contrast_ratio("#fff", "#000"),
+            contrast_ratio("#000", "#fff")
+        );
+        assert_eq!(contrast_ratio("#123456", "#123456"), Some(1.0));
+    }
+
+    #[test]
+    fn test_edge_label_is_bound_text_element() {
+        let input = r#"
+a[Source]
+b[Target]
+a -> b: sends { fontSize: 14; }
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let arrow = elements
+            .iter()
+            .find(|e| e.r#type == ELEMENT_TYPE_ARROW)
+            .unwrap();
+        assert_eq!(arrow.text, None);
+
+        let label = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("sends"))
+            .unwrap();
+        assert_eq!(label.r#type, ELEMENT_TYPE_TEXT);
+        assert_eq!(label.container_id.as_ref(), Some(&arrow.id));
+        assert_eq!(label.font_size, 14);
+        assert!(arrow
+            .bound_elements
+            .iter()
+            .any(|bound| bound["id"] == label.id.as_str() && bound["type"] == ELEMENT_TYPE_TEXT));
+
+        // The label is centered on the routed midpoint of the arrow
+        let (mid_x, mid_y) = ExcalidrawGenerator::routed_midpoint(arrow);
+        let center_x = label.x as f64 + label.width as f64 / 2.0;
+        let center_y = label.y as f64 + label.height as f64 / 2.0;
+        assert!((center_x - mid_x).abs() <= 1.0);
+        assert!((center_y - mid_y).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_node_angle_in_radians() {
+        let input = "box[X] { angle: 90; }\nflat[Y]\n";
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let label = |text: &str| {
+            elements
+                .iter()
+                .find(|e| e.r#type == ELEMENT_TYPE_TEXT && e.text.as_deref() == Some(text))
+                .unwrap()
+        };
+        let shape = |text: &str| {
+            elements
+                .iter()
+                .find(|e| Some(&e.id) == label(text).container_id.as_ref())
+                .unwrap()
+        };
+        assert!((shape("X").angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        assert_eq!(label("X").angle, shape("X").angle);
+        assert_eq!(shape("Y").angle, 0.0);
+        assert_eq!(label("Y").angle, 0.0);
+
+        // An icon turns with its shape and stays at the turned left edge,
+        // which is now the top
+        let input = "box[X] { angle: 90; icon: \"🔒\"; }\n";
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let icon = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("🔒"))
+            .unwrap();
+        let node = igr.graph.node_weights().next().unwrap();
+        assert_eq!(icon.angle, std::f64::consts::FRAC_PI_2);
+        let icon_center_x = icon.x as f64 + icon.width as f64 / 2.0;
+        let icon_center_y = icon.y as f64 + icon.height as f64 / 2.0;
+        assert!((icon_center_x - node.x).abs() <= 1.0);
+        assert!(icon_center_y < node.y - 10.0);
+    }
+
+    #[test]
+    fn test_legend_lists_semantic_group_types() {
+        let input = r##"
+---
+legend: true
+---
+service "Auth" {
+  login[Login]
+}
+layer "Data" {
+  style: { backgroundColor: "#e0f2fe"; strokeColor: "#0369a1"; }
+  db[Database]
+}
+service "Billing" {
+  invoice[Invoice]
+}
+login -> db
+"##;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let legend: Vec<_> = elements
+            .iter()
+            .filter(|e| e.id.starts_with("legend_"))
+            .collect();
+        // The box, then a swatch and a name per type in order of first use
+        assert_eq!(legend.len(), 5);
+        let names: Vec<_> = legend.iter().filter_map(|e| e.text.as_deref()).collect();
+        assert_eq!(names, ["service", "layer"]);
+        assert_eq!(legend[1].background_color, "#f3e8ff");
+        // A group's own colors carry over to its swatch
+        assert_eq!(legend[3].background_color, "#e0f2fe");
+        assert_eq!(legend[3].stroke_color, "#0369a1");
+        assert!(legend
+            .iter()
+            .all(|e| e.group_ids == legend[0].group_ids && e.group_ids.len() == 1));
+
+        // Placed clear of the diagram
+        let diagram_right = elements
+            .iter()
+            .filter(|e| !e.id.starts_with("legend_"))
+            .map(|e| e.x + e.width)
+            .max()
+            .unwrap();
+        assert!(legend.iter().all(|e| e.x > diagram_right));
+
+        // Off by default
+        let igr = crate::parse_and_layout(&input.replace("legend: true", ""), "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        assert!(!elements.iter().any(|e| e.id.starts_with("legend_")));
+    }
+
+    #[test]
+    fn test_edge_label_color_and_background() {
+        let input = r##"
+a[A]
+b[B]
+c[C]
+a -> b: "styled" { color: "#ff0000"; labelColor: "#333333"; labelBackground: "#ffffff"; }
+b -> c: plain
+"##;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let label = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("styled"))
+            .unwrap();
+        assert_eq!(label.stroke_color, "#333333");
+
+        let backgrounds: Vec<_> = elements
+            .iter()
+            .filter(|e| e.id.starts_with("label_bg_"))
+            .collect();
+        assert_eq!(backgrounds.len(), 1);
+        let background = backgrounds[0];
+        assert_eq!(background.background_color, "#ffffff");
+        assert_eq!(background.stroke_color, "transparent");
+        assert!(background.x < label.x && background.y < label.y);
+        assert!(background.x + background.width > label.x + label.width);
+        assert!(background.y + background.height > label.y + label.height);
+        // Drawn beneath the text
+        let position = |id: &str| elements.iter().position(|e| e.id == id).unwrap();
+        assert!(position(&background.id) < position(&label.id));
+    }
+
+    #[test]
+    fn test_edge_routes_through_waypoints() {
+        let input = r#"
+a[A]
+b[B]
+a -> b via (200, 150), (300, 150): "detour"
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let edge = elements.iter().find(|e| e.r#type == "arrow").unwrap();
+        let points = edge.points.as_ref().unwrap();
+        assert_eq!(points.len(), 4);
+        let absolute: Vec<(i32, i32)> = points
+            .iter()
+            .map(|p| (edge.x + p[0], edge.y + p[1]))
+            .collect();
+        let near =
+            |(x, y): (i32, i32), (wx, wy): (i32, i32)| (x - wx).abs() <= 1 && (y - wy).abs() <= 1;
+        assert!(near(absolute[1], (200, 150)));
+        assert!(near(absolute[2], (300, 150)));
+        assert!(elements.iter().any(|e| e.text.as_deref() == Some("detour")));
+    }
+
+    #[test]
+    fn test_edge_points_are_relative_to_arrow_origin() {
+        let input = r#"
+a[A]
+b[B]
+a -> b via (200, 150), (300, 150) { routing: orthogonal; }
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let edge = elements.iter().find(|e| e.r#type == "arrow").unwrap();
+        let points = edge.points.as_ref().unwrap();
+        assert!(points.len() > 3);
+        assert_eq!(points[0], [0, 0]);
+        let absolute: Vec<[i32; 2]> = points
+            .iter()
+            .map(|p| [edge.x + p[0], edge.y + p[1]])
+            .collect();
+        assert!(absolute.contains(&[200, 150]));
+        assert!(absolute.contains(&[300, 150]));
+        assert_eq!((edge.width, edge.height), points_extent(points));
+    }
+
+    #[test]
+    fn test_default_shape_applies_to_unshaped_nodes() {
+        let input = r#"
+---
+default_shape: ellipse
+---
+a[Plain]
+b[Boxed] { shape: rectangle; }
+a -> b
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let shape = |label: &str| {
+            let text = elements
+                .iter()
+                .find(|e| e.text.as_deref() == Some(label))
+                .unwrap();
+            let id = text.container_id.as_deref().unwrap();
+            elements.iter().find(|e| e.id == id).unwrap().r#type.clone()
+        };
+        assert_eq!(shape("Plain"), "ellipse");
+        assert_eq!(shape("Boxed"), "rectangle");
+
+        let invalid = "---\ndefault_shape: blob\n---\na[A]\n";
+        let error = crate::parse_and_layout(invalid, "dagre").unwrap_err();
+        assert!(error.to_string().contains("Invalid default_shape 'blob'"));
+    }
+
+    #[test]
+    fn test_node_meta_becomes_custom_data() {
+        let input = r##"
+api[API] {
+  backgroundColor: "#a5d8ff";
+  meta { owner: "team-x"; sla: 99.9; oncall: true; }
+}
+db[Database] { backgroundColor: "#a5d8ff"; }
+"##;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        // The shape a label is bound to
+        let shape = |label: &str| {
+            let text = elements
+                .iter()
+                .find(|e| e.text.as_deref() == Some(label))
+                .unwrap();
+            elements
+                .iter()
+                .find(|e| Some(&e.id) == text.container_id.as_ref())
+                .unwrap()
+        };
+        let api = shape("API");
+        let db = shape("Database");
+
+        let custom_data = serde_json::to_value(api).unwrap()["customData"].clone();
+        assert_eq!(
+            custom_data,
+            serde_json::json!({ "owner": "team-x", "sla": 99.9, "oncall": true })
+        );
+        assert!(db.custom_data.is_none());
+        assert!(serde_json::to_value(db)
+            .unwrap()
+            .get("customData")
+            .is_none());
+
+        // Metadata doesn't affect styling
+        assert_eq!(api.background_color, db.background_color);
+        assert_eq!(api.stroke_color, db.stroke_color);
+        assert_eq!(api.stroke_width, db.stroke_width);
+    }
+
+    #[test]
+    fn test_show_label_false_draws_no_text() {
+        let input = r#"
+a_very_long_node_identifier { showLabel: false; }
+b[Storage] { showLabel: false; icon: db; }
+c[Shown]
+a_very_long_node_identifier -> b
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let (_, hidden) = igr.get_node_by_id("a_very_long_node_identifier").unwrap();
+        let (_, shown) = igr.get_node_by_id("c").unwrap();
+        // Sized as an empty node, not by the id it would otherwise show
+        assert_eq!((hidden.width, hidden.height), (100.0, 70.0));
+        assert!(!hidden.shows_label());
+        assert!(shown.shows_label());
+
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let texts: Vec<&str> = elements
+            .iter()
+            .filter(|e| e.r#type == "text")
+            .filter_map(|e| e.text.as_deref())
+            .collect();
+        assert!(texts.contains(&"Shown"));
+        assert!(!texts.contains(&"a_very_long_node_identifier"));
+        assert!(!texts.contains(&"Storage"));
+
+        // An icon-only node keeps its icon, centered in the shape
+        let (_, storage) = igr.get_node_by_id("b").unwrap();
+        let icon = elements
+            .iter()
+            .find(|e| e.r#type == "text" && e.text.as_deref() == Some("🗄"))
+            .unwrap();
+        assert!(((icon.x + icon.width / 2) as f64 - storage.x).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_shadow_draws_translucent_copy_behind_node() {
+        let input = r#"
+card[Card] { shadow: true; }
+plain[Plain]
+card -> plain
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let rectangles: Vec<usize> = elements
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.r#type == "rectangle")
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(rectangles.len(), 3);
+
+        let text = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("Card"))
+            .unwrap();
+        let card_index = elements
+            .iter()
+            .position(|e| Some(&e.id) == text.container_id.as_ref())
+            .unwrap();
+        let (shadow_index, shadow) = elements
+            .iter()
+            .enumerate()
+            .find(|(_, e)| e.r#type == "rectangle" && e.opacity < 100)
+            .unwrap();
+        let card = &elements[card_index];
+
+        assert!(shadow_index < card_index);
+        assert_eq!(shadow.opacity, SHADOW_OPACITY);
+        assert_eq!(shadow.stroke_color, "transparent");
+        assert!(shadow.bound_elements.is_empty());
+        assert!(shadow.width > card.width && shadow.height > card.height);
+        // Offset down and to the right
+        assert!(shadow.x + shadow.width > card.x + card.width);
+        assert!(shadow.y + shadow.height > card.y + card.height);
+        assert_eq!(card.opacity, 100);
+    }
+
+    #[test]
+    fn test_tooltips_become_custom_data() {
+        let input = r#"
+api[API] { tooltip: "Public REST API"; meta { owner: "team-x"; } }
+db[Database] { tooltip: "Attribute"; meta { tooltip: "Meta"; } }
+api -> db { tooltip: "SQL over TLS"; }
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let text = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("API"))
+            .unwrap();
+        let api = elements
+            .iter()
+            .find(|e| Some(&e.id) == text.container_id.as_ref())
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(api).unwrap()["customData"],
+            serde_json::json!({ "owner": "team-x", "tooltip": "Public REST API" })
+        );
+
+        // `meta` beats the attribute
+        let text = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("Database"))
+            .unwrap();
+        let db = elements
+            .iter()
+            .find(|e| Some(&e.id) == text.container_id.as_ref())
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(db).unwrap()["customData"],
+            serde_json::json!({ "tooltip": "Meta" })
+        );
+
+        let arrow = elements.iter().find(|e| e.r#type == "arrow").unwrap();
+        assert_eq!(
+            serde_json::to_value(arrow).unwrap()["customData"],
+            serde_json::json!({ "tooltip": "SQL over TLS" })
+        );
+    }
+
+    #[test]
+    fn test_node_icon_generates_grouped_free_text() {
+        let input = r#"
+db[Database] { icon: "🗄"; }
+api[API] { icon: "cloud"; }
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let texts: Vec<_> = elements
+            .iter()
+            .filter(|e| e.r#type == ELEMENT_TYPE_TEXT)
+            .collect();
+        assert_eq!(texts.len(), 4);
+
+        let text_of = |content: &str| {
+            texts
+                .iter()
+                .find(|e| e.text.as_deref() == Some(content))
+                .unwrap()
+        };
+        let icon = text_of("🗄");
+        let label = text_of("Database");
+        assert!(icon.x < label.x);
+
+        // Named icons map to their glyph
+        assert!(texts.iter().any(|e| e.text.as_deref() == Some("☁")));
+
+        // Only the label is bound; the icon is free text grouped with the
+        // shape so they move together
+        let shape = elements
+            .iter()
+            .find(|e| Some(&e.id) == label.container_id.as_ref())
+            .unwrap();
+        assert_eq!(icon.container_id, None);
+        assert_eq!(shape.bound_elements.len(), 1);
+        assert_eq!(shape.bound_elements[0]["id"], serde_json::json!(label.id));
+        assert_eq!(shape.group_ids.len(), 1);
+        assert_eq!(icon.group_ids, shape.group_ids);
+        assert_eq!(label.group_ids, shape.group_ids);
+    }
+
+    #[test]
+    fn test_crows_foot_arrowheads_generate_decorators() {
+        let input = r#"
+customer[Customer]
+order[Order]
+customer -- order { startArrowhead: one; endArrowhead: zero-or-many; }
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let edge = elements.iter().find(|e| e.id.starts_with("edge_")).unwrap();
+        assert_eq!(edge.start_arrowhead, None);
+        assert_eq!(edge.end_arrowhead, None);
+        assert_eq!(edge.group_ids.len(), 1);
+
+        let decorators: Vec<_> = elements
+            .iter()
+            .filter(|e| e.id.starts_with("decorator_"))
+            .collect();
+        // "one" is two bars; "zero-or-many" is a crow's foot plus a circle
+        assert_eq!(decorators.len(), 4);
+        assert_eq!(
+            decorators
+                .iter()
+                .filter(|d| d.r#type == ELEMENT_TYPE_LINE)
+                .count(),
+            3
+        );
+        assert_eq!(
+            decorators
+                .iter()
+                .filter(|d| d.r#type == ELEMENT_TYPE_ELLIPSE)
+                .count(),
+            1
+        );
+        let foot = decorators
+            .iter()
+            .find(|d| d.points.as_ref().is_some_and(|p| p.len() == 3))
+            .unwrap();
+        assert_eq!(foot.group_ids, edge.group_ids);
+    }
+
+    #[test]
+    fn test_explicit_none_arrowhead_overrides_arrow_default() {
+        let input = "a[A]\nb[B]\na -> b { endArrowhead: none; }";
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let edge = elements.iter().find(|e| e.r#type == "arrow").unwrap();
+        assert_eq!(edge.end_arrowhead, None);
+    }
+
+    #[test]
+    fn test_arrow_tokens_set_stroke() {
+        let edge_for = |edge: &str| {
+            let input = format!("a[A]\nb[B]\n{edge}");
+            let document = crate::parser::parse_edsl(&input).unwrap();
+            let igr = IntermediateGraph::from_ast(document).unwrap();
+            let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+            elements
+                .into_iter()
+                .find(|e| e.r#type == "arrow" || e.r#type == "line")
+                .unwrap()
+        };
+
+        let solid = edge_for("a --> b");
+        assert_eq!(solid.r#type, "arrow");
+        assert_eq!(solid.stroke_style, "solid");
+        assert_eq!(solid.end_arrowhead.as_deref(), Some("arrow"));
+
+        let dashed = edge_for("a -.-> b");
+        assert_eq!(dashed.r#type, "arrow");
+        assert_eq!(dashed.stroke_style, "dashed");
+        assert_eq!(dashed.end_arrowhead.as_deref(), Some("arrow"));
+
+        let dotted = edge_for("a ..> b");
+        assert_eq!(dotted.r#type, "arrow");
+        assert_eq!(dotted.stroke_style, "dotted");
+
+        let thick = edge_for("a ==> b");
+        assert_eq!(thick.r#type, "arrow");
+        assert_eq!(thick.stroke_width, THICK_ARROW_WIDTH as i32);
+
+        // Existing tokens are unchanged
+        assert_eq!(edge_for("a -- b").r#type, "line");
+        assert_eq!(edge_for("a -> b").stroke_width, 2);
+
+        // An explicit style still wins over the token's default
+        assert_eq!(
+            edge_for("a ..> b { strokeStyle: dashed; }").stroke_style,
+            "dashed"
+        );
+    }
+
+    #[test]
+    fn test_edge_width_sets_default_edge_stroke() {
+        let input = r#"
+---
+edge_width: 3
+---
+a[A]
+b[B]
+c[C]
+d[D]
+a -> b
+b ==> c
+c -> d { strokeWidth: 1; }
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let mut widths: Vec<i32> = elements
+            .iter()
+            .filter(|e| e.r#type == "arrow")
+            .map(|e| e.stroke_width)
+            .collect();
+        widths.sort();
+        // An explicit width wins; the thick token doubles the default
+        assert_eq!(widths, vec![1, 3, 6]);
+
+        let invalid = "---\nedge_width: 50\n---\na[A]\n";
+        let error = crate::parse_and_layout(invalid, "dagre").unwrap_err();
+        assert!(error.to_string().contains("edge_width must be between"));
+        assert!(GlobalConfig::builder().edge_width(0.0).is_err());
+    }
+
+    #[test]
+    fn test_group_members_share_group_ids() {
+        let input = r#"
+group "Backend" {
+  api[API]
+  worker[Worker]
+  api -> worker
+  group "Storage" {
+    db[Database]
+  }
+}
+client[Client]
+client -> api
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let shape = |label: &str| {
+            let text = elements
+                .iter()
+                .find(|e| e.text.as_deref() == Some(label))
+                .unwrap();
+            let shape = elements
+                .iter()
+                .find(|e| Some(&e.id) == text.container_id.as_ref())
+                .unwrap();
+            assert_eq!(text.group_ids, shape.group_ids, "label follows its shape");
+            shape
+        };
+
+        let api = shape("API");
+        let worker = shape("Worker");
+        let db = shape("Database");
+        let client = shape("Client");
+
+        assert_eq!(api.group_ids.len(), 1);
+        assert_eq!(api.group_ids, worker.group_ids);
+        assert!(client.group_ids.is_empty());
+
+        // The nested group stacks its own id in front of the outer group's
+        assert_eq!(db.group_ids.len(), 2);
+        assert_eq!(db.group_ids[1], api.group_ids[0]);
+
+        let edge_between = |from: &str, to: &str| {
+            let (from, to) = (&shape(from).id, &shape(to).id);
+            elements
+                .iter()
+                .find(|e| {
+                    e.start_binding.as_ref().map(|b| &b.element_id) == Some(from)
+                        && e.end_binding.as_ref().map(|b| &b.element_id) == Some(to)
+                })
+                .unwrap()
+        };
+        assert_eq!(edge_between("API", "Worker").group_ids, api.group_ids);
+        assert!(edge_between("Client", "API").group_ids.is_empty());
+    }
+
+    #[test]
+    fn test_hidden_container_background() {
+        let input = r#"
+---
+show_container_background: false
+---
+container "Backend" as backend {
+  api[API]
+  db[Database]
+  api -> db
+}
+user[User]
+user -> backend
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        assert!(!elements.iter().any(|e| e.id.starts_with("container_")));
+
+        // Children are still laid out inside the computed bounds
+        let bounds = igr.containers[0].bounds.clone().unwrap();
+        let shapes: Vec<_> = ["API", "Database"]
+            .iter()
+            .map(|label| {
+                let text = elements
+                    .iter()
+                    .find(|e| e.text.as_deref() == Some(*label))
+                    .unwrap();
+                elements
+                    .iter()
+                    .find(|e| Some(&e.id) == text.container_id.as_ref())
+                    .unwrap()
+            })
+            .collect();
+        for shape in &shapes {
+            assert!(shape.x as f64 >= bounds.x);
+            assert!(shape.y as f64 >= bounds.y);
+            assert!((shape.x + shape.width) as f64 <= bounds.x + bounds.width);
+            assert!((shape.y + shape.height) as f64 <= bounds.y + bounds.height);
+        }
+
+        // The container is grouped natively and keeps its label as free text
+        assert_eq!(shapes[0].group_ids.len(), 1);
+        assert_eq!(shapes[0].group_ids, shapes[1].group_ids);
+        let label = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("Backend"))
+            .unwrap();
+        assert_eq!(label.container_id, None);
+        assert_eq!(label.group_ids, shapes[0].group_ids);
+
+        // The edge into the hidden container stays unbound at that end
+        let edge = elements
+            .iter()
+            .find(|e| e.r#type == "arrow" && e.end_binding.is_none());
+        assert!(edge.is_some());
+    }
+
+    #[test]
+    fn test_container_to_container_edge() {
+        let input = r#"
+container "Frontend" as frontend {
+  web[Web]
+  mobile[Mobile]
+}
+container "Backend" as backend {
+  api[API]
+  db[Database]
+  api -> db
+}
+frontend -> backend
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let container_element = |label: &str| {
+            let text = elements
+                .iter()
+                .find(|e| e.text.as_deref() == Some(label))
+                .unwrap();
+            elements
+                .iter()
+                .find(|e| Some(&e.id) == text.container_id.as_ref())
+                .unwrap()
+        };
+        let frontend = container_element("Frontend");
+        let backend = container_element("Backend");
+
+        let bound_to = |e: &ExcalidrawElementSkeleton, binding: &Option<ElementBinding>| {
+            binding.as_ref().map(|b| &b.element_id) == Some(&e.id)
+        };
+        let arrows: Vec<_> = elements
+            .iter()
+            .filter(|e| {
+                e.r#type == "arrow"
+                    && bound_to(frontend, &e.start_binding)
+                    && bound_to(backend, &e.end_binding)
+            })
+            .collect();
+        assert_eq!(arrows.len(), 1);
+
+        // Both ends sit on the containers' borders
+        let on_border = |e: &ExcalidrawElementSkeleton, (x, y): (i32, i32)| {
+            let (left, top) = (e.x, e.y);
+            let (right, bottom) = (e.x + e.width, e.y + e.height);
+            let inside = (left - 2..=right + 2).contains(&x) && (top - 2..=bottom + 2).contains(&y);
+            let distance = [x - left, right - x, y - top, bottom - y]
+                .into_iter()
+                .map(i32::abs)
+                .min()
+                .unwrap();
+            inside && distance <= 2
+        };
+        let arrow = arrows[0];
+        let points = arrow.points.as_ref().unwrap();
+        let end = points.last().unwrap();
+        assert!(on_border(frontend, (arrow.x, arrow.y)));
+        assert!(on_border(backend, (arrow.x + end[0], arrow.y + end[1])));
+    }
+
+    #[test]
+    fn test_notes_are_free_floating() {
+        let input = r##"
+a[A]
+note "Needs review" at (400, 50)
+note "Sticky" at (0, 300) { backgroundColor: "#fff3bf"; }
+"##;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let note = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("Needs review"))
+            .unwrap();
+        assert_eq!(note.r#type, "text");
+        assert_eq!((note.x, note.y), (400, 50));
+        assert_eq!(note.container_id, None);
+
+        let sticky_text = elements
+            .iter()
+            .find(|e| e.text.as_deref() == Some("Sticky"))
+            .unwrap();
+        let sticky = elements
+            .iter()
+            .find(|e| Some(&e.id) == sticky_text.container_id.as_ref())
+            .unwrap();
+        assert_eq!(sticky.r#type, "rectangle");
+        assert_eq!((sticky.x, sticky.y), (0, 300));
+        assert_eq!(sticky.background_color, "#fff3bf");
+    }
+
+    #[test]
+    fn test_standalone_line() {
+        let input = r##"
+a[A]
+b[B]
+a -> b
+line from (100, 100) to (400, 100)
+line from (50, 300) to (-30, 220) { strokeStyle: dashed; strokeColor: "#868e96"; }
+"##;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let lines: Vec<_> = elements
+            .iter()
+            .filter(|e| e.r#type == ELEMENT_TYPE_LINE)
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].x, lines[0].y), (100, 100));
+        assert_eq!(lines[0].points, Some(vec![[0, 0], [300, 0]]));
+        assert_eq!((lines[0].width, lines[0].height), (300, 0));
+        assert!(lines[0].start_binding.is_none() && lines[0].end_binding.is_none());
+        assert_eq!(lines[0].end_arrowhead, None);
+
+        assert_eq!((lines[1].x, lines[1].y), (50, 300));
+        assert_eq!(lines[1].points, Some(vec![[0, 0], [-80, -80]]));
+        assert_eq!(lines[1].stroke_style, "dashed");
+        assert_eq!(lines[1].stroke_color, "#868e96");
+
+        // A node may still be called `line`
+        let document = crate::parser::parse_edsl("line[Line]\nline -> a").unwrap();
+        assert!(document.lines.is_empty());
+        assert_eq!(document.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_z_attribute_controls_element_order() {
+        let input = r#"
+top[Top] { z: 10; }
+bottom[Bottom] { z: 1; }
+plain[Plain]
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+
+        let position_of = |label: &str| {
+            elements
+                .iter()
+                .position(|e| e.text.as_deref() == Some(label))
+                .unwrap()
+        };
+        let top_text = position_of("Top");
+        let bottom_text = position_of("Bottom");
+        let plain_text = position_of("Plain");
+
+        assert!(top_text > bottom_text);
+        assert!(bottom_text > plain_text);
+
+        // Bound text stays directly after its shape
+        let top_shape_id = elements[top_text].container_id.clone().unwrap();
+        assert_eq!(elements[top_text - 1].id, top_shape_id);
+    }
+
+    #[test]
+    fn test_edge_ports_attach_to_requested_sides() {
+        let input = r#"
+a[Source]
+b[Target]
+a.bottom -> b.left
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let shape_for = |label: &str| {
+            let text = elements
+                .iter()
+                .find(|e| e.text.as_deref() == Some(label) && e.r#type == ELEMENT_TYPE_TEXT)
+                .unwrap();
+            let shape_id = text.container_id.as_ref().unwrap();
+            elements.iter().find(|e| &e.id == shape_id).unwrap()
+        };
+        let source = shape_for("Source");
+        let target = shape_for("Target");
+        let edge = elements
+            .iter()
+            .find(|e| e.r#type == ELEMENT_TYPE_ARROW)
+            .unwrap();
+
+        // Start point is the middle of the source's bottom side
+        assert!((edge.x - (source.x + source.width / 2)).abs() <= 1);
+        assert!((edge.y - (source.y + source.height)).abs() <= 1);
+
+        // End point is the middle of the target's left side
+        let end = edge.points.as_ref().unwrap().last().unwrap();
+        assert!((edge.x + end[0] - target.x).abs() <= 1);
+        assert!((edge.y + end[1] - (target.y + target.height / 2)).abs() <= 1);
+    }
+
     #[test]
     fn test_generate_text_element_with_color() {
         let text_color = Some("#ff0000".to_string());
@@ -1281,11 +3885,13 @@ mod tests {
             width: 120.0,
             height: 60.0,
             is_virtual_container: false,
+            metadata: Default::default(),
         };
 
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![NodeDefinition {
@@ -1298,6 +3904,9 @@ mod tests {
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let mut igr = IntermediateGraph::from_ast(document).unwrap();
@@ -1342,6 +3951,7 @@ mod tests {
                 width: 200.0,
                 height: 150.0,
             }),
+            is_lane: false,
         };
 
         let igr = IntermediateGraph {
@@ -1352,6 +3962,8 @@ mod tests {
             groups: vec![],
             node_map: HashMap::new(),
             container_map: HashMap::new(),
+            notes: vec![],
+            lines: vec![],
         };
 
         let elements = ExcalidrawGenerator::generate(&igr).unwrap();
@@ -1370,6 +3982,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_container_dotted_border() {
+        let input = r#"
+container "Dotted" {
+    style: { strokeStyle: dotted; }
+    a
+}
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        crate::layout::LayoutManager::new()
+            .layout(&mut igr)
+            .unwrap();
+
+        let elements = ExcalidrawGenerator::generate(&igr).unwrap();
+        let container = elements
+            .iter()
+            .find(|e| e.is_container == Some(true))
+            .expect("Should find container element");
+
+        assert_eq!(container.stroke_style, "dotted");
+    }
+
+    #[test]
+    fn test_group_stroke_style_overrides_type_default() {
+        let group = GroupData {
+            id: "flow".to_string(),
+            label: None,
+            group_type: GroupType::FlowGroup,
+            children: vec![],
+            nested_containers: vec![],
+            nested_groups: vec![],
+            parent_group: None,
+            parent_container: None,
+            attributes: ExcalidrawAttributes {
+                stroke_style: Some(StrokeStyle::Dotted),
+                ..Default::default()
+            },
+            bounds: Some(crate::igr::BoundingBox {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+            }),
+        };
+
+        let element = ExcalidrawGenerator::generate_group(&group)
+            .unwrap()
+            .unwrap();
+        assert_eq!(element.stroke_style, "dotted");
+
+        let default_group = GroupData {
+            attributes: ExcalidrawAttributes::default(),
+            ..group
+        };
+        let element = ExcalidrawGenerator::generate_group(&default_group)
+            .unwrap()
+            .unwrap();
+        assert_eq!(element.stroke_style, "dashed");
+    }
+
+    #[test]
+    fn test_stroke_style_default() {
+        assert_eq!(
+            ExcalidrawGenerator::convert_stroke_style(&None),
+            DEFAULT_STROKE_STYLE
+        );
+        assert_eq!(
+            ExcalidrawGenerator::convert_stroke_style(&Some(StrokeStyle::Dashed)),
+            "dashed"
+        );
+        // The fill default is a constant of its own
+        assert_eq!(
+            ExcalidrawGenerator::convert_fill_style(&None),
+            DEFAULT_FILL_STYLE
+        );
+    }
+
     #[test]
     fn test_font_family_conversion() {
         assert_eq!(ExcalidrawGenerator::convert_font_family(&None), 3);
@@ -0,0 +1,291 @@
+// src/graphml.rs
+//! GraphML export for graph-analysis tools
+//!
+//! Walks an `IntermediateGraph` and emits GraphML. Layout is not needed:
+//! only ids, labels, shapes, colors and edges are written. Containers become
+//! nodes with a nested `<graph>` holding their members, so edges that
+//! reference a container id resolve to that node.
+
+use crate::error::Result;
+use crate::igr::{ExcalidrawAttributes, IntermediateGraph};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+const GRAPHML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="label" for="node" attr.name="label" attr.type="string"/>
+  <key id="shape" for="node" attr.name="shape" attr.type="string"/>
+  <key id="color" for="node" attr.name="color" attr.type="string"/>
+  <key id="edge_label" for="edge" attr.name="label" attr.type="string"/>
+"#;
+
+pub struct GraphmlGenerator;
+
+impl GraphmlGenerator {
+    /// Generate a GraphML document for a graph
+    ///
+    /// # Examples
+    /// ```rust
+    /// use excalidraw_dsl::graphml::GraphmlGenerator;
+    /// use excalidraw_dsl::igr::IntermediateGraph;
+    ///
+    /// let document = excalidraw_dsl::parser::parse_edsl("a[A]\nb[B]\na -> b").unwrap();
+    /// let igr = IntermediateGraph::from_ast(document).unwrap();
+    /// let xml = GraphmlGenerator::generate(&igr).unwrap();
+    /// assert!(xml.contains(r#"<edge source="a" target="b"/>"#));
+    /// ```
+    pub fn generate(igr: &IntermediateGraph) -> Result<String> {
+        let owners = Self::innermost_containers(igr);
+
+        let mut out = String::from(GRAPHML_HEADER);
+        out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+        Self::write_members(&mut out, igr, &owners, None, 2);
+
+        for edge in igr.graph.edge_references() {
+            let source = &igr.graph[edge.source()];
+            let target = &igr.graph[edge.target()];
+            match &edge.weight().label {
+                Some(label) => {
+                    let _ = writeln!(
+                        out,
+                        "    <edge source=\"{}\" target=\"{}\">\n      <data key=\"edge_label\">{}</data>\n    </edge>",
+                        escape_xml(&source.id),
+                        escape_xml(&target.id),
+                        escape_xml(label)
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "    <edge source=\"{}\" target=\"{}\"/>",
+                        escape_xml(&source.id),
+                        escape_xml(&target.id)
+                    );
+                }
+            }
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        Ok(out)
+    }
+
+    /// Write the nodes and containers directly inside `parent` (`None` for
+    /// the top level), recursing into nested containers
+    fn write_members(
+        out: &mut String,
+        igr: &IntermediateGraph,
+        owners: &HashMap<NodeIndex, usize>,
+        parent: Option<usize>,
+        depth: usize,
+    ) {
+        let container_ids: HashSet<&str> = igr
+            .containers
+            .iter()
+            .filter_map(|container| container.id.as_deref())
+            .collect();
+
+        for (idx, container) in igr.containers.iter().enumerate() {
+            if container.parent_container != parent {
+                continue;
+            }
+            let id = container
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("container_{idx}"));
+            let indent = "  ".repeat(depth);
+            let _ = writeln!(out, "{indent}<node id=\"{}\">", escape_xml(&id));
+            Self::write_data(
+                out,
+                container.label.as_deref(),
+                &container.attributes,
+                depth + 1,
+            );
+            let _ = writeln!(
+                out,
+                "{indent}  <graph id=\"{}:\" edgedefault=\"directed\">",
+                escape_xml(&id)
+            );
+            Self::write_members(out, igr, owners, Some(idx), depth + 2);
+            let _ = writeln!(out, "{indent}  </graph>");
+            let _ = writeln!(out, "{indent}</node>");
+        }
+
+        for idx in igr.graph.node_indices() {
+            let node = &igr.graph[idx];
+            // Container virtual nodes are written as the container itself
+            if node.is_virtual_container && container_ids.contains(node.id.as_str()) {
+                continue;
+            }
+            if owners.get(&idx).copied() != parent {
+                continue;
+            }
+            let indent = "  ".repeat(depth);
+            let _ = writeln!(out, "{indent}<node id=\"{}\">", escape_xml(&node.id));
+            Self::write_data(out, Some(&node.label), &node.attributes, depth + 1);
+            let _ = writeln!(out, "{indent}</node>");
+        }
+    }
+
+    fn write_data(
+        out: &mut String,
+        label: Option<&str>,
+        attributes: &ExcalidrawAttributes,
+        depth: usize,
+    ) {
+        let indent = "  ".repeat(depth);
+        let data = [
+            ("label", label),
+            ("shape", attributes.shape.as_deref()),
+            ("color", attributes.stroke_color.as_deref()),
+        ];
+        for (key, value) in data {
+            if let Some(value) = value {
+                let _ = writeln!(
+                    out,
+                    "{indent}<data key=\"{key}\">{}</data>",
+                    escape_xml(value)
+                );
+            }
+        }
+    }
+
+    /// Map each node to the deepest container holding it
+    pub(crate) fn innermost_containers(igr: &IntermediateGraph) -> HashMap<NodeIndex, usize> {
+        let depth = |mut idx: usize| {
+            let mut depth = 0;
+            while let Some(parent) = igr.containers[idx].parent_container {
+                depth += 1;
+                idx = parent;
+            }
+            depth
+        };
+
+        let mut owners: HashMap<NodeIndex, usize> = HashMap::new();
+        for (idx, container) in igr.containers.iter().enumerate() {
+            for &child in &container.children {
+                match owners.get(&child) {
+                    Some(&current) if depth(current) >= depth(idx) => {}
+                    _ => {
+                        owners.insert(child, idx);
+                    }
+                }
+            }
+        }
+        owners
+    }
+}
+
+/// Map each id to `sanitize(id)` for formats with a narrower id syntax.
+/// Ids that are already valid keep their name; others that collide with a
+/// name in use get a `_2`, `_3`, ... suffix.
+pub(crate) fn unique_names<'a>(
+    ids: impl IntoIterator<Item = &'a str>,
+    sanitize: impl Fn(&str) -> String,
+) -> HashMap<&'a str, String> {
+    let mut ids: Vec<&str> = ids.into_iter().collect();
+    // Stable sort, so valid ids claim their names first
+    ids.sort_by_key(|id| sanitize(id) != *id);
+
+    let mut used = HashSet::new();
+    let mut names = HashMap::new();
+    for id in ids {
+        if names.contains_key(id) {
+            continue;
+        }
+        let base = sanitize(id);
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !used.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{base}_{suffix}");
+        }
+        names.insert(id, name);
+    }
+    names
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(input: &str) -> IntermediateGraph {
+        let document = crate::parser::parse_edsl(input).unwrap();
+        IntermediateGraph::from_ast(document).unwrap()
+    }
+
+    #[test]
+    fn test_generate_graphml() {
+        let igr = build(
+            r##"
+web[Web Server] { strokeColor: "#1e40af"; }
+db[Database] { shape: cylinder; }
+cache[Cache]
+web -> db: queries
+web -> cache
+db -> web
+"##,
+        );
+        let xml = GraphmlGenerator::generate(&igr).unwrap();
+
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.trim_end().ends_with("</graphml>"));
+        assert_eq!(xml.matches("<node ").count(), igr.graph.node_count());
+        assert_eq!(xml.matches("<edge ").count(), igr.graph.edge_count());
+        assert!(xml.contains(r#"<data key="label">Web Server</data>"#));
+        assert!(xml.contains(r#"<data key="shape">cylinder</data>"#));
+        assert!(xml.contains(r##"<data key="color">#1e40af</data>"##));
+        assert!(xml.contains(
+            "<edge source=\"web\" target=\"db\">\n      <data key=\"edge_label\">queries</data>"
+        ));
+        assert!(xml.contains(r#"<edge source="web" target="cache"/>"#));
+        assert!(xml.contains(r#"<edge source="db" target="web"/>"#));
+    }
+
+    #[test]
+    fn test_graphml_nests_containers() {
+        let igr = build(
+            r#"
+container "R&D" as rnd {
+  api[API]
+  container "Storage" as storage {
+    db[DB]
+  }
+}
+user[User]
+user -> rnd
+api -> db
+"#,
+        );
+        let xml = GraphmlGenerator::generate(&igr).unwrap();
+
+        assert!(xml.contains("<data key=\"label\">R&amp;D</data>"));
+        assert!(xml.contains(r#"<graph id="rnd:" edgedefault="directed">"#));
+        assert!(xml.contains(r#"<graph id="storage:" edgedefault="directed">"#));
+        // One node per real node and per container, none for virtual nodes
+        assert_eq!(xml.matches("<node ").count(), 5);
+        assert!(xml.contains(r#"<edge source="user" target="rnd"/>"#));
+
+        // db sits inside storage, which sits inside rnd
+        let rnd = xml.find(r#"<graph id="rnd:""#).unwrap();
+        let storage = xml.find(r#"<graph id="storage:""#).unwrap();
+        let db = xml.find(r#"<node id="db">"#).unwrap();
+        assert!(rnd < storage && storage < db);
+    }
+}
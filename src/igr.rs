@@ -2,7 +2,20 @@
 use crate::ast::*;
 use crate::error::{BuildError, Result};
 use petgraph::graph::{DiGraph, NodeIndex};
-use std::collections::HashMap;
+use petgraph::visit::EdgeRef;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Diameter of the junction dot drawn for a hyperedge
+const JUNCTION_SIZE: f64 = 12.0;
+/// Horizontal padding around an auto-sized node's label, to prevent overflow
+const NODE_PADDING_X: f64 = 75.0;
+/// Vertical padding around an auto-sized node's label
+const NODE_PADDING_Y: f64 = 25.0;
+const NODE_MIN_WIDTH: f64 = 100.0;
+const NODE_MIN_HEIGHT: f64 = 70.0;
+/// Largest `minlen` an edge may set
+const MAX_MIN_LEN: u32 = 100;
 
 #[derive(Debug, Clone)]
 pub struct IntermediateGraph {
@@ -13,6 +26,8 @@ pub struct IntermediateGraph {
     pub groups: Vec<GroupData>,
     pub node_map: HashMap<String, NodeIndex>,
     pub container_map: HashMap<String, usize>, // Maps container IDs to container indices
+    pub notes: Vec<NoteData>,                  // Annotations outside the layout
+    pub lines: Vec<LineData>,                  // Unbound lines outside the layout
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +41,47 @@ pub struct NodeData {
     pub width: f64,
     pub height: f64,
     pub is_virtual_container: bool, // Flag to identify virtual container nodes
+    /// Entries of the node's `meta { ... }` block, emitted as `customData`
+    pub metadata: BTreeMap<String, AttributeValue>,
+}
+
+/// Space between a sticky note's edge and its text
+pub(crate) const NOTE_PADDING: f64 = 12.0;
+
+/// A free-floating annotation, positioned by its top-left corner
+#[derive(Debug, Clone)]
+pub struct NoteData {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub attributes: ExcalidrawAttributes,
+}
+
+impl NoteData {
+    /// Estimated `(width, height)`: the text alone, or a padded sticky note
+    /// when the note has a background
+    pub fn estimated_size(&self) -> (f64, f64) {
+        let (text_width, text_height) = measure_label(&self.text, &self.attributes);
+        if self.attributes.background_color.is_none() {
+            return (text_width, text_height);
+        }
+        (
+            self.attributes
+                .width
+                .unwrap_or(text_width + NOTE_PADDING * 2.0),
+            self.attributes
+                .height
+                .unwrap_or(text_height + NOTE_PADDING * 2.0),
+        )
+    }
+}
+
+/// A standalone line between two absolute points
+#[derive(Debug, Clone)]
+pub struct LineData {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    pub attributes: ExcalidrawAttributes,
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +90,8 @@ pub struct EdgeData {
     pub arrow_type: ArrowType,
     pub attributes: ExcalidrawAttributes,
     pub routing_type: Option<crate::ast::RoutingType>,
+    /// Absolute points the edge passes through, in order
+    pub waypoints: Vec<(f64, f64)>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +104,9 @@ pub struct ContainerData {
     pub parent_container: Option<usize>, // Index of parent container if nested
     pub attributes: ExcalidrawAttributes,
     pub bounds: Option<BoundingBox>,
+    /// Declared with `lane`: layouts keep its members in a band of their own
+    /// across the flow, with lanes side by side
+    pub is_lane: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -85,13 +146,35 @@ pub struct ExcalidrawAttributes {
     pub font_size: Option<f64>,
     pub rounded: Option<f64>,
     pub text_color: Option<String>, // Text color for labels
+    pub icon: Option<String>,       // Glyph rendered left of the label
+    pub show_label: Option<bool>,   // false draws the shape without label text
+    pub shadow: Option<bool>,       // Draw a drop shadow behind the shape
+    pub group: Option<String>,      // Id of the group a node joins, created if needed
+    pub aspect_ratio: Option<f64>,  // Width / height that container bounds are padded to
+    pub min_width: Option<f64>,     // Narrowest an auto-sized node is made
+    pub max_width: Option<f64>,     // Widest an auto-sized node grows before wrapping
+    pub angle: Option<f64>,         // Clockwise rotation in degrees
 
     // Arrow properties
     pub start_arrowhead: Option<ArrowheadType>,
     pub end_arrowhead: Option<ArrowheadType>,
+    pub start_port: Option<PortSide>, // Side of the source node the edge leaves from
+    pub end_port: Option<PortSide>,   // Side of the target node the edge enters
+    pub min_len: Option<u32>, // Minimum number of dagre ranks the edge spans, up to MAX_MIN_LEN
+    pub weight: Option<f64>,  // Multiplier on force layout edge attraction
+    pub curvature: Option<f64>, // Bow of curved edges, 0 (straight) to 1
+    pub label_color: Option<String>, // Edge label text color, over `color`
+    pub label_background: Option<String>, // Fill of a box drawn behind the edge label
+    pub tooltip: Option<String>, // Hover text, carried in customData
+
+    // Layout
+    pub direction: Option<FlowDirection>, // Flow of a container's contents in ELK layouts
+
+    // Layering
+    pub z_index: Option<i32>, // Higher values render on top
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub x: f64,
     pub y: f64,
@@ -99,6 +182,65 @@ pub struct BoundingBox {
     pub height: f64,
 }
 
+impl BoundingBox {
+    /// Grow the shorter dimension so `width / height` equals `ratio`, keeping
+    /// the box centered on its contents
+    pub fn expand_to_aspect(&mut self, ratio: f64) {
+        if !(ratio.is_finite() && ratio > 0.0) || self.height <= 0.0 {
+            return;
+        }
+        if self.width / self.height < ratio {
+            let width = self.height * ratio;
+            self.x -= (width - self.width) / 2.0;
+            self.width = width;
+        } else {
+            let height = self.width / ratio;
+            self.y -= (height - self.height) / 2.0;
+            self.height = height;
+        }
+    }
+}
+
+/// Serializable snapshot of an `IntermediateGraph`, used for debugging layouts
+#[derive(Debug, Clone, Serialize)]
+pub struct IgrDump {
+    pub nodes: Vec<NodeDump>,
+    pub edges: Vec<EdgeDump>,
+    pub containers: Vec<ClusterDump>,
+    pub groups: Vec<ClusterDump>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeDump {
+    pub id: String,
+    pub label: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_virtual_container: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EdgeDump {
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing: Option<RoutingType>,
+}
+
+/// Container or group with its computed bounds
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterDump {
+    pub id: Option<String>,
+    pub label: Option<String>,
+    pub children: Vec<String>,
+    pub bounds: Option<BoundingBox>,
+}
+
 impl Default for IntermediateGraph {
     fn default() -> Self {
         Self::new()
@@ -115,6 +257,8 @@ impl IntermediateGraph {
             groups: Vec::new(),
             node_map: HashMap::new(),
             container_map: HashMap::new(),
+            notes: Vec::new(),
+            lines: Vec::new(),
         }
     }
 
@@ -139,22 +283,83 @@ impl IntermediateGraph {
             &mut all_edges,
         )?;
 
+        // Number messages sequence-diagram style, before hyperedges add theirs
+        if igr.global_config.number_messages.unwrap_or(false) {
+            // Edges are collected per container and group, so go back to the
+            // order they were written in; unparsed edges keep theirs, last
+            all_edges.sort_by_key(|edge| edge.position.unwrap_or(usize::MAX));
+            for (index, edge) in all_edges.iter_mut().enumerate() {
+                let number = index + 1;
+                edge.label = Some(match edge.label.take() {
+                    Some(label) => format!("{number}. {label}"),
+                    None => format!("{number}."),
+                });
+            }
+        }
+
         // Build all nodes first
+        validate_node_width("min_node_width", igr.global_config.min_node_width)?;
+        validate_node_width("max_node_width", igr.global_config.max_node_width)?;
+        validate_shape("default_shape", igr.global_config.default_shape.as_deref())?;
+        if let Some(width) = igr.global_config.edge_width {
+            validate_edge_width(width)?;
+        }
         for node_def in all_nodes {
             if igr.node_map.contains_key(&node_def.id) {
                 return Err(BuildError::DuplicateNode(node_def.id).into());
             }
 
-            let node_data = NodeData::from_definition(node_def, &igr.component_types)?;
+            let mut node_data = NodeData::from_definition(node_def, &igr.component_types)?;
+            if node_data.attributes.shape.is_none() {
+                node_data.attributes.shape = igr.global_config.default_shape.clone();
+            }
+            node_data.clamp_width(&igr.global_config);
             let node_idx = igr.graph.add_node(node_data.clone());
             igr.node_map.insert(node_data.id.clone(), node_idx);
         }
 
-        // Build container hierarchy first to populate container_map
+        // Hyperedges become a junction dot joined to each member by a line
+        for (index, hyperedge) in document.hyperedges.into_iter().enumerate() {
+            let junction_id = hyperedge.id.unwrap_or_else(|| format!("_bus{index}"));
+            if igr.node_map.contains_key(&junction_id) {
+                return Err(BuildError::DuplicateNode(junction_id).into());
+            }
+
+            let junction = NodeData::junction(junction_id.clone(), &hyperedge.attributes)?;
+            let node_idx = igr.graph.add_node(junction);
+            igr.node_map.insert(junction_id.clone(), node_idx);
+
+            for member in hyperedge.members {
+                all_edges.push(EdgeDefinition {
+                    from: member,
+                    to: junction_id.clone(),
+                    label: None,
+                    arrow_type: ArrowType::Line,
+                    attributes: hyperedge.attributes.clone(),
+                    style: None,
+                    waypoints: Vec::new(),
+                    position: None,
+                });
+            }
+        }
+
+        // Build container and group hierarchies first to populate container_map
         igr.build_container_hierarchy(document.containers, None)?;
+        igr.build_group_hierarchy(document.groups, None, None)?;
+        igr.build_attribute_groups();
 
-        // Create virtual nodes for containers so they can be referenced in edges
+        // Create virtual nodes for containers, and for groups used as edge
+        // endpoints, so they can be referenced in edges
         igr.create_container_virtual_nodes()?;
+        let endpoints: HashSet<&str> = all_edges
+            .iter()
+            .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()])
+            .chain(document.connections.iter().flat_map(|connection| {
+                std::iter::once(connection.from.as_str())
+                    .chain(connection.to.iter().map(String::as_str))
+            }))
+            .collect();
+        igr.create_group_virtual_nodes(&endpoints);
 
         // Build edges (now container IDs are available in node_map)
         for edge_def in all_edges {
@@ -167,22 +372,42 @@ impl IntermediateGraph {
                 .get(&edge_def.to)
                 .ok_or_else(|| BuildError::UnknownNode(edge_def.to.clone()))?;
 
-            let edge_data = EdgeData::from_definition(edge_def)?;
+            let mut edge_data = EdgeData::from_definition(edge_def)?;
+            if edge_data.routing_type.is_none() {
+                edge_data.routing_type = igr.global_config.routing;
+            }
             igr.graph.add_edge(*from_idx, *to_idx, edge_data);
         }
 
-        // Build group hierarchy
-        igr.build_group_hierarchy(document.groups, None, None)?;
-
         // Process connections (convert to edges)
         for connection in document.connections {
             // Convert each connection to edges
             let edges = EdgeData::from_connection(connection, &igr.node_map)?;
-            for (from_idx, to_idx, edge_data) in edges {
+            for (from_idx, to_idx, mut edge_data) in edges {
+                if edge_data.routing_type.is_none() {
+                    edge_data.routing_type = igr.global_config.routing;
+                }
                 igr.graph.add_edge(from_idx, to_idx, edge_data);
             }
         }
 
+        // Notes keep their absolute positions and never enter the graph
+        for note in document.notes {
+            igr.notes.push(NoteData {
+                text: note.text,
+                x: note.x,
+                y: note.y,
+                attributes: ExcalidrawAttributes::from_hashmap(&note.attributes)?,
+            });
+        }
+        for line in document.lines {
+            igr.lines.push(LineData {
+                from: line.from,
+                to: line.to,
+                attributes: ExcalidrawAttributes::from_hashmap(&line.attributes)?,
+            });
+        }
+
         Ok(igr)
     }
 
@@ -197,6 +422,436 @@ impl IntermediateGraph {
             .map(move |idx| (idx, &mut self.graph[idx]))
     }
 
+    /// Find the first node (in insertion order) whose label matches exactly.
+    ///
+    /// Virtual container nodes are skipped so a container sharing a label with
+    /// a regular node never shadows it.
+    pub fn get_node_by_label(&self, label: &str) -> Option<(NodeIndex, &NodeData)> {
+        self.graph
+            .node_indices()
+            .map(|idx| (idx, &self.graph[idx]))
+            .find(|(_, node)| !node.is_virtual_container && node.label == label)
+    }
+
+    /// Iterate over all nodes (in insertion order) accepted by `predicate`.
+    pub fn nodes_matching<'a, F>(
+        &'a self,
+        predicate: F,
+    ) -> impl Iterator<Item = (NodeIndex, &'a NodeData)> + 'a
+    where
+        F: Fn(&NodeData) -> bool + 'a,
+    {
+        self.graph
+            .node_indices()
+            .map(move |idx| (idx, &self.graph[idx]))
+            .filter(move |(_, node)| predicate(node))
+    }
+
+    /// Multiply all node positions and sizes and all container/group bounds by
+    /// `scale`, zooming the laid-out diagram around the origin
+    pub fn apply_scale(&mut self, scale: f64) {
+        self.apply_transform(scale, 0.0, 0.0);
+    }
+
+    /// Scale everything like `apply_scale`, then shift it by `(dx, dy)`
+    pub fn apply_transform(&mut self, scale: f64, dx: f64, dy: f64) {
+        let point = |(x, y): (f64, f64)| (x * scale + dx, y * scale + dy);
+        for node in self.graph.node_weights_mut() {
+            (node.x, node.y) = point((node.x, node.y));
+            node.width *= scale;
+            node.height *= scale;
+        }
+
+        let scale_bounds = |bounds: &mut Option<BoundingBox>| {
+            if let Some(bounds) = bounds {
+                (bounds.x, bounds.y) = point((bounds.x, bounds.y));
+                bounds.width *= scale;
+                bounds.height *= scale;
+            }
+        };
+        for container in &mut self.containers {
+            scale_bounds(&mut container.bounds);
+        }
+        for group in &mut self.groups {
+            scale_bounds(&mut group.bounds);
+        }
+        for note in &mut self.notes {
+            (note.x, note.y) = point((note.x, note.y));
+        }
+        for line in &mut self.lines {
+            line.from = point(line.from);
+            line.to = point(line.to);
+        }
+        for edge in self.graph.edge_weights_mut() {
+            for waypoint in &mut edge.waypoints {
+                *waypoint = point(*waypoint);
+            }
+        }
+    }
+
+    /// Center the diagram in a canvas spanning `(0, 0)` to its size,
+    /// shrinking it first if it doesn't fit
+    pub fn fit_to_canvas(&mut self, canvas: CanvasSize) {
+        let bounds = self.bounding_box();
+        let mut scale: f64 = 1.0;
+        if bounds.width > 0.0 {
+            scale = scale.min(canvas.width / bounds.width);
+        }
+        if bounds.height > 0.0 {
+            scale = scale.min(canvas.height / bounds.height);
+        }
+        let dx = (canvas.width - bounds.width * scale) / 2.0 - bounds.x * scale;
+        let dy = (canvas.height - bounds.height * scale) / 2.0 - bounds.y * scale;
+        self.apply_transform(scale, dx, dy);
+    }
+
+    /// Smallest box enclosing every laid-out node, container and group,
+    /// every standalone line, the estimated extent of every note and every
+    /// edge waypoint. Edges are otherwise
+    /// routed between these at generation time, so they are not tracked
+    /// separately. An empty graph gives a zero-sized box at the origin.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let node_boxes = self
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container)
+            .map(|node| {
+                (
+                    node.x - node.width / 2.0,
+                    node.y - node.height / 2.0,
+                    node.x + node.width / 2.0,
+                    node.y + node.height / 2.0,
+                )
+            });
+        let cluster_boxes = self
+            .containers
+            .iter()
+            .filter_map(|c| c.bounds.as_ref())
+            .chain(self.groups.iter().filter_map(|g| g.bounds.as_ref()))
+            .map(|b| (b.x, b.y, b.x + b.width, b.y + b.height));
+        let line_boxes = self.lines.iter().map(|line| {
+            (
+                line.from.0.min(line.to.0),
+                line.from.1.min(line.to.1),
+                line.from.0.max(line.to.0),
+                line.from.1.max(line.to.1),
+            )
+        });
+        let note_boxes = self.notes.iter().map(|note| {
+            let (width, height) = note.estimated_size();
+            (note.x, note.y, note.x + width, note.y + height)
+        });
+        let waypoint_boxes = self
+            .graph
+            .edge_weights()
+            .flat_map(|edge| edge.waypoints.iter())
+            .map(|&(x, y)| (x, y, x, y));
+
+        let (min_x, min_y, max_x, max_y) = node_boxes
+            .chain(cluster_boxes)
+            .chain(line_boxes)
+            .chain(note_boxes)
+            .chain(waypoint_boxes)
+            .fold(
+                (
+                    f64::INFINITY,
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                    f64::NEG_INFINITY,
+                ),
+                |(min_x, min_y, max_x, max_y), (x1, y1, x2, y2)| {
+                    (min_x.min(x1), min_y.min(y1), max_x.max(x2), max_y.max(y2))
+                },
+            );
+        if !min_x.is_finite() {
+            return BoundingBox {
+                x: 0.0,
+                y: 0.0,
+                width: 0.0,
+                height: 0.0,
+            };
+        }
+
+        BoundingBox {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x,
+            height: max_y - min_y,
+        }
+    }
+
+    /// Snapshot nodes, edges and container/group bounds for inspection
+    pub fn dump(&self) -> IgrDump {
+        let child_ids = |children: &[NodeIndex]| -> Vec<String> {
+            children
+                .iter()
+                .map(|&idx| self.graph[idx].id.clone())
+                .collect()
+        };
+
+        IgrDump {
+            nodes: self
+                .graph
+                .node_weights()
+                .map(|node| NodeDump {
+                    id: node.id.clone(),
+                    label: node.label.clone(),
+                    x: node.x,
+                    y: node.y,
+                    width: node.width,
+                    height: node.height,
+                    is_virtual_container: node.is_virtual_container,
+                })
+                .collect(),
+            edges: self
+                .graph
+                .edge_references()
+                .map(|edge| EdgeDump {
+                    from: self.graph[edge.source()].id.clone(),
+                    to: self.graph[edge.target()].id.clone(),
+                    label: edge.weight().label.clone(),
+                    routing: edge.weight().routing_type,
+                })
+                .collect(),
+            containers: self
+                .containers
+                .iter()
+                .map(|container| ClusterDump {
+                    id: container.id.clone(),
+                    label: container.label.clone(),
+                    children: child_ids(&container.children),
+                    bounds: container.bounds.clone(),
+                })
+                .collect(),
+            groups: self
+                .groups
+                .iter()
+                .map(|group| ClusterDump {
+                    id: Some(group.id.clone()),
+                    label: group.label.clone(),
+                    children: child_ids(&group.children),
+                    bounds: group.bounds.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Build a new graph containing only the named nodes and the edges entirely
+    /// among them.
+    ///
+    /// Node attributes are preserved and node indices in the returned graph are
+    /// fresh. Containers are carried over when all of their children (and
+    /// nested containers) are part of the subgraph; groups are not. Unknown IDs
+    /// are ignored.
+    pub fn extract_subgraph(&self, node_ids: &[&str]) -> IntermediateGraph {
+        let mut subgraph = IntermediateGraph::new();
+        subgraph.global_config = self.global_config.clone();
+        subgraph.component_types = self.component_types.clone();
+
+        let mut index_map: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for id in node_ids {
+            if let Some(&old_idx) = self.node_map.get(*id) {
+                if index_map.contains_key(&old_idx) {
+                    continue;
+                }
+                let new_idx = subgraph.graph.add_node(self.graph[old_idx].clone());
+                subgraph.node_map.insert(id.to_string(), new_idx);
+                index_map.insert(old_idx, new_idx);
+            }
+        }
+
+        // Decide which containers are fully contained in the selection
+        fn is_contained(
+            idx: usize,
+            containers: &[ContainerData],
+            index_map: &HashMap<NodeIndex, NodeIndex>,
+            memo: &mut HashMap<usize, bool>,
+        ) -> bool {
+            if let Some(&contained) = memo.get(&idx) {
+                return contained;
+            }
+            let container = &containers[idx];
+            let has_content =
+                !container.children.is_empty() || !container.nested_containers.is_empty();
+            let contained = has_content
+                && container
+                    .children
+                    .iter()
+                    .all(|child| index_map.contains_key(child))
+                && container
+                    .nested_containers
+                    .iter()
+                    .all(|&nested| is_contained(nested, containers, index_map, memo));
+            memo.insert(idx, contained);
+            contained
+        }
+
+        let mut memo = HashMap::new();
+        let mut container_index_map: HashMap<usize, usize> = HashMap::new();
+        for idx in 0..self.containers.len() {
+            if is_contained(idx, &self.containers, &index_map, &mut memo) {
+                container_index_map.insert(idx, container_index_map.len());
+            }
+        }
+
+        for idx in 0..self.containers.len() {
+            if !container_index_map.contains_key(&idx) {
+                continue;
+            }
+            let container = &self.containers[idx];
+            let mut container_data = container.clone();
+            container_data.children = container
+                .children
+                .iter()
+                .map(|child| index_map[child])
+                .collect();
+            container_data.nested_containers = container
+                .nested_containers
+                .iter()
+                .map(|nested| container_index_map[nested])
+                .collect();
+            container_data.nested_groups = Vec::new();
+            container_data.parent_container = container
+                .parent_container
+                .and_then(|parent| container_index_map.get(&parent).copied());
+
+            if let Some(ref container_id) = container.id {
+                subgraph
+                    .container_map
+                    .insert(container_id.clone(), subgraph.containers.len());
+
+                // Keep the virtual node so edges to the container survive,
+                // unless the selection named the container and copied it
+                if let Some(&old_idx) = self.node_map.get(container_id) {
+                    if self.graph[old_idx].is_virtual_container && !index_map.contains_key(&old_idx)
+                    {
+                        let new_idx = subgraph.graph.add_node(self.graph[old_idx].clone());
+                        subgraph.node_map.insert(container_id.clone(), new_idx);
+                        index_map.insert(old_idx, new_idx);
+                    }
+                }
+            }
+
+            subgraph.containers.push(container_data);
+        }
+
+        for edge in self.graph.edge_references() {
+            if let (Some(&source), Some(&target)) =
+                (index_map.get(&edge.source()), index_map.get(&edge.target()))
+            {
+                subgraph
+                    .graph
+                    .add_edge(source, target, edge.weight().clone());
+            }
+        }
+
+        subgraph
+    }
+
+    /// Nodes inside container `idx` or any container nested in it
+    fn container_members(&self, idx: usize) -> HashSet<NodeIndex> {
+        let mut members: HashSet<NodeIndex> =
+            self.containers[idx].children.iter().copied().collect();
+        for &nested in &self.containers[idx].nested_containers {
+            members.extend(self.container_members(nested));
+        }
+        members
+    }
+
+    /// Indices of containers that are not nested in another container
+    pub fn top_level_containers(&self) -> Vec<usize> {
+        (0..self.containers.len())
+            .filter(|&idx| self.containers[idx].parent_container.is_none())
+            .collect()
+    }
+
+    /// Build a graph holding only the contents of container `idx` (including
+    /// the container itself), so it can be laid out on its own
+    pub fn container_subgraph(&self, idx: usize) -> IntermediateGraph {
+        let members = self.container_members(idx);
+        let ids: Vec<&str> = members
+            .iter()
+            .map(|&member| self.graph[member].id.as_str())
+            .collect();
+        self.extract_subgraph(&ids)
+    }
+
+    /// Build an overview graph with one node per top-level container plus the
+    /// nodes outside any container, joined by the edges that cross between
+    /// them. Parallel edges collapse into one.
+    pub fn container_overview(&self) -> Result<IntermediateGraph> {
+        let mut overview = IntermediateGraph::new();
+        overview.global_config = self.global_config.clone();
+        overview.component_types = self.component_types.clone();
+
+        let top_level_id = |mut idx: usize| {
+            while let Some(parent) = self.containers[idx].parent_container {
+                idx = parent;
+            }
+            self.containers[idx]
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("container_{idx}"))
+        };
+
+        let mut representatives: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for idx in self.top_level_containers() {
+            let container = &self.containers[idx];
+            let id = top_level_id(idx);
+            let node = NodeData::from_definition(
+                NodeDefinition {
+                    id: id.clone(),
+                    label: container.label.clone(),
+                    component_type: None,
+                    attributes: HashMap::new(),
+                },
+                &self.component_types,
+            )?;
+            let overview_idx = overview.graph.add_node(node);
+            overview.node_map.insert(id, overview_idx);
+            for member in self.container_members(idx) {
+                representatives.insert(member, overview_idx);
+            }
+        }
+
+        for idx in self.graph.node_indices() {
+            if representatives.contains_key(&idx) {
+                continue;
+            }
+            let node = &self.graph[idx];
+            // Edges to a container's virtual node land on its top-level container
+            let container = node
+                .is_virtual_container
+                .then(|| self.container_map.get(&node.id))
+                .flatten();
+            let overview_idx = match container {
+                Some(&container_idx) => overview.node_map[&top_level_id(container_idx)],
+                None => {
+                    let overview_idx = overview.graph.add_node(node.clone());
+                    overview.node_map.insert(node.id.clone(), overview_idx);
+                    overview_idx
+                }
+            };
+            representatives.insert(idx, overview_idx);
+        }
+
+        let mut connected = HashSet::new();
+        for edge in self.graph.edge_references() {
+            let source = representatives[&edge.source()];
+            let target = representatives[&edge.target()];
+            if source == target || !connected.insert((source, target)) {
+                continue;
+            }
+            // Ports refer to the sides of the original endpoints
+            let mut edge_data = edge.weight().clone();
+            edge_data.attributes.start_port = None;
+            edge_data.attributes.end_port = None;
+            overview.graph.add_edge(source, target, edge_data);
+        }
+
+        Ok(overview)
+    }
+
     /// Build the container hierarchy with proper parent-child relationships
     fn build_container_hierarchy(
         &mut self,
@@ -274,6 +929,7 @@ impl IntermediateGraph {
                         width: 100.0,               // Default width
                         height: 50.0,               // Default height
                         is_virtual_container: true, // Mark as virtual
+                        metadata: BTreeMap::new(),
                     };
 
                     let node_idx = self.graph.add_node(virtual_node);
@@ -284,6 +940,84 @@ impl IntermediateGraph {
         Ok(())
     }
 
+    /// Create virtual nodes for groups referenced in connections. Unreferenced
+    /// groups get none, so they don't take part in layout.
+    fn create_group_virtual_nodes(&mut self, endpoints: &HashSet<&str>) {
+        for group in &self.groups {
+            if !endpoints.contains(group.id.as_str()) || self.node_map.contains_key(&group.id) {
+                continue;
+            }
+            let virtual_node = NodeData {
+                id: group.id.clone(),
+                label: group.label.clone().unwrap_or_else(|| group.id.clone()),
+                attributes: group.attributes.clone(),
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 50.0,
+                is_virtual_container: true,
+                metadata: BTreeMap::new(),
+            };
+            let node_idx = self.graph.add_node(virtual_node);
+            self.node_map.insert(group.id.clone(), node_idx);
+        }
+    }
+
+    /// Fit virtual container and group nodes to their laid-out bounds, so
+    /// edges between clusters attach to the cluster boundaries
+    pub fn fit_virtual_nodes_to_bounds(&mut self) {
+        let clusters = self
+            .containers
+            .iter()
+            .filter_map(|container| Some((container.id.as_ref()?, container.bounds.as_ref()?)))
+            .chain(
+                self.groups
+                    .iter()
+                    .filter_map(|group| Some((&group.id, group.bounds.as_ref()?))),
+            );
+        for (id, bounds) in clusters {
+            let Some(&node_idx) = self.node_map.get(id) else {
+                continue;
+            };
+            let node = &mut self.graph[node_idx];
+            if node.is_virtual_container {
+                node.x = bounds.x + bounds.width / 2.0;
+                node.y = bounds.y + bounds.height / 2.0;
+                node.width = bounds.width;
+                node.height = bounds.height;
+            }
+        }
+    }
+
+    /// Synthesize groups from `group` node attributes. Nodes sharing a value
+    /// become one basic group, or join the explicit group with that id.
+    fn build_attribute_groups(&mut self) {
+        for node_idx in self.graph.node_indices() {
+            let Some(name) = self.graph[node_idx].attributes.group.clone() else {
+                continue;
+            };
+            match self.groups.iter_mut().find(|group| group.id == name) {
+                Some(group) => {
+                    if !group.children.contains(&node_idx) {
+                        group.children.push(node_idx);
+                    }
+                }
+                None => self.groups.push(GroupData {
+                    id: name.clone(),
+                    label: Some(name),
+                    group_type: GroupType::BasicGroup,
+                    children: vec![node_idx],
+                    nested_containers: Vec::new(),
+                    nested_groups: Vec::new(),
+                    parent_group: None,
+                    parent_container: None,
+                    attributes: ExcalidrawAttributes::default(),
+                    bounds: None,
+                }),
+            }
+        }
+    }
+
     /// Build the group hierarchy with proper parent-child relationships
     fn build_group_hierarchy(
         &mut self,
@@ -418,6 +1152,63 @@ impl IntermediateGraph {
 }
 
 impl NodeData {
+    /// Whether the node draws its label: it has one and `showLabel` isn't false
+    pub fn shows_label(&self) -> bool {
+        !self.label.is_empty() && self.attributes.show_label != Some(false)
+    }
+
+    /// Small filled dot joining the members of a hyperedge, colored like its lines
+    fn junction(id: String, attributes: &HashMap<String, AttributeValue>) -> Result<Self> {
+        let mut attributes = ExcalidrawAttributes::from_hashmap(attributes)?;
+        attributes.shape = Some("ellipse".to_string());
+        attributes.background_color = Some(
+            attributes
+                .stroke_color
+                .clone()
+                .unwrap_or_else(|| "#000000".to_string()),
+        );
+        attributes.fill_style = Some(FillStyle::Solid);
+
+        Ok(NodeData {
+            id,
+            label: String::new(),
+            attributes,
+            x: 0.0,
+            y: 0.0,
+            width: JUNCTION_SIZE,
+            height: JUNCTION_SIZE,
+            is_virtual_container: false,
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    /// Clamp an auto-sized node's width to its `minWidth`/`maxWidth` (or the
+    /// document's `min_node_width`/`max_node_width`). Labels too wide for the
+    /// maximum wrap onto more lines; a minimum above the maximum wins.
+    fn clamp_width(&mut self, config: &GlobalConfig) {
+        if self.attributes.width.is_some() {
+            return;
+        }
+        let min_width = self.attributes.min_width.or(config.min_node_width);
+        let max_width = self.attributes.max_width.or(config.max_node_width);
+
+        if let Some(max_width) = max_width.filter(|&max_width| self.width > max_width) {
+            if !self.shows_label() {
+                self.width = max_width;
+                return;
+            }
+            self.label = wrap_label(&self.label, max_width - NODE_PADDING_X, &self.attributes);
+            self.width = max_width;
+            if self.attributes.height.is_none() {
+                let (_, text_height) = measure_label(&self.label, &self.attributes);
+                self.height = (text_height + NODE_PADDING_Y).max(NODE_MIN_HEIGHT);
+            }
+        }
+        if let Some(min_width) = min_width {
+            self.width = self.width.max(min_width);
+        }
+    }
+
     pub fn from_definition(
         def: NodeDefinition,
         component_types: &HashMap<String, ComponentTypeDefinition>,
@@ -465,60 +1256,31 @@ impl NodeData {
             }
         }
 
-        let label = def.label.unwrap_or_else(|| def.id.clone());
-
-        // Estimate initial dimensions based on label with better text metrics
-        let font_size = attributes.font_size.unwrap_or(20.0);
-        let font_family = match attributes.font.as_deref() {
-            Some("Virgil") => 1,
-            Some("Helvetica") => 2,
-            Some("Cascadia") | Some("Code") => 3,
-            None => 3, // Default to Cascadia
-            _ => 3,
-        };
-
-        // Calculate text dimensions using improved logic for better accuracy
-        let char_width_multiplier = match font_family {
-            1 => 0.65, // Virgil - slightly wider
-            2 => 0.55, // Helvetica - slightly wider
-            3 => 0.6,  // Cascadia - wider for better readability
-            _ => 0.6,
-        };
-
-        // Improved character width calculation with better handling for common characters
-        let effective_length = label
-            .chars()
-            .map(|c| {
-                match c {
-                    // Narrow characters
-                    'i' | 'l' | '.' | '!' | '|' | '\'' | '`' | 'I' | 'j' | 'f' | 't' => 0.4,
-                    // Wide characters
-                    'w' | 'm' | 'W' | 'M' | '@' | '%' | '#' => 1.4,
-                    // Uppercase letters (generally wider)
-                    'A'..='Z' => 1.15,
-                    // Space (reduced to save space)
-                    ' ' => 0.35,
-                    // Numbers and common punctuation
-                    '0'..='9' | '(' | ')' | '[' | ']' | '{' | '}' | '-' | '_' | '=' | '+' => 0.9,
-                    // Default for most lowercase and other characters
-                    _ => 1.0,
-                }
+        let metadata = def
+            .attributes
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(META_PREFIX)
+                    .map(|key| (key.to_string(), value.clone()))
             })
-            .sum::<f64>();
+            .collect();
 
-        let text_width = effective_length * font_size * char_width_multiplier;
-        let text_height = font_size * 1.3; // Slightly more height for better appearance
+        let label = def.label.unwrap_or_else(|| def.id.clone());
 
-        // Increased padding for better text visibility and node appearance
-        let padding_x = 75.0; // Even more horizontal padding to prevent text overflow
-        let padding_y = 25.0; // More vertical padding
+        // Estimate initial dimensions based on label with better text metrics;
+        // a hidden label leaves the default size
+        let (text_width, text_height) = if attributes.show_label == Some(false) {
+            (0.0, 0.0)
+        } else {
+            measure_label(&label, &attributes)
+        };
 
         let estimated_width = attributes
             .width
-            .unwrap_or_else(|| (text_width + padding_x).max(100.0)); // Increased minimum width
+            .unwrap_or_else(|| (text_width + NODE_PADDING_X).max(NODE_MIN_WIDTH));
         let estimated_height = attributes
             .height
-            .unwrap_or_else(|| (text_height + padding_y).max(70.0)); // Increased minimum height
+            .unwrap_or_else(|| (text_height + NODE_PADDING_Y).max(NODE_MIN_HEIGHT));
 
         Ok(NodeData {
             id: def.id,
@@ -529,6 +1291,7 @@ impl NodeData {
             width: estimated_width,
             height: estimated_height,
             is_virtual_container: false, // Regular nodes are not virtual containers
+            metadata,
         })
     }
 }
@@ -566,7 +1329,13 @@ impl EdgeData {
                 .or(def.style.as_ref().and_then(|s| s.label.clone())),
             arrow_type: def.arrow_type,
             attributes,
-            routing_type: def.style.as_ref().and_then(|s| s.routing),
+            routing_type: def.style.as_ref().and_then(|s| s.routing).or_else(|| {
+                def.attributes
+                    .get("routing")
+                    .and_then(|value| value.as_string())
+                    .and_then(|s| s.parse().ok())
+            }),
+            waypoints: def.waypoints,
         })
     }
 
@@ -630,6 +1399,7 @@ impl EdgeData {
                 arrow_type,
                 attributes,
                 routing_type: style.routing,
+                waypoints: Vec::new(),
             };
 
             edges.push((from_idx, to_idx, edge_data));
@@ -645,6 +1415,10 @@ impl ContainerData {
         node_map: &HashMap<String, NodeIndex>,
     ) -> Result<Self> {
         let attributes = ExcalidrawAttributes::from_hashmap(&def.attributes)?;
+        let is_lane = matches!(
+            def.attributes.get(LANE_ATTRIBUTE),
+            Some(AttributeValue::Boolean(true))
+        );
 
         // Resolve child node indices
         let mut children = Vec::new();
@@ -674,6 +1448,7 @@ impl ContainerData {
             parent_container: None,
             attributes,
             bounds: None,
+            is_lane,
         })
     }
 }
@@ -714,6 +1489,124 @@ impl GroupData {
     }
 }
 
+/// Map a built-in icon name to its unicode glyph; other values are used verbatim
+pub fn resolve_icon(name: &str) -> &str {
+    match name {
+        "database" | "db" => "🗄",
+        "cloud" => "☁",
+        "server" => "🖥",
+        "user" => "👤",
+        "users" => "👥",
+        "lock" => "🔒",
+        "globe" => "🌐",
+        "queue" => "📨",
+        "cache" => "⚡",
+        "file" => "📄",
+        "gear" => "⚙",
+        "mobile" => "📱",
+        _ => name,
+    }
+}
+
+/// Estimated width of a single line of text
+fn measure_line(line: &str, attributes: &ExcalidrawAttributes) -> f64 {
+    let font_size = attributes.font_size.unwrap_or(20.0);
+    let font_family = match attributes.font.as_deref() {
+        Some("Virgil") => 1,
+        Some("Helvetica") => 2,
+        Some("Cascadia") | Some("Code") => 3,
+        None => 3, // Default to Cascadia
+        _ => 3,
+    };
+
+    // Calculate text dimensions using improved logic for better accuracy
+    let char_width_multiplier = match font_family {
+        1 => 0.65, // Virgil - slightly wider
+        2 => 0.55, // Helvetica - slightly wider
+        3 => 0.6,  // Cascadia - wider for better readability
+        _ => 0.6,
+    };
+
+    // Improved character width calculation with better handling for common characters
+    let effective_length = line
+        .chars()
+        .map(|c| {
+            match c {
+                // Narrow characters
+                'i' | 'l' | '.' | '!' | '|' | '\'' | '`' | 'I' | 'j' | 'f' | 't' => 0.4,
+                // Wide characters
+                'w' | 'm' | 'W' | 'M' | '@' | '%' | '#' => 1.4,
+                // Uppercase letters (generally wider)
+                'A'..='Z' => 1.15,
+                // Space (reduced to save space)
+                ' ' => 0.35,
+                // Numbers and common punctuation
+                '0'..='9' | '(' | ')' | '[' | ']' | '{' | '}' | '-' | '_' | '=' | '+' => 0.9,
+                // Default for most lowercase and other characters
+                _ => 1.0,
+            }
+        })
+        .sum::<f64>();
+
+    effective_length * font_size * char_width_multiplier
+}
+
+/// Estimated `(width, height)` of a label, one line per `\n`
+fn measure_label(label: &str, attributes: &ExcalidrawAttributes) -> (f64, f64) {
+    let font_size = attributes.font_size.unwrap_or(20.0);
+    let width = label
+        .lines()
+        .map(|line| measure_line(line, attributes))
+        .fold(0.0, f64::max);
+    let line_count = label.lines().count().max(1);
+    // Slightly more height than the font size for better appearance
+    (width, font_size * 1.3 * line_count as f64)
+}
+
+/// Greedily wrap a label so each line fits in `max_width`, breaking words
+/// that are too long on their own
+fn wrap_label(label: &str, max_width: f64, attributes: &ExcalidrawAttributes) -> String {
+    let fits = |text: &str| measure_line(text, attributes) <= max_width;
+    let mut lines: Vec<String> = Vec::new();
+
+    for paragraph in label.lines() {
+        let mut current = String::new();
+        for word in paragraph.split(' ').filter(|word| !word.is_empty()) {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{current} {word}")
+            };
+            if fits(&candidate) {
+                current = candidate;
+                continue;
+            }
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            for c in word.chars() {
+                current.push(c);
+                if !fits(&current) && current.chars().count() > 1 {
+                    current.pop();
+                    lines.push(std::mem::take(&mut current));
+                    current.push(c);
+                }
+            }
+        }
+        lines.push(current);
+    }
+
+    lines.join("\n")
+}
+
+/// Parse a `w:h` aspect ratio into `w / h`, rejecting zero or negative parts
+fn parse_aspect_ratio(value: &str) -> Option<f64> {
+    let (width, height) = value.split_once(':')?;
+    let width: f64 = width.trim().parse().ok()?;
+    let height: f64 = height.trim().parse().ok()?;
+    (width > 0.0 && height > 0.0).then_some(width / height)
+}
+
 impl ExcalidrawAttributes {
     pub fn from_hashmap(attrs: &HashMap<String, AttributeValue>) -> Result<Self> {
         let mut excalidraw_attrs = ExcalidrawAttributes::default();
@@ -798,16 +1691,155 @@ impl ExcalidrawAttributes {
                         excalidraw_attrs.end_arrowhead = s.parse().ok();
                     }
                 }
+                "startPort" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.start_port = s.parse().ok();
+                    }
+                }
+                "endPort" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.end_port = s.parse().ok();
+                    }
+                }
                 "rounded" => {
                     if let Some(n) = value.as_number() {
                         excalidraw_attrs.rounded = Some(n);
                     }
                 }
+                "angle" => {
+                    if let Some(n) = value.as_number() {
+                        excalidraw_attrs.angle = Some(n);
+                    }
+                }
                 "color" => {
                     if let Some(s) = value.as_string() {
                         excalidraw_attrs.text_color = Some(s.to_string());
                     }
                 }
+                "labelColor" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.label_color = Some(s.to_string());
+                    }
+                }
+                "showLabel" => {
+                    if let Some(b) = value.as_boolean() {
+                        excalidraw_attrs.show_label = Some(b);
+                    }
+                }
+                "group" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.group = Some(s.to_string());
+                    }
+                }
+                "shadow" => {
+                    if let Some(b) = value.as_boolean() {
+                        excalidraw_attrs.shadow = Some(b);
+                    }
+                }
+                "tooltip" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.tooltip = Some(s.to_string());
+                    }
+                }
+                "labelBackground" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.label_background = Some(s.to_string());
+                    }
+                }
+                "z" => {
+                    if let Some(n) = value.as_number() {
+                        excalidraw_attrs.z_index = Some(n as i32);
+                    }
+                }
+                "minlen" => {
+                    // Dagre allocates a layer per rank, so the span is capped
+                    let min_len = value
+                        .as_number()
+                        .filter(|n| (1.0..=MAX_MIN_LEN as f64).contains(n) && n.fract() == 0.0);
+                    match min_len {
+                        Some(n) => excalidraw_attrs.min_len = Some(n as u32),
+                        None => {
+                            return Err(BuildError::InvalidAttribute {
+                                attribute: key.clone(),
+                                value: format!(
+                                    "{value:?} (expected an integer from 1 to {MAX_MIN_LEN})"
+                                ),
+                            }
+                            .into())
+                        }
+                    }
+                }
+                "curvature" => {
+                    let curvature = value.as_number().filter(|n| (0.0..=1.0).contains(n));
+                    match curvature {
+                        Some(n) => excalidraw_attrs.curvature = Some(n),
+                        None => {
+                            return Err(BuildError::InvalidAttribute {
+                                attribute: key.clone(),
+                                value: format!("{value:?} (expected a number from 0 to 1)"),
+                            }
+                            .into())
+                        }
+                    }
+                }
+                "weight" => {
+                    let weight = value.as_number().filter(|n| n.is_finite() && *n > 0.0);
+                    match weight {
+                        Some(n) => excalidraw_attrs.weight = Some(n),
+                        None => {
+                            return Err(BuildError::InvalidAttribute {
+                                attribute: key.clone(),
+                                value: format!("{value:?} (expected a number > 0)"),
+                            }
+                            .into())
+                        }
+                    }
+                }
+                "icon" => {
+                    if let Some(s) = value.as_string() {
+                        excalidraw_attrs.icon = Some(resolve_icon(s).to_string());
+                    }
+                }
+                "minWidth" | "maxWidth" => {
+                    let width = value.as_number().filter(|n| n.is_finite() && *n > 0.0);
+                    match width {
+                        Some(n) if key == "minWidth" => excalidraw_attrs.min_width = Some(n),
+                        Some(n) => excalidraw_attrs.max_width = Some(n),
+                        None => {
+                            return Err(BuildError::InvalidAttribute {
+                                attribute: key.clone(),
+                                value: format!("{value:?} (expected a number > 0)"),
+                            }
+                            .into())
+                        }
+                    }
+                }
+                "aspect" => {
+                    let ratio = value.as_string().and_then(parse_aspect_ratio);
+                    match ratio {
+                        Some(ratio) => excalidraw_attrs.aspect_ratio = Some(ratio),
+                        None => {
+                            return Err(BuildError::InvalidAttribute {
+                                attribute: key.clone(),
+                                value: format!("{value:?} (expected a ratio like 16:9)"),
+                            }
+                            .into())
+                        }
+                    }
+                }
+                "direction" => {
+                    let direction = value.as_string().and_then(|s| s.parse().ok());
+                    match direction {
+                        Some(direction) => excalidraw_attrs.direction = Some(direction),
+                        None => {
+                            return Err(BuildError::InvalidAttribute {
+                                attribute: key.clone(),
+                                value: format!("{value:?} (expected right, down, left or up)"),
+                            }
+                            .into())
+                        }
+                    }
+                }
                 _ => {
                     // Unknown attribute - could log a warning here
                 }
@@ -827,6 +1859,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -850,10 +1883,15 @@ mod tests {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: HashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             }],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -868,6 +1906,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -888,6 +1927,9 @@ mod tests {
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let result = IntermediateGraph::from_ast(document);
@@ -935,6 +1977,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![NodeDefinition {
@@ -947,6 +1990,9 @@ mod tests {
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -975,6 +2021,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![NodeDefinition {
@@ -993,6 +2040,9 @@ mod tests {
             }],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -1017,6 +2067,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![NodeDefinition {
@@ -1036,6 +2087,9 @@ mod tests {
                 internal_statements: vec![],
             }],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -1049,6 +2103,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_group_attribute_synthesizes_groups() {
+        let input = r#"
+a[A] { group: "x"; }
+b[B] { group: "x"; }
+c[C] { group: "x"; }
+d[D]
+a -> b
+c -> d
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        assert_eq!(igr.groups.len(), 1);
+
+        let group = &igr.groups[0];
+        assert_eq!(group.id, "x");
+        assert_eq!(group.group_type, GroupType::BasicGroup);
+        let children: Vec<&str> = group
+            .children
+            .iter()
+            .map(|&idx| igr.graph[idx].id.as_str())
+            .collect();
+        assert_eq!(children, ["a", "b", "c"]);
+
+        // Laid out through the group-aware path, which bounds the members
+        let bounds = group.bounds.as_ref().unwrap();
+        for &idx in &group.children {
+            let node = &igr.graph[idx];
+            assert!(node.x - node.width / 2.0 >= bounds.x);
+            assert!(node.x + node.width / 2.0 <= bounds.x + bounds.width);
+        }
+    }
+
     #[test]
     fn test_missing_text_color_defaults() {
         let attrs = HashMap::new(); // No color attribute
@@ -1059,4 +2145,103 @@ mod tests {
             "Text color should be None when not specified"
         );
     }
+
+    #[test]
+    fn test_number_messages() {
+        let input = r#"
+---
+number_messages: true
+---
+client[Client]
+server[Server]
+db[Database]
+client -> server: request
+server -> db
+db -> server: rows
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+
+        let labels: Vec<_> = igr
+            .graph
+            .edge_weights()
+            .map(|edge| edge.label.as_deref().unwrap())
+            .collect();
+        assert_eq!(labels, ["1. request", "2.", "3. rows"]);
+    }
+
+    #[test]
+    fn test_number_messages_follows_declaration_order() {
+        let input = r#"
+---
+number_messages: true
+---
+client[Client]
+client -> api: login
+container "Backend" {
+  api[API]
+  db[Database]
+  api -> db: query
+}
+api -> client: token
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+
+        let mut labels: Vec<_> = igr
+            .graph
+            .edge_weights()
+            .map(|edge| edge.label.clone().unwrap())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, ["1. login", "2. query", "3. token"]);
+    }
+
+    #[test]
+    fn test_bounding_box_encloses_layout() {
+        let input = r##"
+container "Backend" {
+  api[API]
+  db[Database]
+  api -> db
+}
+user[User]
+user -> api
+line from (-300, 0) to (-250, 40)
+note "Far away" at (2000, 1500) { backgroundColor: "#fff3bf"; }
+"##;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let bounds = igr.bounding_box();
+
+        let encloses = |x1: f64, y1: f64, x2: f64, y2: f64| {
+            x1 >= bounds.x - 1e-6
+                && y1 >= bounds.y - 1e-6
+                && x2 <= bounds.x + bounds.width + 1e-6
+                && y2 <= bounds.y + bounds.height + 1e-6
+        };
+        for node in igr.graph.node_weights() {
+            assert!(encloses(
+                node.x - node.width / 2.0,
+                node.y - node.height / 2.0,
+                node.x + node.width / 2.0,
+                node.y + node.height / 2.0
+            ));
+        }
+        let container = igr.containers[0].bounds.as_ref().unwrap();
+        assert!(encloses(
+            container.x,
+            container.y,
+            container.x + container.width,
+            container.y + container.height
+        ));
+        assert_eq!(bounds.x, -300.0);
+        // The sticky note's padded box, not just its corner
+        let (width, height) = igr.notes[0].estimated_size();
+        assert!(width > NOTE_PADDING * 2.0 && height > NOTE_PADDING * 2.0);
+        assert!(encloses(2000.0, 1500.0, 2000.0 + width, 1500.0 + height));
+        assert_eq!(bounds.x + bounds.width, 2000.0 + width);
+
+        let empty = IntermediateGraph::new().bounding_box();
+        assert_eq!((empty.width, empty.height), (0.0, 0.0));
+    }
 }
@@ -0,0 +1,125 @@
+// src/layout/benchmark.rs
+//! Compare layout engines on a set of graphs
+
+use super::{count_edge_crossings, LayoutManager};
+use crate::igr::IntermediateGraph;
+use std::time::{Duration, Instant};
+
+/// Engines compared by [`compare_engines`], in report order
+pub const BENCHMARK_ENGINES: [&str; 3] = ["dagre", "force", "elk"];
+
+/// Layout quality and speed of one engine, summed over every graph it laid
+/// out successfully
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineReport {
+    pub engine: &'static str,
+    /// Graphs the engine laid out
+    pub graphs: usize,
+    /// Graphs the engine failed on, which contribute no metrics
+    pub failures: usize,
+    /// Pairs of edges whose center-to-center lines cross
+    pub crossings: usize,
+    /// Pairs of nodes whose boxes intersect
+    pub overlaps: usize,
+    /// Area of each graph's bounding box
+    pub bbox_area: f64,
+    /// Time spent in layout
+    pub duration: Duration,
+}
+
+/// Lay out every graph with each of [`BENCHMARK_ENGINES`] and report one
+/// row per engine. The layout cache is disabled so each run is timed from
+/// scratch, and the input graphs are left untouched.
+pub fn compare_engines(graphs: &[IntermediateGraph]) -> Vec<EngineReport> {
+    let mut manager = LayoutManager::new();
+    manager.enable_cache(false);
+
+    BENCHMARK_ENGINES
+        .iter()
+        .map(|&engine| {
+            let mut report = EngineReport {
+                engine,
+                graphs: 0,
+                failures: 0,
+                crossings: 0,
+                overlaps: 0,
+                bbox_area: 0.0,
+                duration: Duration::ZERO,
+            };
+
+            for graph in graphs {
+                let mut igr = graph.clone();
+                igr.global_config.layout = Some(engine.to_string());
+
+                let start = Instant::now();
+                let result = manager.layout(&mut igr);
+                let elapsed = start.elapsed();
+                if let Err(e) = result {
+                    log::warn!("{engine} layout failed: {e}");
+                    report.failures += 1;
+                    continue;
+                }
+
+                let bounds = igr.bounding_box();
+                report.graphs += 1;
+                report.crossings += count_edge_crossings(&igr);
+                report.overlaps += count_node_overlaps(&igr);
+                report.bbox_area += bounds.width * bounds.height;
+                report.duration += elapsed;
+            }
+
+            report
+        })
+        .collect()
+}
+
+/// Number of pairs of nodes whose boxes intersect
+pub fn count_node_overlaps(igr: &IntermediateGraph) -> usize {
+    let nodes: Vec<_> = igr
+        .graph
+        .node_weights()
+        .filter(|node| !node.is_virtual_container)
+        .collect();
+    let mut overlaps = 0;
+    for (i, first) in nodes.iter().enumerate() {
+        overlaps += nodes[i + 1..]
+            .iter()
+            .filter(|second| {
+                (first.x - second.x).abs() < (first.width + second.width) / 2.0
+                    && (first.y - second.y).abs() < (first.height + second.height) / 2.0
+            })
+            .count();
+    }
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_edsl;
+
+    #[test]
+    fn test_compare_engines_reports_each_engine() {
+        let graphs: Vec<_> = [
+            "a[A]\nb[B]\nc[C]\nd[D]\na -> b\na -> c\nb -> d\nc -> d",
+            "x[X]\ny[Y]\nz[Z]\nw[W]\nx -> y\nx -> z",
+        ]
+        .iter()
+        .map(|src| IntermediateGraph::from_ast(parse_edsl(src).unwrap()).unwrap())
+        .collect();
+
+        let reports = compare_engines(&graphs);
+        let engines: Vec<_> = reports.iter().map(|r| r.engine).collect();
+        assert_eq!(engines, BENCHMARK_ENGINES);
+        for report in &reports {
+            assert_eq!(report.graphs, 2, "{report:?}");
+            assert_eq!(report.failures, 0, "{report:?}");
+            assert!(
+                report.bbox_area.is_finite() && report.bbox_area > 0.0,
+                "{report:?}"
+            );
+        }
+        // The inputs are cloned, never laid out in place
+        assert!(graphs[0].graph.node_weights().all(|n| n.x == 0.0));
+    }
+}
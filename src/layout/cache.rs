@@ -1,8 +1,11 @@
 // src/layout/cache.rs
-use crate::igr::IntermediateGraph;
-use std::collections::hash_map::DefaultHasher;
+use crate::ast::PortSide;
+use crate::igr::{BoundingBox, IntermediateGraph};
+use crate::stable_hash::StableHasher;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 
 /// Cache key for layout results
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -13,45 +16,191 @@ pub struct LayoutCacheKey {
 
 impl LayoutCacheKey {
     pub fn from_igr(igr: &IntermediateGraph, engine: &str) -> Self {
-        let mut hasher = DefaultHasher::new();
+        Self::from_igr_with_options(igr, engine, "")
+    }
+
+    /// Build a key from the graph topology, edge ports, node sizes, container and group
+    /// membership and attributes, layout-relevant config and the engine's
+    /// option fingerprint. The hash is stable across Rust releases, so keys
+    /// persisted in a cache directory stay valid.
+    pub fn from_igr_with_options(
+        igr: &IntermediateGraph,
+        engine: &str,
+        engine_options: &str,
+    ) -> Self {
+        let mut hasher = StableHasher::default();
 
-        // Hash nodes
-        let mut node_ids: Vec<_> = igr
+        // Hash nodes with their sizes
+        let mut nodes: Vec<_> = igr
             .graph
             .node_indices()
-            .map(|idx| &igr.graph[idx].id)
+            .map(|idx| {
+                let node = &igr.graph[idx];
+                (&node.id, node.width.to_bits(), node.height.to_bits())
+            })
             .collect();
-        node_ids.sort();
+        nodes.sort();
 
-        for id in &node_ids {
-            id.hash(&mut hasher);
+        for node in &nodes {
+            node.hash(&mut hasher);
         }
 
-        // Hash edges
-        let mut edge_pairs: Vec<_> = igr
+        // Hash edges, including the attributes that influence ranking and
+        // the ports the user picked, which a cache hit would otherwise
+        // replace with the ones stored for the old graph
+        let mut edges: Vec<_> = igr
             .graph
             .edge_indices()
             .map(|idx| {
                 let (source, target) = igr.graph.edge_endpoints(idx).unwrap();
-                (&igr.graph[source].id, &igr.graph[target].id)
+                let edge = &igr.graph[idx];
+                (
+                    &igr.graph[source].id,
+                    &igr.graph[target].id,
+                    edge.label.as_deref(),
+                    edge.attributes.min_len,
+                    edge.attributes.weight.map(f64::to_bits),
+                    edge.attributes.start_port,
+                    edge.attributes.end_port,
+                )
             })
             .collect();
-        edge_pairs.sort();
+        edges.sort();
 
-        for (source, target) in &edge_pairs {
-            source.hash(&mut hasher);
-            target.hash(&mut hasher);
+        for edge in &edges {
+            edge.hash(&mut hasher);
         }
 
+        // Container and group membership and attributes affect
+        // cluster-aware layouts
+        let member_ids = |children: &[petgraph::graph::NodeIndex]| {
+            let mut ids: Vec<&str> = children
+                .iter()
+                .map(|&idx| igr.graph[idx].id.as_str())
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+        for container in &igr.containers {
+            container.id.hash(&mut hasher);
+            member_ids(&container.children).hash(&mut hasher);
+            container.nested_containers.hash(&mut hasher);
+            container.nested_groups.hash(&mut hasher);
+            container.parent_container.hash(&mut hasher);
+            container.is_lane.hash(&mut hasher);
+            container
+                .attributes
+                .aspect_ratio
+                .map(f64::to_bits)
+                .hash(&mut hasher);
+            format!("{:?}", container.attributes.direction).hash(&mut hasher);
+        }
+        for group in &igr.groups {
+            group.id.hash(&mut hasher);
+            format!("{:?}", group.group_type).hash(&mut hasher);
+            member_ids(&group.children).hash(&mut hasher);
+            group.nested_containers.hash(&mut hasher);
+            group.nested_groups.hash(&mut hasher);
+            group.parent_group.hash(&mut hasher);
+            group.parent_container.hash(&mut hasher);
+        }
+
+        serde_json::to_string(&igr.global_config)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        engine_options.hash(&mut hasher);
+
         Self {
             graph_hash: hasher.finish(),
             engine: engine.to_string(),
         }
     }
+
+    /// File name used for this key in an on-disk cache directory
+    pub fn file_name(&self) -> String {
+        format!("{}-{:016x}.json", self.engine, self.graph_hash)
+    }
+
+    /// Whether `name` has the shape of a [`file_name`](Self::file_name), so
+    /// clearing a cache directory leaves unrelated files alone
+    pub fn is_cache_file_name(name: &str) -> bool {
+        let Some((engine, hash)) = name
+            .strip_suffix(".json")
+            .and_then(|stem| stem.rsplit_once('-'))
+        else {
+            return false;
+        };
+        !engine.is_empty() && hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+    }
 }
 
 /// Cached layout positions
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CachedLayout {
     pub positions: HashMap<String, (f64, f64)>,
+    #[serde(default)]
+    pub container_bounds: Vec<Option<BoundingBox>>,
+    #[serde(default)]
+    pub group_bounds: Vec<Option<BoundingBox>>,
+    /// Start and end ports per edge, in edge index order, for engines that
+    /// choose attachment sides
+    #[serde(default)]
+    pub edge_ports: Vec<(Option<PortSide>, Option<PortSide>)>,
+}
+
+impl CachedLayout {
+    /// Capture the layout result of a graph
+    pub fn from_igr(igr: &IntermediateGraph) -> Self {
+        Self {
+            positions: igr
+                .graph
+                .node_weights()
+                .map(|node| (node.id.clone(), (node.x, node.y)))
+                .collect(),
+            container_bounds: igr.containers.iter().map(|c| c.bounds.clone()).collect(),
+            group_bounds: igr.groups.iter().map(|g| g.bounds.clone()).collect(),
+            edge_ports: igr
+                .graph
+                .edge_weights()
+                .map(|edge| (edge.attributes.start_port, edge.attributes.end_port))
+                .collect(),
+        }
+    }
+
+    /// Apply cached positions and bounds to a graph
+    pub fn apply(&self, igr: &mut IntermediateGraph) {
+        for node in igr.graph.node_weights_mut() {
+            if let Some(&(x, y)) = self.positions.get(&node.id) {
+                node.x = x;
+                node.y = y;
+            }
+        }
+        for (container, bounds) in igr.containers.iter_mut().zip(&self.container_bounds) {
+            container.bounds = bounds.clone();
+        }
+        for (group, bounds) in igr.groups.iter_mut().zip(&self.group_bounds) {
+            group.bounds = bounds.clone();
+        }
+        for (edge, &(start_port, end_port)) in igr.graph.edge_weights_mut().zip(&self.edge_ports) {
+            edge.attributes.start_port = start_port;
+            edge.attributes.end_port = end_port;
+        }
+    }
+
+    /// Read a cached layout from `dir`, returning `None` if missing or unreadable
+    pub fn load(dir: &Path, key: &LayoutCacheKey) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(dir, key)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Write a cached layout to `dir`, creating the directory if needed
+    pub fn store(&self, dir: &Path, key: &LayoutCacheKey) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let content = serde_json::to_string(self)?;
+        std::fs::write(Self::path(dir, key), content)
+    }
+
+    fn path(dir: &Path, key: &LayoutCacheKey) -> PathBuf {
+        dir.join(key.file_name())
+    }
 }
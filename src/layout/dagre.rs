@@ -1,5 +1,5 @@
 // src/layout/dagre.rs
-use super::LayoutEngine;
+use super::{LayoutContext, LayoutEngine};
 use crate::ast::GroupType;
 use crate::error::{LayoutError, Result};
 use crate::igr::{BoundingBox, ContainerData, EdgeData, IntermediateGraph, NodeData};
@@ -8,11 +8,30 @@ use petgraph::visit::EdgeRef;
 use petgraph::Direction as PetDirection;
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 // use std::sync::{Arc, Mutex}; // Reserved for future parallel processing
 
+/// Space between a container's border and its contents
+const CONTAINER_PADDING: f64 = 20.0;
+
+/// Crossing reduction passes for graphs up to `large_graph_threshold` nodes
+const CROSSING_PASSES: usize = 4;
+
+/// Marks nodes absent from the reference layer in `LayerOrder::positions`
+const NOT_IN_LAYER: usize = usize::MAX;
+
+/// Buffers reused by every layer sort of a crossing reduction run
+struct LayerOrder {
+    /// Position of each node (by index) in the reference layer
+    positions: Vec<usize>,
+    /// Scratch list of each layer node and its barycenter
+    barycenters: Vec<(NodeIndex, Option<f64>)>,
+}
+
 // Basic Dagre-like hierarchical layout
 pub struct DagreLayout {
     options: DagreLayoutOptions,
+    crossing_passes_run: AtomicUsize,
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +45,15 @@ pub struct DagreLayoutOptions {
     pub parallel: bool,
     /// Minimum nodes per layer to trigger parallel processing
     pub parallel_threshold: usize,
+    /// Pull adjacent layers closer after positioning (overridden by `compact_layers` config)
+    pub compact_layers: bool,
+    /// Smallest gap left between layers when compacting
+    pub min_layer_gap: f64,
+    /// Graphs with more nodes than this run `large_graph_passes` crossing
+    /// reduction passes instead of the usual four
+    pub large_graph_threshold: usize,
+    /// Crossing reduction passes for graphs above `large_graph_threshold`
+    pub large_graph_passes: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +81,10 @@ impl Default for DagreLayoutOptions {
             ranker: RankingAlgorithm::LongestPath,
             parallel: true,         // Enable parallel processing by default
             parallel_threshold: 10, // Use parallel processing for layers with 10+ nodes
+            compact_layers: false,
+            min_layer_gap: 40.0,
+            large_graph_threshold: 500,
+            large_graph_passes: 2,
         }
     }
 }
@@ -65,18 +97,34 @@ impl Default for DagreLayout {
 
 impl DagreLayout {
     pub fn new() -> Self {
+        Self::with_options(DagreLayoutOptions::default())
+    }
+
+    pub fn with_options(options: DagreLayoutOptions) -> Self {
         Self {
-            options: DagreLayoutOptions::default(),
+            options,
+            crossing_passes_run: AtomicUsize::new(0),
         }
     }
 
-    pub fn with_options(options: DagreLayoutOptions) -> Self {
-        Self { options }
+    /// Crossing reduction passes performed by the most recent crossing
+    /// minimization
+    pub fn crossing_passes_run(&self) -> usize {
+        self.crossing_passes_run.load(Ordering::Relaxed)
     }
 }
 
 impl LayoutEngine for DagreLayout {
     fn layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
+        let context = LayoutContext::from_config(&igr.global_config);
+        self.layout_with_context(igr, &context)
+    }
+
+    fn layout_with_context(
+        &self,
+        igr: &mut IntermediateGraph,
+        context: &LayoutContext,
+    ) -> Result<()> {
         if igr.graph.node_count() == 0 {
             return Ok(());
         }
@@ -86,11 +134,13 @@ impl LayoutEngine for DagreLayout {
             self.layout_with_groups(igr)?;
         } else {
             // Standard layout without groups
-            self.layout_standard(igr)?;
+            self.layout_standard(igr, context)?;
         }
+        self.constrain_lanes(igr);
 
         // Calculate bounds for containers and groups
         self.calculate_container_bounds(igr);
+        self.stretch_lane_bounds(igr);
         self.calculate_group_bounds(igr);
 
         Ok(())
@@ -99,18 +149,361 @@ impl LayoutEngine for DagreLayout {
     fn name(&self) -> &'static str {
         "dagre"
     }
+
+    fn options_fingerprint(&self) -> String {
+        format!("{:?}", self.options)
+    }
 }
 
 impl DagreLayout {
-    fn layout_standard(&self, igr: &mut IntermediateGraph) -> Result<()> {
-        // Standard layout algorithm
-        let node_ranks = self.assign_ranks(igr)?;
+    fn layout_standard(&self, igr: &mut IntermediateGraph, context: &LayoutContext) -> Result<()> {
+        // Standard layout algorithm; isolated nodes are kept out of the
+        // ranking so they don't crowd the sink layer
+        let isolated = Self::isolated_nodes(igr);
+        let isolated_set: HashSet<NodeIndex> = isolated.iter().copied().collect();
+        let mut node_ranks = self.assign_ranks(igr)?;
+        node_ranks.retain(|idx, _| !isolated_set.contains(idx));
         let layers = self.build_layers(igr, &node_ranks);
         let ordered_layers = self.minimize_crossings(igr, layers);
         self.position_nodes(igr, &ordered_layers)?;
+
+        self.compact_if_enabled(igr);
+        self.place_isolated_nodes(igr, &isolated, context);
         Ok(())
     }
 
+    /// Run [`compact_layers`](Self::compact_layers) when the config or the
+    /// engine options ask for it
+    fn compact_if_enabled(&self, igr: &mut IntermediateGraph) {
+        let compact = igr
+            .global_config
+            .compact_layers
+            .unwrap_or(self.options.compact_layers);
+        if compact {
+            self.compact_layers(igr, self.options.min_layer_gap);
+        }
+    }
+
+    /// Nodes with no edges, outside any container, in a graph that has edges
+    ///
+    /// A graph without edges is laid out as usual, in a single layer.
+    fn isolated_nodes(igr: &IntermediateGraph) -> Vec<NodeIndex> {
+        if igr.graph.edge_count() == 0 {
+            return Vec::new();
+        }
+        let contained: HashSet<NodeIndex> = igr
+            .containers
+            .iter()
+            .flat_map(|container| container.children.iter().copied())
+            .collect();
+        igr.graph
+            .node_indices()
+            .filter(|&idx| {
+                !igr.graph[idx].is_virtual_container
+                    && !contained.contains(&idx)
+                    && igr.graph.neighbors_undirected(idx).next().is_none()
+            })
+            .collect()
+    }
+
+    /// Arrange isolated nodes in a grid past the end of the flow, in
+    /// declaration order, one rank separation away from the rest. The grid
+    /// wraps to fit the context's layout area.
+    fn place_isolated_nodes(
+        &self,
+        igr: &mut IntermediateGraph,
+        isolated: &[NodeIndex],
+        context: &LayoutContext,
+    ) {
+        if isolated.is_empty() {
+            return;
+        }
+        let skip: HashSet<NodeIndex> = isolated.iter().copied().collect();
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (0.0_f64, 0.0_f64, 0.0_f64, 0.0_f64);
+        let mut placed = igr
+            .graph
+            .node_indices()
+            .filter(|idx| !skip.contains(idx))
+            .map(|idx| &igr.graph[idx])
+            .peekable();
+        if let Some(first) = placed.peek() {
+            (min_x, min_y, max_x, max_y) = (first.x, first.y, first.x, first.y);
+        }
+        for node in placed {
+            min_x = min_x.min(node.x - node.width / 2.0);
+            min_y = min_y.min(node.y - node.height / 2.0);
+            max_x = max_x.max(node.x + node.width / 2.0);
+            max_y = max_y.max(node.y + node.height / 2.0);
+        }
+
+        let cell_width = isolated
+            .iter()
+            .map(|&idx| igr.graph[idx].width)
+            .fold(0.0, f64::max);
+        let cell_height = isolated
+            .iter()
+            .map(|&idx| igr.graph[idx].height)
+            .fold(0.0, f64::max);
+        let columns = context.grid_columns(
+            isolated.len(),
+            cell_width,
+            cell_height,
+            self.options.node_sep,
+        );
+        let rows = isolated.len().div_ceil(columns);
+        let (step_x, step_y) = (
+            cell_width + self.options.node_sep,
+            cell_height + self.options.node_sep,
+        );
+        let grid_width = columns as f64 * step_x - self.options.node_sep;
+        let grid_height = rows as f64 * step_y - self.options.node_sep;
+
+        let (left, top) = match self.options.direction {
+            Direction::LeftRight => (max_x + self.options.rank_sep, min_y),
+            Direction::RightLeft => (min_x - self.options.rank_sep - grid_width, min_y),
+            Direction::TopBottom => (min_x, max_y + self.options.rank_sep),
+            Direction::BottomTop => (min_x, min_y - self.options.rank_sep - grid_height),
+        };
+        for (i, &idx) in isolated.iter().enumerate() {
+            let node = &mut igr.graph[idx];
+            node.x = left + (i % columns) as f64 * step_x + cell_width / 2.0;
+            node.y = top + (i / columns) as f64 * step_y + cell_height / 2.0;
+        }
+    }
+
+    /// Whether ranks advance along x, so lanes are horizontal bands
+    fn is_horizontal(&self) -> bool {
+        matches!(
+            self.options.direction,
+            Direction::LeftRight | Direction::RightLeft
+        )
+    }
+
+    /// Top-level lanes, in declaration order, with the real nodes inside each
+    /// (including those of nested containers)
+    fn lanes(igr: &IntermediateGraph) -> Vec<(usize, Vec<NodeIndex>)> {
+        fn collect(igr: &IntermediateGraph, idx: usize, members: &mut Vec<NodeIndex>) {
+            let container = &igr.containers[idx];
+            members.extend(
+                container
+                    .children
+                    .iter()
+                    .filter(|&&child| !igr.graph[child].is_virtual_container),
+            );
+            for &nested in &container.nested_containers {
+                collect(igr, nested, members);
+            }
+        }
+
+        igr.containers
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_lane && c.parent_container.is_none())
+            .map(|(idx, _)| {
+                let mut members = Vec::new();
+                collect(igr, idx, &mut members);
+                (idx, members)
+            })
+            .collect()
+    }
+
+    /// Give each lane its own band across the flow. Members keep their
+    /// relative cross-axis positions; the bands are stacked in declaration
+    /// order so that the padded lane borders meet, and nodes outside any
+    /// lane follow the last band. Rank positions are left untouched.
+    fn constrain_lanes(&self, igr: &mut IntermediateGraph) {
+        let lanes = Self::lanes(igr);
+        if lanes.is_empty() {
+            return;
+        }
+        let horizontal = self.is_horizontal();
+        // (center, size) of a node across the flow
+        let cross = |node: &NodeData| {
+            if horizontal {
+                (node.y, node.height)
+            } else {
+                (node.x, node.width)
+            }
+        };
+
+        let in_lane: HashSet<NodeIndex> = lanes
+            .iter()
+            .flat_map(|(_, members)| members.iter().copied())
+            .collect();
+        let outside: Vec<NodeIndex> = igr
+            .graph
+            .node_indices()
+            .filter(|idx| !in_lane.contains(idx) && !igr.graph[*idx].is_virtual_container)
+            .collect();
+
+        let mut band_start = 0.0;
+        for members in lanes
+            .into_iter()
+            .map(|(_, members)| members)
+            .chain(std::iter::once(outside))
+        {
+            let (low, high) =
+                members
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &idx| {
+                        let (center, size) = cross(&igr.graph[idx]);
+                        (low.min(center - size / 2.0), high.max(center + size / 2.0))
+                    });
+            if !low.is_finite() {
+                continue;
+            }
+            let shift = band_start - low;
+            for &idx in &members {
+                let node = &mut igr.graph[idx];
+                if horizontal {
+                    node.y += shift;
+                } else {
+                    node.x += shift;
+                }
+            }
+            band_start += high - low + 2.0 * CONTAINER_PADDING;
+        }
+    }
+
+    /// Extend every lane along the flow to the combined extent of all lanes,
+    /// so they read as parallel bands
+    fn stretch_lane_bounds(&self, igr: &mut IntermediateGraph) {
+        let lanes: Vec<usize> = Self::lanes(igr).into_iter().map(|(idx, _)| idx).collect();
+        let horizontal = self.is_horizontal();
+        let (start, end) = lanes
+            .iter()
+            .filter_map(|&idx| igr.containers[idx].bounds.as_ref())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(start, end), b| {
+                if horizontal {
+                    (start.min(b.x), end.max(b.x + b.width))
+                } else {
+                    (start.min(b.y), end.max(b.y + b.height))
+                }
+            });
+        if !start.is_finite() {
+            return;
+        }
+        for idx in lanes {
+            if let Some(bounds) = igr.containers[idx].bounds.as_mut() {
+                if horizontal {
+                    bounds.x = start;
+                    bounds.width = end - start;
+                } else {
+                    bounds.y = start;
+                    bounds.height = end - start;
+                }
+            }
+        }
+    }
+
+    /// Reduce whitespace between adjacent layers after positioning.
+    ///
+    /// Layers are recovered from the rank-axis coordinates. The gap between two
+    /// layers shrinks towards `min_gap` when the edges between them are short
+    /// along the cross axis; layers joined by labeled edges keep `rank_sep` so
+    /// the labels have room.
+    pub fn compact_layers(&self, igr: &mut IntermediateGraph, min_gap: f64) {
+        let horizontal = matches!(
+            self.options.direction,
+            Direction::LeftRight | Direction::RightLeft
+        );
+        let sign = match self.options.direction {
+            Direction::RightLeft | Direction::BottomTop => -1.0,
+            _ => 1.0,
+        };
+        let rank_coord = |node: &NodeData| (if horizontal { node.x } else { node.y }) * sign;
+        let rank_extent = |node: &NodeData| if horizontal { node.width } else { node.height };
+        let cross_coord = |node: &NodeData| if horizontal { node.y } else { node.x };
+
+        // Group nodes into layers by their rank coordinate
+        let mut layers: Vec<(f64, Vec<NodeIndex>)> = Vec::new();
+        // Isolated nodes sit beside the layers rather than in them
+        let isolated: HashSet<NodeIndex> = Self::isolated_nodes(igr).into_iter().collect();
+        let mut indices: Vec<NodeIndex> = igr
+            .graph
+            .node_indices()
+            .filter(|idx| !isolated.contains(idx))
+            .collect();
+        indices.sort_by(|a, b| {
+            rank_coord(&igr.graph[*a])
+                .partial_cmp(&rank_coord(&igr.graph[*b]))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        for idx in indices {
+            let pos = rank_coord(&igr.graph[idx]);
+            match layers.last_mut() {
+                Some((layer_pos, members)) if (*layer_pos - pos).abs() < 0.5 => members.push(idx),
+                _ => layers.push((pos, vec![idx])),
+            }
+        }
+
+        if layers.len() < 2 {
+            return;
+        }
+
+        let layer_of: HashMap<NodeIndex, usize> = layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer_idx, (_, members))| members.iter().map(move |&idx| (idx, layer_idx)))
+            .collect();
+        let half_extent = |members: &[NodeIndex]| {
+            members
+                .iter()
+                .map(|&idx| rank_extent(&igr.graph[idx]))
+                .fold(0.0, f64::max)
+                / 2.0
+        };
+
+        // Desired gap after each layer, based on the edges leaving it
+        let mut gaps = Vec::with_capacity(layers.len() - 1);
+        for layer_idx in 0..layers.len() - 1 {
+            let mut longest_cross = 0.0_f64;
+            let mut has_label = false;
+            for edge in igr.graph.edge_references() {
+                let (Some(&from), Some(&to)) =
+                    (layer_of.get(&edge.source()), layer_of.get(&edge.target()))
+                else {
+                    continue;
+                };
+                if from.min(to) == layer_idx && from.max(to) == layer_idx + 1 {
+                    let source = &igr.graph[edge.source()];
+                    let target = &igr.graph[edge.target()];
+                    longest_cross =
+                        longest_cross.max((cross_coord(source) - cross_coord(target)).abs());
+                    has_label |= edge.weight().label.is_some();
+                }
+            }
+
+            let gap = if has_label {
+                self.options.rank_sep
+            } else {
+                (longest_cross / 2.0).clamp(min_gap, self.options.rank_sep.max(min_gap))
+            };
+            gaps.push(gap);
+        }
+
+        // Re-stack layers using the compacted gaps
+        let mut new_positions = Vec::with_capacity(layers.len());
+        new_positions.push(layers[0].0);
+        for layer_idx in 1..layers.len() {
+            let previous_end = new_positions[layer_idx - 1] + half_extent(&layers[layer_idx - 1].1);
+            let pos = previous_end + gaps[layer_idx - 1] + half_extent(&layers[layer_idx].1);
+            // Never spread layers further apart than the original layout
+            new_positions.push(pos.min(layers[layer_idx].0));
+        }
+
+        for ((_, members), &pos) in layers.iter().zip(new_positions.iter()) {
+            for &idx in members {
+                let node = &mut igr.graph[idx];
+                if horizontal {
+                    node.x = pos * sign;
+                } else {
+                    node.y = pos * sign;
+                }
+            }
+        }
+    }
+
     fn layout_with_groups(&self, igr: &mut IntermediateGraph) -> Result<()> {
         // Create a map of nodes to their group
         let mut node_to_group: HashMap<NodeIndex, usize> = HashMap::new();
@@ -150,6 +543,7 @@ impl DagreLayout {
 
         // Adjust positions to ensure groups don't overlap
         self.adjust_group_positions(igr);
+        self.compact_if_enabled(igr);
 
         Ok(())
     }
@@ -402,9 +796,10 @@ impl DagreLayout {
             .edges_directed(node, PetDirection::Outgoing)
             .map(|edge| {
                 let target = edge.target();
-                // Edge weight (min length) is 1 by default
-                let edge_weight = 1;
-                Self::dfs_rank(igr, target, ranks, visited) - edge_weight
+                // Edge weight (min length) is 1 unless the edge sets `minlen`
+                let min_len = edge.weight().attributes.min_len.unwrap_or(1);
+                let edge_weight = i32::try_from(min_len).unwrap_or(i32::MAX);
+                Self::dfs_rank(igr, target, ranks, visited).saturating_sub(edge_weight)
             })
             .collect();
 
@@ -428,13 +823,14 @@ impl DagreLayout {
             layers_map.entry(rank).or_insert_with(Vec::new).push(*node);
         }
 
-        // Convert to sorted vector of layers
-        let mut sorted_ranks: Vec<i32> = layers_map.keys().copied().collect();
-        sorted_ranks.sort();
+        // Convert to a vector of layers, keeping empty ranks skipped by `minlen` edges
+        let (Some(&min_rank), Some(&max_rank)) = (layers_map.keys().min(), layers_map.keys().max())
+        else {
+            return Vec::new();
+        };
 
-        sorted_ranks
-            .into_iter()
-            .map(|rank| layers_map.remove(&rank).unwrap())
+        (min_rank..=max_rank)
+            .map(|rank| layers_map.remove(&rank).unwrap_or_default())
             .collect()
     }
 
@@ -444,171 +840,127 @@ impl DagreLayout {
         igr: &IntermediateGraph,
         mut layers: Vec<Vec<NodeIndex>>,
     ) -> Vec<Vec<NodeIndex>> {
+        let passes = if igr.graph.node_count() > self.options.large_graph_threshold {
+            self.options.large_graph_passes
+        } else {
+            CROSSING_PASSES
+        };
+        let mut order = LayerOrder {
+            positions: vec![NOT_IN_LAYER; igr.graph.node_count()],
+            barycenters: Vec::new(),
+        };
+
         // Multiple passes to improve crossing reduction
-        for _ in 0..4 {
+        let mut passes_run = 0;
+        for _ in 0..passes {
+            passes_run += 1;
+            let mut changed = false;
+
             // Forward pass (top to bottom)
             for i in 1..layers.len() {
                 let (prev_part, curr_part) = layers.split_at_mut(i);
-                let prev_layer = &prev_part[i - 1];
-                let current_layer = &mut curr_part[0];
-                self.sort_layer_by_barycenter(igr, current_layer, prev_layer, true);
+                changed |= self.sort_layer_by_barycenter(
+                    igr,
+                    &mut curr_part[0],
+                    &prev_part[i - 1],
+                    true,
+                    &mut order,
+                );
             }
 
             // Backward pass (bottom to top)
-            for i in (0..layers.len() - 1).rev() {
+            for i in (0..layers.len().saturating_sub(1)).rev() {
                 let (curr_part, next_part) = layers.split_at_mut(i + 1);
-                let next_layer = &next_part[0];
-                let current_layer = &mut curr_part[i];
-                self.sort_layer_by_barycenter(igr, current_layer, next_layer, false);
+                changed |= self.sort_layer_by_barycenter(
+                    igr,
+                    &mut curr_part[i],
+                    &next_part[0],
+                    false,
+                    &mut order,
+                );
+            }
+
+            // A pass that moved nothing would repeat identically
+            if !changed {
+                break;
             }
         }
+        self.crossing_passes_run
+            .store(passes_run, Ordering::Relaxed);
 
         layers
     }
 
-    // Sort nodes in a layer based on barycenter of connected nodes
+    /// Sort nodes in a layer by the barycenter of their neighbors in
+    /// `reference_layer`; nodes without such neighbors keep their relative
+    /// order at the end. Returns whether the order changed.
     fn sort_layer_by_barycenter(
         &self,
         igr: &IntermediateGraph,
         layer: &mut [NodeIndex],
         reference_layer: &[NodeIndex],
         forward: bool,
-    ) {
-        if self.options.parallel && layer.len() >= self.options.parallel_threshold {
-            self.sort_layer_by_barycenter_parallel(igr, layer, reference_layer, forward);
-        } else {
-            self.sort_layer_by_barycenter_sequential(igr, layer, reference_layer, forward);
+        order: &mut LayerOrder,
+    ) -> bool {
+        for (i, &node) in reference_layer.iter().enumerate() {
+            order.positions[node.index()] = i;
         }
-    }
 
-    // Parallel version of barycenter sorting
-    fn sort_layer_by_barycenter_parallel(
-        &self,
-        igr: &IntermediateGraph,
-        layer: &mut [NodeIndex],
-        reference_layer: &[NodeIndex],
-        forward: bool,
-    ) {
-        // Create position map for reference layer
-        let positions: HashMap<NodeIndex, usize> = reference_layer
-            .iter()
-            .enumerate()
-            .map(|(i, &node)| (node, i))
-            .collect();
-
-        // Calculate barycenters in parallel
-        let mut barycenters: Vec<(NodeIndex, Option<f64>)> = layer
-            .par_iter()
-            .map(|&node| {
-                let edges = if forward {
-                    igr.graph.edges_directed(node, PetDirection::Incoming)
-                } else {
-                    igr.graph.edges_directed(node, PetDirection::Outgoing)
-                };
-
-                let (sum, count) = edges.fold((0.0, 0), |(sum, count), edge| {
-                    let other_node = if forward {
-                        edge.source()
-                    } else {
-                        edge.target()
-                    };
-                    if let Some(&pos) = positions.get(&other_node) {
-                        (sum + pos as f64, count + 1)
-                    } else {
-                        (sum, count)
-                    }
-                });
-
-                let barycenter = if count > 0 {
-                    Some(sum / count as f64)
-                } else {
-                    None
-                };
+        let positions = &order.positions;
+        let barycenter = |node: NodeIndex| {
+            let direction = if forward {
+                // Look at incoming edges from previous layer
+                PetDirection::Incoming
+            } else {
+                // Look at outgoing edges to next layer
+                PetDirection::Outgoing
+            };
+            let (sum, count) =
+                igr.graph
+                    .edges_directed(node, direction)
+                    .fold((0.0, 0), |(sum, count), edge| {
+                        let other_node = if forward {
+                            edge.source()
+                        } else {
+                            edge.target()
+                        };
+                        match positions[other_node.index()] {
+                            NOT_IN_LAYER => (sum, count),
+                            pos => (sum + pos as f64, count + 1),
+                        }
+                    });
+            (count > 0).then(|| sum / count as f64)
+        };
 
-                (node, barycenter)
-            })
-            .collect();
+        order.barycenters.clear();
+        if self.options.parallel && layer.len() >= self.options.parallel_threshold {
+            order
+                .barycenters
+                .par_extend(layer.par_iter().map(|&node| (node, barycenter(node))));
+        } else {
+            order
+                .barycenters
+                .extend(layer.iter().map(|&node| (node, barycenter(node))));
+        }
 
-        // Sort by barycenter (must be sequential)
-        barycenters.sort_by(|a, b| match (a.1, b.1) {
+        // Stable, so ties and unconnected nodes keep their current order
+        order.barycenters.sort_by(|a, b| match (a.1, b.1) {
             (Some(ba), Some(bb)) => ba.partial_cmp(&bb).unwrap(),
             (Some(_), None) => std::cmp::Ordering::Less,
             (None, Some(_)) => std::cmp::Ordering::Greater,
             (None, None) => std::cmp::Ordering::Equal,
         });
 
-        // Update the layer
-        for (i, (node, _)) in barycenters.iter().enumerate() {
-            layer[i] = *node;
+        for &node in reference_layer {
+            order.positions[node.index()] = NOT_IN_LAYER;
         }
-    }
 
-    // Sequential version of barycenter sorting
-    fn sort_layer_by_barycenter_sequential(
-        &self,
-        igr: &IntermediateGraph,
-        layer: &mut [NodeIndex],
-        reference_layer: &[NodeIndex],
-        forward: bool,
-    ) {
-        // Create position map for reference layer
-        let positions: HashMap<NodeIndex, usize> = reference_layer
-            .iter()
-            .enumerate()
-            .map(|(i, &node)| (node, i))
-            .collect();
-
-        // Calculate barycenter for each node
-        let barycenters: Vec<(NodeIndex, Option<f64>)> = layer
-            .iter()
-            .map(|&node| {
-                let edges = if forward {
-                    // Look at incoming edges from previous layer
-                    igr.graph.edges_directed(node, PetDirection::Incoming)
-                } else {
-                    // Look at outgoing edges to next layer
-                    igr.graph.edges_directed(node, PetDirection::Outgoing)
-                };
-
-                let mut sum = 0.0;
-                let mut count = 0;
-
-                for edge in edges {
-                    let other_node = if forward {
-                        edge.source()
-                    } else {
-                        edge.target()
-                    };
-                    if let Some(&pos) = positions.get(&other_node) {
-                        sum += pos as f64;
-                        count += 1;
-                    }
-                }
-
-                let barycenter = if count > 0 {
-                    Some(sum / count as f64)
-                } else {
-                    None
-                };
-
-                (node, barycenter)
-            })
-            .collect();
-
-        // Sort by barycenter (nodes without connections stay in place)
-        let mut sorted_indices: Vec<usize> = (0..layer.len()).collect();
-        sorted_indices.sort_by(|&a, &b| match (barycenters[a].1, barycenters[b].1) {
-            (Some(bc_a), Some(bc_b)) => bc_a.partial_cmp(&bc_b).unwrap(),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.cmp(&b),
-        });
-
-        // Apply the new ordering
-        let original_layer = layer.to_owned();
-        for (i, &idx) in sorted_indices.iter().enumerate() {
-            layer[i] = original_layer[idx];
+        let mut changed = false;
+        for (slot, &(node, _)) in layer.iter_mut().zip(order.barycenters.iter()) {
+            changed |= *slot != node;
+            *slot = node;
         }
+        changed
     }
 
     fn position_nodes(&self, igr: &mut IntermediateGraph, layers: &[Vec<NodeIndex>]) -> Result<()> {
@@ -628,7 +980,10 @@ impl DagreLayout {
 
         // Calculate position for each layer
         for layer in layers {
+            // Empty ranks (from `minlen` edges) still take up one rank separation
             if layer.is_empty() {
+                layer_positions.push(current_pos);
+                current_pos += self.options.rank_sep;
                 continue;
             }
 
@@ -764,83 +1119,71 @@ impl DagreLayout {
         layers: &[Vec<NodeIndex>],
     ) {
         // Track which paths nodes belong to for better separation
-        let mut path_groups: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut path_groups = vec![NOT_IN_LAYER; igr.graph.node_count()];
         let mut next_path_id = 0;
 
         // Assign path IDs based on connectivity
         for layer in layers.iter() {
             for &node_idx in layer {
-                // Check if this node has an assigned path from a parent
-                let incoming_paths: HashSet<usize> = igr
+                // Continue a parent's path; where paths converge take the
+                // smallest ID
+                let path_id = igr
                     .graph
                     .edges_directed(node_idx, PetDirection::Incoming)
-                    .filter_map(|edge| path_groups.get(&edge.source()).copied())
-                    .collect();
-
-                let path_id = if incoming_paths.is_empty() {
-                    // New path starting from this node
-                    let id = next_path_id;
-                    next_path_id += 1;
-                    id
-                } else if incoming_paths.len() == 1 {
-                    // Continue on the same path
-                    *incoming_paths.iter().next().unwrap()
-                } else {
-                    // Multiple paths converging - take the smallest path ID
-                    *incoming_paths.iter().min().unwrap()
-                };
-
-                path_groups.insert(node_idx, path_id);
+                    .map(|edge| path_groups[edge.source().index()])
+                    .filter(|&path_id| path_id != NOT_IN_LAYER)
+                    .min()
+                    .unwrap_or_else(|| {
+                        // New path starting from this node
+                        next_path_id += 1;
+                        next_path_id - 1
+                    });
+
+                path_groups[node_idx.index()] = path_id;
             }
         }
 
+        let path_separation = self.options.node_sep * 2.0; // Extra space between different paths
+        let mut members: Vec<(usize, NodeIndex, f64)> = Vec::new();
+
         for layer in layers.iter() {
             if layer.is_empty() {
                 continue;
             }
 
-            // Group nodes by their path
-            let mut nodes_by_path: HashMap<usize, Vec<(NodeIndex, f64)>> = HashMap::new();
-
-            for &node_idx in layer {
+            // Group nodes by their path, keeping layer order within a path
+            members.clear();
+            members.extend(layer.iter().map(|&node_idx| {
                 let node = &igr.graph[node_idx];
                 let size = match self.options.direction {
                     Direction::LeftRight | Direction::RightLeft => node.height,
                     Direction::TopBottom | Direction::BottomTop => node.width,
                 };
-
-                let path_id = path_groups.get(&node_idx).copied().unwrap_or(0);
-                nodes_by_path
-                    .entry(path_id)
-                    .or_default()
-                    .push((node_idx, size));
-            }
-
-            // Sort paths by ID for consistent ordering
-            let mut paths: Vec<_> = nodes_by_path.into_iter().collect();
-            paths.sort_by_key(|(path_id, _)| *path_id);
+                (path_groups[node_idx.index()], node_idx, size)
+            }));
+            members.sort_by_key(|&(path_id, _, _)| path_id);
 
             // Calculate total size needed for this layer with extra spacing between paths
-            let path_separation = self.options.node_sep * 2.0; // Extra space between different paths
             let mut total_size = 0.0;
-
-            for (_, nodes) in &paths {
-                total_size += nodes.iter().map(|(_, size)| size).sum::<f64>();
+            let mut path_count = 0;
+            for nodes in members.chunk_by(|a, b| a.0 == b.0) {
+                total_size += nodes.iter().map(|(_, _, size)| size).sum::<f64>();
                 total_size += (nodes.len().saturating_sub(1)) as f64 * self.options.node_sep;
+                path_count += 1;
             }
-            total_size += (paths.len().saturating_sub(1)) as f64 * path_separation;
+            total_size += (path_count - 1) as f64 * path_separation;
 
             // Start positioning from the center
             let mut current_pos = -total_size / 2.0;
 
             // Position each path group
-            for (path_idx, (_path_id, nodes)) in paths.iter().enumerate() {
+            for (path_idx, nodes) in members.chunk_by(|a, b| a.0 == b.0).enumerate() {
                 if path_idx > 0 {
                     current_pos += path_separation;
                 }
 
                 // Position nodes within this path
-                for (i, &(node_idx, size)) in nodes.iter().enumerate() {
+                for (i, &(_, node_idx, size)) in nodes.iter().enumerate() {
                     if i > 0 {
                         current_pos += self.options.node_sep;
                     }
@@ -917,14 +1260,17 @@ impl DagreLayout {
 
             // Only set bounds if we found any content
             if min_x != f64::INFINITY {
-                // Add padding
-                let padding = 20.0;
-                containers[idx].bounds = Some(BoundingBox {
+                let padding = CONTAINER_PADDING;
+                let mut bounds = BoundingBox {
                     x: min_x - padding,
                     y: min_y - padding,
                     width: (max_x - min_x) + 2.0 * padding,
                     height: (max_y - min_y) + 2.0 * padding,
-                });
+                };
+                if let Some(ratio) = containers[idx].attributes.aspect_ratio {
+                    bounds.expand_to_aspect(ratio);
+                }
+                containers[idx].bounds = Some(bounds);
             }
 
             processed[idx] = true;
@@ -1,16 +1,23 @@
 // src/layout/elk.rs
-use super::LayoutEngine;
-use crate::ast::GroupType;
+use super::{Direction, LayoutEngine};
+use crate::ast::{FlowDirection, GroupType, PortSide, MAX_LAYOUT_ITERATIONS};
 use crate::error::Result;
 use crate::igr::{BoundingBox, ContainerData, EdgeData, IntermediateGraph, NodeData};
 use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
 use petgraph::Direction as PetDirection;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Seed used for randomized initial placement when none is configured
+pub const DEFAULT_LAYOUT_SEED: u64 = 0x5eed;
 
 // ELK Layout Engine - Hierarchical and layered layout algorithm
 pub struct ElkLayout {
     options: ElkLayoutOptions,
+    iterations_run: AtomicUsize,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +28,28 @@ pub struct ElkLayoutOptions {
     pub spacing_edge_edge: f64,
     pub direction: ElkDirection,
     pub hierarchy_handling: HierarchyHandling,
+    /// Seed for randomized initial placement; equal seeds give equal layouts
+    pub seed: u64,
+    /// Simulation steps for the force algorithm, unless `force_iterations` is set
+    pub force_iterations: usize,
+    /// Simulation steps for the stress algorithm, unless `stress_iterations` is set
+    pub stress_iterations: usize,
+    /// How layered layouts arrange top-level containers relative to each other
+    pub container_packing: ContainerPacking,
+    /// Width / height of the area `ContainerPacking::Shelf` aims to fill
+    pub packing_aspect_ratio: f64,
+}
+
+/// Arrangement of separately laid-out containers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerPacking {
+    /// `ceil(sqrt(n))` columns in declaration order, each row as tall as its
+    /// tallest container
+    Grid,
+    /// First-fit decreasing height: tallest containers first, each on the
+    /// first shelf with room, shelves capped at a width derived from the
+    /// total area and `packing_aspect_ratio`
+    Shelf,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +75,28 @@ pub enum HierarchyHandling {
     IgnoreChildren,
 }
 
+impl From<FlowDirection> for ElkDirection {
+    fn from(direction: FlowDirection) -> Self {
+        match direction {
+            FlowDirection::Right => ElkDirection::Right,
+            FlowDirection::Down => ElkDirection::Down,
+            FlowDirection::Left => ElkDirection::Left,
+            FlowDirection::Up => ElkDirection::Up,
+        }
+    }
+}
+
+impl From<Direction> for ElkDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::LeftRight => ElkDirection::Right,
+            Direction::TopBottom => ElkDirection::Down,
+            Direction::RightLeft => ElkDirection::Left,
+            Direction::BottomTop => ElkDirection::Up,
+        }
+    }
+}
+
 impl Default for ElkLayoutOptions {
     fn default() -> Self {
         Self {
@@ -55,6 +106,11 @@ impl Default for ElkLayoutOptions {
             spacing_edge_edge: 10.0,
             direction: ElkDirection::Right,
             hierarchy_handling: HierarchyHandling::IncludeChildren,
+            seed: DEFAULT_LAYOUT_SEED,
+            force_iterations: 300,
+            stress_iterations: 200,
+            container_packing: ContainerPacking::Shelf,
+            packing_aspect_ratio: 16.0 / 9.0,
         }
     }
 }
@@ -67,13 +123,19 @@ impl Default for ElkLayout {
 
 impl ElkLayout {
     pub fn new() -> Self {
+        Self::with_options(ElkLayoutOptions::default())
+    }
+
+    pub fn with_options(options: ElkLayoutOptions) -> Self {
         Self {
-            options: ElkLayoutOptions::default(),
+            options,
+            iterations_run: AtomicUsize::new(0),
         }
     }
 
-    pub fn with_options(options: ElkLayoutOptions) -> Self {
-        Self { options }
+    /// Simulation steps performed by the most recent force or stress layout
+    pub fn iterations_run(&self) -> usize {
+        self.iterations_run.load(Ordering::Relaxed)
     }
 }
 
@@ -93,6 +155,7 @@ impl LayoutEngine for ElkLayout {
         // Calculate bounds for containers and groups
         self.calculate_container_bounds(igr);
         self.calculate_group_bounds(igr);
+        Self::clamp_to_origin(igr);
 
         Ok(())
     }
@@ -100,9 +163,55 @@ impl LayoutEngine for ElkLayout {
     fn name(&self) -> &'static str {
         "elk"
     }
+
+    fn options_fingerprint(&self) -> String {
+        format!("{:?}", self.options)
+    }
 }
 
 impl ElkLayout {
+    /// Shift the layout right and down as needed so no node, container or
+    /// group extends past the origin, whatever the direction
+    fn clamp_to_origin(igr: &mut IntermediateGraph) {
+        let node_corners = igr
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container)
+            .map(|node| (node.x - node.width / 2.0, node.y - node.height / 2.0));
+        let cluster_corners = igr
+            .containers
+            .iter()
+            .filter_map(|container| container.bounds.as_ref())
+            .chain(igr.groups.iter().filter_map(|group| group.bounds.as_ref()))
+            .map(|bounds| (bounds.x, bounds.y));
+        let (min_x, min_y) = node_corners
+            .chain(cluster_corners)
+            .fold((0.0_f64, 0.0_f64), |(min_x, min_y), (x, y)| {
+                (min_x.min(x), min_y.min(y))
+            });
+        if min_x == 0.0 && min_y == 0.0 {
+            return;
+        }
+
+        for node in igr.graph.node_weights_mut() {
+            node.x -= min_x;
+            node.y -= min_y;
+        }
+        let clusters = igr
+            .containers
+            .iter_mut()
+            .filter_map(|container| container.bounds.as_mut())
+            .chain(
+                igr.groups
+                    .iter_mut()
+                    .filter_map(|group| group.bounds.as_mut()),
+            );
+        for bounds in clusters {
+            bounds.x -= min_x;
+            bounds.y -= min_y;
+        }
+    }
+
     fn layered_layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
         // First, layout nodes that are in containers separately
         self.layout_containers_separately(igr)?;
@@ -120,16 +229,21 @@ impl ElkLayout {
 
     fn stress_layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
         // Stress minimization layout - positions nodes to minimize stress
-        let iterations = 200;
+        let iterations = igr
+            .global_config
+            .stress_iterations
+            .unwrap_or(self.options.stress_iterations)
+            .clamp(1, MAX_LAYOUT_ITERATIONS);
         let cooling_factor: f64 = 0.95;
 
         // Initialize with circular layout
         self.initialize_circular(igr);
 
         for iteration in 0..iterations {
-            let temperature = 1.0 * cooling_factor.powi(iteration);
+            let temperature = 1.0 * cooling_factor.powi(iteration as i32);
             self.apply_stress_forces(igr, temperature);
         }
+        self.iterations_run.store(iterations, Ordering::Relaxed);
 
         // Ensure all positions are non-negative
         self.normalize_positions(igr);
@@ -139,7 +253,11 @@ impl ElkLayout {
 
     fn force_layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
         // Enhanced force-directed layout with ELK parameters
-        let iterations = 300;
+        let iterations = igr
+            .global_config
+            .force_iterations
+            .unwrap_or(self.options.force_iterations)
+            .clamp(1, MAX_LAYOUT_ITERATIONS);
         let initial_temp = 200.0; // Increased initial temperature for better spread
 
         self.initialize_random(igr);
@@ -148,6 +266,7 @@ impl ElkLayout {
             let temperature = initial_temp * (1.0 - i as f64 / iterations as f64);
             self.apply_elk_forces(igr, temperature);
         }
+        self.iterations_run.store(iterations, Ordering::Relaxed);
 
         // Ensure all positions are non-negative
         self.normalize_positions(igr);
@@ -164,16 +283,43 @@ impl ElkLayout {
             return self.layered_layout(igr);
         }
 
-        // Layout each tree with proper spacing
+        // Layout each tree with proper spacing, growing downwards
         let mut current_x = 0.0;
         for root in roots {
             let tree_width = self.layout_tree_recursive(igr, root, current_x, 50.0, 0)?;
             current_x += tree_width + 100.0; // Space between trees
         }
 
+        self.orient_tree(igr);
         Ok(())
     }
 
+    /// Rotate a downward tree into the configured direction and attach each
+    /// parent -> child edge on the facing sides, unless it has explicit ports
+    fn orient_tree(&self, igr: &mut IntermediateGraph) {
+        let (start_port, end_port) = match self.options.direction {
+            ElkDirection::Down => (PortSide::Bottom, PortSide::Top),
+            ElkDirection::Up => (PortSide::Top, PortSide::Bottom),
+            ElkDirection::Right => (PortSide::Right, PortSide::Left),
+            ElkDirection::Left => (PortSide::Left, PortSide::Right),
+        };
+
+        for node in igr.graph.node_weights_mut() {
+            let (across, along) = (node.x, node.y);
+            (node.x, node.y) = match self.options.direction {
+                ElkDirection::Down => (across, along),
+                ElkDirection::Up => (across, -along),
+                ElkDirection::Right => (along, across),
+                ElkDirection::Left => (-along, across),
+            };
+        }
+
+        for edge in igr.graph.edge_weights_mut() {
+            edge.attributes.start_port.get_or_insert(start_port);
+            edge.attributes.end_port.get_or_insert(end_port);
+        }
+    }
+
     fn minimize_crossings_elk(&self, igr: &IntermediateGraph, layers: &mut [Vec<NodeIndex>]) {
         // ELK-style crossing minimization with multiple passes
         for _ in 0..8 {
@@ -255,7 +401,7 @@ impl ElkLayout {
         igr: &mut IntermediateGraph,
         layers: &[Vec<NodeIndex>],
     ) -> Result<()> {
-        self.position_nodes_elk_with_offset(igr, layers, 0.0, 0.0)
+        self.position_nodes_elk_with_offset(igr, layers, 0.0, 0.0, &self.options.direction)
     }
 
     fn position_nodes_elk_with_offset(
@@ -264,22 +410,33 @@ impl ElkLayout {
         layers: &[Vec<NodeIndex>],
         offset_x: f64,
         offset_y: f64,
+        direction: &ElkDirection,
     ) -> Result<()> {
         let layer_spacing = 250.0; // Further increased layer spacing
         let base_node_spacing = self.options.spacing_node_node.max(50.0); // Minimum 50px spacing
 
+        // Left and Up number their layers from the far end, so they mirror
+        // Right and Down within the same area instead of going negative
+        let last_x = offset_x + layers.len().saturating_sub(1) as f64 * layer_spacing;
+
         // Position layers
         for (layer_idx, layer) in layers.iter().enumerate() {
             let x = offset_x + layer_idx as f64 * layer_spacing;
+            let mirrored_x = last_x - (x - offset_x);
             let mut y = offset_y;
 
             for &node_idx in layer {
                 let node = &mut igr.graph[node_idx];
 
-                // Calculate dynamic spacing based on node size
-                let node_spacing = base_node_spacing + (node.height * 0.1);
+                // Nodes in a layer stack along the cross axis, so space them
+                // by their extent along it
+                let extent = match direction {
+                    ElkDirection::Right | ElkDirection::Left => node.height,
+                    ElkDirection::Down | ElkDirection::Up => node.width,
+                };
+                let node_spacing = base_node_spacing + (extent * 0.1);
 
-                match self.options.direction {
+                match direction {
                     ElkDirection::Right => {
                         node.x = x;
                         node.y = y;
@@ -289,16 +446,16 @@ impl ElkLayout {
                         node.y = x;
                     }
                     ElkDirection::Left => {
-                        node.x = -x;
+                        node.x = mirrored_x;
                         node.y = y;
                     }
                     ElkDirection::Up => {
                         node.x = y;
-                        node.y = -x;
+                        node.y = mirrored_x;
                     }
                 }
 
-                y += node.height + node_spacing;
+                y += extent + node_spacing;
             }
         }
 
@@ -320,10 +477,11 @@ impl ElkLayout {
 
     fn initialize_random(&self, igr: &mut IntermediateGraph) {
         let bounds = 200.0;
+        let mut rng = StdRng::seed_from_u64(self.options.seed);
         for node_idx in igr.graph.node_indices() {
             let node = &mut igr.graph[node_idx];
-            node.x = (rand::random::<f64>() - 0.5) * bounds;
-            node.y = (rand::random::<f64>() - 0.5) * bounds;
+            node.x = (rng.random::<f64>() - 0.5) * bounds;
+            node.y = (rng.random::<f64>() - 0.5) * bounds;
         }
     }
 
@@ -455,6 +613,8 @@ impl ElkLayout {
             .collect()
     }
 
+    /// Lay out the subtree under `node` with `x` across siblings and `y` along
+    /// the tree's depth; `orient_tree` maps these onto the final direction
     fn layout_tree_recursive(
         &self,
         igr: &mut IntermediateGraph,
@@ -463,10 +623,13 @@ impl ElkLayout {
         y: f64,
         depth: i32,
     ) -> Result<f64> {
-        // Position this node (center position)
+        // Position this node (center position); sideways trees swap the
+        // node's extent along each axis
         let node_data = &igr.graph[node];
-        let node_width = node_data.width;
-        let node_height = node_data.height;
+        let (node_width, node_height) = match self.options.direction {
+            ElkDirection::Down | ElkDirection::Up => (node_data.width, node_data.height),
+            ElkDirection::Right | ElkDirection::Left => (node_data.height, node_data.width),
+        };
 
         igr.graph[node].x = x + node_width / 2.0;
         igr.graph[node].y = y + node_height / 2.0;
@@ -612,12 +775,16 @@ impl ElkLayout {
             if min_x != f64::INFINITY {
                 // Add padding - increased for better separation
                 let padding = 50.0;
-                containers[idx].bounds = Some(BoundingBox {
+                let mut bounds = BoundingBox {
                     x: min_x - padding,
                     y: min_y - padding,
                     width: (max_x - min_x) + 2.0 * padding,
                     height: (max_y - min_y) + 2.0 * padding,
-                });
+                };
+                if let Some(ratio) = containers[idx].attributes.aspect_ratio {
+                    bounds.expand_to_aspect(ratio);
+                }
+                containers[idx].bounds = Some(bounds);
             }
 
             processed[idx] = true;
@@ -748,8 +915,14 @@ impl ElkLayout {
             let mut layers = self.build_layers_for_nodes(igr, &container_children)?;
             self.minimize_crossings_elk(igr, &mut layers);
 
-            // Position nodes with better spacing
-            self.position_nodes_elk_with_offset(igr, &layers, 50.0, 50.0)?;
+            // Position nodes with better spacing, in the container's own
+            // direction if it sets one
+            let direction = igr.containers[container_idx]
+                .attributes
+                .direction
+                .map(ElkDirection::from)
+                .unwrap_or_else(|| self.options.direction.clone());
+            self.position_nodes_elk_with_offset(igr, &layers, 50.0, 50.0, &direction)?;
 
             // Normalize positions within container to eliminate negative coordinates
             let mut min_x = f64::INFINITY;
@@ -791,52 +964,27 @@ impl ElkLayout {
             container_info.push((min_x, min_y, width, height));
         }
 
-        // Second pass: Arrange containers in a grid with improved spacing
-        let cols = ((container_count as f64).sqrt().ceil() as usize).max(1);
+        // Second pass: Arrange the containers
         let container_spacing = 150.0; // Significantly increased spacing to prevent overlaps
         let global_padding = 100.0; // Increased global padding
-
-        // Pre-calculate row heights for better grid arrangement
-        let rows = container_count.div_ceil(cols);
-        let mut row_heights = vec![0.0; rows];
-
-        #[allow(clippy::needless_range_loop)]
-        for container_idx in 0..container_count {
-            let (_, _, _, height) = container_info[container_idx];
-            let row = container_idx / cols;
-            if row < row_heights.len() {
-                let current_height: f64 = row_heights[row];
-                row_heights[row] = current_height.max(height);
+        let sizes: Vec<(f64, f64)> = container_info
+            .iter()
+            .map(|&(_, _, width, height)| (width, height))
+            .collect();
+        let targets = match self.options.container_packing {
+            ContainerPacking::Grid => grid_positions(&sizes, container_spacing),
+            ContainerPacking::Shelf => {
+                shelf_positions(&sizes, container_spacing, self.options.packing_aspect_ratio)
             }
-        }
+        };
 
         for container_idx in 0..container_count {
             let (min_x, min_y, width, height) = container_info[container_idx];
             if width == 0.0 && height == 0.0 {
                 continue; // Skip empty containers
             }
-
-            let row = container_idx / cols;
-            let col = container_idx % cols;
-
-            // Calculate target position for this container
-            let mut target_x = global_padding;
-            let mut target_y = global_padding;
-
-            // Add widths of previous containers in this row
-            for c in 0..col {
-                let idx = row * cols + c;
-                if idx < container_info.len() {
-                    target_x += container_info[idx].2 + container_spacing;
-                }
-            }
-
-            // Add heights of previous rows
-            for r in 0..row {
-                if r < row_heights.len() {
-                    target_y += row_heights[r] + container_spacing;
-                }
-            }
+            let target_x = global_padding + targets[container_idx].0;
+            let target_y = global_padding + targets[container_idx].1;
 
             // Calculate the final offset needed to move container to target position
             let offset_x = target_x + padding - min_x;
@@ -925,3 +1073,75 @@ impl ElkLayout {
         *ranks.get(&node).unwrap_or(&0)
     }
 }
+
+/// Top-left offsets of `ceil(sqrt(n))`-column grid cells for boxes of the
+/// given sizes, in order
+fn grid_positions(sizes: &[(f64, f64)], spacing: f64) -> Vec<(f64, f64)> {
+    let cols = ((sizes.len() as f64).sqrt().ceil() as usize).max(1);
+    let row_heights: Vec<f64> = sizes
+        .chunks(cols)
+        .map(|row| row.iter().fold(0.0, |tallest: f64, &(_, h)| tallest.max(h)))
+        .collect();
+
+    let mut positions = Vec::with_capacity(sizes.len());
+    let mut y = 0.0;
+    for (row, row_sizes) in sizes.chunks(cols).enumerate() {
+        let mut x = 0.0;
+        for &(width, _) in row_sizes {
+            positions.push((x, y));
+            x += width + spacing;
+        }
+        y += row_heights[row] + spacing;
+    }
+    positions
+}
+
+/// Top-left offsets packing boxes of the given sizes onto shelves, first fit
+/// by decreasing height. Shelves are as wide as the widest box or the width
+/// at which the total area reaches `aspect_ratio`, whichever is larger.
+/// Zero-sized boxes are left at the origin.
+fn shelf_positions(sizes: &[(f64, f64)], spacing: f64, aspect_ratio: f64) -> Vec<(f64, f64)> {
+    let mut order: Vec<usize> = (0..sizes.len())
+        .filter(|&i| sizes[i] != (0.0, 0.0))
+        .collect();
+    // Stable sort: equal heights keep declaration order
+    order.sort_by(|&a, &b| sizes[b].1.total_cmp(&sizes[a].1));
+
+    let aspect_ratio = if aspect_ratio.is_finite() && aspect_ratio > 0.0 {
+        aspect_ratio
+    } else {
+        1.0
+    };
+    let area: f64 = order
+        .iter()
+        .map(|&i| (sizes[i].0 + spacing) * (sizes[i].1 + spacing))
+        .sum();
+    let widest = order.iter().fold(0.0, |w: f64, &i| w.max(sizes[i].0));
+    let shelf_width = widest.max((area * aspect_ratio).sqrt() - spacing);
+
+    // (y, height, used width) of each shelf
+    let mut shelves: Vec<(f64, f64, f64)> = Vec::new();
+    let mut positions = vec![(0.0, 0.0); sizes.len()];
+    for i in order {
+        let (width, height) = sizes[i];
+        let fits = shelves.iter_mut().find(|(_, _, used)| {
+            let x = if *used > 0.0 { *used + spacing } else { 0.0 };
+            x + width <= shelf_width
+        });
+        match fits {
+            Some((y, _, used)) => {
+                let x = if *used > 0.0 { *used + spacing } else { 0.0 };
+                positions[i] = (x, *y);
+                *used = x + width;
+            }
+            None => {
+                let y = shelves
+                    .last()
+                    .map_or(0.0, |&(y, shelf_height, _)| y + shelf_height + spacing);
+                positions[i] = (0.0, y);
+                shelves.push((y, height, width));
+            }
+        }
+    }
+    positions
+}
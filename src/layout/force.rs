@@ -1,13 +1,35 @@
 // src/layout/force.rs
-use super::LayoutEngine;
-use crate::error::Result;
+use super::{LayoutContext, LayoutEngine};
+use crate::ast::{ForceSeed, MAX_LAYOUT_ITERATIONS};
+use crate::error::{LayoutError, Result};
 use crate::igr::{BoundingBox, ContainerData, EdgeData, IntermediateGraph, NodeData};
+use petgraph::algo::tarjan_scc;
 use petgraph::graph::NodeIndex;
-use std::collections::HashMap;
+use petgraph::unionfind::UnionFind;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Upper bound on edge `weight`; heavier edges make the simulation overshoot
+const MAX_EDGE_WEIGHT: f64 = 10.0;
+
+/// Space between the bounding boxes of packed connected components
+const COMPONENT_GAP: f64 = 80.0;
+
+/// Swap rounds run by `reduce_crossings: true`
+const CROSSING_REDUCTION_ROUNDS: usize = 10;
+
+/// Nearest nodes a node on a crossing edge is tried swapping with
+const SWAP_CANDIDATES: usize = 8;
+
+/// Swaps tried per node over all rounds, so crossing reduction stays
+/// linear in the graph size however many crossings are left
+const SWAP_EVALUATIONS_PER_NODE: usize = 16;
 
 // Simple force-directed layout
 pub struct ForceLayout {
     options: ForceLayoutOptions,
+    iterations_run: AtomicUsize,
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +38,9 @@ pub struct ForceLayoutOptions {
     pub repulsion_strength: f64,
     pub attraction_strength: f64,
     pub damping: f64,
+    /// Wall-clock budget for the simulation; exceeding it fails the layout
+    /// with `LayoutError::Timeout`
+    pub timeout: Option<Duration>,
 }
 
 impl Default for ForceLayoutOptions {
@@ -25,6 +50,7 @@ impl Default for ForceLayoutOptions {
             repulsion_strength: 5000.0,
             attraction_strength: 0.05,
             damping: 0.85,
+            timeout: None,
         }
     }
 }
@@ -37,30 +63,85 @@ impl Default for ForceLayout {
 
 impl ForceLayout {
     pub fn new() -> Self {
+        Self::with_options(ForceLayoutOptions::default())
+    }
+
+    pub fn with_options(options: ForceLayoutOptions) -> Self {
         Self {
-            options: ForceLayoutOptions::default(),
+            options,
+            iterations_run: AtomicUsize::new(0),
         }
     }
 
-    pub fn with_options(options: ForceLayoutOptions) -> Self {
-        Self { options }
+    /// Simulation steps performed by the most recent layout
+    pub fn iterations_run(&self) -> usize {
+        self.iterations_run.load(Ordering::Relaxed)
     }
 }
 
 impl LayoutEngine for ForceLayout {
     fn layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
+        let context = LayoutContext::from_config(&igr.global_config);
+        self.layout_with_context(igr, &context)
+    }
+
+    fn layout_with_context(
+        &self,
+        igr: &mut IntermediateGraph,
+        context: &LayoutContext,
+    ) -> Result<()> {
         if igr.graph.node_count() == 0 {
             return Ok(());
         }
 
-        // Initialize random positions
-        self.initialize_positions(igr);
-
-        // Run force simulation
-        for _ in 0..self.options.iterations {
-            self.apply_forces(igr);
+        // Seed starting positions
+        match igr.global_config.seed {
+            Some(ForceSeed::Scc) => self.initialize_scc_positions(igr),
+            Some(ForceSeed::Circle) | None => self.initialize_positions(igr),
         }
 
+        // Run force simulation; `force_iterations` overrides the option
+        let iterations = igr
+            .global_config
+            .force_iterations
+            .unwrap_or(self.options.iterations)
+            .clamp(1, MAX_LAYOUT_ITERATIONS);
+        let components = connected_components(igr);
+        // The tighter of this engine's own timeout and the caller's
+        let timeout = self
+            .options
+            .timeout
+            .into_iter()
+            .chain(context.timeout)
+            .min();
+        let started = Instant::now();
+        for iteration in 0..iterations {
+            self.apply_forces(igr, &components);
+            if let Some(timeout) = timeout {
+                let elapsed = started.elapsed();
+                if elapsed > timeout {
+                    self.iterations_run.store(iteration + 1, Ordering::Relaxed);
+                    return Err(LayoutError::Timeout {
+                        engine: self.name().to_string(),
+                        elapsed,
+                    }
+                    .into());
+                }
+            }
+        }
+        self.iterations_run.store(iterations, Ordering::Relaxed);
+
+        if igr.global_config.reduce_crossings.unwrap_or(false) {
+            let deadline = timeout.map(|timeout| started + timeout);
+            if reduce_crossings_until(igr, CROSSING_REDUCTION_ROUNDS, deadline).is_none() {
+                return Err(LayoutError::Timeout {
+                    engine: self.name().to_string(),
+                    elapsed: started.elapsed(),
+                }
+                .into());
+            }
+        }
+        pack_components(igr, &components, context);
         self.calculate_container_bounds(igr);
 
         Ok(())
@@ -69,6 +150,10 @@ impl LayoutEngine for ForceLayout {
     fn name(&self) -> &'static str {
         "force"
     }
+
+    fn options_fingerprint(&self) -> String {
+        format!("{:?}", self.options)
+    }
 }
 
 impl ForceLayout {
@@ -87,7 +172,48 @@ impl ForceLayout {
         }
     }
 
-    fn apply_forces(&self, igr: &mut IntermediateGraph) {
+    /// Place each strongly connected component on its own circle, with the
+    /// component centers spread far enough apart that the circles don't
+    /// overlap, so dense clusters start out already separated
+    fn initialize_scc_positions(&self, igr: &mut IntermediateGraph) {
+        use std::f64::consts::PI;
+
+        let components = tarjan_scc(&igr.graph);
+        let radius = |size: usize| (size as f64).sqrt() * 100.0;
+        let max_radius = components
+            .iter()
+            .map(|component| radius(component.len()))
+            .fold(0.0, f64::max);
+        let center_radius = if components.len() > 1 {
+            2.0 * max_radius + (components.len() as f64).sqrt() * 100.0
+        } else {
+            0.0
+        };
+
+        for (c, component) in components.iter().enumerate() {
+            let center_angle = 2.0 * PI * c as f64 / components.len() as f64;
+            let (cx, cy) = (
+                center_radius * center_angle.cos(),
+                center_radius * center_angle.sin(),
+            );
+            let component_radius = if component.len() > 1 {
+                radius(component.len())
+            } else {
+                0.0
+            };
+
+            for (i, &node_idx) in component.iter().enumerate() {
+                let angle = 2.0 * PI * i as f64 / component.len() as f64;
+                let node = &mut igr.graph[node_idx];
+                node.x = cx + component_radius * angle.cos();
+                node.y = cy + component_radius * angle.sin();
+            }
+        }
+    }
+
+    /// One simulation step; nodes only repel others in their own component,
+    /// so each component is laid out independently
+    fn apply_forces(&self, igr: &mut IntermediateGraph, components: &[usize]) {
         let mut velocities: HashMap<NodeIndex, (f64, f64)> = HashMap::new();
 
         // Initialize velocities
@@ -101,6 +227,9 @@ impl ForceLayout {
             for j in (i + 1)..nodes.len() {
                 let node_i = nodes[i];
                 let node_j = nodes[j];
+                if components[node_i.index()] != components[node_j.index()] {
+                    continue;
+                }
 
                 let pos_i = (igr.graph[node_i].x, igr.graph[node_i].y);
                 let pos_j = (igr.graph[node_j].x, igr.graph[node_j].y);
@@ -139,7 +268,13 @@ impl ForceLayout {
             let dy = pos_target.1 - pos_source.1;
             let distance = (dx * dx + dy * dy).sqrt().max(1.0);
 
-            let force = self.options.attraction_strength * distance;
+            // Heavier edges pull their endpoints together harder
+            let weight = igr.graph[edge]
+                .attributes
+                .weight
+                .unwrap_or(1.0)
+                .min(MAX_EDGE_WEIGHT);
+            let force = self.options.attraction_strength * weight * distance;
             let fx = force * dx / distance;
             let fy = force * dy / distance;
 
@@ -218,12 +353,16 @@ impl ForceLayout {
             if min_x != f64::INFINITY {
                 // Add padding
                 let padding = 20.0;
-                containers[idx].bounds = Some(BoundingBox {
+                let mut bounds = BoundingBox {
                     x: min_x - padding,
                     y: min_y - padding,
                     width: (max_x - min_x) + 2.0 * padding,
                     height: (max_y - min_y) + 2.0 * padding,
-                });
+                };
+                if let Some(ratio) = containers[idx].attributes.aspect_ratio {
+                    bounds.expand_to_aspect(ratio);
+                }
+                containers[idx].bounds = Some(bounds);
             }
 
             processed[idx] = true;
@@ -242,3 +381,281 @@ impl ForceLayout {
         }
     }
 }
+
+/// Label each node with its connected component, indexed by node index.
+/// Nodes sharing a container count as connected so containers stay whole.
+fn connected_components(igr: &IntermediateGraph) -> Vec<usize> {
+    let mut components = UnionFind::new(igr.graph.node_count());
+    for edge in igr.graph.edge_indices() {
+        let (source, target) = igr.graph.edge_endpoints(edge).unwrap();
+        components.union(source.index(), target.index());
+    }
+    for container in 0..igr.containers.len() {
+        let mut members = Vec::new();
+        collect_container_nodes(igr, container, &mut members);
+        for pair in members.windows(2) {
+            components.union(pair[0].index(), pair[1].index());
+        }
+    }
+    components.into_labeling()
+}
+
+fn collect_container_nodes(igr: &IntermediateGraph, container: usize, out: &mut Vec<NodeIndex>) {
+    out.extend(&igr.containers[container].children);
+    for &nested in &igr.containers[container].nested_containers {
+        collect_container_nodes(igr, nested, out);
+    }
+}
+
+/// Number of pairs of edges whose straight center-to-center lines cross;
+/// edges sharing an endpoint never count
+pub fn count_edge_crossings(igr: &IntermediateGraph) -> usize {
+    let segments = edge_segments(igr);
+    let mut crossings = 0;
+    for (i, first) in segments.iter().enumerate() {
+        crossings += segments[i + 1..]
+            .iter()
+            .filter(|second| segments_cross(igr, first, second))
+            .count();
+    }
+    crossings
+}
+
+/// Swap the positions of two nodes at a time while that lowers
+/// [`count_edge_crossings`], for at most `max_rounds` passes.
+///
+/// Each pass tries every node on a crossing edge against its nearest
+/// nodes, up to a total number of tries proportional to the node count.
+/// Only nodes in the same connected component and the same innermost
+/// container are swapped, so components and containers keep their extent,
+/// and swaps that would make nodes of different sizes overlap are skipped.
+/// Returns the number of crossings left.
+pub fn reduce_crossings(igr: &mut IntermediateGraph, max_rounds: usize) -> usize {
+    reduce_crossings_until(igr, max_rounds, None).expect("no deadline to miss")
+}
+
+/// [`reduce_crossings`], giving up with `None` once `deadline` passes
+fn reduce_crossings_until(
+    igr: &mut IntermediateGraph,
+    max_rounds: usize,
+    deadline: Option<Instant>,
+) -> Option<usize> {
+    let components = connected_components(igr);
+    let owners = crate::graphml::GraphmlGenerator::innermost_containers(igr);
+    let nodes: Vec<NodeIndex> = igr
+        .graph
+        .node_indices()
+        .filter(|&idx| {
+            !igr.graph[idx].is_virtual_container
+                && igr.graph.neighbors_undirected(idx).next().is_some()
+        })
+        .collect();
+    let segments = edge_segments(igr);
+    let mut incident: HashMap<NodeIndex, Vec<usize>> = HashMap::new();
+    for (i, &(source, target)) in segments.iter().enumerate() {
+        incident.entry(source).or_default().push(i);
+        incident.entry(target).or_default().push(i);
+    }
+
+    // Crossings involving the edges at `a` or `b`, the only ones a swap of
+    // the two can change: each such edge against every other edge, counting
+    // pairs of two such edges once
+    let local_crossings = |igr: &IntermediateGraph, a: NodeIndex, b: NodeIndex| {
+        let touches = |(source, target): &(NodeIndex, NodeIndex)| {
+            [a, b]
+                .iter()
+                .any(|&node| node == *source || node == *target)
+        };
+        let mut touching: Vec<usize> = [a, b]
+            .iter()
+            .filter_map(|node| incident.get(node))
+            .flatten()
+            .copied()
+            .collect();
+        touching.sort_unstable();
+        touching.dedup();
+
+        let mut crossings = 0;
+        for &i in &touching {
+            for (j, other) in segments.iter().enumerate() {
+                let counted = if touches(other) { i < j } else { true };
+                if counted && segments_cross(igr, &segments[i], other) {
+                    crossings += 1;
+                }
+            }
+        }
+        crossings
+    };
+    // Other nodes the boxes of `a` or `b` overlap
+    let overlaps = |igr: &IntermediateGraph, a: NodeIndex, b: NodeIndex| {
+        let boxes_overlap = |first: &NodeData, second: &NodeData| {
+            (first.x - second.x).abs() < (first.width + second.width) / 2.0
+                && (first.y - second.y).abs() < (first.height + second.height) / 2.0
+        };
+        igr.graph
+            .node_indices()
+            .filter(|&idx| idx != a && idx != b && !igr.graph[idx].is_virtual_container)
+            .map(|idx| {
+                [a, b]
+                    .iter()
+                    .filter(|&&node| boxes_overlap(&igr.graph[node], &igr.graph[idx]))
+                    .count()
+            })
+            .sum::<usize>()
+    };
+    let swap = |igr: &mut IntermediateGraph, a: NodeIndex, b: NodeIndex| {
+        let (ax, ay) = (igr.graph[a].x, igr.graph[a].y);
+        let (bx, by) = (igr.graph[b].x, igr.graph[b].y);
+        igr.graph[a].x = bx;
+        igr.graph[a].y = by;
+        igr.graph[b].x = ax;
+        igr.graph[b].y = ay;
+    };
+
+    let mut evaluations_left = SWAP_EVALUATIONS_PER_NODE * nodes.len();
+    'rounds: for _ in 0..max_rounds {
+        // Endpoints of the edges that cross another, the only nodes whose
+        // moves can remove a crossing
+        let mut crossing: HashSet<NodeIndex> = HashSet::new();
+        for (i, first) in segments.iter().enumerate() {
+            for second in &segments[i + 1..] {
+                if segments_cross(igr, first, second) {
+                    crossing.extend([first.0, first.1, second.0, second.1]);
+                }
+            }
+        }
+
+        let mut improved = false;
+        for &a in nodes.iter().filter(|node| crossing.contains(node)) {
+            if deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                return None;
+            }
+            let distance = |node: &NodeData| {
+                let (dx, dy) = (node.x - igr.graph[a].x, node.y - igr.graph[a].y);
+                dx * dx + dy * dy
+            };
+            let mut candidates: Vec<NodeIndex> = nodes
+                .iter()
+                .copied()
+                .filter(|&b| {
+                    b != a
+                        && components[a.index()] == components[b.index()]
+                        && owners.get(&a) == owners.get(&b)
+                })
+                .collect();
+            candidates
+                .sort_by(|&b, &c| distance(&igr.graph[b]).total_cmp(&distance(&igr.graph[c])));
+            candidates.truncate(SWAP_CANDIDATES);
+
+            for b in candidates {
+                if evaluations_left == 0 {
+                    break 'rounds;
+                }
+                evaluations_left -= 1;
+                let before = local_crossings(igr, a, b);
+                if before == 0 {
+                    continue;
+                }
+                let overlaps_before = overlaps(igr, a, b);
+                swap(igr, a, b);
+                if local_crossings(igr, a, b) < before && overlaps(igr, a, b) <= overlaps_before {
+                    improved = true;
+                } else {
+                    swap(igr, a, b);
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    Some(count_edge_crossings(igr))
+}
+
+/// Endpoints of the edges drawn between two real nodes
+fn edge_segments(igr: &IntermediateGraph) -> Vec<(NodeIndex, NodeIndex)> {
+    igr.graph
+        .edge_indices()
+        .filter_map(|edge| igr.graph.edge_endpoints(edge))
+        .filter(|&(source, target)| {
+            source != target
+                && !igr.graph[source].is_virtual_container
+                && !igr.graph[target].is_virtual_container
+        })
+        .collect()
+}
+
+/// Whether the center-to-center lines of two edges properly intersect
+fn segments_cross(
+    igr: &IntermediateGraph,
+    &(a, b): &(NodeIndex, NodeIndex),
+    &(c, d): &(NodeIndex, NodeIndex),
+) -> bool {
+    if a == c || a == d || b == c || b == d {
+        return false;
+    }
+    let point = |idx: NodeIndex| (igr.graph[idx].x, igr.graph[idx].y);
+    let (p1, p2, p3, p4) = (point(a), point(b), point(c), point(d));
+    let orientation = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    };
+    let (d1, d2) = (orientation(p3, p4, p1), orientation(p3, p4, p2));
+    let (d3, d4) = (orientation(p1, p2, p3), orientation(p1, p2, p4));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Pack the components' bounding boxes into a grid, row by row in order of
+/// each component's first node, so disconnected parts never overlap. The
+/// grid wraps to fit the context's layout area.
+fn pack_components(igr: &mut IntermediateGraph, components: &[usize], context: &LayoutContext) {
+    let mut order: Vec<usize> = Vec::new();
+    let mut bounds: HashMap<usize, (f64, f64, f64, f64)> = HashMap::new();
+    for node_idx in igr.graph.node_indices() {
+        let node = &igr.graph[node_idx];
+        let (hw, hh) = (node.width / 2.0, node.height / 2.0);
+        let label = components[node_idx.index()];
+        let entry = bounds.entry(label).or_insert_with(|| {
+            order.push(label);
+            (
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            )
+        });
+        entry.0 = entry.0.min(node.x - hw);
+        entry.1 = entry.1.min(node.y - hh);
+        entry.2 = entry.2.max(node.x + hw);
+        entry.3 = entry.3.max(node.y + hh);
+    }
+    if order.len() < 2 {
+        return;
+    }
+
+    let (widest, tallest) = order.iter().fold((0.0_f64, 0.0_f64), |(w, h), label| {
+        let (min_x, min_y, max_x, max_y) = bounds[label];
+        (w.max(max_x - min_x), h.max(max_y - min_y))
+    });
+    let columns = context.grid_columns(order.len(), widest, tallest, COMPONENT_GAP);
+    let mut offsets: HashMap<usize, (f64, f64)> = HashMap::new();
+    let mut y = 0.0;
+    for row in order.chunks(columns) {
+        let mut x = 0.0;
+        let mut row_height: f64 = 0.0;
+        for label in row {
+            let (min_x, min_y, max_x, max_y) = bounds[label];
+            offsets.insert(*label, (x - min_x, y - min_y));
+            x += max_x - min_x + COMPONENT_GAP;
+            row_height = row_height.max(max_y - min_y);
+        }
+        y += row_height + COMPONENT_GAP;
+    }
+
+    for node_idx in igr.graph.node_indices() {
+        let (dx, dy) = offsets[&components[node_idx.index()]];
+        let node = &mut igr.graph[node_idx];
+        node.x += dx;
+        node.y += dy;
+    }
+}
@@ -1,20 +1,36 @@
 // src/layout/manager.rs
 #[cfg(feature = "ml-layout")]
 use super::{AdaptiveStrategy, LayoutEngineAdapter, LayoutStrategy, MLLayoutStrategy};
-use super::{CachedLayout, DagreLayout, ElkLayout, ForceLayout, LayoutCacheKey, LayoutEngine};
+use super::{
+    CachedLayout, DagreLayout, DagreLayoutOptions, Direction, ElkLayout, ElkLayoutOptions,
+    ForceLayout, LayoutCacheKey, LayoutContext, LayoutEngine,
+};
 use crate::error::{LayoutError, Result};
 use crate::igr::IntermediateGraph;
-use petgraph::visit::IntoNodeReferences;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub struct LayoutManager {
     engines: HashMap<String, Box<dyn LayoutEngine>>,
     cache: Mutex<HashMap<LayoutCacheKey, CachedLayout>>,
     cache_enabled: bool,
+    cache_dir: Option<PathBuf>,
     parallel_enabled: bool,
     thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// Options of the built-in `dagre` engine, so its direction survives
+    /// re-registration
+    dagre_options: DagreLayoutOptions,
+    /// Options of the built-in `elk` engine, so its seed and direction
+    /// can be set independently
+    elk_options: ElkLayoutOptions,
+    /// Names the caller registered an engine under, which `set_seed` and
+    /// `set_direction` leave alone
+    custom_engines: HashSet<String>,
+    /// Longest a single layout may run
+    timeout: Option<Duration>,
 }
 
 impl Default for LayoutManager {
@@ -29,14 +45,19 @@ impl LayoutManager {
             engines: HashMap::new(),
             cache: Mutex::new(HashMap::new()),
             cache_enabled: true,
+            cache_dir: None,
             parallel_enabled: true,
             thread_pool: None,
+            dagre_options: DagreLayoutOptions::default(),
+            elk_options: ElkLayoutOptions::default(),
+            custom_engines: HashSet::new(),
+            timeout: None,
         };
 
         // Register available layout engines
-        manager.register("dagre", Box::new(DagreLayout::new()));
-        manager.register("force", Box::new(ForceLayout::new()));
-        manager.register("elk", Box::new(ElkLayout::new()));
+        manager.register_builtin("dagre", Box::new(DagreLayout::new()));
+        manager.register_builtin("force", Box::new(ForceLayout::new()));
+        manager.register_builtin("elk", Box::new(ElkLayout::new()));
 
         // Register ML layout if feature is enabled
         #[cfg(feature = "ml-layout")]
@@ -52,8 +73,8 @@ impl LayoutManager {
             // Create ML layout adapter
             if let Ok(ml_strategy) = MLLayoutStrategy::new(cycle_aware_fallback) {
                 let ml_strategy_arc = Arc::new(ml_strategy);
-                manager.register("ml", Box::new(MLLayoutEngine(ml_strategy_arc.clone())));
-                manager.register("ml-enhanced", Box::new(MLLayoutEngine(ml_strategy_arc)));
+                manager.register_builtin("ml", Box::new(MLLayoutEngine(ml_strategy_arc.clone())));
+                manager.register_builtin("ml-enhanced", Box::new(MLLayoutEngine(ml_strategy_arc)));
             }
         }
 
@@ -71,22 +92,134 @@ impl LayoutManager {
         self.parallel_enabled = enabled;
     }
 
+    /// Re-register the built-in engines that use randomness with a fixed seed,
+    /// so identical inputs produce identical layouts. Engines registered
+    /// under their names by the caller are kept.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.elk_options.seed = seed;
+        self.reregister_builtins();
+    }
+
+    /// Re-register the built-in dagre and elk engines to flow in
+    /// `direction`. Engines registered under their names by the caller are
+    /// kept.
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.dagre_options.direction = direction.clone();
+        self.elk_options.direction = direction.into();
+        self.reregister_builtins();
+    }
+
+    /// Rebuild the configurable built-in engines from their current options
+    fn reregister_builtins(&mut self) {
+        if !self.custom_engines.contains("dagre") {
+            let engine = DagreLayout::with_options(self.dagre_options.clone());
+            self.register_builtin("dagre", Box::new(engine));
+        }
+        if !self.custom_engines.contains("elk") {
+            let engine = ElkLayout::with_options(self.elk_options.clone());
+            self.register_builtin("elk", Box::new(engine));
+        }
+    }
+
+    /// Limit how long any engine's layout may run before it fails with
+    /// `LayoutError::Timeout`. Force stops as soon as the budget is spent;
+    /// dagre and elk can't be interrupted, so they fail once they finish
+    /// over budget.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
     pub fn enable_cache(&mut self, enabled: bool) {
         self.cache_enabled = enabled;
     }
 
+    pub fn is_cache_enabled(&self) -> bool {
+        self.cache_enabled
+    }
+
+    /// Persist layouts as JSON files under `dir` so they survive across processes
+    pub fn set_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.cache_dir = dir;
+    }
+
+    /// Drop cached layouts, including those persisted in the cache directory.
+    /// Only files named like cache entries are deleted there.
     pub fn clear_cache(&self) {
+        self.clear_memory_cache();
+        let Some(ref dir) = self.cache_dir else {
+            return;
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let is_entry = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(LayoutCacheKey::is_cache_file_name);
+            if is_entry && path.is_file() {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    fn clear_memory_cache(&self) {
         if let Ok(mut cache) = self.cache.lock() {
             cache.clear();
         }
     }
 
+    /// Register a layout engine under `name`, which documents select with
+    /// `layout: <name>` in their front matter. Replacing an existing engine
+    /// drops cached layouts, since they may have come from the old engine.
     pub fn register(&mut self, name: &str, engine: Box<dyn LayoutEngine>) {
-        self.engines.insert(name.to_string(), engine);
+        self.custom_engines.insert(name.to_string());
+        self.register_builtin(name, engine);
+    }
+
+    fn register_builtin(&mut self, name: &str, engine: Box<dyn LayoutEngine>) {
+        if self.engines.insert(name.to_string(), engine).is_some() {
+            self.clear_memory_cache();
+        }
+    }
+
+    /// Builder-style variant of [`register`](Self::register)
+    pub fn with_engine(mut self, name: &str, engine: Box<dyn LayoutEngine>) -> Self {
+        self.register(name, engine);
+        self
+    }
+
+    /// Names of all registered engines, sorted
+    pub fn engine_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.engines.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
     }
 
     pub fn layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
-        let layout_name = igr.global_config.layout.as_deref().unwrap_or("dagre");
+        self.layout_unscaled(igr)?;
+        igr.fit_virtual_nodes_to_bounds();
+
+        // Zoom the finished layout; cached entries stay unscaled
+        if let Some(scale) = igr.global_config.scale {
+            crate::ast::validate_scale(scale)?;
+            igr.apply_scale(scale);
+        }
+        if let Some(canvas) = igr.global_config.canvas {
+            igr.fit_to_canvas(canvas);
+        }
+
+        Ok(())
+    }
+
+    fn layout_unscaled(&self, igr: &mut IntermediateGraph) -> Result<()> {
+        let layout_name = igr
+            .global_config
+            .layout
+            .as_deref()
+            .unwrap_or("dagre")
+            .to_string();
+        let layout_name = layout_name.as_str();
 
         let engine = self
             .engines
@@ -95,42 +228,41 @@ impl LayoutManager {
 
         // Check cache if enabled
         if self.cache_enabled {
-            let cache_key = LayoutCacheKey::from_igr(igr, layout_name);
+            let cache_key = LayoutCacheKey::from_igr_with_options(
+                igr,
+                layout_name,
+                &engine.options_fingerprint(),
+            );
 
-            // Try to get from cache
+            // Try the in-memory cache first, then the on-disk cache
             if let Ok(cache) = self.cache.lock() {
                 if let Some(cached_layout) = cache.get(&cache_key) {
-                    // Apply cached positions
-                    let updates: Vec<_> = igr
-                        .graph
-                        .node_references()
-                        .filter_map(|(node_idx, node_data)| {
-                            cached_layout
-                                .positions
-                                .get(&node_data.id)
-                                .map(|&(x, y)| (node_idx, x, y))
-                        })
-                        .collect();
-
-                    // Apply updates after collecting
-                    for (node_idx, x, y) in updates {
-                        igr.graph[node_idx].x = x;
-                        igr.graph[node_idx].y = y;
+                    cached_layout.apply(igr);
+                    return Ok(());
+                }
+            }
+            if let Some(ref dir) = self.cache_dir {
+                if let Some(cached_layout) = CachedLayout::load(dir, &cache_key) {
+                    cached_layout.apply(igr);
+                    if let Ok(mut cache) = self.cache.lock() {
+                        cache.insert(cache_key, cached_layout);
                     }
                     return Ok(());
                 }
             }
 
             // Not in cache, compute layout
-            engine.layout(igr)?;
+            self.run_engine(engine.as_ref(), layout_name, igr)?;
+
+            let cached_layout = CachedLayout::from_igr(igr);
+
+            // A failed disk write only costs a recomputation next time
+            if let Some(ref dir) = self.cache_dir {
+                let _ = cached_layout.store(dir, &cache_key);
+            }
 
             // Store in cache
             if let Ok(mut cache) = self.cache.lock() {
-                let mut positions = HashMap::new();
-                for (_, node_data) in igr.graph.node_references() {
-                    positions.insert(node_data.id.clone(), (node_data.x, node_data.y));
-                }
-
                 // Simple LRU: remove oldest if cache is too large
                 if cache.len() > 100 {
                     // Remove a random entry (simple eviction)
@@ -139,15 +271,43 @@ impl LayoutManager {
                     }
                 }
 
-                cache.insert(cache_key, CachedLayout { positions });
+                cache.insert(cache_key, cached_layout);
             }
 
             Ok(())
         } else {
-            engine.layout(igr)
+            self.run_engine(engine.as_ref(), layout_name, igr)
         }
     }
 
+    /// Run `engine` with the context from the document's config, failing
+    /// with `LayoutError::Timeout` if it takes longer than the timeout
+    fn run_engine(
+        &self,
+        engine: &dyn LayoutEngine,
+        name: &str,
+        igr: &mut IntermediateGraph,
+    ) -> Result<()> {
+        let context = LayoutContext {
+            timeout: self.timeout,
+            ..LayoutContext::from_config(&igr.global_config)
+        };
+        let started = Instant::now();
+        engine.layout_with_context(igr, &context)?;
+
+        if let Some(timeout) = self.timeout {
+            let elapsed = started.elapsed();
+            if elapsed > timeout {
+                return Err(LayoutError::Timeout {
+                    engine: name.to_string(),
+                    elapsed,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
     /// Layout with parallel processing for subgraphs if enabled
     pub fn layout_parallel(&self, igr: &mut IntermediateGraph) -> Result<()> {
         if !self.parallel_enabled || igr.containers.is_empty() {
@@ -155,11 +315,16 @@ impl LayoutManager {
             return self.layout(igr);
         }
 
-        let layout_name = igr.global_config.layout.as_deref().unwrap_or("dagre");
+        let layout_name = igr
+            .global_config
+            .layout
+            .as_deref()
+            .unwrap_or("dagre")
+            .to_string();
         let engine = self
             .engines
-            .get(layout_name)
-            .ok_or_else(|| LayoutError::UnknownEngine(layout_name.to_string()))?;
+            .get(&layout_name)
+            .ok_or_else(|| LayoutError::UnknownEngine(layout_name.clone()))?;
 
         // Pre-allocate vectors for parallel processing
         let container_count = igr.containers.len();
@@ -190,7 +355,7 @@ impl LayoutManager {
         }
 
         // Apply the main layout
-        engine.layout(igr)?;
+        self.run_engine(engine.as_ref(), &layout_name, igr)?;
 
         Ok(())
     }
@@ -86,7 +86,11 @@ pub struct NeuralConstraintSolver {
 
 impl NeuralConstraintSolver {
     pub fn new(max_iterations: usize) -> Result<Self> {
-        let device = Device::Cpu;
+        Self::new_on_device(max_iterations, Device::Cpu)
+    }
+
+    /// Create a solver whose tensors live on `device`
+    pub fn new_on_device(max_iterations: usize, device: Device) -> Result<Self> {
         let var_map = VarMap::new();
         let vb = VarBuilder::from_varmap(&var_map, candle_core::DType::F32, &device);
 
@@ -105,7 +109,11 @@ impl NeuralConstraintSolver {
     }
 
     pub fn from_path(path: &Path, max_iterations: usize) -> Result<Self> {
-        let device = Device::Cpu;
+        Self::from_path_on_device(path, max_iterations, Device::Cpu)
+    }
+
+    /// Load a saved solver onto `device`
+    pub fn from_path_on_device(path: &Path, max_iterations: usize, device: Device) -> Result<Self> {
         let mut var_map = VarMap::new();
 
         var_map.load(path).map_err(|e| {
@@ -782,6 +790,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: StdHashMap::new(),
+            edge_types: StdHashMap::new(),
             templates: StdHashMap::new(),
             diagram: None,
             nodes: vec![
@@ -808,6 +817,9 @@ mod tests {
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -0,0 +1,101 @@
+// src/layout/ml/device.rs
+//! Choosing the device ML layout models run on
+
+use candle_core::Device;
+
+/// Device requested for ML layout inference
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DevicePreference {
+    /// The best accelerator available (Metal, then CUDA), else the CPU
+    Auto,
+    #[default]
+    Cpu,
+    /// CUDA GPU with this ordinal
+    Cuda(usize),
+    /// Metal GPU with this ordinal
+    Metal(usize),
+}
+
+impl DevicePreference {
+    /// Open the requested device, falling back to the CPU when it isn't
+    /// available, such as CUDA on a build without CUDA support
+    pub fn select(self) -> Device {
+        let device = match self {
+            DevicePreference::Cpu => Ok(Device::Cpu),
+            DevicePreference::Cuda(ordinal) => Device::new_cuda(ordinal),
+            DevicePreference::Metal(ordinal) => Device::new_metal(ordinal),
+            DevicePreference::Auto => {
+                if candle_core::utils::metal_is_available() {
+                    Device::new_metal(0)
+                } else if candle_core::utils::cuda_is_available() {
+                    Device::new_cuda(0)
+                } else {
+                    Ok(Device::Cpu)
+                }
+            }
+        };
+
+        match device {
+            Ok(device) => {
+                log::info!("ML layout running on {device:?}");
+                device
+            }
+            Err(e) => {
+                log::warn!("Device {self:?} unavailable ({e}), falling back to CPU");
+                Device::Cpu
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::ml::{DirectMLLayoutStrategy, GNNLayoutPredictor, NeuralConstraintSolver};
+    use crate::layout::{DagreLayout, LayoutContext, LayoutEngineAdapter, LayoutStrategy};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_select_device_falls_back_to_cpu() {
+        assert!(matches!(DevicePreference::Cpu.select(), Device::Cpu));
+        // No CUDA device has this ordinal, whether or not CUDA is compiled in
+        assert!(matches!(DevicePreference::Cuda(99).select(), Device::Cpu));
+
+        let fallback = Arc::new(LayoutEngineAdapter::new(DagreLayout::new()));
+        let strategy =
+            DirectMLLayoutStrategy::with_device(fallback.clone(), DevicePreference::Cpu).unwrap();
+        let mut igr = crate::parse_and_layout("a[A]\nb[B]\nc[C]\na -> b\nb -> c", "dagre").unwrap();
+        strategy.apply(&mut igr, &LayoutContext::default()).unwrap();
+        assert!(igr
+            .graph
+            .node_weights()
+            .all(|n| n.x.is_finite() && n.y.is_finite()));
+
+        assert!(
+            DirectMLLayoutStrategy::with_device(fallback.clone(), DevicePreference::Cuda(99))
+                .is_ok()
+        );
+
+        // Saved models load onto the selected device too
+        let dir = tempfile::tempdir().unwrap();
+        let gnn_path = dir.path().join("gnn_model.bin");
+        GNNLayoutPredictor::new(32, 64, 3)
+            .unwrap()
+            .save(&gnn_path)
+            .unwrap();
+        assert!(DirectMLLayoutStrategy::with_model_path_on_device(
+            &gnn_path,
+            fallback,
+            DevicePreference::Cuda(99)
+        )
+        .is_ok());
+
+        let solver_path = dir.path().join("constraint_solver.safetensors");
+        NeuralConstraintSolver::new(10)
+            .unwrap()
+            .save(&solver_path)
+            .unwrap();
+        let device = DevicePreference::Cuda(99).select();
+        assert!(NeuralConstraintSolver::from_path_on_device(&solver_path, 10, device).is_ok());
+    }
+}
@@ -7,7 +7,7 @@ use crate::layout::{LayoutContext, LayoutStrategy};
 use std::path::Path;
 use std::sync::Arc;
 
-use super::GNNLayoutPredictor;
+use super::{DevicePreference, GNNLayoutPredictor};
 
 /// True ML Layout that predicts node positions directly using Graph Neural Networks
 #[cfg(feature = "ml-layout")]
@@ -20,22 +20,36 @@ pub struct DirectMLLayoutStrategy {
 impl DirectMLLayoutStrategy {
     /// Create a new direct ML layout with trained GNN model
     pub fn new(fallback_strategy: Arc<dyn LayoutStrategy>) -> Result<Self> {
+        Self::with_device(fallback_strategy, DevicePreference::Cpu)
+    }
+
+    /// Create a direct ML layout running on the requested device, or the
+    /// CPU if it's unavailable
+    pub fn with_device(
+        fallback_strategy: Arc<dyn LayoutStrategy>,
+        device: DevicePreference,
+    ) -> Result<Self> {
+        let device = device.select();
+
         // Try to load trained GNN model
         let gnn_predictor = if std::path::Path::new("./models/models/gnn_model.bin").exists() {
             log::info!("Loading trained GNN model from ./models/models/gnn_model.bin");
-            match GNNLayoutPredictor::from_path(Path::new("./models/models/gnn_model.bin")) {
+            match GNNLayoutPredictor::from_path_on_device(
+                Path::new("./models/models/gnn_model.bin"),
+                device.clone(),
+            ) {
                 Ok(predictor) => {
                     log::info!("Successfully loaded trained GNN model");
                     predictor
                 }
                 Err(e) => {
                     log::warn!("Failed to load trained GNN model: {e}, using default");
-                    GNNLayoutPredictor::new(32, 64, 3)?
+                    GNNLayoutPredictor::new_on_device(32, 64, 3, device)?
                 }
             }
         } else {
             log::info!("No trained GNN model found, using default architecture");
-            GNNLayoutPredictor::new(32, 64, 3)?
+            GNNLayoutPredictor::new_on_device(32, 64, 3, device)?
         };
 
         Ok(Self {
@@ -49,7 +63,17 @@ impl DirectMLLayoutStrategy {
         model_path: &Path,
         fallback_strategy: Arc<dyn LayoutStrategy>,
     ) -> Result<Self> {
-        let gnn_predictor = GNNLayoutPredictor::from_path(model_path)?;
+        Self::with_model_path_on_device(model_path, fallback_strategy, DevicePreference::Cpu)
+    }
+
+    /// Load the model at `model_path` onto the requested device, or the CPU
+    /// if it's unavailable
+    pub fn with_model_path_on_device(
+        model_path: &Path,
+        fallback_strategy: Arc<dyn LayoutStrategy>,
+        device: DevicePreference,
+    ) -> Result<Self> {
+        let gnn_predictor = GNNLayoutPredictor::from_path_on_device(model_path, device.select())?;
         log::info!("Loaded GNN model from {}", model_path.display());
 
         Ok(Self {
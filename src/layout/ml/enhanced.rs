@@ -10,7 +10,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 use super::{
-    Axis, FeedbackCollector, FeedbackType, GNNLayoutPredictor, LayoutConstraint,
+    Axis, DevicePreference, FeedbackCollector, FeedbackType, GNNLayoutPredictor, LayoutConstraint,
     MLStrategySelector, NeuralConstraintSolver, OnlineModelUpdater, RLLayoutOptimizer,
 };
 
@@ -31,6 +31,8 @@ pub struct EnhancedMLConfig {
     pub constraint_max_iterations: usize,
     /// Model directory path
     pub model_dir: Option<String>,
+    /// Device the GNN and constraint solver run on
+    pub device: DevicePreference,
 }
 
 impl Default for EnhancedMLConfig {
@@ -43,6 +45,7 @@ impl Default for EnhancedMLConfig {
             rl_max_episodes: 50,
             constraint_max_iterations: 20,
             model_dir: None,
+            device: DevicePreference::default(),
         }
     }
 }
@@ -57,6 +60,8 @@ pub struct EnhancedMLLayoutStrategy {
     feedback_collector: Option<Arc<FeedbackCollector>>,
     online_updater: Option<OnlineModelUpdater>,
     fallback_strategy: Arc<dyn LayoutStrategy>,
+    /// Device the GNN and constraint solver run on
+    device: candle_core::Device,
 }
 
 impl EnhancedMLLayoutStrategy {
@@ -71,8 +76,19 @@ impl EnhancedMLLayoutStrategy {
             Arc::new(MLStrategySelector::new()?)
         };
 
+        let device = if config.use_gnn || config.use_constraints {
+            config.device.select()
+        } else {
+            candle_core::Device::Cpu
+        };
+
         let gnn_predictor = if config.use_gnn {
-            Some(GNNLayoutPredictor::new(32, 64, 3)?)
+            Some(GNNLayoutPredictor::new_on_device(
+                32,
+                64,
+                3,
+                device.clone(),
+            )?)
         } else {
             None
         };
@@ -84,8 +100,9 @@ impl EnhancedMLLayoutStrategy {
         };
 
         let constraint_solver = if config.use_constraints {
-            Some(NeuralConstraintSolver::new(
+            Some(NeuralConstraintSolver::new_on_device(
                 config.constraint_max_iterations,
+                device.clone(),
             )?)
         } else {
             None
@@ -108,6 +125,7 @@ impl EnhancedMLLayoutStrategy {
             feedback_collector,
             online_updater,
             fallback_strategy,
+            device,
         })
     }
 
@@ -128,7 +146,10 @@ impl EnhancedMLLayoutStrategy {
         if strategy.gnn_predictor.is_some() {
             let gnn_path = model_path.join("gnn_layout.safetensors");
             if gnn_path.exists() {
-                strategy.gnn_predictor = Some(GNNLayoutPredictor::from_path(&gnn_path)?);
+                strategy.gnn_predictor = Some(GNNLayoutPredictor::from_path_on_device(
+                    &gnn_path,
+                    strategy.device.clone(),
+                )?);
                 log::info!("Loaded pre-trained GNN model");
             }
         }
@@ -136,9 +157,10 @@ impl EnhancedMLLayoutStrategy {
         if strategy.constraint_solver.is_some() {
             let constraint_path = model_path.join("constraint_solver.safetensors");
             if constraint_path.exists() {
-                strategy.constraint_solver = Some(NeuralConstraintSolver::from_path(
+                strategy.constraint_solver = Some(NeuralConstraintSolver::from_path_on_device(
                     &constraint_path,
                     strategy.config.constraint_max_iterations,
+                    strategy.device.clone(),
                 )?);
                 log::info!("Loaded pre-trained constraint solver");
             }
@@ -375,6 +397,12 @@ impl EnhancedMLLayoutBuilder {
         self
     }
 
+    /// Run the models on this device, or the CPU if it's unavailable
+    pub fn with_device(mut self, device: DevicePreference) -> Self {
+        self.config.device = device;
+        self
+    }
+
     pub fn with_fallback(mut self, strategy: Arc<dyn LayoutStrategy>) -> Self {
         self.fallback_strategy = Some(strategy);
         self
@@ -419,6 +447,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: StdHashMap::new(),
+            edge_types: StdHashMap::new(),
             templates: StdHashMap::new(),
             diagram: None,
             nodes: vec![
@@ -449,6 +478,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: StdHashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "b".to_string(),
@@ -457,11 +488,16 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: StdHashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
             ],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let mut igr = IntermediateGraph::from_ast(document).unwrap();
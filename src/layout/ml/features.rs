@@ -403,6 +403,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -426,10 +427,15 @@ mod tests {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: HashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             }],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -422,6 +422,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: StdHashMap::new(),
+            edge_types: StdHashMap::new(),
             templates: StdHashMap::new(),
             diagram: None,
             nodes: vec![NodeDefinition {
@@ -434,6 +435,9 @@ mod tests {
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -3,6 +3,7 @@
 
 use crate::error::{EDSLError, Result};
 use crate::igr::IntermediateGraph;
+use crate::layout::ml::onnx::{Attribute, Dim, GraphBuilder};
 use candle_core::{Device, Module, Tensor};
 use candle_nn::{linear, VarBuilder, VarMap};
 use petgraph::graph::NodeIndex;
@@ -225,6 +226,121 @@ impl GATLayer {
 
         Ok(output)
     }
+
+    /// Add this layer to an ONNX graph. `forward`'s per-node neighbor loop
+    /// becomes attention over the adjacency matrix: scores of non-neighbors
+    /// are masked out, and nodes without neighbors keep their own features.
+    fn export_onnx(
+        &self,
+        graph: &mut GraphBuilder,
+        prefix: &str,
+        input: &str,
+        shared: &OnnxShared,
+    ) -> candle_core::Result<String> {
+        // The attention weights score [node ; neighbor], so split them
+        let weights: Vec<f32> = self.attention_weights.weight().flatten_all()?.to_vec1()?;
+        let feature_dim = weights.len() / 2;
+        let node_weights = graph.constant(
+            &format!("{prefix}.attention.node"),
+            &[feature_dim, 1],
+            &weights[..feature_dim],
+        );
+        let neighbor_weights = graph.constant(
+            &format!("{prefix}.attention.neighbor"),
+            &[feature_dim, 1],
+            &weights[feature_dim..],
+        );
+        let bias = linear_bias(&self.attention_weights, 1)?;
+        let bias = graph.constant(&format!("{prefix}.attention.bias"), &[1], &bias);
+
+        let node_scores = graph.op("MatMul", &[input, &node_weights], &[]);
+        let neighbor_scores = graph.op("MatMul", &[input, &neighbor_weights], &[]);
+        let neighbor_scores = graph.op(
+            "Transpose",
+            &[&neighbor_scores],
+            &[("perm", Attribute::Ints(vec![1, 0]))],
+        );
+        let scores = graph.op("Add", &[&node_scores, &neighbor_scores], &[]);
+        let scores = graph.op("Add", &[&scores, &bias], &[]);
+        let scores = graph.op("Add", &[&scores, &shared.penalty], &[]);
+
+        // Softmax over each node's neighbors, weighted by edge count
+        let max = graph.op(
+            "ReduceMax",
+            &[&scores],
+            &[
+                ("axes", Attribute::Ints(vec![1])),
+                ("keepdims", Attribute::Int(1)),
+            ],
+        );
+        let shifted = graph.op("Sub", &[&scores, &max], &[]);
+        let exp = graph.op("Exp", &[&shifted], &[]);
+        let weighted = graph.op("Mul", &[&exp, "adjacency"], &[]);
+        let total = graph.op(
+            "ReduceSum",
+            &[&weighted, &shared.rows],
+            &[("keepdims", Attribute::Int(1))],
+        );
+        let total = graph.op("Max", &[&total, &shared.tiny], &[]);
+        let probs = graph.op("Div", &[&weighted, &total], &[]);
+
+        let aggregated = graph.op("MatMul", &[&probs, input], &[]);
+        let own = graph.op("Mul", &[&shared.isolated, input], &[]);
+        let aggregated = graph.op("Add", &[&aggregated, &own], &[]);
+
+        export_linear(
+            graph,
+            &format!("{prefix}.value"),
+            &self.value_transform,
+            &aggregated,
+            None,
+        )
+    }
+}
+
+/// Values every exported GAT layer reads
+struct OnnxShared {
+    /// 0 for neighbors, a large negative number elsewhere
+    penalty: String,
+    /// 1 for nodes without neighbors, 0 otherwise
+    isolated: String,
+    /// Axis of the neighbors in the adjacency matrix
+    rows: String,
+    /// Floor for softmax denominators of nodes without neighbors
+    tiny: String,
+}
+
+fn linear_bias(linear: &candle_nn::Linear, out_dim: usize) -> candle_core::Result<Vec<f32>> {
+    match linear.bias() {
+        Some(bias) => bias.flatten_all()?.to_vec1(),
+        None => Ok(vec![0.0; out_dim]),
+    }
+}
+
+/// Add `x W^T + b` to an ONNX graph, writing `output` when given
+fn export_linear(
+    graph: &mut GraphBuilder,
+    prefix: &str,
+    linear: &candle_nn::Linear,
+    input: &str,
+    output: Option<&str>,
+) -> candle_core::Result<String> {
+    let weight = linear.weight();
+    let (out_dim, in_dim) = weight.dims2()?;
+    let weights: Vec<f32> = weight.flatten_all()?.to_vec1()?;
+    let weight = graph.constant(&format!("{prefix}.weight"), &[out_dim, in_dim], &weights);
+    let bias = linear_bias(linear, out_dim)?;
+    let bias = graph.constant(&format!("{prefix}.bias"), &[out_dim], &bias);
+
+    let inputs = [input, weight.as_str(), bias.as_str()];
+    let attributes = [("transB", Attribute::Int(1))];
+    Ok(match output {
+        Some(output) => {
+            graph.node("Gemm", &inputs, &attributes, output);
+            output.to_string()
+        }
+        None => graph.op("Gemm", &inputs, &attributes),
+    })
 }
 
 /// GNN model for layout prediction
@@ -240,7 +356,16 @@ pub struct GNNLayoutPredictor {
 impl GNNLayoutPredictor {
     /// Create a new GNN layout predictor
     pub fn new(input_dim: usize, hidden_dim: usize, num_layers: usize) -> Result<Self> {
-        let device = Device::Cpu;
+        Self::new_on_device(input_dim, hidden_dim, num_layers, Device::Cpu)
+    }
+
+    /// Create a new GNN layout predictor whose tensors live on `device`
+    pub fn new_on_device(
+        input_dim: usize,
+        hidden_dim: usize,
+        num_layers: usize,
+        device: Device,
+    ) -> Result<Self> {
         let var_map = VarMap::new();
         let vb = VarBuilder::from_varmap(&var_map, candle_core::DType::F32, &device);
 
@@ -278,7 +403,11 @@ impl GNNLayoutPredictor {
 
     /// Load model from file
     pub fn from_path(path: &Path) -> Result<Self> {
-        let device = Device::Cpu;
+        Self::from_path_on_device(path, Device::Cpu)
+    }
+
+    /// Load model from file onto `device`
+    pub fn from_path_on_device(path: &Path, device: Device) -> Result<Self> {
         let mut var_map = VarMap::new();
 
         // Load weights
@@ -333,6 +462,84 @@ impl GNNLayoutPredictor {
         })
     }
 
+    /// Width of the node feature rows the model takes
+    pub fn input_dim(&self) -> usize {
+        match self.gat_layers.first() {
+            Some(layer) => layer.value_transform.weight().dims()[1],
+            None => self.position_head.weight().dims()[1],
+        }
+    }
+
+    /// Export the model to ONNX, to run it outside Rust
+    ///
+    /// The graph takes `node_features` (`[num_nodes, input_dim]`, the rows
+    /// `predict_layout` builds) and `adjacency` (`[num_nodes, num_nodes]`,
+    /// where entry `[i][j]` counts the edges from node `j` to node `i`), and
+    /// produces `positions` (`[num_nodes, 2]`) and `confidence`
+    /// (`[num_nodes, 1]`, before the `tanh` `predict_layout` applies).
+    pub fn export_onnx(&self, path: &Path) -> Result<()> {
+        let bytes = self.to_onnx().map_err(|e| {
+            EDSLError::Layout(crate::error::LayoutError::CalculationFailed(format!(
+                "Failed to export GNN model to ONNX: {e}"
+            )))
+        })?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    fn to_onnx(&self) -> candle_core::Result<Vec<u8>> {
+        let nodes = Dim::Param("num_nodes");
+        let mut graph = GraphBuilder::new("gnn_layout");
+        graph.input("node_features", &[nodes, Dim::Value(self.input_dim())]);
+        graph.input("adjacency", &[nodes, nodes]);
+        graph.output("positions", &[nodes, Dim::Value(2)]);
+        graph.output("confidence", &[nodes, Dim::Value(1)]);
+
+        let one = graph.constant("one", &[], &[1.0]);
+        let large = graph.constant("large", &[], &[1e9]);
+        let rows = graph.constant_i64("rows", &[1], &[1]);
+        let tiny = graph.constant("tiny", &[], &[1e-12]);
+
+        let mask = graph.op("Min", &["adjacency", &one], &[]);
+        let penalty = graph.op("Sub", &[&mask, &one], &[]);
+        let penalty = graph.op("Mul", &[&penalty, &large], &[]);
+        let degree = graph.op(
+            "ReduceSum",
+            &["adjacency", &rows],
+            &[("keepdims", Attribute::Int(1))],
+        );
+        let has_neighbors = graph.op("Min", &[&degree, &one], &[]);
+        let isolated = graph.op("Sub", &[&one, &has_neighbors], &[]);
+        let shared = OnnxShared {
+            penalty,
+            isolated,
+            rows,
+            tiny,
+        };
+
+        let mut hidden = "node_features".to_string();
+        for (i, layer) in self.gat_layers.iter().enumerate() {
+            hidden = layer.export_onnx(&mut graph, &format!("gat_layer_{i}"), &hidden, &shared)?;
+            hidden = graph.op("Relu", &[&hidden], &[]);
+        }
+        export_linear(
+            &mut graph,
+            "position_head",
+            &self.position_head,
+            &hidden,
+            Some("positions"),
+        )?;
+        export_linear(
+            &mut graph,
+            "confidence_head",
+            &self.confidence_head,
+            &hidden,
+            Some("confidence"),
+        )?;
+
+        Ok(graph.into_model_bytes())
+    }
+
     /// Predict layout for a graph
     pub fn predict_layout(&self, igr: &IntermediateGraph) -> Result<LayoutPrediction> {
         // Extract node features
@@ -518,6 +725,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -548,6 +756,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "b".to_string(),
@@ -556,11 +766,16 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
             ],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
@@ -14,6 +14,8 @@ mod training;
 #[cfg(feature = "ml-layout")]
 mod constraints;
 #[cfg(feature = "ml-layout")]
+mod device;
+#[cfg(feature = "ml-layout")]
 mod direct;
 #[cfg(feature = "ml-layout")]
 mod enhanced;
@@ -22,6 +24,8 @@ mod feedback;
 #[cfg(feature = "ml-layout")]
 mod gnn;
 #[cfg(feature = "ml-layout")]
+mod onnx;
+#[cfg(feature = "ml-layout")]
 mod rl;
 
 // Phase 1 exports
@@ -40,6 +44,8 @@ pub use constraints::{
     Axis, ConstraintSolution, Direction, LayoutConstraint, NeuralConstraintSolver,
 };
 #[cfg(feature = "ml-layout")]
+pub use device::DevicePreference;
+#[cfg(feature = "ml-layout")]
 pub use direct::DirectMLLayoutStrategy;
 #[cfg(feature = "ml-layout")]
 pub use enhanced::{EnhancedMLConfig, EnhancedMLLayoutBuilder, EnhancedMLLayoutStrategy};
@@ -73,12 +79,36 @@ impl MLLayoutStrategy {
         Ok(Self { direct_ml })
     }
 
+    /// Run the GNN on the requested device, or the CPU if it's unavailable
+    pub fn with_device(
+        fallback_strategy: Arc<dyn LayoutStrategy>,
+        device: DevicePreference,
+    ) -> Result<Self> {
+        let direct_ml = DirectMLLayoutStrategy::with_device(fallback_strategy, device)?;
+
+        Ok(Self { direct_ml })
+    }
+
     pub fn with_model_path(
         model_path: &str,
         fallback_strategy: Arc<dyn LayoutStrategy>,
+    ) -> Result<Self> {
+        Self::with_model_path_on_device(model_path, fallback_strategy, DevicePreference::Cpu)
+    }
+
+    /// Load `gnn_model.bin` from `model_path` onto the requested device, or
+    /// the CPU if it's unavailable
+    pub fn with_model_path_on_device(
+        model_path: &str,
+        fallback_strategy: Arc<dyn LayoutStrategy>,
+        device: DevicePreference,
     ) -> Result<Self> {
         let model_path = std::path::Path::new(model_path).join("gnn_model.bin");
-        let direct_ml = DirectMLLayoutStrategy::with_model_path(&model_path, fallback_strategy)?;
+        let direct_ml = DirectMLLayoutStrategy::with_model_path_on_device(
+            &model_path,
+            fallback_strategy,
+            device,
+        )?;
 
         Ok(Self { direct_ml })
     }
@@ -124,6 +154,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -160,6 +191,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "b".to_string(),
@@ -168,6 +201,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "a".to_string(),
@@ -176,6 +211,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "d".to_string(),
@@ -184,11 +221,16 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
             ],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         IntermediateGraph::from_ast(document).unwrap()
@@ -482,6 +524,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -512,6 +555,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "b".to_string(),
@@ -520,11 +565,16 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
             ],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         IntermediateGraph::from_ast(document).unwrap()
@@ -534,6 +584,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -576,6 +627,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "client2".to_string(),
@@ -584,6 +637,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "server".to_string(),
@@ -592,6 +647,8 @@ mod tests {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
                 EdgeDefinition {
                     from: "server".to_string(),
@@ -600,6 +657,8 @@ mod tests {
                     arrow_type: ArrowType::DoubleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 },
             ],
             containers: vec![ContainerDefinition {
@@ -618,6 +677,9 @@ mod tests {
                 internal_statements: vec![],
             }],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         IntermediateGraph::from_ast(document).unwrap()
@@ -0,0 +1,348 @@
+// src/layout/ml/onnx.rs
+//! Minimal ONNX writer for exporting trained models
+//!
+//! Candle can't export ONNX, so models describe their computation with a
+//! `GraphBuilder` and this module encodes it as an ONNX `ModelProto`. Only
+//! the protobuf fields the exporters need are written.
+
+/// ONNX IR version matching `OPSET_VERSION`
+const IR_VERSION: u64 = 7;
+const OPSET_VERSION: u64 = 13;
+
+const TENSOR_FLOAT: u64 = 1;
+const TENSOR_INT64: u64 = 7;
+
+const ATTRIBUTE_FLOAT: u64 = 1;
+const ATTRIBUTE_INT: u64 = 2;
+const ATTRIBUTE_INTS: u64 = 7;
+
+/// A dimension of a graph input or output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Dim {
+    Value(usize),
+    /// Symbolic size, like the number of nodes, fixed when the model runs
+    Param(&'static str),
+}
+
+/// A node attribute
+pub(crate) enum Attribute {
+    Int(i64),
+    Float(f32),
+    Ints(Vec<i64>),
+}
+
+/// Protobuf message being encoded
+#[derive(Default)]
+struct Message(Vec<u8>);
+
+impl Message {
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.raw_varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn varint(&mut self, field: u32, value: u64) {
+        self.key(field, 0);
+        self.raw_varint(value);
+    }
+
+    fn float(&mut self, field: u32, value: f32) {
+        self.key(field, 5);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bytes(&mut self, field: u32, data: &[u8]) {
+        self.key(field, 2);
+        self.raw_varint(data.len() as u64);
+        self.0.extend_from_slice(data);
+    }
+
+    fn string(&mut self, field: u32, text: &str) {
+        self.bytes(field, text.as_bytes());
+    }
+
+    fn message(&mut self, field: u32, message: Message) {
+        self.bytes(field, &message.0);
+    }
+}
+
+/// Builds an ONNX graph node by node, naming intermediate values itself
+pub(crate) struct GraphBuilder {
+    name: String,
+    nodes: Vec<Message>,
+    initializers: Vec<Message>,
+    inputs: Vec<Message>,
+    outputs: Vec<Message>,
+    next_value: usize,
+}
+
+impl GraphBuilder {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            nodes: Vec::new(),
+            initializers: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            next_value: 0,
+        }
+    }
+
+    /// Declare a float tensor input of the graph
+    pub(crate) fn input(&mut self, name: &str, dims: &[Dim]) {
+        self.inputs.push(value_info(name, dims));
+    }
+
+    /// Declare a float tensor output of the graph; some node must produce it
+    pub(crate) fn output(&mut self, name: &str, dims: &[Dim]) {
+        self.outputs.push(value_info(name, dims));
+    }
+
+    /// Add a float constant, such as a weight matrix, and return its name
+    pub(crate) fn constant(&mut self, name: &str, dims: &[usize], values: &[f32]) -> String {
+        let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.initializers
+            .push(tensor(name, dims, TENSOR_FLOAT, &raw));
+        name.to_string()
+    }
+
+    /// Add an int64 constant, such as reduction axes, and return its name
+    pub(crate) fn constant_i64(&mut self, name: &str, dims: &[usize], values: &[i64]) -> String {
+        let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.initializers
+            .push(tensor(name, dims, TENSOR_INT64, &raw));
+        name.to_string()
+    }
+
+    /// Add a node writing a fresh value, and return that value's name
+    pub(crate) fn op(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: &[(&str, Attribute)],
+    ) -> String {
+        let output = format!("{}_{}", op_type.to_lowercase(), self.next_value);
+        self.next_value += 1;
+        self.node(op_type, inputs, attributes, &output);
+        output
+    }
+
+    /// Add a node writing the value named `output`
+    pub(crate) fn node(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: &[(&str, Attribute)],
+        output: &str,
+    ) {
+        let mut node = Message::default();
+        for input in inputs {
+            node.string(1, input);
+        }
+        node.string(2, output);
+        node.string(3, &format!("node_{}", self.nodes.len()));
+        node.string(4, op_type);
+        for (name, value) in attributes {
+            let mut attribute = Message::default();
+            attribute.string(1, name);
+            match value {
+                Attribute::Float(f) => {
+                    attribute.float(2, *f);
+                    attribute.varint(20, ATTRIBUTE_FLOAT);
+                }
+                Attribute::Int(i) => {
+                    attribute.varint(3, *i as u64);
+                    attribute.varint(20, ATTRIBUTE_INT);
+                }
+                Attribute::Ints(ints) => {
+                    for i in ints {
+                        attribute.varint(8, *i as u64);
+                    }
+                    attribute.varint(20, ATTRIBUTE_INTS);
+                }
+            }
+            node.message(5, attribute);
+        }
+        self.nodes.push(node);
+    }
+
+    /// Encode the graph as a serialized ONNX `ModelProto`
+    pub(crate) fn into_model_bytes(self) -> Vec<u8> {
+        let mut graph = Message::default();
+        for node in self.nodes {
+            graph.message(1, node);
+        }
+        graph.string(2, &self.name);
+        for initializer in self.initializers {
+            graph.message(5, initializer);
+        }
+        for input in self.inputs {
+            graph.message(11, input);
+        }
+        for output in self.outputs {
+            graph.message(12, output);
+        }
+
+        let mut opset = Message::default();
+        opset.varint(2, OPSET_VERSION);
+
+        let mut model = Message::default();
+        model.varint(1, IR_VERSION);
+        model.string(2, "excalidraw-dsl");
+        model.string(3, env!("CARGO_PKG_VERSION"));
+        model.message(7, graph);
+        model.message(8, opset);
+        model.0
+    }
+}
+
+fn tensor(name: &str, dims: &[usize], data_type: u64, raw: &[u8]) -> Message {
+    let mut tensor = Message::default();
+    for &dim in dims {
+        tensor.varint(1, dim as u64);
+    }
+    tensor.varint(2, data_type);
+    tensor.string(8, name);
+    tensor.bytes(9, raw);
+    tensor
+}
+
+fn value_info(name: &str, dims: &[Dim]) -> Message {
+    let mut shape = Message::default();
+    for dim in dims {
+        let mut dimension = Message::default();
+        match dim {
+            Dim::Value(value) => dimension.varint(1, *value as u64),
+            Dim::Param(param) => dimension.string(2, param),
+        }
+        shape.message(1, dimension);
+    }
+
+    let mut tensor_type = Message::default();
+    tensor_type.varint(1, TENSOR_FLOAT);
+    tensor_type.message(2, shape);
+
+    let mut type_proto = Message::default();
+    type_proto.message(1, tensor_type);
+
+    let mut info = Message::default();
+    info.string(1, name);
+    info.message(2, type_proto);
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::ml::GNNLayoutPredictor;
+
+    enum Field<'a> {
+        Varint(u64),
+        Bytes(&'a [u8]),
+    }
+
+    /// Top-level fields of a protobuf message, in order
+    fn fields(mut bytes: &[u8]) -> Vec<(u32, Field<'_>)> {
+        fn varint(bytes: &mut &[u8]) -> u64 {
+            let mut value = 0;
+            let mut shift = 0;
+            loop {
+                let rest: &[u8] = bytes;
+                let byte = rest[0];
+                *bytes = &rest[1..];
+                value |= ((byte & 0x7f) as u64) << shift;
+                if byte < 0x80 {
+                    return value;
+                }
+                shift += 7;
+            }
+        }
+
+        let mut fields = Vec::new();
+        while !bytes.is_empty() {
+            let key = varint(&mut bytes);
+            let field = (key >> 3) as u32;
+            let value = match key & 7 {
+                0 => Field::Varint(varint(&mut bytes)),
+                2 => {
+                    let len = varint(&mut bytes) as usize;
+                    let (data, rest) = bytes.split_at(len);
+                    bytes = rest;
+                    Field::Bytes(data)
+                }
+                5 => {
+                    bytes = &bytes[4..];
+                    continue;
+                }
+                wire_type => panic!("unexpected wire type {wire_type}"),
+            };
+            fields.push((field, value));
+        }
+        fields
+    }
+
+    fn submessages(bytes: &[u8], field: u32) -> Vec<&[u8]> {
+        fields(bytes)
+            .into_iter()
+            .filter_map(|(number, value)| match value {
+                Field::Bytes(data) if number == field => Some(data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Name and dims (values or symbolic names) of a `ValueInfoProto`
+    fn value_info(bytes: &[u8]) -> (String, Vec<String>) {
+        let name = String::from_utf8(submessages(bytes, 1)[0].to_vec()).unwrap();
+        let type_proto = submessages(bytes, 2)[0];
+        let tensor_type = submessages(type_proto, 1)[0];
+        let shape = submessages(tensor_type, 2)[0];
+        let dims = submessages(shape, 1)
+            .into_iter()
+            .map(|dim| match &fields(dim)[0] {
+                (1, Field::Varint(value)) => value.to_string(),
+                (2, Field::Bytes(param)) => String::from_utf8(param.to_vec()).unwrap(),
+                _ => panic!("unexpected dimension"),
+            })
+            .collect();
+        (name, dims)
+    }
+
+    #[test]
+    fn test_export_gnn_declares_model_dims() {
+        let predictor = GNNLayoutPredictor::new(32, 64, 2).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gnn_model.onnx");
+        predictor.export_onnx(&path).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(!bytes.is_empty());
+
+        let graph = submessages(&bytes, 7)[0];
+        let inputs: Vec<_> = submessages(graph, 11).into_iter().map(value_info).collect();
+        let outputs: Vec<_> = submessages(graph, 12).into_iter().map(value_info).collect();
+        let dims = |dims: &[&str]| dims.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            [
+                ("node_features".to_string(), dims(&["num_nodes", "32"])),
+                ("adjacency".to_string(), dims(&["num_nodes", "num_nodes"])),
+            ]
+        );
+        assert_eq!(
+            outputs,
+            [
+                ("positions".to_string(), dims(&["num_nodes", "2"])),
+                ("confidence".to_string(), dims(&["num_nodes", "1"])),
+            ]
+        );
+        // Every layer's weights travel with the model
+        assert!(submessages(graph, 5).len() >= 2 * 4 + 4);
+    }
+}
@@ -715,6 +715,7 @@ mod tests {
         let document = ParsedDocument {
             config: GlobalConfig::default(),
             component_types: StdHashMap::new(),
+            edge_types: StdHashMap::new(),
             templates: StdHashMap::new(),
             diagram: None,
             nodes: vec![
@@ -738,10 +739,15 @@ mod tests {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: StdHashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             }],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let igr = IntermediateGraph::from_ast(document).unwrap();
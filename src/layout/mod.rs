@@ -1,4 +1,5 @@
 // src/layout/mod.rs
+pub mod benchmark;
 mod cache;
 mod dagre;
 mod elk;
@@ -11,8 +12,11 @@ pub mod ml;
 
 pub use cache::{CachedLayout, LayoutCacheKey};
 pub use dagre::{DagreLayout, DagreLayoutOptions, Direction, RankingAlgorithm};
-pub use elk::{ElkAlgorithm, ElkDirection, ElkLayout, ElkLayoutOptions, HierarchyHandling};
-pub use force::{ForceLayout, ForceLayoutOptions};
+pub use elk::{
+    ContainerPacking, ElkAlgorithm, ElkDirection, ElkLayout, ElkLayoutOptions, HierarchyHandling,
+    DEFAULT_LAYOUT_SEED,
+};
+pub use force::{count_edge_crossings, reduce_crossings, ForceLayout, ForceLayoutOptions};
 pub use manager::LayoutManager;
 pub use strategy::{
     AdaptiveStrategy, ComplexityHint, CompositeStrategy, LayoutContext, LayoutStrategy,
@@ -27,6 +31,22 @@ use crate::igr::IntermediateGraph;
 pub trait LayoutEngine: Send + Sync {
     fn layout(&self, igr: &mut IntermediateGraph) -> Result<()>;
     fn name(&self) -> &'static str;
+
+    /// Lay out within the area described by `context`. Engines that can
+    /// wrap or pack to fit override this; the rest ignore the context.
+    fn layout_with_context(
+        &self,
+        igr: &mut IntermediateGraph,
+        _context: &LayoutContext,
+    ) -> Result<()> {
+        self.layout(igr)
+    }
+
+    /// Description of the options that affect this engine's output, used in
+    /// layout cache keys so differently configured engines don't share entries
+    fn options_fingerprint(&self) -> String {
+        String::new()
+    }
 }
 
 /// Adapter to use LayoutEngine implementations as LayoutStrategy
@@ -41,8 +61,8 @@ impl<T: LayoutEngine> LayoutEngineAdapter<T> {
 }
 
 impl<T: LayoutEngine> LayoutStrategy for LayoutEngineAdapter<T> {
-    fn apply(&self, igr: &mut IntermediateGraph, _context: &LayoutContext) -> Result<()> {
-        self.engine.layout(igr)
+    fn apply(&self, igr: &mut IntermediateGraph, context: &LayoutContext) -> Result<()> {
+        self.engine.layout_with_context(igr, context)
     }
 
     fn name(&self) -> &'static str {
@@ -54,7 +74,7 @@ impl<T: LayoutEngine> LayoutStrategy for LayoutEngineAdapter<T> {
 mod tests {
     use super::*;
     use crate::ast::*;
-    use crate::igr::IntermediateGraph;
+    use crate::igr::{BoundingBox, IntermediateGraph};
     use std::collections::HashMap;
 
     #[test]
@@ -65,6 +85,7 @@ mod tests {
                 ..Default::default()
             },
             component_types: HashMap::new(),
+            edge_types: HashMap::new(),
             templates: HashMap::new(),
             diagram: None,
             nodes: vec![
@@ -88,10 +109,15 @@ mod tests {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: HashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             }],
             containers: vec![],
             groups: vec![],
             connections: vec![],
+            notes: vec![],
+            lines: vec![],
+            hyperedges: vec![],
         };
 
         let mut igr = IntermediateGraph::from_ast(document).unwrap();
@@ -106,4 +132,935 @@ mod tests {
         // In a left-right layout, B should be to the right of A
         assert!(node_b.x > node_a.x);
     }
+
+    struct CountingLayout(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl LayoutEngine for CountingLayout {
+        fn layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            DagreLayout::new().layout(igr)
+        }
+
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+    }
+
+    #[test]
+    fn test_timeout_applies_to_every_engine() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        struct SlowLayout;
+        impl LayoutEngine for SlowLayout {
+            fn layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
+                std::thread::sleep(Duration::from_millis(20));
+                DagreLayout::new().layout(igr)
+            }
+
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+        }
+
+        let layout = |manager: &LayoutManager, engine: &str| {
+            let input = format!("---\nlayout: {engine}\n---\na[A]\nb[B]\na -> b\n");
+            let document = crate::parser::parse_edsl(&input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            manager.layout(&mut igr)
+        };
+
+        let mut manager = LayoutManager::new();
+        manager.enable_cache(false);
+        manager.register("slow", Box::new(SlowLayout));
+        manager.set_timeout(Duration::from_millis(1));
+        match layout(&manager, "slow") {
+            Err(crate::error::EDSLError::Layout(crate::error::LayoutError::Timeout {
+                engine,
+                elapsed,
+            })) => {
+                assert_eq!(engine, "slow");
+                assert!(elapsed >= Duration::from_millis(20));
+            }
+            other => panic!("Expected a layout timeout, got {other:?}"),
+        }
+
+        // Setting a timeout keeps a custom force engine registered
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut manager = LayoutManager::new();
+        manager.enable_cache(false);
+        manager.register("force", Box::new(CountingLayout(calls.clone())));
+        manager.set_timeout(Duration::from_secs(60));
+        layout(&manager, "force").unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_disk_layout_cache_survives_new_manager() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let cache_dir = tempfile::tempdir().unwrap();
+        let input = "---\nlayout: counting\n---\na[A]\nb[B]\na -> b\n";
+
+        let run = || {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let mut manager = LayoutManager::new();
+            manager.register("counting", Box::new(CountingLayout(calls.clone())));
+            manager.set_cache_dir(Some(cache_dir.path().to_path_buf()));
+
+            let document = crate::parser::parse_edsl(input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            manager.layout(&mut igr).unwrap();
+            let (_, b) = igr.get_node_by_id("b").unwrap();
+            (calls.load(Ordering::SeqCst), b.x, b.y)
+        };
+
+        let (first_calls, x1, y1) = run();
+        assert_eq!(first_calls, 1);
+        assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 1);
+
+        // A fresh manager (as in a second CLI run) reads positions from disk
+        let (second_calls, x2, y2) = run();
+        assert_eq!(second_calls, 0);
+        assert_eq!((x1, y1), (x2, y2));
+
+        // Clearing removes cache entries but not other JSON files there
+        std::fs::write(cache_dir.path().join("package.json"), "{}").unwrap();
+        std::fs::write(cache_dir.path().join("dagre-notes.json"), "{}").unwrap();
+        let mut manager = LayoutManager::new();
+        manager.set_cache_dir(Some(cache_dir.path().to_path_buf()));
+        manager.clear_cache();
+        let mut left: Vec<_> = std::fs::read_dir(cache_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["dagre-notes.json", "package.json"]);
+    }
+
+    #[test]
+    fn test_layout_cache_key_tracks_cluster_membership_and_attributes() {
+        let key = |first: &str, second: &str, style: &str| {
+            let input = format!(
+                "container \"One\" as c1 {{\n  {style}\n  {first}\n}}\n\
+                 container \"Two\" as c2 {{\n  {second}\n}}\na -> b\n"
+            );
+            let document = crate::parser::parse_edsl(&input).unwrap();
+            let igr = IntermediateGraph::from_ast(document).unwrap();
+            LayoutCacheKey::from_igr(&igr, "dagre")
+        };
+        let base = key("a", "b", "");
+        assert_eq!(base, key("a", "b", ""));
+
+        // Same container sizes, members swapped
+        assert_ne!(base, key("b", "a", ""));
+        // Same members, container attributes changed
+        assert_ne!(base, key("a", "b", "style: { aspect: 2:1; }"));
+        assert_ne!(base, key("a", "b", "style: { direction: down; }"));
+    }
+
+    #[test]
+    fn test_layout_cache_keeps_edited_ports() {
+        let manager = LayoutManager::new();
+        let layout = |input: &str| {
+            let document = crate::parser::parse_edsl(input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            manager.layout(&mut igr).unwrap();
+            let edge = igr.graph.edge_weights().next().unwrap();
+            (edge.attributes.start_port, edge.attributes.end_port)
+        };
+
+        assert_eq!(
+            layout("a[A]\nb[B]\na.bottom -> b.left\n"),
+            (Some(PortSide::Bottom), Some(PortSide::Left))
+        );
+        assert_eq!(
+            layout("a[A]\nb[B]\na.top -> b.left\n"),
+            (Some(PortSide::Top), Some(PortSide::Left))
+        );
+        assert_eq!(
+            layout("a[A]\nb[B]\na -> b.left\n"),
+            (None, Some(PortSide::Left))
+        );
+    }
+
+    #[test]
+    fn test_dagre_edge_minlen_spans_ranks() {
+        // q -> r -> s -> b is a reference chain placing q three ranks before b
+        let layout = |edge_style: &str| {
+            let input =
+                format!("a[A]\nb[B]\nq[Q]\nr[R]\ns[S]\na -> b {edge_style}\nq -> r -> s -> b\n");
+            let document = crate::parser::parse_edsl(&input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            LayoutManager::new().layout(&mut igr).unwrap();
+            let x_of = |id: &str| igr.get_node_by_id(id).unwrap().1.x;
+            (x_of("a"), x_of("q"), x_of("s"))
+        };
+
+        // Without minlen, a sits one rank before b, alongside s
+        let (a, q, s) = layout("");
+        assert_eq!(a, s);
+        assert_ne!(a, q);
+
+        // With minlen 3, a sits three ranks before b, alongside q
+        let (a, q, _) = layout("{ minlen: 3; }");
+        assert_eq!(a, q);
+
+        // Ranks skipped by minlen still take up space
+        let span = |input: &str| {
+            let igr = crate::parse_and_layout(input, "dagre").unwrap();
+            igr.get_node_by_id("b").unwrap().1.x - igr.get_node_by_id("a").unwrap().1.x
+        };
+        assert!(span("a[A]\nb[B]\na -> b { minlen: 3; }") > span("a[A]\nb[B]\na -> b"));
+    }
+
+    #[test]
+    fn test_edge_minlen_must_be_positive() {
+        for min_len in ["0", "101", "3000000000"] {
+            let input = format!("a[A]\nb[B]\na -> b {{ minlen: {min_len}; }}");
+            let document = crate::parser::parse_edsl(&input).unwrap();
+            assert!(IntermediateGraph::from_ast(document).is_err(), "{min_len}");
+        }
+        let document = crate::parser::parse_edsl("a[A]\nb[B]\na -> b { minlen: 100; }").unwrap();
+        assert!(IntermediateGraph::from_ast(document).is_ok());
+    }
+
+    #[test]
+    fn test_force_edge_weight_pulls_endpoints_closer() {
+        let spacing = |edge_style: &str| {
+            let input = format!("a[A]\nb[B]\nc[C]\nd[D]\na -> b {edge_style}\nb -> c\nc -> d\n");
+            let igr = crate::parse_and_layout(&input, "force").unwrap();
+            let (_, a) = igr.get_node_by_id("a").unwrap();
+            let (_, b) = igr.get_node_by_id("b").unwrap();
+            ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+        };
+
+        assert!(spacing("{ weight: 5; }") < spacing(""));
+    }
+
+    #[test]
+    fn test_edge_weight_must_be_positive() {
+        let document = crate::parser::parse_edsl("a[A]\nb[B]\na -> b { weight: 0; }").unwrap();
+        assert!(IntermediateGraph::from_ast(document).is_err());
+    }
+
+    #[test]
+    fn test_elk_force_layout_is_seeded() {
+        let input = r#"
+a[A]
+b[B]
+c[C]
+d[D]
+a -> b -> c -> a
+c -> d
+"#;
+        let run = |seed: u64| {
+            let document = crate::parser::parse_edsl(input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            let engine = ElkLayout::with_options(ElkLayoutOptions {
+                algorithm: ElkAlgorithm::Force,
+                seed,
+                ..Default::default()
+            });
+            engine.layout(&mut igr).unwrap();
+            igr.graph
+                .node_weights()
+                .map(|n| (n.id.clone(), n.x, n.y))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn test_layout_iterations_from_config() {
+        let mut input: String = (0..30).map(|i| format!("n{i}[Node {i}]\n")).collect();
+        input.extend((1..30).map(|i| format!("n{} -> n{i}\n", i / 2)));
+        let document = crate::parser::parse_edsl(&input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+
+        let run = |force: Option<usize>, stress: Option<usize>| {
+            let mut igr = igr.clone();
+            igr.global_config.force_iterations = force;
+            igr.global_config.stress_iterations = stress;
+
+            let force_engine = ForceLayout::new();
+            force_engine.layout(&mut igr).unwrap();
+
+            let stress_engine = ElkLayout::with_options(ElkLayoutOptions {
+                algorithm: ElkAlgorithm::Stress,
+                ..Default::default()
+            });
+            stress_engine.layout(&mut igr).unwrap();
+            (
+                force_engine.iterations_run(),
+                stress_engine.iterations_run(),
+            )
+        };
+
+        assert_eq!(run(Some(2), Some(3)), (2, 3));
+        assert_eq!(run(Some(400), None), (400, 200));
+
+        // Out-of-range counts are capped when run and rejected by validation
+        let document = crate::parser::parse_edsl("a[A]\nb[B]\na -> b").unwrap();
+        let mut small = IntermediateGraph::from_ast(document).unwrap();
+        small.global_config.force_iterations = Some(1_000_000_000);
+        let engine = ForceLayout::new();
+        engine.layout(&mut small).unwrap();
+        assert_eq!(engine.iterations_run(), crate::ast::MAX_LAYOUT_ITERATIONS);
+        assert!(crate::ast::GlobalConfig::builder()
+            .force_iterations(0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_force_packs_disconnected_components() {
+        let input =
+            "a1\na2\na3\nb1\nb2\nb3\na1 -> a2\na2 -> a3\na3 -> a1\nb1 -> b2\nb2 -> b3\nb3 -> b1\n";
+        let igr = crate::parse_and_layout(input, "force").unwrap();
+
+        let bounds = |prefix: char| {
+            igr.graph
+                .node_weights()
+                .filter(|node| node.id.starts_with(prefix))
+                .fold(
+                    (
+                        f64::INFINITY,
+                        f64::INFINITY,
+                        f64::NEG_INFINITY,
+                        f64::NEG_INFINITY,
+                    ),
+                    |(x0, y0, x1, y1), node| {
+                        (
+                            x0.min(node.x - node.width / 2.0),
+                            y0.min(node.y - node.height / 2.0),
+                            x1.max(node.x + node.width / 2.0),
+                            y1.max(node.y + node.height / 2.0),
+                        )
+                    },
+                )
+        };
+        let (a, b) = (bounds('a'), bounds('b'));
+        let overlap = a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3;
+        assert!(!overlap, "{a:?} overlaps {b:?}");
+    }
+
+    #[test]
+    fn test_force_scc_seeding_separates_clusters() {
+        use crate::ast::ForceSeed;
+
+        // Two 4-node cycles joined by one edge, declared interleaved so the
+        // plain circle mixes them
+        let mut input: String = (0..4).map(|i| format!("a{i}\nb{i}\n")).collect();
+        input.extend(
+            (0..4).map(|i| format!("a{i} -> a{}\nb{i} -> b{}\n", (i + 1) % 4, (i + 1) % 4)),
+        );
+        input.push_str("a0 -> b0\n");
+        let document = crate::parser::parse_edsl(&input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+
+        // Distance between cluster centroids over the widest cluster spread
+        let separation = |seed: ForceSeed, iterations: usize| {
+            let mut igr = igr.clone();
+            igr.global_config.seed = Some(seed);
+            igr.global_config.force_iterations = Some(iterations);
+            ForceLayout::new().layout(&mut igr).unwrap();
+
+            let cluster = |prefix: char| {
+                let points: Vec<(f64, f64)> = igr
+                    .graph
+                    .node_weights()
+                    .filter(|node| node.id.starts_with(prefix))
+                    .map(|node| (node.x, node.y))
+                    .collect();
+                let n = points.len() as f64;
+                let centroid = (
+                    points.iter().map(|p| p.0).sum::<f64>() / n,
+                    points.iter().map(|p| p.1).sum::<f64>() / n,
+                );
+                let spread = points
+                    .iter()
+                    .map(|p| (p.0 - centroid.0).hypot(p.1 - centroid.1))
+                    .fold(0.0, f64::max);
+                (centroid, spread)
+            };
+            let ((ca, spread_a), (cb, spread_b)) = (cluster('a'), cluster('b'));
+            (ca.0 - cb.0).hypot(ca.1 - cb.1) / spread_a.max(spread_b)
+        };
+
+        // Fewest simulation steps after which the clusters are clearly apart
+        let steps_to_separate = |seed: ForceSeed| {
+            [1, 2, 5, 10, 20, 50, 100, 200, 400]
+                .into_iter()
+                .find(|&iterations| separation(seed, iterations) > 2.0)
+        };
+        let scc = steps_to_separate(ForceSeed::Scc).expect("SCC seeding separates clusters");
+        let circle = steps_to_separate(ForceSeed::Circle).unwrap_or(usize::MAX);
+        assert!(scc < circle, "scc needed {scc} steps, circle {circle}");
+        assert!(separation(ForceSeed::Scc, 200) > 2.0);
+    }
+
+    #[test]
+    fn test_container_aspect_ratio() {
+        let input = r#"
+container "Row" as row {
+  style: { aspect: 1:1; }
+  a[Alpha]
+  b[Beta]
+  c[Gamma]
+  d[Delta]
+}
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        assert_eq!(igr.containers[0].attributes.aspect_ratio, Some(1.0));
+
+        let engines: Vec<Box<dyn LayoutEngine>> =
+            vec![Box::new(DagreLayout::new()), Box::new(ElkLayout::new())];
+        for engine in engines {
+            let mut igr = igr.clone();
+            engine.layout(&mut igr).unwrap();
+
+            let bounds = igr.containers[0].bounds.clone().unwrap();
+            assert!((bounds.width - bounds.height).abs() < 1e-6);
+            for node in igr.graph.node_weights() {
+                if node.is_virtual_container {
+                    continue;
+                }
+                assert!(bounds.x <= node.x - node.width / 2.0 + 1e-6);
+                assert!(bounds.y <= node.y - node.height / 2.0 + 1e-6);
+                assert!(node.x + node.width / 2.0 <= bounds.x + bounds.width + 1e-6);
+                assert!(node.y + node.height / 2.0 <= bounds.y + bounds.height + 1e-6);
+            }
+        }
+
+        let document =
+            crate::parser::parse_edsl("container \"Bad\" {\n  style: { aspect: 0:1; }\n  a[A]\n}")
+                .unwrap();
+        assert!(IntermediateGraph::from_ast(document).is_err());
+    }
+
+    #[test]
+    fn test_elk_tree_edges_attach_along_direction() {
+        use crate::ast::PortSide;
+        use petgraph::visit::EdgeRef;
+
+        let input = r#"
+root[Root]
+left[Left]
+right[Right]
+leaf[Leaf]
+root -> left
+root -> right
+left -> leaf
+right.left -> leaf
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+
+        let layout = |direction: ElkDirection| {
+            let mut igr = igr.clone();
+            ElkLayout::with_options(ElkLayoutOptions {
+                algorithm: ElkAlgorithm::Tree,
+                direction,
+                ..Default::default()
+            })
+            .layout(&mut igr)
+            .unwrap();
+            igr
+        };
+
+        let down = layout(ElkDirection::Down);
+        for edge in down.graph.edge_references() {
+            let (source, target) = (&down.graph[edge.source()], &down.graph[edge.target()]);
+            assert!(source.y < target.y, "{} above {}", source.id, target.id);
+            let ports = (
+                edge.weight().attributes.start_port,
+                edge.weight().attributes.end_port,
+            );
+            if source.id == "right" {
+                // Explicit ports are kept
+                assert_eq!(ports, (Some(PortSide::Left), Some(PortSide::Top)));
+            } else {
+                assert_eq!(ports, (Some(PortSide::Bottom), Some(PortSide::Top)));
+            }
+        }
+
+        // Arrows leave the parent's bottom center and enter the child's top center
+        let elements = crate::generator::ExcalidrawGenerator::generate(&down).unwrap();
+        let root = &down.graph[down.node_map["root"]];
+        let left = &down.graph[down.node_map["left"]];
+        let arrow = elements
+            .iter()
+            .find(|e| {
+                e.r#type == "arrow"
+                    && (e.x - root.x.round() as i32).abs() <= 1
+                    && (e.y - (root.y + root.height / 2.0).round() as i32).abs() <= 1
+                    && (e.x + e.width - left.x.round() as i32).abs() <= 1
+            })
+            .expect("root -> left attaches bottom to top");
+        assert!((arrow.y + arrow.height - (left.y - left.height / 2.0).round() as i32).abs() <= 1);
+
+        let right = layout(ElkDirection::Right);
+        for edge in right.graph.edge_references() {
+            let (source, target) = (&right.graph[edge.source()], &right.graph[edge.target()]);
+            assert!(source.x < target.x, "{} left of {}", source.id, target.id);
+        }
+        let edge = right.graph.edge_weights().next().unwrap();
+        assert_eq!(edge.attributes.start_port, Some(PortSide::Right));
+        assert_eq!(edge.attributes.end_port, Some(PortSide::Left));
+
+        // Mirrored trees stay clear of negative coordinates
+        let up = layout(ElkDirection::Up);
+        let left_tree = layout(ElkDirection::Left);
+        for (laid_out, reference) in [(&up, &down), (&left_tree, &right)] {
+            let a = laid_out.bounding_box();
+            let b = reference.bounding_box();
+            assert!(a.x > -1e-6 && a.y > -1e-6, "{a:?}");
+            assert!(
+                (a.width - b.width).abs() < 1e-6 && (a.height - b.height).abs() < 1e-6,
+                "{a:?} vs {b:?}"
+            );
+        }
+        for edge in up.graph.edge_references() {
+            assert!(up.graph[edge.source()].y > up.graph[edge.target()].y);
+        }
+
+        // Cached layouts restore the chosen ports along with positions
+        let mut cached = igr.clone();
+        CachedLayout::from_igr(&right).apply(&mut cached);
+        let edge = cached.graph.edge_weights().next().unwrap();
+        assert_eq!(edge.attributes.start_port, Some(PortSide::Right));
+    }
+
+    #[test]
+    fn test_elk_container_direction() {
+        let input = r#"
+container "Services" as services {
+  a[A]
+  b[B]
+  c[C]
+  a -> b
+  b -> c
+}
+container "Stack" as stack {
+  style: { direction: down; }
+  x[X]
+  y[Y]
+  z[Z]
+  x -> y
+  y -> z
+}
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        ElkLayout::with_options(ElkLayoutOptions {
+            direction: ElkDirection::Right,
+            ..Default::default()
+        })
+        .layout(&mut igr)
+        .unwrap();
+
+        let position = |id: &str| {
+            let node = igr.graph.node_weights().find(|n| n.id == id).unwrap();
+            (node.x, node.y)
+        };
+        // Services inherits the global direction and flows left to right
+        let (a, b, c) = (position("a"), position("b"), position("c"));
+        assert!(a.0 < b.0 && b.0 < c.0);
+        assert!(a.1 == b.1 && b.1 == c.1);
+        // Stack overrides it and flows top to bottom
+        let (x, y, z) = (position("x"), position("y"), position("z"));
+        assert!(x.1 < y.1 && y.1 < z.1);
+        assert!(x.0 == y.0 && y.0 == z.0);
+
+        let document = crate::parser::parse_edsl(
+            "container \"Bad\" {\n  style: { direction: sideways; }\n  a[A]\n}\n",
+        )
+        .unwrap();
+        assert!(IntermediateGraph::from_ast(document).is_err());
+    }
+
+    #[test]
+    fn test_elk_shelf_packing_is_tighter_than_grid() {
+        let input = r#"
+container "Pipeline" as pipeline {
+  a[A]
+  b[B]
+  c[C]
+  d[D]
+  e[E]
+  a -> b -> c -> d -> e
+}
+container "Cache" as cache {
+  x[X]
+}
+container "Queue" as queue {
+  q1[Q1]
+  q2[Q2]
+  q1 -> q2
+}
+"#;
+        let layout = |packing: ContainerPacking| {
+            let document = crate::parser::parse_edsl(input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            ElkLayout::with_options(ElkLayoutOptions {
+                container_packing: packing,
+                ..Default::default()
+            })
+            .layout(&mut igr)
+            .unwrap();
+            igr.containers
+                .iter()
+                .map(|c| c.bounds.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let area = |boxes: &[BoundingBox]| {
+            let min_x = boxes.iter().map(|b| b.x).fold(f64::INFINITY, f64::min);
+            let min_y = boxes.iter().map(|b| b.y).fold(f64::INFINITY, f64::min);
+            let max_x = boxes.iter().map(|b| b.x + b.width).fold(f64::MIN, f64::max);
+            let max_y = boxes
+                .iter()
+                .map(|b| b.y + b.height)
+                .fold(f64::MIN, f64::max);
+            (max_x - min_x) * (max_y - min_y)
+        };
+
+        let shelf = layout(ContainerPacking::Shelf);
+        for (i, a) in shelf.iter().enumerate() {
+            for b in &shelf[i + 1..] {
+                let overlap = a.x < b.x + b.width
+                    && b.x < a.x + a.width
+                    && a.y < b.y + b.height
+                    && b.y < a.y + a.height;
+                assert!(!overlap, "{a:?} overlaps {b:?}");
+            }
+        }
+        let grid = layout(ContainerPacking::Grid);
+        assert!(
+            area(&shelf) < area(&grid),
+            "{} vs {}",
+            area(&shelf),
+            area(&grid)
+        );
+    }
+
+    #[test]
+    fn test_dagre_lanes_keep_members_in_band() {
+        let input = r#"
+lane "Dev" as dev {
+  code[Write code]
+  review[Review]
+}
+lane "QA" as qa {
+  test[Test]
+  release[Release]
+}
+code -> test
+test -> review
+review -> release
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        assert!(igr.containers.iter().all(|c| c.is_lane));
+
+        // Left-to-right flow, so lanes are horizontal bands
+        let band = |ids: &[&str]| {
+            ids.iter().map(|id| igr.get_node_by_id(id).unwrap().1).fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(top, bottom), n| {
+                    (
+                        top.min(n.y - n.height / 2.0),
+                        bottom.max(n.y + n.height / 2.0),
+                    )
+                },
+            )
+        };
+        let dev = band(&["code", "review"]);
+        let qa = band(&["test", "release"]);
+        assert!(dev.1 < qa.0, "{dev:?} and {qa:?} overlap");
+
+        let dev_bounds = igr.containers[0].bounds.as_ref().unwrap();
+        let qa_bounds = igr.containers[1].bounds.as_ref().unwrap();
+        assert!(dev_bounds.y + dev_bounds.height <= qa_bounds.y + 1e-6);
+        assert_eq!(
+            (dev_bounds.x, dev_bounds.width),
+            (qa_bounds.x, qa_bounds.width)
+        );
+    }
+
+    #[test]
+    fn test_dagre_compact_layers() {
+        let plain = r#"
+a[Start]
+b[Middle]
+c[End]
+d[Side]
+a -> b -> c
+a -> d
+"#;
+        // Groups take a separate layout path
+        let grouped = r#"
+group "Team" {
+  a[Start]
+  b[Middle]
+  c[End]
+}
+d[Side]
+a -> b -> c
+a -> d
+"#;
+        let layout_extent = |input: &str, compact: bool| {
+            let mut document = crate::parser::parse_edsl(input).unwrap();
+            document.config.compact_layers = Some(compact);
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            DagreLayout::new().layout(&mut igr).unwrap();
+
+            let nodes: Vec<_> = igr.graph.node_weights().cloned().collect();
+            for (i, first) in nodes.iter().enumerate() {
+                for second in nodes.iter().skip(i + 1) {
+                    let overlap_x = (first.x - second.x).abs() < (first.width + second.width) / 2.0;
+                    let overlap_y =
+                        (first.y - second.y).abs() < (first.height + second.height) / 2.0;
+                    assert!(
+                        !(overlap_x && overlap_y),
+                        "{} overlaps {}",
+                        first.id,
+                        second.id
+                    );
+                }
+            }
+
+            let min_x = nodes
+                .iter()
+                .map(|n| n.x - n.width / 2.0)
+                .fold(f64::INFINITY, f64::min);
+            let max_x = nodes
+                .iter()
+                .map(|n| n.x + n.width / 2.0)
+                .fold(f64::NEG_INFINITY, f64::max);
+            max_x - min_x
+        };
+
+        // Default direction is left-right, so the rank axis is x
+        for input in [plain, grouped] {
+            assert!(layout_extent(input, true) < layout_extent(input, false));
+        }
+    }
+
+    #[test]
+    fn test_dagre_places_isolated_nodes_apart() {
+        let input = "a\nb\nc\nx1\nx2\nx3\nx4\nx5\na -> b\nb -> c\n";
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+
+        let nodes: Vec<_> = igr.graph.node_weights().cloned().collect();
+        for (i, first) in nodes.iter().enumerate() {
+            for second in nodes.iter().skip(i + 1) {
+                let overlap_x = (first.x - second.x).abs() < (first.width + second.width) / 2.0;
+                let overlap_y = (first.y - second.y).abs() < (first.height + second.height) / 2.0;
+                assert!(
+                    !(overlap_x && overlap_y),
+                    "{} overlaps {}",
+                    first.id,
+                    second.id
+                );
+            }
+        }
+
+        // Left-right flow: the isolated nodes sit past the end of the chain
+        // rather than in the sink's layer
+        let node = |id: &str| igr.get_node_by_id(id).unwrap().1;
+        let chain_end = node("c").x + node("c").width / 2.0;
+        for id in ["x1", "x2", "x3", "x4", "x5"] {
+            assert!(node(id).x - node(id).width / 2.0 > chain_end, "{id}");
+        }
+    }
+
+    #[test]
+    fn test_dagre_isolated_grid_wraps_to_max_width() {
+        let input = "a\nb\nx1\nx2\nx3\nx4\nx5\nx6\nx7\nx8\nx9\na -> b\n";
+        let rows = |context: &LayoutContext| {
+            let document = crate::parser::parse_edsl(input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            DagreLayout::new()
+                .layout_with_context(&mut igr, context)
+                .unwrap();
+            let mut rows: Vec<i64> = igr
+                .graph
+                .node_weights()
+                .filter(|node| node.id.starts_with('x'))
+                .map(|node| node.y.round() as i64)
+                .collect();
+            rows.sort_unstable();
+            rows.dedup();
+            (rows.len(), igr)
+        };
+
+        let (unbounded, igr) = rows(&LayoutContext::default());
+        assert_eq!(unbounded, 3);
+
+        // Room for two of the isolated nodes side by side
+        let width = igr.get_node_by_id("x1").unwrap().1.width;
+        let max_width = 2.0 * width + DagreLayoutOptions::default().node_sep;
+        let context = LayoutContext {
+            max_width: Some(max_width),
+            ..Default::default()
+        };
+        let (wrapped, _) = rows(&context);
+        assert_eq!(wrapped, 5);
+
+        // A canvas in the front matter bounds the layout area
+        let config = GlobalConfig {
+            canvas: Some(CanvasSize::new(800.0, 400.0).unwrap()),
+            ..Default::default()
+        };
+        let context = LayoutContext::from_config(&config);
+        assert_eq!(context.max_width, Some(800.0));
+        assert_eq!(context.max_height, Some(400.0));
+        assert_eq!(context.aspect_ratio, Some(2.0));
+    }
+
+    #[test]
+    fn test_dagre_lays_out_large_dag() {
+        // 26 layers of 20 nodes, each feeding two nodes of the next layer;
+        // past the 500 node threshold for fewer crossing reduction passes
+        let mut input = String::new();
+        for layer in 0..26 {
+            for i in 0..20 {
+                input.push_str(&format!("n{layer}_{i}\n"));
+            }
+        }
+        for layer in 0..25 {
+            for i in 0..20 {
+                input.push_str(&format!("n{layer}_{i} -> n{}_{}\n", layer + 1, i));
+                input.push_str(&format!(
+                    "n{layer}_{i} -> n{}_{}\n",
+                    layer + 1,
+                    (i * 7 + 3) % 20
+                ));
+            }
+        }
+
+        let document = crate::parser::parse_edsl(&input).unwrap();
+        let graph = IntermediateGraph::from_ast(document).unwrap();
+        assert_eq!(graph.graph.node_count(), 520);
+
+        // Above the threshold the pass limit applies; this graph settles
+        // after two passes otherwise
+        let passes = |large_graph_threshold| {
+            let engine = DagreLayout::with_options(DagreLayoutOptions {
+                large_graph_threshold,
+                large_graph_passes: 1,
+                ..Default::default()
+            });
+            engine.layout(&mut graph.clone()).unwrap();
+            engine.crossing_passes_run()
+        };
+        assert_eq!(passes(500), 1);
+        assert_eq!(passes(1000), 2);
+
+        let started = std::time::Instant::now();
+        let mut igr = graph;
+        DagreLayout::new().layout(&mut igr).unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(30));
+
+        let nodes: Vec<_> = igr.graph.node_weights().collect();
+        for (i, first) in nodes.iter().enumerate() {
+            for second in nodes.iter().skip(i + 1) {
+                let overlap_x = (first.x - second.x).abs() < (first.width + second.width) / 2.0;
+                let overlap_y = (first.y - second.y).abs() < (first.height + second.height) / 2.0;
+                assert!(
+                    !(overlap_x && overlap_y),
+                    "{} overlaps {}",
+                    first.id,
+                    second.id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce_crossings_after_force_layout() {
+        let document = crate::parser::parse_edsl("a\nb\nc\nd\na -> b\nc -> d\na -> c\n").unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        // a-b and c-d are the crossing diagonals of a square
+        for (id, x, y) in [
+            ("a", 0.0, 0.0),
+            ("b", 100.0, 100.0),
+            ("c", 100.0, 0.0),
+            ("d", 0.0, 100.0),
+        ] {
+            let (idx, _) = igr.get_node_by_id(id).unwrap();
+            igr.graph[idx].x = x;
+            igr.graph[idx].y = y;
+        }
+        assert_eq!(count_edge_crossings(&igr), 1);
+        assert_eq!(reduce_crossings(&mut igr, 5), 0);
+        assert_eq!(count_edge_crossings(&igr), 0);
+
+        // As a force layout option, the pass never adds crossings
+        let mut input: String = (0..12).map(|i| format!("n{i}\n")).collect();
+        for i in 0..12 {
+            input.push_str(&format!(
+                "n{i} -> n{}\nn{i} -> n{}\n",
+                (i + 1) % 12,
+                (i + 5) % 12
+            ));
+        }
+        let crossings = |reduce: bool| {
+            let document = crate::parser::parse_edsl(&input).unwrap();
+            let mut igr = IntermediateGraph::from_ast(document).unwrap();
+            igr.global_config.reduce_crossings = Some(reduce);
+            ForceLayout::new().layout(&mut igr).unwrap();
+            count_edge_crossings(&igr)
+        };
+        assert!(crossings(true) <= crossings(false));
+    }
+
+    #[test]
+    fn test_reduce_crossings_on_a_large_graph() {
+        // 300 nodes and 600 edges; trying every pair of nodes each round
+        // took minutes here
+        let mut input: String = (0..300).map(|i| format!("n{i}\n")).collect();
+        for i in 0..300 {
+            input.push_str(&format!(
+                "n{i} -> n{}\nn{i} -> n{}\n",
+                (i + 1) % 300,
+                (i * 7 + 3) % 300
+            ));
+        }
+        let document = crate::parser::parse_edsl(&input).unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        ForceLayout::new().layout(&mut igr).unwrap();
+        let before = count_edge_crossings(&igr);
+        assert!(reduce_crossings(&mut igr, 10) <= before);
+    }
+
+    #[test]
+    fn test_reduce_crossings_skips_swaps_that_overlap() {
+        let document =
+            crate::parser::parse_edsl("a\nb\nc\nd\ne\nf\na -> b\nc -> d\nb -> c\n").unwrap();
+        let mut igr = IntermediateGraph::from_ast(document).unwrap();
+        // a-b and c-d cross. a and b are wide, so moving either to c's or
+        // d's corner would hit the unconnected blockers e and f, while
+        // swapping the narrow c and d can't uncross the edges
+        for (id, x, y, width) in [
+            ("a", 0.0, 0.0, 250.0),
+            ("b", 300.0, 300.0, 250.0),
+            ("c", 300.0, 0.0, 10.0),
+            ("d", 0.0, 300.0, 10.0),
+            ("e", 400.0, 0.0, 10.0),
+            ("f", -100.0, 300.0, 10.0),
+        ] {
+            let (idx, _) = igr.get_node_by_id(id).unwrap();
+            let node = &mut igr.graph[idx];
+            (node.x, node.y, node.width, node.height) = (x, y, width, 10.0);
+        }
+        assert_eq!(crate::layout::benchmark::count_node_overlaps(&igr), 0);
+
+        assert_eq!(reduce_crossings(&mut igr, 5), 1);
+        assert_eq!(crate::layout::benchmark::count_node_overlaps(&igr), 0);
+    }
 }
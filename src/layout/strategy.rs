@@ -1,4 +1,5 @@
 // src/layout/strategy.rs
+use crate::ast::GlobalConfig;
 use crate::error::Result;
 use crate::igr::IntermediateGraph;
 use std::sync::Arc;
@@ -10,6 +11,10 @@ pub struct LayoutContext {
     pub max_width: Option<f64>,
     /// Maximum height for the layout area
     pub max_height: Option<f64>,
+    /// Width / height the layout area should approach
+    pub aspect_ratio: Option<f64>,
+    /// Longest the layout may run before failing with `LayoutError::Timeout`
+    pub timeout: Option<std::time::Duration>,
     /// Node spacing preferences
     pub node_spacing: f64,
     /// Edge spacing preferences
@@ -25,6 +30,8 @@ impl Default for LayoutContext {
         Self {
             max_width: None,
             max_height: None,
+            aspect_ratio: None,
+            timeout: None,
             node_spacing: 100.0,
             edge_spacing: 50.0,
             optimize_readability: true,
@@ -33,6 +40,59 @@ impl Default for LayoutContext {
     }
 }
 
+impl LayoutContext {
+    /// Context for a document's configuration: a `canvas` bounds the layout
+    /// area and sets its aspect ratio, in layout units before `scale` zooms
+    /// the result
+    pub fn from_config(config: &GlobalConfig) -> Self {
+        let mut context = Self::default();
+        if let Some(canvas) = config.canvas {
+            let scale = config.scale.filter(|s| s.is_finite() && *s > 0.0);
+            let scale = scale.unwrap_or(1.0);
+            context.max_width = Some(canvas.width / scale);
+            context.max_height = Some(canvas.height / scale);
+            context.aspect_ratio = Some(canvas.width / canvas.height);
+        }
+        context
+    }
+
+    /// Columns for a grid of `count` cells of up to `cell_width` by
+    /// `cell_height`, `spacing` apart.
+    ///
+    /// Without constraints this is `ceil(sqrt(count))`, or the column count
+    /// whose shape comes closest to `aspect_ratio`. Enough columns are then
+    /// added for the rows to fit `max_height`, and columns are dropped to fit
+    /// `max_width`, which wins when both can't be met. Always at least one.
+    pub fn grid_columns(
+        &self,
+        count: usize,
+        cell_width: f64,
+        cell_height: f64,
+        spacing: f64,
+    ) -> usize {
+        if count == 0 {
+            return 1;
+        }
+        let step_x = (cell_width + spacing).max(f64::EPSILON);
+        let step_y = (cell_height + spacing).max(f64::EPSILON);
+
+        let mut columns = match self.aspect_ratio {
+            Some(ratio) if ratio.is_finite() && ratio > 0.0 => {
+                (count as f64 * ratio * step_y / step_x).sqrt().round() as usize
+            }
+            _ => (count as f64).sqrt().ceil() as usize,
+        };
+        if let Some(max_height) = self.max_height {
+            let rows = (((max_height + spacing) / step_y).floor() as usize).max(1);
+            columns = columns.max(count.div_ceil(rows));
+        }
+        if let Some(max_width) = self.max_width {
+            columns = columns.min(((max_width + spacing) / step_x).floor() as usize);
+        }
+        columns.clamp(1, count)
+    }
+}
+
 /// Strategy trait for different layout algorithms
 pub trait LayoutStrategy: Send + Sync {
     /// Apply the layout strategy to the graph
@@ -1,15 +1,23 @@
 // src/lib.rs
 
 pub mod ast;
+pub mod dot;
+pub mod edsl;
 pub mod error;
 pub mod fluent;
 pub mod generator;
+pub mod graphml;
 pub mod igr;
 pub mod layout;
+pub mod lint;
+mod output_cache;
 pub mod parser;
 pub mod presets;
 #[cfg(feature = "routing")]
 pub mod routing;
+mod stable_hash;
+pub mod svg;
+pub mod tikz;
 
 #[cfg(feature = "templates")]
 pub mod template;
@@ -20,6 +28,9 @@ pub mod llm;
 #[cfg(feature = "server")]
 pub mod server;
 
+#[cfg(feature = "mermaid")]
+pub mod mermaid;
+
 #[cfg(feature = "ml-layout")]
 pub mod training;
 
@@ -32,19 +43,97 @@ pub use presets::{DiagramPresets, ThemePresets};
 
 use crate::generator::ExcalidrawGenerator;
 use crate::igr::IntermediateGraph;
-use crate::layout::LayoutManager;
-use crate::parser::parse_edsl;
+use crate::layout::{Direction, LayoutManager};
+use crate::output_cache::OutputCache;
+use crate::parser::{parse_edsl, parse_edsl_with_limits, ParseLimits};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "templates")]
 use crate::template::TemplateProcessor;
 
+/// Parse EDSL source and lay it out with the named engine, without generating output.
+///
+/// The `layout` argument overrides any `layout` set in the source's front matter.
+/// This is the entry point for custom renderers that consume positioned nodes
+/// directly.
+///
+/// # Example
+///
+/// ```rust
+/// let igr = excalidraw_dsl::parse_and_layout("a[A]\nb[B]\na -> b", "dagre").unwrap();
+/// assert_eq!(igr.graph.node_count(), 2);
+/// ```
+pub fn parse_and_layout(src: &str, layout: &str) -> Result<IntermediateGraph> {
+    let parsed_doc = parse_edsl(src)?;
+    let mut igr = IntermediateGraph::from_ast(parsed_doc)?;
+    igr.global_config.layout = Some(layout.to_string());
+
+    let mut layout_manager = LayoutManager::new();
+    layout_manager.enable_cache(false);
+    layout_manager.layout(&mut igr)?;
+
+    Ok(igr)
+}
+
+/// Wall-clock time spent in each compilation phase
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompileStats {
+    /// Parsing, including template expansion
+    pub parse: Duration,
+    /// Building the intermediate graph
+    pub build: Duration,
+    /// Layout, including LLM optimization when enabled
+    pub layout: Duration,
+    /// Element generation and JSON serialization
+    pub generate: Duration,
+    /// Whether the output came from the compiled-output cache, skipping
+    /// every phase
+    pub cached: bool,
+    /// Nodes in the compiled graph, not counting container placeholders
+    pub nodes: usize,
+    /// Edges in the compiled graph
+    pub edges: usize,
+}
+
+impl CompileStats {
+    /// The phases in pipeline order, paired with their names
+    pub fn phases(&self) -> [(&'static str, Duration); 4] {
+        [
+            ("parse", self.parse),
+            ("build", self.build),
+            ("layout", self.layout),
+            ("generate", self.generate),
+        ]
+    }
+
+    /// Total time across all phases
+    pub fn total(&self) -> Duration {
+        self.parse + self.build + self.layout + self.generate
+    }
+}
+
+/// One Excalidraw document per top-level container, from
+/// [`EDSLCompiler::compile_split`]
+#[derive(Debug, Clone, Default)]
+pub struct SplitOutput {
+    /// `(file stem, Excalidraw JSON)` for each top-level container, named
+    /// after the container's id or label
+    pub containers: Vec<(String, String)>,
+    /// Overview with one node per top-level container and the connections
+    /// between them
+    pub index: String,
+}
+
+/// File stem used for the overview written alongside split containers
+pub const SPLIT_INDEX_NAME: &str = "index";
+
 /// The main EDSL compiler that orchestrates parsing, layout, and generation
 pub struct EDSLCompiler {
     layout_manager: LayoutManager,
     #[cfg(feature = "llm")]
     llm_optimizer: Option<llm::LLMLayoutOptimizer>,
-    /// Whether to validate output after generation
-    #[allow(dead_code)]
+    /// Whether to run `validate_excalidraw` on every generated file
     validate_output: bool,
     /// Whether to use parallel processing for layout calculations
     #[allow(dead_code)]
@@ -52,6 +141,26 @@ pub struct EDSLCompiler {
     /// Maximum number of threads for parallel operations
     #[allow(dead_code)]
     max_threads: Option<usize>,
+    /// Maximum number of elements a compiled diagram may contain
+    max_elements: usize,
+    /// Size limits applied when parsing source documents
+    parse_limits: ParseLimits,
+    /// Theme that supersedes the document's `theme:` front matter
+    theme: Option<String>,
+    /// Derive element ids from DSL ids, superseding `stable_ids:`
+    stable_ids: Option<bool>,
+    /// Seed for generated randomness, superseding `random_seed:`
+    seed: Option<u64>,
+    /// Named preset filling in settings the front matter leaves unset
+    preset: Option<String>,
+    /// File `version`, superseding `file_version:`
+    file_version: Option<u32>,
+    /// File `source`, superseding `file_source:`
+    file_source: Option<String>,
+    /// Compiled JSON of recent sources, when enabled on the builder
+    output_cache: Option<OutputCache<(String, CompileStats)>>,
+    /// Compile from scratch even when the output cache holds the source
+    bypass_output_cache: bool,
 }
 
 /// Builder for creating customized EDSLCompiler instances
@@ -62,7 +171,20 @@ pub struct EDSLCompilerBuilder {
     validate_output: bool,
     parallel_layout: bool,
     max_threads: Option<usize>,
-    cache_enabled: bool,
+    cache_enabled: Option<bool>,
+    max_elements: usize,
+    layout_seed: Option<u64>,
+    layout_timeout: Option<std::time::Duration>,
+    layout_direction: Option<Direction>,
+    layout_cache_dir: Option<std::path::PathBuf>,
+    parse_limits: ParseLimits,
+    theme: Option<String>,
+    stable_ids: Option<bool>,
+    seed: Option<u64>,
+    preset: Option<String>,
+    file_version: Option<u32>,
+    file_source: Option<String>,
+    output_cache_capacity: usize,
 }
 
 impl Default for EDSLCompilerBuilder {
@@ -74,7 +196,20 @@ impl Default for EDSLCompilerBuilder {
             validate_output: false,
             parallel_layout: true,
             max_threads: None,
-            cache_enabled: true,
+            cache_enabled: None,
+            max_elements: generator::DEFAULT_MAX_ELEMENTS,
+            layout_seed: None,
+            layout_timeout: None,
+            layout_direction: None,
+            layout_cache_dir: None,
+            parse_limits: ParseLimits::default(),
+            theme: None,
+            stable_ids: None,
+            seed: None,
+            preset: None,
+            file_version: None,
+            file_source: None,
+            output_cache_capacity: 0,
         }
     }
 }
@@ -86,12 +221,16 @@ impl EDSLCompilerBuilder {
     }
 
     /// Set a custom layout manager
+    ///
+    /// Engines registered on the manager are selected by name through the
+    /// `layout:` front-matter value, alongside the built-in engines.
     pub fn with_layout_manager(mut self, manager: LayoutManager) -> Self {
         self.layout_manager = Some(manager);
         self
     }
 
-    /// Enable output validation
+    /// Check every generated file with `validate_excalidraw`, failing the
+    /// compile if the compiler itself produced invalid output
     pub fn with_validation(mut self, enabled: bool) -> Self {
         self.validate_output = enabled;
         self
@@ -111,7 +250,92 @@ impl EDSLCompilerBuilder {
 
     /// Enable or disable layout caching
     pub fn with_cache(mut self, enabled: bool) -> Self {
-        self.cache_enabled = enabled;
+        self.cache_enabled = Some(enabled);
+        self
+    }
+
+    /// Persist computed layouts under `dir` so repeated runs can reuse them
+    pub fn with_layout_cache_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.layout_cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Seed the randomized layout engines so identical inputs give identical layouts
+    pub fn with_layout_seed(mut self, seed: u64) -> Self {
+        self.layout_seed = Some(seed);
+        self
+    }
+
+    /// Fail compilation with `LayoutError::Timeout` when the layout runs
+    /// longer than `timeout`
+    pub fn with_layout_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.layout_timeout = Some(timeout);
+        self
+    }
+
+    /// Make the dagre and elk engines flow in `direction` instead of left
+    /// to right
+    pub fn with_layout_direction(mut self, direction: Direction) -> Self {
+        self.layout_direction = Some(direction);
+        self
+    }
+
+    /// Set the maximum number of generated elements before compilation fails
+    pub fn with_max_elements(mut self, max_elements: usize) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+
+    /// Override the parser's node, edge, container and input size limits
+    pub fn with_parse_limits(mut self, limits: ParseLimits) -> Self {
+        self.parse_limits = limits;
+        self
+    }
+
+    /// Override the document's theme ("light" or "dark")
+    pub fn with_theme(mut self, theme: impl Into<String>) -> Self {
+        self.theme = Some(theme.into());
+        self
+    }
+
+    /// Derive element ids from DSL ids so recompiles produce identical ids
+    pub fn with_stable_ids(mut self, enabled: bool) -> Self {
+        self.stable_ids = Some(enabled);
+        self
+    }
+
+    /// Make compilation fully reproducible: seeds the layout engines (unless
+    /// `with_layout_seed` is also given), element ids and Excalidraw's
+    /// per-element randomness, so equal seeds give identical output
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Apply a named preset (see `DiagramPresets::names`) to every document;
+    /// its front matter still wins for the settings it sets
+    pub fn with_preset(mut self, name: impl Into<String>) -> Self {
+        self.preset = Some(name.into());
+        self
+    }
+
+    /// Set the `version` recorded in generated Excalidraw files (default 2)
+    pub fn with_file_version(mut self, version: u32) -> Self {
+        self.file_version = Some(version);
+        self
+    }
+
+    /// Set the `source` recorded in generated Excalidraw files, e.g. the
+    /// URL of the product embedding the diagrams
+    pub fn with_file_source(mut self, source: impl Into<String>) -> Self {
+        self.file_source = Some(source.into());
+        self
+    }
+
+    /// Keep the compiled JSON of up to `capacity` recent sources, so
+    /// recompiling one skips the whole pipeline (0 disables the cache)
+    pub fn with_output_cache(mut self, capacity: usize) -> Self {
+        self.output_cache_capacity = capacity;
         self
     }
 
@@ -125,7 +349,21 @@ impl EDSLCompilerBuilder {
     /// Build the EDSLCompiler instance
     pub fn build(self) -> EDSLCompiler {
         let mut layout_manager = self.layout_manager.unwrap_or_default();
-        layout_manager.enable_cache(self.cache_enabled);
+        if let Some(enabled) = self.cache_enabled {
+            layout_manager.enable_cache(enabled);
+        }
+        if let Some(seed) = self.layout_seed.or(self.seed) {
+            layout_manager.set_seed(seed);
+        }
+        if let Some(timeout) = self.layout_timeout {
+            layout_manager.set_timeout(timeout);
+        }
+        if let Some(direction) = self.layout_direction {
+            layout_manager.set_direction(direction);
+        }
+        if self.layout_cache_dir.is_some() {
+            layout_manager.set_cache_dir(self.layout_cache_dir);
+        }
 
         EDSLCompiler {
             layout_manager,
@@ -134,6 +372,17 @@ impl EDSLCompilerBuilder {
             validate_output: self.validate_output,
             parallel_layout: self.parallel_layout,
             max_threads: self.max_threads,
+            max_elements: self.max_elements,
+            parse_limits: self.parse_limits,
+            theme: self.theme,
+            stable_ids: self.stable_ids,
+            seed: self.seed,
+            preset: self.preset,
+            file_version: self.file_version,
+            file_source: self.file_source,
+            output_cache: (self.output_cache_capacity > 0)
+                .then(|| OutputCache::new(self.output_cache_capacity)),
+            bypass_output_cache: false,
         }
     }
 }
@@ -149,6 +398,37 @@ impl EDSLCompiler {
         EDSLCompilerBuilder::new()
     }
 
+    /// The layout manager used for compilation
+    pub fn layout_manager(&self) -> &LayoutManager {
+        &self.layout_manager
+    }
+
+    /// Drop cached layouts, in memory and in the layout cache directory,
+    /// along with cached compiled output
+    pub fn clear_cache(&mut self) {
+        self.layout_manager.clear_cache();
+        self.clear_output_cache();
+    }
+
+    /// Compile every source from scratch while `bypass` is set, keeping the
+    /// output cache's entries for later
+    pub fn set_output_cache_bypass(&mut self, bypass: bool) {
+        self.bypass_output_cache = bypass;
+    }
+
+    fn clear_output_cache(&mut self) {
+        if let Some(cache) = &mut self.output_cache {
+            cache.clear();
+        }
+    }
+
+    /// Register a custom layout engine, selectable with `layout: <name>`
+    pub fn register_layout_engine(&mut self, name: &str, engine: Box<dyn layout::LayoutEngine>) {
+        self.layout_manager.register(name, engine);
+        // Outputs laid out by a replaced engine are stale
+        self.clear_output_cache();
+    }
+
     /// Process templates if the feature is enabled
     fn process_templates(
         &self,
@@ -174,21 +454,61 @@ impl EDSLCompiler {
     #[deprecated(note = "Use EDSLCompiler::builder().with_llm_optimization() instead")]
     pub fn with_llm_optimization(mut self, api_key: String) -> Self {
         self.llm_optimizer = Some(llm::LLMLayoutOptimizer::new(api_key));
+        self.clear_output_cache();
         self
     }
 
     /// Compile EDSL source code to Excalidraw JSON
     pub fn compile(&mut self, edsl_source: &str) -> Result<String> {
-        // Parse EDSL
-        let parsed_doc = parse_edsl(edsl_source)?;
+        self.compile_with_stats(edsl_source).map(|(json, _)| json)
+    }
 
-        // Process templates if present
-        let processed_doc = self.process_templates(parsed_doc)?;
+    /// Compile EDSL source code to Excalidraw JSON, timing each phase
+    pub fn compile_with_stats(&mut self, edsl_source: &str) -> Result<(String, CompileStats)> {
+        self.compile_with_progress(edsl_source, |_| {})
+    }
 
-        // Build intermediate graph representation
-        let mut igr = IntermediateGraph::from_ast(processed_doc)?;
+    /// Like [`compile_with_stats`](Self::compile_with_stats), calling
+    /// `on_phase` with each phase's name (as in [`CompileStats::phases`])
+    /// just before it starts. A cache hit runs no phases.
+    pub fn compile_with_progress(
+        &mut self,
+        edsl_source: &str,
+        mut on_phase: impl FnMut(&'static str),
+    ) -> Result<(String, CompileStats)> {
+        let cache_key = match &mut self.output_cache {
+            Some(cache) if !self.bypass_output_cache => {
+                let options = format!(
+                    "{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{}",
+                    self.theme,
+                    self.stable_ids,
+                    self.seed,
+                    self.preset,
+                    self.file_version,
+                    self.file_source,
+                    self.parse_limits,
+                    self.max_elements
+                );
+                let key = output_cache::key(edsl_source, &options);
+                if let Some((json, cached)) = cache.get(&key) {
+                    let stats = CompileStats {
+                        cached: true,
+                        nodes: cached.nodes,
+                        edges: cached.edges,
+                        ..Default::default()
+                    };
+                    return Ok((json, stats));
+                }
+                Some(key)
+            }
+            _ => None,
+        };
+        let mut stats = CompileStats::default();
+        let mut igr = self.build_igr_timed(edsl_source, &mut stats, &mut on_phase)?;
 
         // Apply layout algorithms
+        on_phase("layout");
+        let started = Instant::now();
         self.layout_manager.layout(&mut igr)?;
 
         // Apply LLM optimization if enabled
@@ -196,12 +516,71 @@ impl EDSLCompiler {
         if let Some(optimizer) = &mut self.llm_optimizer {
             optimizer.optimize_layout(&mut igr, edsl_source)?;
         }
+        stats.layout = started.elapsed();
+
+        // Generate Excalidraw file and serialize to JSON
+        on_phase("generate");
+        let started = Instant::now();
+        let file = ExcalidrawGenerator::generate_file_with_limit(&igr, self.max_elements)?;
+        let json = serde_json::to_string_pretty(&file).map_err(EDSLError::Json)?;
+        self.check_output(&json)?;
+        stats.generate = started.elapsed();
+
+        if let (Some(cache), Some(key)) = (&mut self.output_cache, cache_key) {
+            cache.insert(key, (json.clone(), stats.clone()));
+        }
+        Ok((json, stats))
+    }
 
-        // Generate Excalidraw file
-        let file = ExcalidrawGenerator::generate_file(&igr)?;
+    /// Compile each top-level container into its own document, laid out
+    /// independently, plus an index of the connections between containers
+    pub fn compile_split(&mut self, edsl_source: &str) -> Result<SplitOutput> {
+        let igr = self.build_igr(edsl_source)?;
+
+        let mut used_names: HashSet<String> = HashSet::from([SPLIT_INDEX_NAME.to_string()]);
+        let mut containers = Vec::new();
+        for idx in igr.top_level_containers() {
+            let container = &igr.containers[idx];
+            let base = container
+                .id
+                .as_deref()
+                .or(container.label.as_deref())
+                .map(file_stem)
+                .filter(|stem| !stem.is_empty())
+                .unwrap_or_else(|| format!("container_{idx}"));
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while !used_names.insert(name.clone()) {
+                name = format!("{base}_{suffix}");
+                suffix += 1;
+            }
 
-        // Serialize to JSON
-        serde_json::to_string_pretty(&file).map_err(EDSLError::Json)
+            let subgraph = igr.container_subgraph(idx);
+            containers.push((name, self.render(subgraph)?));
+        }
+
+        let index = self.render(igr.container_overview()?)?;
+        Ok(SplitOutput { containers, index })
+    }
+
+    /// Lay out a graph and serialize it as an Excalidraw file
+    fn render(&mut self, mut igr: IntermediateGraph) -> Result<String> {
+        self.layout_manager.layout(&mut igr)?;
+        let file = ExcalidrawGenerator::generate_file_with_limit(&igr, self.max_elements)?;
+        let json = serde_json::to_string_pretty(&file).map_err(EDSLError::Json)?;
+        self.check_output(&json)?;
+        Ok(json)
+    }
+
+    /// Self-check generated JSON when output validation is enabled
+    fn check_output(&self, json: &str) -> Result<()> {
+        if !self.validate_output {
+            return Ok(());
+        }
+        self.validate_excalidraw(json)
+            .map_err(|e| EDSLError::Validation {
+                message: format!("generated output is invalid: {e}"),
+            })
     }
 
     /// Compile EDSL source code and return raw elements (without JSON serialization)
@@ -209,9 +588,10 @@ impl EDSLCompiler {
         &mut self,
         edsl_source: &str,
     ) -> Result<Vec<generator::ExcalidrawElementSkeleton>> {
-        let parsed_doc = parse_edsl(edsl_source)?;
-        let processed_doc = self.process_templates(parsed_doc)?;
+        let parsed_doc = parse_edsl_with_limits(edsl_source, &self.parse_limits)?;
+        let processed_doc = self.apply_preset(self.process_templates(parsed_doc)?)?;
         let mut igr = IntermediateGraph::from_ast(processed_doc)?;
+        self.apply_overrides(&mut igr)?;
 
         self.layout_manager.layout(&mut igr)?;
 
@@ -220,15 +600,12 @@ impl EDSLCompiler {
             optimizer.optimize_layout(&mut igr, edsl_source)?;
         }
 
-        ExcalidrawGenerator::generate(&igr)
+        ExcalidrawGenerator::generate_with_limit(&igr, self.max_elements)
     }
 
     /// Parse and validate EDSL source code without generating output
     pub fn validate(&self, edsl_source: &str) -> Result<()> {
-        let parsed_doc = parse_edsl(edsl_source)?;
-        let processed_doc = self.process_templates(parsed_doc)?;
-        let _igr = IntermediateGraph::from_ast(processed_doc)?;
-        Ok(())
+        self.build_igr(edsl_source).map(|_| ())
     }
 
     /// Validate Excalidraw JSON file format
@@ -323,12 +700,98 @@ impl EDSLCompiler {
 
     /// Get the intermediate graph representation for debugging/inspection
     pub fn get_igr(&self, edsl_source: &str) -> Result<IntermediateGraph> {
-        let parsed_doc = parse_edsl(edsl_source)?;
-        let processed_doc = self.process_templates(parsed_doc)?;
-        let mut igr = IntermediateGraph::from_ast(processed_doc)?;
+        self.get_igr_with_stats(edsl_source).map(|(igr, _)| igr)
+    }
+
+    /// Like [`get_igr`](Self::get_igr), timing the parse, build and layout
+    /// phases. Generation is left to the caller, so its time is zero.
+    pub fn get_igr_with_stats(
+        &self,
+        edsl_source: &str,
+    ) -> Result<(IntermediateGraph, CompileStats)> {
+        let (mut igr, mut stats) = self.build_igr_with_stats(edsl_source)?;
+        let started = Instant::now();
         self.layout_manager.layout(&mut igr)?;
+        stats.layout = started.elapsed();
+        Ok((igr, stats))
+    }
+
+    /// Parse EDSL source into an intermediate graph without running layout
+    pub fn build_igr(&self, edsl_source: &str) -> Result<IntermediateGraph> {
+        self.build_igr_with_stats(edsl_source).map(|(igr, _)| igr)
+    }
+
+    /// Like [`build_igr`](Self::build_igr), timing the parse and build phases
+    pub fn build_igr_with_stats(
+        &self,
+        edsl_source: &str,
+    ) -> Result<(IntermediateGraph, CompileStats)> {
+        let mut stats = CompileStats::default();
+        let igr = self.build_igr_timed(edsl_source, &mut stats, &mut |_| {})?;
+        Ok((igr, stats))
+    }
+
+    /// Parse and build the intermediate graph, recording both phases and the
+    /// graph's size in `stats`
+    fn build_igr_timed(
+        &self,
+        edsl_source: &str,
+        stats: &mut CompileStats,
+        on_phase: &mut impl FnMut(&'static str),
+    ) -> Result<IntermediateGraph> {
+        // Parse EDSL and process templates if present
+        on_phase("parse");
+        let started = Instant::now();
+        let parsed_doc = parse_edsl_with_limits(edsl_source, &self.parse_limits)?;
+        let processed_doc = self.apply_preset(self.process_templates(parsed_doc)?)?;
+        stats.parse = started.elapsed();
+
+        // Build intermediate graph representation
+        on_phase("build");
+        let started = Instant::now();
+        let mut igr = IntermediateGraph::from_ast(processed_doc)?;
+        self.apply_overrides(&mut igr)?;
+        stats.build = started.elapsed();
+        stats.nodes = igr
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container)
+            .count();
+        stats.edges = igr.graph.edge_count();
         Ok(igr)
     }
+
+    /// Fill in the configured preset's settings the front matter leaves unset
+    fn apply_preset(
+        &self,
+        mut document: crate::ast::ParsedDocument,
+    ) -> Result<crate::ast::ParsedDocument> {
+        if let Some(ref preset) = self.preset {
+            DiagramPresets::apply(preset, &mut document.config)?;
+        }
+        Ok(document)
+    }
+
+    /// Apply compiler settings that supersede the document's front matter
+    fn apply_overrides(&self, igr: &mut IntermediateGraph) -> Result<()> {
+        if let Some(ref theme) = self.theme {
+            igr.global_config.theme = Some(theme.clone());
+            igr.global_config.validate()?;
+        }
+        if self.stable_ids.is_some() {
+            igr.global_config.stable_ids = self.stable_ids;
+        }
+        if self.seed.is_some() {
+            igr.global_config.random_seed = self.seed;
+        }
+        if self.file_version.is_some() {
+            igr.global_config.file_version = self.file_version;
+        }
+        if self.file_source.is_some() {
+            igr.global_config.file_source = self.file_source.clone();
+        }
+        Ok(())
+    }
 }
 
 impl Default for EDSLCompiler {
@@ -337,6 +800,20 @@ impl Default for EDSLCompiler {
     }
 }
 
+/// Turn a container id or label into a safe file stem
+fn file_stem(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 // Additional integration tests are located in tests/ directory
 
 #[cfg(test)]
@@ -414,6 +891,105 @@ container "Backend Services" {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_scale_config_zooms_diagram() {
+        let extent = |scale: &str| {
+            let edsl = format!(
+                "---\nscale: {scale}\n---\ncontainer \"Backend\" {{\n  api[API]\n  db[Database]\n  api -> db\n}}\nuser[User]\nuser -> api\n"
+            );
+            let elements = EDSLCompiler::new().compile_to_elements(&edsl).unwrap();
+            let shapes: Vec<_> = elements
+                .iter()
+                .filter(|e| e.r#type == "rectangle")
+                .collect();
+            let min_x = shapes.iter().map(|e| e.x).min().unwrap();
+            let min_y = shapes.iter().map(|e| e.y).min().unwrap();
+            let max_x = shapes.iter().map(|e| e.x + e.width).max().unwrap();
+            let max_y = shapes.iter().map(|e| e.y + e.height).max().unwrap();
+            ((max_x - min_x) as f64, (max_y - min_y) as f64)
+        };
+
+        let (w1, h1) = extent("1.0");
+        let (w2, h2) = extent("2.0");
+        assert!((w2 - 2.0 * w1).abs() <= 2.0, "{w1} -> {w2}");
+        assert!((h2 - 2.0 * h1).abs() <= 2.0, "{h1} -> {h2}");
+    }
+
+    #[test]
+    fn test_scale_must_be_positive() {
+        let edsl = "---\nscale: 0\n---\na[A]\n";
+        assert!(EDSLCompiler::new().compile(edsl).is_err());
+        assert!(crate::ast::GlobalConfig::builder().scale(-1.0).is_err());
+    }
+
+    #[test]
+    fn test_canvas_centers_and_fits_diagram() {
+        let body = "a[Alpha]\nb[Beta]\nc[Gamma]\nd[Delta]\na -> b\nb -> c\nc -> d\n";
+        let layout = |canvas: &str| {
+            let edsl = format!("---\ncanvas: {canvas}\n---\n{body}");
+            parse_and_layout(&edsl, "dagre").unwrap().bounding_box()
+        };
+        let natural = parse_and_layout(body, "dagre").unwrap().bounding_box();
+
+        // Fits as is: same size, centered
+        let centered = layout("1920x1080");
+        assert!((centered.width - natural.width).abs() < 1e-6);
+        assert!((centered.x + centered.width / 2.0 - 960.0).abs() < 1e-6);
+        assert!((centered.y + centered.height / 2.0 - 540.0).abs() < 1e-6);
+
+        // Too tall: scaled down until it just fits, still centered
+        let height = natural.height / 2.0;
+        let fitted = layout(&format!("1000x{height}"));
+        assert!((fitted.height - height).abs() < 1e-6);
+        assert!((fitted.width - natural.width / 2.0).abs() < 1e-6);
+        assert!(fitted.y.abs() < 1e-6);
+        assert!((fitted.x + fitted.width / 2.0 - 500.0).abs() < 1e-6);
+
+        // The optional background covers the canvas, behind everything
+        let edsl = format!("---\ncanvas: 800x600\ncanvas_background: ivory\n---\n{body}");
+        let elements = EDSLCompiler::new().compile_to_elements(&edsl).unwrap();
+        let background = &elements[0];
+        assert_eq!(background.background_color, "ivory");
+        assert_eq!(
+            (
+                background.x,
+                background.y,
+                background.width,
+                background.height
+            ),
+            (0, 0, 800, 600)
+        );
+
+        assert!(EDSLCompiler::new()
+            .compile("---\ncanvas: 0x100\n---\na[A]\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_and_layout_force() {
+        let edsl = r#"
+        a[Node A]
+        b[Node B]
+        c[Node C]
+        a -> b -> c
+        "#;
+
+        let igr = parse_and_layout(edsl, "force").unwrap();
+
+        assert_eq!(igr.graph.node_count(), 3);
+        let positions: Vec<(f64, f64)> = igr.graph.node_weights().map(|n| (n.x, n.y)).collect();
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                assert_ne!(a, b, "nodes should be placed at distinct positions");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_and_layout_unknown_engine() {
+        assert!(parse_and_layout("a[A]", "nope").is_err());
+    }
+
     #[test]
     fn test_get_igr() {
         let edsl = r#"
@@ -433,4 +1009,288 @@ container "Backend Services" {
             assert!(node.x != 0.0 || node.y != 0.0); // At least one node should be positioned
         }
     }
+
+    /// Places nodes left to right on a single row, in id order
+    struct RowLayout;
+
+    impl layout::LayoutEngine for RowLayout {
+        fn layout(&self, igr: &mut IntermediateGraph) -> Result<()> {
+            let mut indices: Vec<_> = igr.graph.node_indices().collect();
+            indices.sort_by(|a, b| igr.graph[*a].id.cmp(&igr.graph[*b].id));
+            for (i, idx) in indices.into_iter().enumerate() {
+                let node = &mut igr.graph[idx];
+                node.x = 1000.0 + i as f64 * 300.0;
+                node.y = 500.0;
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            "row"
+        }
+    }
+
+    #[test]
+    fn test_compile_with_stats_reports_phases() {
+        let edsl = r#"
+container "Backend" as backend {
+  api[API]
+  worker[Worker]
+  db[Database] { shape: cylinder; }
+  api -> worker -> db
+}
+user[User]
+user -> api: request
+"#;
+        let mut compiler = EDSLCompiler::new();
+        let mut started = Vec::new();
+        let (json, stats) = compiler
+            .compile_with_progress(edsl, |phase| started.push(phase))
+            .unwrap();
+        assert!(json.contains("\"Database\""));
+        assert_eq!(started, ["parse", "build", "layout", "generate"]);
+        assert_eq!((stats.nodes, stats.edges), (4, 3));
+
+        let names: Vec<_> = stats.phases().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["parse", "build", "layout", "generate"]);
+        assert!(stats.phases().iter().all(|(_, d)| *d >= Duration::ZERO));
+        assert!(stats.total() > Duration::ZERO);
+        assert_eq!(
+            stats.total(),
+            stats.parse + stats.build + stats.layout + stats.generate
+        );
+    }
+
+    #[test]
+    fn test_generated_output_passes_self_validation() {
+        let edsl = r##"
+---
+legend: true
+---
+note "Deployed nightly" at (0, -200) { backgroundColor: "#fff3bf"; }
+line from (0, -250) to (300, -250)
+service "Edge" {
+  gw[Gateway] { shape: diamond; }
+}
+container "Backend" as backend {
+  api[API] { shape: ellipse; icon: "cloud"; }
+  db[Database] { shape: cylinder; backgroundColor: "#a5d8ff"; }
+  q[Queue] { shape: hexagon; }
+  api -> db: reads
+  api -> q
+}
+gw -> api { routing: curved; }
+gw -- backend
+"##;
+        let mut compiler = EDSLCompiler::builder().with_validation(true).build();
+        let json = compiler.compile(edsl).unwrap();
+        compiler.validate_excalidraw(&json).unwrap();
+
+        let split = compiler.compile_split(edsl).unwrap();
+        assert_eq!(split.containers.len(), 1);
+    }
+
+    #[test]
+    fn test_output_cache() {
+        let edsl = "a[A]\nb[B]\na -> b\n";
+        let mut compiler = EDSLCompiler::builder().with_output_cache(4).build();
+        let (first, stats) = compiler.compile_with_stats(edsl).unwrap();
+        assert!(!stats.cached);
+
+        // The second compile skips every phase, parsing included
+        let (second, stats) = compiler.compile_with_stats(edsl).unwrap();
+        assert_eq!(first, second);
+        assert!(stats.cached);
+        assert_eq!(stats.parse, Duration::ZERO);
+        assert_eq!((stats.nodes, stats.edges), (2, 1));
+
+        compiler.set_output_cache_bypass(true);
+        let (_, stats) = compiler.compile_with_stats(edsl).unwrap();
+        assert!(!stats.cached);
+        compiler.set_output_cache_bypass(false);
+
+        compiler.clear_cache();
+        let (_, stats) = compiler.compile_with_stats(edsl).unwrap();
+        assert!(!stats.cached);
+
+        let mut uncached = EDSLCompiler::new();
+        uncached.compile(edsl).unwrap();
+        let (_, stats) = uncached.compile_with_stats(edsl).unwrap();
+        assert!(!stats.cached);
+    }
+
+    #[test]
+    fn test_stable_ids_are_reproducible() {
+        let edsl = r#"
+group "Team" {
+  api[API] { icon: "⚙"; }
+  worker[Worker]
+}
+container "Storage" as storage {
+  db[Database]
+}
+api -> worker
+worker -> db: writes
+worker -> db
+api -> storage
+line from (-300, 0) to (-250, 40)
+"#;
+        let ids = |stable: bool| {
+            let mut compiler = EDSLCompiler::builder().with_stable_ids(stable).build();
+            let elements = compiler.compile_to_elements(edsl).unwrap();
+            elements
+                .iter()
+                .map(|e| (e.id.clone(), e.group_ids.clone(), e.container_id.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let first = ids(true);
+        assert_eq!(first, ids(true));
+        assert_ne!(ids(false), ids(false));
+
+        let unique: std::collections::HashSet<_> = first.iter().map(|(id, _, _)| id).collect();
+        assert_eq!(unique.len(), first.len());
+        assert!(first.iter().any(|(id, _, _)| id.starts_with("node_")));
+        // No random UUID survives, even for elements without a DSL id
+        for (id, group_ids, _) in &first {
+            assert!(!id.contains('-'), "random id {id}");
+            assert!(group_ids.iter().all(|g| !g.contains('-')), "{group_ids:?}");
+        }
+    }
+
+    #[test]
+    fn test_force_layout_timeout() {
+        let mut edsl = String::from("---\nlayout: force\n---\n");
+        edsl.extend((0..20).map(|i| format!("n{i}[Node {i}]\n")));
+        edsl.extend((1..20).map(|i| format!("n{} -> n{i}\n", i / 2)));
+
+        let mut compiler = EDSLCompiler::builder()
+            .with_layout_timeout(std::time::Duration::from_nanos(1))
+            .build();
+        match compiler.compile(&edsl) {
+            Err(EDSLError::Layout(error::LayoutError::Timeout { engine, elapsed })) => {
+                assert_eq!(engine, "force");
+                assert!(elapsed > std::time::Duration::from_nanos(1));
+            }
+            other => panic!("Expected a layout timeout, got {other:?}"),
+        }
+
+        // A generous budget leaves the layout untouched
+        let mut compiler = EDSLCompiler::builder()
+            .with_layout_timeout(std::time::Duration::from_secs(60))
+            .build();
+        assert!(compiler.compile(&edsl).is_ok());
+    }
+
+    #[test]
+    fn test_bound_elements_are_unique_and_ordered() {
+        let edsl = r#"
+---
+layout: force
+---
+a[A]
+b[B]
+c[C]
+c -> a
+b -> a: reads
+a -> a: retry
+a -> b
+"#;
+        let bound = || {
+            let mut compiler = EDSLCompiler::builder().with_stable_ids(true).build();
+            let elements = compiler.compile_to_elements(edsl).unwrap();
+            elements
+                .into_iter()
+                .map(|e| (e.id, e.bound_elements))
+                .collect::<Vec<_>>()
+        };
+
+        let first = bound();
+        assert_eq!(first, bound());
+        for (_, entries) in &first {
+            let ids: Vec<&str> = entries.iter().filter_map(|e| e["id"].as_str()).collect();
+            let unique: std::collections::HashSet<_> = ids.iter().collect();
+            assert_eq!(unique.len(), ids.len(), "duplicate boundElements: {ids:?}");
+
+            // Text first, then arrows by id
+            let keys: Vec<_> = entries
+                .iter()
+                .map(|e| (e["type"] != "text", e["id"].as_str().unwrap_or_default()))
+                .collect();
+            assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+
+        // a carries its label plus four distinct arrows, the self-loop once
+        let node_a = first
+            .iter()
+            .map(|(_, entries)| entries)
+            .find(|entries| entries.iter().filter(|e| e["type"] == "arrow").count() == 4)
+            .expect("node a is bound to four arrows");
+        assert_eq!(node_a[0]["type"], "text");
+    }
+
+    #[test]
+    fn test_custom_layout_engine() {
+        let edsl = r#"
+---
+layout: row
+---
+a[Node A]
+b[Node B]
+a -> b
+"#;
+
+        let manager = LayoutManager::new().with_engine("row", Box::new(RowLayout));
+        assert!(manager.engine_names().contains(&"row"));
+
+        let compiler = EDSLCompiler::builder().with_layout_manager(manager).build();
+        for _ in 0..2 {
+            // The second pass is served from the layout cache
+            let igr = compiler.get_igr(edsl).unwrap();
+            let (_, a) = igr.get_node_by_id("a").unwrap();
+            let (_, b) = igr.get_node_by_id("b").unwrap();
+            assert_eq!((a.x, a.y), (1000.0, 500.0));
+            assert_eq!((b.x, b.y), (1300.0, 500.0));
+        }
+
+        // Without the engine registered the document is rejected
+        assert!(EDSLCompiler::new().get_igr(edsl).is_err());
+
+        let mut compiler = EDSLCompiler::new();
+        compiler.register_layout_engine("row", Box::new(RowLayout));
+        assert!(compiler.compile(edsl).is_ok());
+
+        // Setting a seed or direction doesn't replace an engine registered
+        // under a built-in name
+        let manager = LayoutManager::new().with_engine("dagre", Box::new(RowLayout));
+        let compiler = EDSLCompiler::builder()
+            .with_layout_manager(manager)
+            .with_layout_seed(7)
+            .with_layout_direction(Direction::LeftRight)
+            .build();
+        let igr = compiler
+            .get_igr(&edsl.replace("layout: row", "layout: dagre"))
+            .unwrap();
+        let (_, b) = igr.get_node_by_id("b").unwrap();
+        assert_eq!((b.x, b.y), (1300.0, 500.0));
+    }
+
+    #[test]
+    fn test_file_version_and_source() {
+        let edsl = "---\nfile_source: https://docs.example.com\n---\na\nb\na -> b\n";
+
+        let file: serde_json::Value =
+            serde_json::from_str(&EDSLCompiler::new().compile(edsl).unwrap()).unwrap();
+        assert_eq!(file["source"], "https://docs.example.com");
+        assert_eq!(file["version"], 2);
+
+        let mut compiler = EDSLCompiler::builder()
+            .with_file_source("https://editor.example.com")
+            .with_file_version(3)
+            .build();
+        let file: serde_json::Value =
+            serde_json::from_str(&compiler.compile(edsl).unwrap()).unwrap();
+        assert_eq!(file["source"], "https://editor.example.com");
+        assert_eq!(file["version"], 3);
+    }
 }
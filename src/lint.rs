@@ -0,0 +1,509 @@
+// src/lint.rs
+//! Stylistic and structural linting for EDSL diagrams
+//!
+//! Lint rules run on a parsed document and its intermediate graph and report
+//! [`Diagnostic`]s. Unlike parse or build errors, diagnostics never stop
+//! compilation; they point at diagrams that are likely hard to read.
+
+use crate::ast::{NodeDefinition, ParsedDocument, Statement};
+use crate::generator::{contrast_ratio, ExcalidrawGenerator};
+use crate::igr::IntermediateGraph;
+use crate::Result;
+use petgraph::Direction;
+use std::collections::HashSet;
+use std::fmt;
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single lint finding
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the rule that produced this diagnostic
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// ID of the node or container the diagnostic refers to, if any
+    pub target: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.rule, self.message)
+    }
+}
+
+/// Settings shared by all lint rules
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    /// Rule names that should not run
+    pub disabled_rules: HashSet<String>,
+    /// Labels longer than this are reported by `long-label`
+    pub max_label_length: usize,
+    /// Treat the diagram as a DAG and report cycles
+    pub require_dag: bool,
+    /// Lowest label-to-background contrast ratio accepted by `low-contrast`
+    /// (WCAG AA for normal text is 4.5)
+    pub min_contrast_ratio: f64,
+    /// Smallest font size, in pixels, accepted by `small-text`
+    pub min_font_size: f64,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            disabled_rules: HashSet::new(),
+            max_label_length: 40,
+            require_dag: false,
+            min_contrast_ratio: 4.5,
+            min_font_size: 12.0,
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn is_enabled(&self, rule: &str) -> bool {
+        !self.disabled_rules.contains(rule)
+    }
+}
+
+/// Context handed to every lint rule
+pub struct LintContext<'a> {
+    pub document: &'a ParsedDocument,
+    pub igr: &'a IntermediateGraph,
+    pub config: &'a LintConfig,
+}
+
+/// A lint rule
+pub trait Lint: Send + Sync {
+    /// Stable rule name used in output and to disable the rule
+    fn name(&self) -> &'static str;
+
+    /// Run the rule and return its findings
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic>;
+}
+
+/// Warns on nodes declared without a label
+pub struct UnlabeledNode;
+
+impl Lint for UnlabeledNode {
+    fn name(&self) -> &'static str {
+        "unlabeled-node"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        all_node_definitions(ctx.document)
+            .into_iter()
+            .filter(|node| node.label.is_none() && node.component_type.is_none())
+            .map(|node| Diagnostic {
+                rule: self.name(),
+                severity: Severity::Warning,
+                message: format!("Node '{}' has no label", node.id),
+                target: Some(node.id.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Warns on nodes that have no incoming or outgoing edges
+pub struct OrphanNode;
+
+impl Lint for OrphanNode {
+    fn name(&self) -> &'static str {
+        "orphan-node"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        let graph = &ctx.igr.graph;
+        graph
+            .node_indices()
+            .filter(|&idx| !graph[idx].is_virtual_container)
+            .filter(|&idx| {
+                graph
+                    .neighbors_directed(idx, Direction::Incoming)
+                    .next()
+                    .is_none()
+                    && graph
+                        .neighbors_directed(idx, Direction::Outgoing)
+                        .next()
+                        .is_none()
+            })
+            .map(|idx| Diagnostic {
+                rule: self.name(),
+                severity: Severity::Warning,
+                message: format!("Node '{}' is not connected to anything", graph[idx].id),
+                target: Some(graph[idx].id.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Warns on labels longer than `LintConfig::max_label_length`
+pub struct LongLabel;
+
+impl Lint for LongLabel {
+    fn name(&self) -> &'static str {
+        "long-label"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        let max = ctx.config.max_label_length;
+        ctx.igr
+            .graph
+            .node_weights()
+            .filter(|node| node.label.chars().count() > max)
+            .map(|node| Diagnostic {
+                rule: self.name(),
+                severity: Severity::Warning,
+                message: format!(
+                    "Label of node '{}' is {} characters long (max {max})",
+                    node.id,
+                    node.label.chars().count()
+                ),
+                target: Some(node.id.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Reports cycles when the diagram is declared as a DAG
+pub struct DagCycle;
+
+impl Lint for DagCycle {
+    fn name(&self) -> &'static str {
+        "dag-cycle"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        if !ctx.config.require_dag || !petgraph::algo::is_cyclic_directed(&ctx.igr.graph) {
+            return Vec::new();
+        }
+
+        // Report one node from every strongly connected component with a cycle
+        petgraph::algo::tarjan_scc(&ctx.igr.graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || ctx.igr.graph.contains_edge(component[0], component[0])
+            })
+            .map(|component| {
+                let mut ids: Vec<_> = component
+                    .iter()
+                    .map(|&idx| ctx.igr.graph[idx].id.clone())
+                    .collect();
+                ids.sort();
+                Diagnostic {
+                    rule: self.name(),
+                    severity: Severity::Error,
+                    message: format!("Cycle found in DAG between: {}", ids.join(", ")),
+                    target: ids.first().cloned(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Warns on containers that wrap a single element
+pub struct SingleChildContainer;
+
+impl Lint for SingleChildContainer {
+    fn name(&self) -> &'static str {
+        "single-child-container"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        ctx.igr
+            .containers
+            .iter()
+            .filter(|container| {
+                container.children.len()
+                    + container.nested_containers.len()
+                    + container.nested_groups.len()
+                    == 1
+            })
+            .map(|container| {
+                let name = container
+                    .id
+                    .clone()
+                    .or_else(|| container.label.clone())
+                    .unwrap_or_else(|| "<anonymous>".to_string());
+                Diagnostic {
+                    rule: self.name(),
+                    severity: Severity::Info,
+                    message: format!("Container '{name}' only has a single child"),
+                    target: container.id.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Warns on node labels whose contrast against their background, after
+/// theming, is below `LintConfig::min_contrast_ratio`
+pub struct LowContrast;
+
+impl Lint for LowContrast {
+    fn name(&self) -> &'static str {
+        "low-contrast"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        let min = ctx.config.min_contrast_ratio;
+        ctx.igr
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container && !node.label.is_empty())
+            .filter_map(|node| {
+                let (text, background) = ExcalidrawGenerator::label_colors(ctx.igr, &node.attributes);
+                // Colors in formats we can't measure are skipped
+                let ratio = contrast_ratio(&text, &background)?;
+                (ratio < min).then(|| Diagnostic {
+                    rule: self.name(),
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Label of node '{}' has contrast ratio {ratio:.2}:1 ({text} on {background}), below {min}:1",
+                        node.id
+                    ),
+                    target: Some(node.id.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Warns on node labels smaller than `LintConfig::min_font_size`
+pub struct SmallText;
+
+impl Lint for SmallText {
+    fn name(&self) -> &'static str {
+        "small-text"
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        let min = ctx.config.min_font_size;
+        ctx.igr
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container && !node.label.is_empty())
+            .filter_map(|node| {
+                let size = node.attributes.font_size?;
+                (size < min).then(|| Diagnostic {
+                    rule: self.name(),
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Label of node '{}' uses font size {size}px, below {min}px",
+                        node.id
+                    ),
+                    target: Some(node.id.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Runs a set of lint rules against EDSL documents
+pub struct Linter {
+    rules: Vec<Box<dyn Lint>>,
+    config: LintConfig,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new(LintConfig::default())
+    }
+}
+
+impl Linter {
+    /// Create a linter with all built-in rules
+    pub fn new(config: LintConfig) -> Self {
+        Self {
+            rules: vec![
+                Box::new(UnlabeledNode),
+                Box::new(OrphanNode),
+                Box::new(LongLabel),
+                Box::new(DagCycle),
+                Box::new(SingleChildContainer),
+                Box::new(LowContrast),
+                Box::new(SmallText),
+            ],
+            config,
+        }
+    }
+
+    /// Add a custom rule
+    pub fn with_rule(mut self, rule: Box<dyn Lint>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Names of all registered rules
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// Lint a parsed document
+    pub fn lint_document(&self, document: &ParsedDocument) -> Result<Vec<Diagnostic>> {
+        let igr = IntermediateGraph::from_ast(document.clone())?;
+        let ctx = LintContext {
+            document,
+            igr: &igr,
+            config: &self.config,
+        };
+
+        let mut diagnostics: Vec<Diagnostic> = self
+            .rules
+            .iter()
+            .filter(|rule| self.config.is_enabled(rule.name()))
+            .flat_map(|rule| rule.check(&ctx))
+            .collect();
+        diagnostics.sort_by_key(|d| std::cmp::Reverse(d.severity));
+
+        Ok(diagnostics)
+    }
+
+    /// Parse and lint EDSL source
+    pub fn lint(&self, input: &str) -> Result<Vec<Diagnostic>> {
+        let document = crate::parser::parse_edsl(input)?;
+        self.lint_document(&document)
+    }
+}
+
+/// Collect node definitions from the top level and from nested containers/groups
+fn all_node_definitions(document: &ParsedDocument) -> Vec<&NodeDefinition> {
+    fn collect<'a>(statements: &'a [Statement], nodes: &mut Vec<&'a NodeDefinition>) {
+        for statement in statements {
+            match statement {
+                Statement::Node(node) => nodes.push(node),
+                Statement::Container(container) => collect(&container.internal_statements, nodes),
+                Statement::Group(group) => collect(&group.internal_statements, nodes),
+                _ => {}
+            }
+        }
+    }
+
+    let mut nodes: Vec<&NodeDefinition> = document.nodes.iter().collect();
+    for container in &document.containers {
+        collect(&container.internal_statements, &mut nodes);
+    }
+    for group in &document.groups {
+        collect(&group.internal_statements, &mut nodes);
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_fired(diagnostics: &[Diagnostic]) -> Vec<&'static str> {
+        diagnostics.iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn test_orphan_node_rule() {
+        let input = r#"
+a[A]
+b[B]
+lonely[Lonely]
+a -> b
+"#;
+        let diagnostics = Linter::default().lint(input).unwrap();
+
+        let orphans: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.rule == "orphan-node")
+            .collect();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].target.as_deref(), Some("lonely"));
+        assert_eq!(orphans[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_unlabeled_node_rule() {
+        let input = r#"
+a[A]
+b
+a -> b
+"#;
+        let diagnostics = Linter::default().lint(input).unwrap();
+
+        let unlabeled: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.rule == "unlabeled-node")
+            .collect();
+        assert_eq!(unlabeled.len(), 1);
+        assert_eq!(unlabeled[0].target.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_disabled_rules_do_not_run() {
+        let mut config = LintConfig::default();
+        config.disabled_rules.insert("orphan-node".to_string());
+        config.disabled_rules.insert("unlabeled-node".to_string());
+
+        let diagnostics = Linter::new(config).lint("lonely").unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", rules_fired(&diagnostics));
+    }
+
+    #[test]
+    fn test_dag_cycle_rule_requires_dag_config() {
+        let input = "a[A]\nb[B]\na -> b\nb -> a";
+
+        let diagnostics = Linter::default().lint(input).unwrap();
+        assert!(!rules_fired(&diagnostics).contains(&"dag-cycle"));
+
+        let config = LintConfig {
+            require_dag: true,
+            ..Default::default()
+        };
+        let diagnostics = Linter::new(config).lint(input).unwrap();
+        assert_eq!(diagnostics[0].rule, "dag-cycle");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_accessibility_rules() {
+        let input = r##"
+faint[Faint] { backgroundColor: "#ffffff"; color: "#cccccc"; }
+tiny[Tiny] { fontSize: 8; }
+clear[Clear] { backgroundColor: "#1e40af"; color: "#ffffff"; fontSize: 16; }
+faint -> tiny
+tiny -> clear
+"##;
+        let diagnostics = Linter::default().lint(input).unwrap();
+        let fired = |rule: &str| -> Vec<&str> {
+            diagnostics
+                .iter()
+                .filter(|d| d.rule == rule)
+                .filter_map(|d| d.target.as_deref())
+                .collect()
+        };
+
+        assert_eq!(fired("low-contrast"), ["faint"]);
+        assert_eq!(fired("small-text"), ["tiny"]);
+        let faint = diagnostics
+            .iter()
+            .find(|d| d.rule == "low-contrast")
+            .unwrap();
+        assert!(faint.message.contains("1.61:1"), "{}", faint.message);
+
+        // Default text is switched to a light color on the dark canvas
+        let dark = "---\ntheme: dark\n---\na[A]\nb[B]\na -> b";
+        let diagnostics = Linter::default().lint(dark).unwrap();
+        assert!(diagnostics.iter().all(|d| d.rule != "low-contrast"));
+    }
+}
@@ -1,6 +1,16 @@
 // src/main.rs
 use clap::{Parser, Subcommand};
-use excalidraw_dsl::EDSLCompiler;
+use excalidraw_dsl::dot::DotGenerator;
+use excalidraw_dsl::edsl::{parse_excalidraw, EdslGenerator};
+use excalidraw_dsl::error::ErrorReport;
+use excalidraw_dsl::graphml::GraphmlGenerator;
+use excalidraw_dsl::igr::IntermediateGraph;
+use excalidraw_dsl::layout::Direction;
+use excalidraw_dsl::parser::{parse_edsl, parse_edsl_lossless, ParseLimits};
+use excalidraw_dsl::svg::SvgGenerator;
+use excalidraw_dsl::tikz::TikzGenerator;
+use excalidraw_dsl::{CompileStats, DiagramPresets, EDSLCompiler, EDSLError, SPLIT_INDEX_NAME};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use std::process;
 
@@ -12,22 +22,135 @@ use std::process;
     author = "ExcaliDraw-DSL Team"
 )]
 struct Cli {
+    /// How errors are reported on stderr
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    error_format: ErrorFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorFormat {
+    /// Human-readable message with its cause chain
+    Text,
+    /// A single JSON object with kind, message, line and column
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ThemeChoice {
+    Light,
+    Dark,
+    /// Respect the document's `theme:`, else light
+    Auto,
+}
+
+impl ThemeChoice {
+    /// Theme that overrides the document, if any
+    fn override_theme(self) -> Option<&'static str> {
+        match self {
+            ThemeChoice::Light => Some("light"),
+            ThemeChoice::Dark => Some("dark"),
+            ThemeChoice::Auto => None,
+        }
+    }
+}
+
+/// Flow direction for the dagre and elk engines
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum LayoutDirection {
+    /// Top to bottom
+    #[value(name = "TB")]
+    Tb,
+    /// Bottom to top
+    #[value(name = "BT")]
+    Bt,
+    /// Left to right
+    #[value(name = "LR")]
+    Lr,
+    /// Right to left
+    #[value(name = "RL")]
+    Rl,
+}
+
+impl From<LayoutDirection> for Direction {
+    fn from(direction: LayoutDirection) -> Self {
+        match direction {
+            LayoutDirection::Tb => Direction::TopBottom,
+            LayoutDirection::Bt => Direction::BottomTop,
+            LayoutDirection::Lr => Direction::LeftRight,
+            LayoutDirection::Rl => Direction::RightLeft,
+        }
+    }
+}
+
+/// Output file formats; without `--format` the output extension decides
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Excalidraw JSON (.excalidraw)
+    Excalidraw,
+    /// Standalone SVG image (.svg)
+    Svg,
+    /// PNG image (.png); not available in this build, use svg
+    Png,
+    /// Mermaid flowchart (.mmd)
+    Mermaid,
+    /// Graphviz DOT (.dot, .gv)
+    Dot,
+    /// TikZ picture for LaTeX (.tex)
+    Tikz,
+    /// GraphML for graph-analysis tools (.graphml)
+    Graphml,
+    /// EDSL source (.edsl); the input may also be an Excalidraw file
+    Edsl,
+}
+
+impl OutputFormat {
+    /// Format implied by a file extension, defaulting to Excalidraw JSON
+    fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => OutputFormat::Svg,
+            Some("png") => OutputFormat::Png,
+            Some("mmd") | Some("mermaid") => OutputFormat::Mermaid,
+            Some("dot") | Some("gv") => OutputFormat::Dot,
+            Some("tex") => OutputFormat::Tikz,
+            Some("graphml") => OutputFormat::Graphml,
+            Some("edsl") => OutputFormat::Edsl,
+            _ => OutputFormat::Excalidraw,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Excalidraw => "excalidraw",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Png => "png",
+            OutputFormat::Mermaid => "mmd",
+            OutputFormat::Dot => "dot",
+            OutputFormat::Tikz => "tex",
+            OutputFormat::Graphml => "graphml",
+            OutputFormat::Edsl => "edsl",
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Convert EDSL file to Excalidraw JSON
     #[command(alias = "compile")]
     Convert {
-        /// Input EDSL file
+        /// Input EDSL file, or an Excalidraw file when converting to EDSL
         input: PathBuf,
 
-        /// Output file path (defaults to input with .excalidraw extension)
+        /// Output file path (defaults to input with the format's extension)
         #[arg(short, long)]
         output: Option<PathBuf>,
 
+        /// Output format, overriding the one implied by the output extension
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+
         /// Layout algorithm
         #[arg(short, long, value_enum, default_value = "dagre")]
         layout: LayoutAlgorithm,
@@ -36,15 +159,92 @@ enum Commands {
         #[arg(long)]
         validate: bool,
 
+        /// Run the full pipeline including generation, but write no files
+        #[arg(long, conflicts_with = "validate")]
+        dry_run: bool,
+
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
 
-        /// Watch for file changes and recompile automatically
-        #[arg(short, long)]
+        /// Watch for file changes and recompile automatically. Compiles with
+        /// the document's own settings, so the options that override them
+        /// can't be combined with it.
+        #[arg(short, long, conflicts_with_all = ["theme", "stable_ids", "seed", "preset", "layout_direction"])]
         watch: bool,
+
+        /// Open the generated file with the system default application
+        #[arg(long)]
+        open: bool,
+
+        /// Write the laid-out intermediate graph as JSON to this path (for debugging)
+        #[arg(long, value_name = "PATH")]
+        dump_igr: Option<PathBuf>,
+
+        /// Directory for persisting layouts across runs
+        #[arg(long, value_name = "DIR")]
+        layout_cache_dir: Option<PathBuf>,
+
+        /// Maximum number of nodes accepted by the parser (default 1000)
+        #[arg(long, value_name = "N")]
+        max_nodes: Option<usize>,
+
+        /// Maximum number of edges accepted by the parser (default 5000)
+        #[arg(long, value_name = "N")]
+        max_edges: Option<usize>,
+
+        /// Print the time spent in each compilation phase
+        #[arg(long)]
+        profile: bool,
+
+        /// Theme, overriding the document's `theme:` front matter
+        #[arg(long, value_enum, default_value = "auto")]
+        theme: ThemeChoice,
+
+        /// Derive element ids from DSL ids so recompiles keep the same ids
+        #[arg(long)]
+        stable_ids: bool,
+
+        /// Write one .excalidraw file per top-level container into this
+        /// directory, plus index.excalidraw with the connections between them
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "validate", "dry_run"])]
+        split_containers: Option<PathBuf>,
+
+        /// Emit only the elements array instead of the full file, written to
+        /// the output path or stdout
+        #[arg(long, conflicts_with_all = ["validate", "dry_run", "split_containers"])]
+        elements_only: bool,
+
+        /// Compute every layout from scratch instead of reusing cached ones
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Delete cached layouts, including those in --layout-cache-dir, before compiling
+        #[arg(long)]
+        clear_cache: bool,
+
+        /// Check the generated Excalidraw JSON and fail if it is invalid
+        #[arg(long)]
+        validate_output: bool,
+
+        /// Seed layout, element ids and Excalidraw's randomness so runs with
+        /// the same seed produce identical output
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Preset filling in layout, routing, spacing and theme settings the
+        /// document leaves unset (see `edsl presets`)
+        #[arg(long, value_name = "NAME", value_parser = clap::builder::PossibleValuesParser::new(DiagramPresets::names()))]
+        preset: Option<String>,
+
+        /// Direction the dagre and elk layouts flow in (default LR)
+        #[arg(long, value_enum, ignore_case = true, value_name = "DIR")]
+        layout_direction: Option<LayoutDirection>,
     },
 
+    /// List the presets accepted by `convert --preset`
+    Presets,
+
     /// Run HTTP/WebSocket server for EDSL compilation
     #[command(alias = "serve")]
     Server {
@@ -71,6 +271,28 @@ enum Commands {
         verbose: bool,
     },
 
+    /// Lint EDSL file for diagram style issues
+    Lint {
+        /// Input EDSL file
+        input: PathBuf,
+
+        /// Exit with an error if any warning is reported
+        #[arg(long)]
+        fail_on_warning: bool,
+
+        /// Disable a lint rule by name (can be repeated)
+        #[arg(long = "disable", value_name = "RULE")]
+        disabled_rules: Vec<String>,
+
+        /// Maximum label length before `long-label` fires
+        #[arg(long, default_value = "40")]
+        max_label_length: usize,
+
+        /// Treat the diagram as a DAG and report cycles
+        #[arg(long)]
+        dag: bool,
+    },
+
     /// Validate Excalidraw JSON file
     #[command(alias = "validate-ex")]
     ValidateExcalidraw {
@@ -94,6 +316,20 @@ enum Commands {
         /// Enable verbose output
         #[arg(short, long)]
         verbose: bool,
+
+        /// Run the server and push each rebuild to WebSocket clients for live preview
+        #[arg(long)]
+        serve: bool,
+
+        /// Port for the live preview server
+        #[arg(short, long, default_value = "3002")]
+        port: u16,
+    },
+
+    /// Compare the dagre, force and elk layouts on every .edsl file in a directory
+    Bench {
+        /// Directory of EDSL files
+        dir: PathBuf,
     },
 
     /// Train ML layout models
@@ -147,21 +383,63 @@ impl std::fmt::Display for LayoutAlgorithm {
 }
 
 fn main() {
-    env_logger::init();
-
     let cli = Cli::parse();
+    init_logger(&cli.command);
+    let error_format = cli.error_format;
 
     if let Err(e) = run(cli) {
-        eprintln!("Error: {e}");
+        eprintln!("{}", format_error(e.as_ref(), error_format));
+        process::exit(1);
+    }
+}
 
-        // Print the error chain
-        let mut source = e.source();
-        while let Some(err) = source {
-            eprintln!("  Caused by: {err}");
-            source = err.source();
-        }
+/// Set up `env_logger` from `RUST_LOG`. Training reports its progress through
+/// `log`, so without `RUST_LOG` it shows info from the CLI and the training
+/// modules, or debug with `--verbose`.
+fn init_logger(command: &Commands) {
+    let default_filter = match command {
+        #[cfg(feature = "ml-layout")]
+        Commands::Train { verbose: true, .. } => "edsl=debug,excalidraw_dsl::training=debug",
+        #[cfg(feature = "ml-layout")]
+        Commands::Train { .. } => "edsl=info,excalidraw_dsl::training=info",
+        _ => "error",
+    };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter))
+        .init();
+}
 
-        process::exit(1);
+/// Render a CLI failure for stderr in the requested format
+fn format_error(e: &(dyn std::error::Error + 'static), format: ErrorFormat) -> String {
+    match format {
+        ErrorFormat::Text => {
+            let mut out = format!("Error: {e}");
+
+            // Append the error chain
+            let mut source = e.source();
+            while let Some(err) = source {
+                out.push_str(&format!("\n  Caused by: {err}"));
+                source = err.source();
+            }
+            out
+        }
+        ErrorFormat::Json => {
+            let report = if let Some(err) = e.downcast_ref::<EDSLError>() {
+                err.to_report()
+            } else {
+                ErrorReport {
+                    kind: if e.is::<std::io::Error>() {
+                        "io"
+                    } else {
+                        "other"
+                    }
+                    .to_string(),
+                    message: e.to_string(),
+                    line: None,
+                    column: None,
+                }
+            };
+            serde_json::to_string(&report).unwrap_or_else(|_| report.message.clone())
+        }
     }
 }
 
@@ -170,24 +448,63 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         Commands::Convert {
             input,
             output,
+            format,
             layout,
             validate,
+            dry_run,
             verbose,
             watch,
+            open,
+            dump_igr,
+            layout_cache_dir,
+            max_nodes,
+            max_edges,
+            profile,
+            theme,
+            stable_ids,
+            split_containers,
+            elements_only,
+            no_cache,
+            clear_cache,
+            validate_output,
+            seed,
+            preset,
+            layout_direction,
         } => {
             if watch {
                 run_watch(WatchArgs {
                     input,
                     output,
                     verbose,
+                    serve: false,
+                    #[cfg(feature = "server")]
+                    port: 3002,
                 })
             } else {
                 run_convert(ConvertArgs {
                     input,
                     output,
+                    format,
                     layout,
                     validate,
+                    dry_run,
                     verbose,
+                    open,
+                    dump_igr,
+                    layout_cache_dir,
+                    max_nodes,
+                    max_edges,
+                    profile,
+                    theme,
+                    stable_ids,
+                    split_containers,
+                    elements_only,
+                    no_cache,
+                    clear_cache,
+                    validate_output,
+                    seed,
+                    preset,
+                    layout_direction,
                 })
             }
         }
@@ -200,7 +517,24 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             host,
             verbose,
         }),
+        Commands::Presets => {
+            run_presets();
+            Ok(())
+        }
         Commands::Validate { input, verbose } => run_validate(ValidateArgs { input, verbose }),
+        Commands::Lint {
+            input,
+            fail_on_warning,
+            disabled_rules,
+            max_label_length,
+            dag,
+        } => run_lint(LintArgs {
+            input,
+            fail_on_warning,
+            disabled_rules,
+            max_label_length,
+            dag,
+        }),
         Commands::ValidateExcalidraw { input, verbose } => {
             run_validate_excalidraw(ValidateExcalidrawArgs { input, verbose })
         }
@@ -208,11 +542,19 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             input,
             output,
             verbose,
+            serve,
+            #[cfg(feature = "server")]
+            port,
+            ..
         } => run_watch(WatchArgs {
             input,
             output,
             verbose,
+            serve,
+            #[cfg(feature = "server")]
+            port,
         }),
+        Commands::Bench { dir } => run_bench(&dir),
         #[cfg(feature = "ml-layout")]
         Commands::Train {
             output,
@@ -237,9 +579,68 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
 struct ConvertArgs {
     input: PathBuf,
     output: Option<PathBuf>,
+    format: Option<OutputFormat>,
     layout: LayoutAlgorithm,
     validate: bool,
+    dry_run: bool,
     verbose: bool,
+    open: bool,
+    dump_igr: Option<PathBuf>,
+    layout_cache_dir: Option<PathBuf>,
+    max_nodes: Option<usize>,
+    max_edges: Option<usize>,
+    profile: bool,
+    theme: ThemeChoice,
+    stable_ids: bool,
+    split_containers: Option<PathBuf>,
+    elements_only: bool,
+    no_cache: bool,
+    clear_cache: bool,
+    validate_output: bool,
+    seed: Option<u64>,
+    preset: Option<String>,
+    layout_direction: Option<LayoutDirection>,
+}
+
+/// Create the compiler configured by the convert flags
+fn build_compiler(args: &ConvertArgs) -> EDSLCompiler {
+    let mut builder = EDSLCompiler::builder();
+    if let Some(ref dir) = args.layout_cache_dir {
+        builder = builder.with_layout_cache_dir(dir);
+    }
+    if args.no_cache {
+        builder = builder.with_cache(false);
+    }
+    if let Some(theme) = args.theme.override_theme() {
+        builder = builder.with_theme(theme);
+    }
+    if args.stable_ids {
+        builder = builder.with_stable_ids(true);
+    }
+    if args.validate_output {
+        builder = builder.with_validation(true);
+    }
+    if let Some(seed) = args.seed {
+        builder = builder.with_seed(seed);
+    }
+    if let Some(ref preset) = args.preset {
+        builder = builder.with_preset(preset);
+    }
+    if let Some(direction) = args.layout_direction {
+        builder = builder.with_layout_direction(direction.into());
+    }
+    let mut limits = ParseLimits::default();
+    if let Some(max_nodes) = args.max_nodes {
+        limits.max_nodes = max_nodes;
+    }
+    if let Some(max_edges) = args.max_edges {
+        limits.max_edges = max_edges;
+    }
+    let mut compiler = builder.with_parse_limits(limits).build();
+    if args.clear_cache {
+        compiler.clear_cache();
+    }
+    compiler
 }
 
 fn run_convert(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -249,15 +650,17 @@ fn run_convert(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     // Read input file
     let input_content = std::fs::read_to_string(&args.input).map_err(|e| {
-        format!(
-            "Failed to read input file '{}': {}",
-            args.input.display(),
-            e
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to read input file '{}': {}",
+                args.input.display(),
+                e
+            ),
         )
     })?;
 
-    // Create compiler
-    let mut compiler = EDSLCompiler::new();
+    let mut compiler = build_compiler(&args);
 
     // Validate mode
     if args.validate {
@@ -277,21 +680,90 @@ fn run_convert(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Determine output path
+    // Dry run mode: generate everything, write nothing
+    if args.dry_run {
+        return run_dry_run(&mut compiler, &input_content, args.verbose);
+    }
+
+    // One file per top-level container, plus an index of their connections
+    if let Some(ref dir) = args.split_containers {
+        return run_split_containers(&mut compiler, &input_content, dir, args.verbose);
+    }
+
+    // Bare elements array for pasting into an existing canvas
+    if args.elements_only {
+        return run_elements_only(&mut compiler, &input_content, args.output.as_deref());
+    }
+
+    // Determine output path and format; --format beats the extension
+    let format = args
+        .format
+        .or_else(|| args.output.as_deref().map(OutputFormat::from_path))
+        .unwrap_or(OutputFormat::Excalidraw);
     let output_path = args.output.unwrap_or_else(|| {
         let mut path = args.input.clone();
-        path.set_extension("excalidraw");
+        path.set_extension(format.extension());
         path
     });
+    let excalidraw_input =
+        args.input.extension().and_then(|ext| ext.to_str()) == Some("excalidraw");
+    if excalidraw_input && format != OutputFormat::Edsl {
+        return Err("Excalidraw input can only be converted to EDSL; use --format edsl".into());
+    }
+    if output_path == args.input {
+        return Err(format!(
+            "Output would overwrite the input file '{}'; pass -o",
+            args.input.display()
+        )
+        .into());
+    }
 
     if args.verbose {
-        println!("Compiling EDSL to Excalidraw JSON...");
+        println!("Compiling EDSL to {format:?}...");
         println!("Output file: {}", output_path.display());
         println!("Layout algorithm: {}", args.layout);
     }
 
-    // Compile EDSL
-    let output_json = compiler.compile(&input_content)?;
+    // Exporters other than Excalidraw JSON
+    if let Some(export) = run_export(&compiler, &input_content, format, excalidraw_input)? {
+        if args.profile {
+            print_profile(&export.stats);
+        }
+        if let Some(ref dump_path) = args.dump_igr {
+            write_igr_dump(&export.igr, dump_path, args.verbose)?;
+        }
+        std::fs::write(&output_path, export.content).map_err(|e| {
+            format!(
+                "Failed to write output file '{}': {}",
+                output_path.display(),
+                e
+            )
+        })?;
+        println!(
+            "Generated {}: {}",
+            export.description,
+            output_path.display()
+        );
+        if args.open {
+            open_output(&output_path);
+        }
+        return Ok(());
+    }
+
+    // Compile EDSL, with a spinner and summary on an interactive terminal
+    let interactive = !args.verbose && std::io::stdout().is_terminal();
+    let (output_json, stats) = if interactive {
+        compile_with_spinner(&mut compiler, &input_content)?
+    } else {
+        compiler.compile_with_stats(&input_content)?
+    };
+    if args.profile {
+        print_profile(&stats);
+    }
+
+    if let Some(ref dump_path) = args.dump_igr {
+        write_igr_dump(&compiler.get_igr(&input_content)?, dump_path, args.verbose)?;
+    }
 
     // Write output
     std::fs::write(&output_path, &output_json).map_err(|e| {
@@ -308,10 +780,267 @@ fn run_convert(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     println!("Generated Excalidraw JSON: {}", output_path.display());
+    if interactive {
+        println!("{}", compile_summary(&stats));
+    }
+
+    if args.open {
+        open_output(&output_path);
+    }
+
+    Ok(())
+}
+
+/// A diagram rendered to a format other than Excalidraw JSON
+struct Export {
+    content: String,
+    /// What was generated, for the success message
+    description: &'static str,
+    /// The graph it was rendered from
+    igr: IntermediateGraph,
+    stats: CompileStats,
+}
+
+/// Render a format other than Excalidraw JSON; `None` means the format is
+/// Excalidraw JSON, which the caller compiles itself
+fn run_export(
+    compiler: &EDSLCompiler,
+    input_content: &str,
+    format: OutputFormat,
+    excalidraw_input: bool,
+) -> Result<Option<Export>, Box<dyn std::error::Error>> {
+    let (igr, mut stats) = match format {
+        OutputFormat::Excalidraw => return Ok(None),
+        OutputFormat::Png => {
+            return Err("PNG output is not supported yet; use --format svg".into());
+        }
+        #[cfg(not(feature = "mermaid"))]
+        OutputFormat::Mermaid => {
+            return Err("Mermaid output requires the 'mermaid' feature".into());
+        }
+        OutputFormat::Svg | OutputFormat::Tikz => compiler.get_igr_with_stats(input_content)?,
+        OutputFormat::Edsl if excalidraw_input => {
+            let started = std::time::Instant::now();
+            let document = parse_excalidraw(input_content)?;
+            let parse = started.elapsed();
+            let started = std::time::Instant::now();
+            let igr = IntermediateGraph::from_ast(document)?;
+            let stats = CompileStats {
+                parse,
+                build: started.elapsed(),
+                ..Default::default()
+            };
+            (igr, stats)
+        }
+        // Graph-only formats need no layout
+        _ => compiler.build_igr_with_stats(input_content)?,
+    };
+
+    let started = std::time::Instant::now();
+    let (content, description) = match format {
+        OutputFormat::Svg => (SvgGenerator::generate(&igr)?, "SVG image"),
+        OutputFormat::Tikz => (TikzGenerator::generate(&igr)?, "TikZ picture"),
+        OutputFormat::Dot => (DotGenerator::generate(&igr)?, "Graphviz DOT"),
+        OutputFormat::Graphml => (GraphmlGenerator::generate(&igr)?, "GraphML"),
+        OutputFormat::Edsl if excalidraw_input => (EdslGenerator::generate(&igr)?, "EDSL source"),
+        OutputFormat::Edsl => {
+            // Reformatting source keeps its comments
+            let (_, comments) = parse_edsl_lossless(input_content)?;
+            (
+                EdslGenerator::generate_with_comments(&igr, &comments)?,
+                "EDSL source",
+            )
+        }
+        #[cfg(feature = "mermaid")]
+        OutputFormat::Mermaid => (
+            excalidraw_dsl::mermaid::MermaidGenerator::generate(&igr)?,
+            "Mermaid flowchart",
+        ),
+        _ => unreachable!("{format:?} is handled before building the graph"),
+    };
+    stats.generate = started.elapsed();
+    Ok(Some(Export {
+        content,
+        description,
+        igr,
+        stats,
+    }))
+}
+
+/// Write the intermediate graph as JSON for debugging
+fn write_igr_dump(
+    igr: &IntermediateGraph,
+    dump_path: &std::path::Path,
+    verbose: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dump_json = serde_json::to_string_pretty(&igr.dump())?;
+    std::fs::write(dump_path, dump_json)
+        .map_err(|e| format!("Failed to write IGR dump '{}': {}", dump_path.display(), e))?;
+    if verbose {
+        println!("Wrote intermediate graph dump: {}", dump_path.display());
+    }
+    Ok(())
+}
+
+/// Compile all the way through generation and report what would be written.
+///
+/// Catches generation-only errors (such as unknown shapes) that `--validate`
+/// misses. Lint warnings are reported but don't fail the run.
+fn run_dry_run(
+    compiler: &mut EDSLCompiler,
+    input_content: &str,
+    verbose: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use excalidraw_dsl::lint::{LintConfig, Linter};
+
+    if verbose {
+        println!("Compiling EDSL without writing output...");
+    }
+
+    let elements = compiler.compile_to_elements(input_content)?;
+
+    // Polygon shapes and standalone lines are `line` elements too, so tell
+    // connections apart by whether they attach to anything
+    let texts = elements.iter().filter(|e| e.r#type == "text").count();
+    let connections = elements
+        .iter()
+        .filter(|e| e.r#type == "arrow" || e.start_binding.is_some() || e.end_binding.is_some())
+        .count();
+    println!("✓ Dry run passed: {} elements", elements.len());
+    println!("  - {} shapes", elements.len() - texts - connections);
+    println!("  - {connections} connections");
+    println!("  - {texts} text elements");
+
+    let diagnostics = Linter::new(LintConfig::default()).lint(input_content)?;
+    for diagnostic in &diagnostics {
+        println!("  {diagnostic}");
+    }
+
+    Ok(())
+}
+
+/// Serialize just the generated elements as a JSON array, to `output` or stdout
+fn run_elements_only(
+    compiler: &mut EDSLCompiler,
+    input_content: &str,
+    output: Option<&std::path::Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let elements = compiler.compile_to_elements(input_content)?;
+    let json = serde_json::to_string_pretty(&elements)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, json)
+                .map_err(|e| format!("Failed to write output file '{}': {}", path.display(), e))?;
+            println!("Generated Excalidraw elements: {}", path.display());
+        }
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
+
+/// Write each top-level container to `<dir>/<name>.excalidraw` and the
+/// overview to `<dir>/index.excalidraw`
+fn run_split_containers(
+    compiler: &mut EDSLCompiler,
+    input_content: &str,
+    dir: &std::path::Path,
+    verbose: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if verbose {
+        println!("Splitting containers into: {}", dir.display());
+    }
+
+    let split = compiler.compile_split(input_content)?;
+    std::fs::create_dir_all(dir).map_err(|e| {
+        format!(
+            "Failed to create output directory '{}': {}",
+            dir.display(),
+            e
+        )
+    })?;
+
+    let files = split
+        .containers
+        .iter()
+        .map(|(name, json)| (name.as_str(), json))
+        .chain(std::iter::once((SPLIT_INDEX_NAME, &split.index)));
+    for (name, json) in files {
+        let path = dir.join(format!("{name}.excalidraw"));
+        std::fs::write(&path, json)
+            .map_err(|e| format!("Failed to write output file '{}': {}", path.display(), e))?;
+        println!("Generated Excalidraw JSON: {}", path.display());
+    }
 
     Ok(())
 }
 
+/// Compile behind a spinner naming the current phase. The spinner draws on
+/// stderr and is cleared once compilation ends.
+#[cfg(feature = "progress")]
+fn compile_with_spinner(
+    compiler: &mut EDSLCompiler,
+    input_content: &str,
+) -> excalidraw_dsl::Result<(String, CompileStats)> {
+    let spinner = indicatif::ProgressBar::new_spinner();
+    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+    let result = compiler.compile_with_progress(input_content, |phase| {
+        spinner.set_message(format!("{phase}..."));
+    });
+    spinner.finish_and_clear();
+    result
+}
+
+#[cfg(not(feature = "progress"))]
+fn compile_with_spinner(
+    compiler: &mut EDSLCompiler,
+    input_content: &str,
+) -> excalidraw_dsl::Result<(String, CompileStats)> {
+    compiler.compile_with_stats(input_content)
+}
+
+/// One-line summary of a compile: graph size and total time
+fn compile_summary(stats: &CompileStats) -> String {
+    let plural =
+        |count: usize, noun: &str| format!("{count} {noun}{}", if count == 1 { "" } else { "s" });
+    let time = if stats.cached {
+        "from cache".to_string()
+    } else {
+        format!("in {:.1} ms", stats.total().as_secs_f64() * 1000.0)
+    };
+    format!(
+        "Compiled {}, {} {time}",
+        plural(stats.nodes, "node"),
+        plural(stats.edges, "edge")
+    )
+}
+
+/// Print per-phase compile timings to stderr, keeping stdout for results
+fn print_profile(stats: &CompileStats) {
+    eprintln!("Compile profile:");
+    for (phase, duration) in stats.phases() {
+        eprintln!("  {phase:<10} {:>10.3} ms", duration.as_secs_f64() * 1000.0);
+    }
+    eprintln!(
+        "  {:<10} {:>10.3} ms",
+        "total",
+        stats.total().as_secs_f64() * 1000.0
+    );
+}
+
+/// Open a generated file with the OS default handler.
+///
+/// Failing to open is not a compilation error, so this only reports the problem.
+fn open_output(path: &std::path::Path) {
+    if let Err(e) = open::that(path) {
+        eprintln!(
+            "Could not open '{}' automatically: {e}. Open it manually in Excalidraw.",
+            path.display()
+        );
+    }
+}
+
 struct ServerArgs {
     port: u16,
     host: String,
@@ -342,6 +1071,25 @@ fn run_server(args: ServerArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+fn run_presets() {
+    print!("{}", presets_listing());
+}
+
+/// One line per preset: its name, padded, and description
+fn presets_listing() -> String {
+    let names = DiagramPresets::names();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    names
+        .into_iter()
+        .map(|name| {
+            format!(
+                "{name:<width$}  {}\n",
+                DiagramPresets::description(name).unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
 struct ValidateArgs {
     input: PathBuf,
     verbose: bool,
@@ -354,10 +1102,13 @@ fn run_validate(args: ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     // Read input file
     let input_content = std::fs::read_to_string(&args.input).map_err(|e| {
-        format!(
-            "Failed to read input file '{}': {}",
-            args.input.display(),
-            e
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to read input file '{}': {}",
+                args.input.display(),
+                e
+            ),
         )
     })?;
 
@@ -388,6 +1139,110 @@ fn run_validate(args: ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+struct LintArgs {
+    input: PathBuf,
+    fail_on_warning: bool,
+    disabled_rules: Vec<String>,
+    max_label_length: usize,
+    dag: bool,
+}
+
+fn run_lint(args: LintArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use excalidraw_dsl::lint::{LintConfig, Linter, Severity};
+
+    let input_content = std::fs::read_to_string(&args.input).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to read input file '{}': {}",
+                args.input.display(),
+                e
+            ),
+        )
+    })?;
+
+    let config = LintConfig {
+        disabled_rules: args.disabled_rules.into_iter().collect(),
+        max_label_length: args.max_label_length,
+        require_dag: args.dag,
+        ..Default::default()
+    };
+    let diagnostics = Linter::new(config).lint(&input_content)?;
+
+    for diagnostic in &diagnostics {
+        println!("{}: {diagnostic}", args.input.display());
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .count();
+
+    if diagnostics.is_empty() {
+        println!("✓ No lint issues found");
+    } else {
+        println!("{errors} error(s), {warnings} warning(s)");
+    }
+
+    if errors > 0 || (args.fail_on_warning && warnings > 0) {
+        return Err(format!("Lint failed with {errors} error(s) and {warnings} warning(s)").into());
+    }
+    Ok(())
+}
+
+fn run_bench(dir: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    use excalidraw_dsl::layout::benchmark::compare_engines;
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!("Failed to read directory '{}': {}", dir.display(), e),
+            )
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "edsl"))
+        .collect();
+    paths.sort();
+
+    let mut graphs = Vec::new();
+    for path in &paths {
+        let graph = std::fs::read_to_string(path)
+            .map_err(EDSLError::from)
+            .and_then(|source| parse_edsl(&source))
+            .and_then(IntermediateGraph::from_ast);
+        match graph {
+            Ok(graph) => graphs.push(graph),
+            Err(e) => eprintln!("Skipping {}: {e}", path.display()),
+        }
+    }
+    if graphs.is_empty() {
+        return Err(format!("No .edsl files to benchmark in '{}'", dir.display()).into());
+    }
+
+    println!("Benchmarked {} diagram(s)\n", graphs.len());
+    println!(
+        "{:<8} {:>9} {:>9} {:>14} {:>10} {:>8}",
+        "engine", "crossings", "overlaps", "bbox area", "time (ms)", "failures"
+    );
+    for report in compare_engines(&graphs) {
+        println!(
+            "{:<8} {:>9} {:>9} {:>14.0} {:>10.1} {:>8}",
+            report.engine,
+            report.crossings,
+            report.overlaps,
+            report.bbox_area,
+            report.duration.as_secs_f64() * 1000.0,
+            report.failures
+        );
+    }
+    Ok(())
+}
+
 struct ValidateExcalidrawArgs {
     input: PathBuf,
     verbose: bool,
@@ -400,10 +1255,13 @@ fn run_validate_excalidraw(args: ValidateExcalidrawArgs) -> Result<(), Box<dyn s
 
     // Read input file
     let input_content = std::fs::read_to_string(&args.input).map_err(|e| {
-        format!(
-            "Failed to read input file '{}': {}",
-            args.input.display(),
-            e
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to read input file '{}': {}",
+                args.input.display(),
+                e
+            ),
         )
     })?;
 
@@ -442,6 +1300,10 @@ struct WatchArgs {
     input: PathBuf,
     output: Option<PathBuf>,
     verbose: bool,
+    serve: bool,
+    /// Preview server port for `serve`
+    #[cfg(feature = "server")]
+    port: u16,
 }
 
 #[cfg(feature = "ml-layout")]
@@ -472,6 +1334,16 @@ fn run_watch(args: WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Initial compilation
     compile_file(&args.input, &output_path, args.verbose)?;
 
+    #[cfg(feature = "server")]
+    let preview = args.serve.then(|| start_preview_server(args.port));
+
+    #[cfg(not(feature = "server"))]
+    if args.serve {
+        return Err(
+            "Server feature not enabled. Build with --features server to use --serve.".into(),
+        );
+    }
+
     // Create a channel to receive the events
     let (tx, rx) = channel();
 
@@ -495,6 +1367,11 @@ fn run_watch(args: WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
             Ok(event) => match event.kind {
                 EventKind::Modify(_) | EventKind::Create(_) => {
                     println!("\n📝 File changed, recompiling...");
+                    #[cfg(feature = "server")]
+                    if let Some(ref state) = preview {
+                        reload_preview(state, &args.input, &output_path);
+                        continue;
+                    }
                     match compile_file(&args.input, &output_path, args.verbose) {
                         Ok(_) => println!("✓ Compilation successful"),
                         Err(e) => eprintln!("✗ Compilation failed: {e}"),
@@ -510,6 +1387,62 @@ fn run_watch(args: WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+/// Start the HTTP/WebSocket server on a background thread for `watch --serve`
+#[cfg(feature = "server")]
+fn start_preview_server(port: u16) -> excalidraw_dsl::server::AppState {
+    let state = excalidraw_dsl::server::AppState::new();
+    let server_state = state.clone();
+
+    std::thread::spawn(move || {
+        let result = tokio::runtime::Runtime::new()
+            .map_err(excalidraw_dsl::EDSLError::Io)
+            .and_then(|runtime| {
+                runtime.block_on(excalidraw_dsl::server::start_server(port, server_state))
+            });
+        if let Err(e) = result {
+            eprintln!("✗ Preview server stopped: {e}");
+        }
+    });
+
+    println!("Live preview on ws://localhost:{port}/api/ws");
+    state
+}
+
+/// Recompile a watched file, push the result to preview clients and write the
+/// output on success
+#[cfg(feature = "server")]
+fn reload_preview(
+    state: &excalidraw_dsl::server::AppState,
+    input_path: &PathBuf,
+    output_path: &PathBuf,
+) {
+    use excalidraw_dsl::server::websocket::WebSocketResponse;
+
+    let input_content = match std::fs::read_to_string(input_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("✗ Failed to read {}: {e}", input_path.display());
+            return;
+        }
+    };
+
+    match state.publish_update(&input_path.display().to_string(), &input_content) {
+        WebSocketResponse::DiagramUpdate { data, .. } => {
+            let written = serde_json::to_string_pretty(&data)
+                .map_err(|e| e.to_string())
+                .and_then(|json| std::fs::write(output_path, json).map_err(|e| e.to_string()));
+            match written {
+                Ok(()) => println!("✓ Compilation successful, preview updated"),
+                Err(e) => eprintln!("✗ Failed to write output: {e}"),
+            }
+        }
+        WebSocketResponse::DiagramError { error, .. } => {
+            eprintln!("✗ Compilation failed: {error}")
+        }
+        _ => {}
+    }
+}
+
 fn compile_file(
     input_path: &PathBuf,
     output_path: &PathBuf,
@@ -546,15 +1479,16 @@ fn count_elements_in_json(json: &str) -> usize {
 fn run_train(args: TrainArgs) -> Result<(), Box<dyn std::error::Error>> {
     use excalidraw_dsl::training::{config::TrainingConfig, TrainingOrchestrator};
 
-    if args.verbose {
-        println!("🚀 Starting ML Layout Training");
-        println!("Output directory: {}", args.output.display());
-        println!("Configuration: {}", args.config);
-        println!("Phases: {}", args.phases);
-        println!("Samples: {}", args.samples);
-        if args.m4_optimize {
-            println!("🍎 Apple M4 optimizations enabled");
-        }
+    // Progress goes through `log`; see `init_logger` for what is shown
+    log::info!(
+        "Starting ML layout training: output {}, config {}, phases {}, {} samples",
+        args.output.display(),
+        args.config,
+        args.phases,
+        args.samples
+    );
+    if args.m4_optimize {
+        log::info!("Apple M4 optimizations enabled");
     }
 
     // Create output directory
@@ -568,7 +1502,7 @@ fn run_train(args: TrainArgs) -> Result<(), Box<dyn std::error::Error>> {
         "quick-test" => TrainingConfig::quick_test(),
         "m4-optimized" => TrainingConfig::m4_optimized(),
         _ => {
-            eprintln!("Unknown config '{}'. Using quick-test.", args.config);
+            log::warn!("Unknown config '{}'. Using quick-test.", args.config);
             TrainingConfig::quick_test()
         }
     };
@@ -583,76 +1517,32 @@ fn run_train(args: TrainArgs) -> Result<(), Box<dyn std::error::Error>> {
         config.data_config.num_samples = args.samples;
     }
 
-    // Skip evaluation if requested
-    if args.skip_eval {
-        // Skip evaluation phase (handled by phase selection)
-    }
-
-    if args.verbose {
-        println!("\n📋 Training Configuration:");
-        println!("  Data samples: {}", config.data_config.num_samples);
-        println!("  GNN epochs: {}", config.gnn_config.model.max_epochs);
-        println!("  RL episodes: {}", config.rl_config.num_episodes);
-        println!(
-            "  Constraint epochs: {}",
-            config.constraint_config.model.max_epochs
-        );
-        println!(
-            "  Enhanced epochs: {}",
-            config.enhanced_config.num_integration_epochs
-        );
-        println!("  Device: {:?}", config.device);
-    }
-
-    // Create and run training orchestrator
+    log::debug!(
+        "Training configuration: {} data samples, {} GNN epochs, {} RL episodes, \
+         {} constraint epochs, {} enhanced epochs, device {:?}",
+        config.data_config.num_samples,
+        config.gnn_config.model.max_epochs,
+        config.rl_config.num_episodes,
+        config.constraint_config.model.max_epochs,
+        config.enhanced_config.num_integration_epochs,
+        config.device
+    );
+
+    // Create and run training orchestrator; each phase logs its own start
     let mut orchestrator = TrainingOrchestrator::new(config)?;
-
-    println!("\n🎯 Starting training pipeline...");
-
-    // Run training phases
     for phase in &requested_phases {
         match *phase {
-            "data" => {
-                if args.verbose {
-                    println!("\n📊 Running data generation phase...");
-                }
-                orchestrator.run_data_generation()?;
-            }
-            "gnn" => {
-                if args.verbose {
-                    println!("\n🧠 Running GNN training phase...");
-                }
-                orchestrator.run_gnn_training()?;
-            }
-            "rl" => {
-                if args.verbose {
-                    println!("\n🎮 Running RL training phase...");
-                }
-                orchestrator.run_rl_training()?;
-            }
-            "constraint" => {
-                if args.verbose {
-                    println!("\n🧩 Running constraint training phase...");
-                }
-                orchestrator.run_constraint_training()?;
-            }
-            "enhanced" => {
-                if args.verbose {
-                    println!("\n⚡ Running enhanced model training phase...");
-                }
-                orchestrator.run_enhanced_training()?;
-            }
+            "data" => orchestrator.run_data_generation()?,
+            "gnn" => orchestrator.run_gnn_training()?,
+            "rl" => orchestrator.run_rl_training()?,
+            "constraint" => orchestrator.run_constraint_training()?,
+            "enhanced" => orchestrator.run_enhanced_training()?,
             "eval" => {
                 if !args.skip_eval {
-                    if args.verbose {
-                        println!("\n📈 Running evaluation phase...");
-                    }
                     orchestrator.run_evaluation()?;
                 }
             }
-            _ => {
-                eprintln!("⚠️  Unknown phase '{phase}', skipping...");
-            }
+            _ => log::warn!("Unknown phase '{phase}', skipping"),
         }
     }
 
@@ -663,41 +1553,37 @@ fn run_train(args: TrainArgs) -> Result<(), Box<dyn std::error::Error>> {
     orchestrator.save_metrics(&metrics_path)?;
     orchestrator.generate_report(&report_path)?;
 
-    println!("\n✅ Training completed successfully!");
-    println!("📊 Metrics saved to: {}", metrics_path.display());
-    println!("📄 Report generated: {}", report_path.display());
-    println!("🗂️  Models saved in: {}", args.output.display());
+    println!("✓ Training completed");
+    println!("  Metrics: {}", metrics_path.display());
+    println!("  Report: {}", report_path.display());
+    println!("  Models: {}", args.output.join("models").display());
 
     if args.verbose {
-        println!("\n📋 Training Summary:");
         if let Some(metrics) = orchestrator.get_metrics() {
             if let Some(ref data_stats) = metrics.data_generation {
-                println!(
-                    "  📊 Generated {} training samples",
-                    data_stats.total_samples
-                );
+                println!("  Training samples: {}", data_stats.total_samples);
             }
             if let Some(ref gnn_results) = metrics.gnn_training {
-                println!("  🧠 GNN final loss: {:.6}", gnn_results.final_loss);
+                println!("  GNN final loss: {:.6}", gnn_results.final_loss);
             }
             if let Some(ref rl_results) = metrics.rl_training {
-                println!("  🎮 RL average reward: {:.3}", rl_results.avg_reward);
+                println!("  RL average reward: {:.3}", rl_results.avg_reward);
             }
             if let Some(ref constraint_results) = metrics.constraint_training {
                 println!(
-                    "  🧩 Constraint accuracy: {:.1}%",
+                    "  Constraint accuracy: {:.1}%",
                     constraint_results.accuracy * 100.0
                 );
             }
             if let Some(ref enhanced_results) = metrics.enhanced_training {
                 println!(
-                    "  ⚡ Enhanced quality score: {:.3}",
+                    "  Enhanced quality score: {:.3}",
                     enhanced_results.quality_score
                 );
             }
             if let Some(ref eval_results) = metrics.evaluation {
                 println!(
-                    "  📈 Overall evaluation score: {:.3}",
+                    "  Overall evaluation score: {:.3}",
                     eval_results.overall_score
                 );
             }
@@ -739,8 +1625,26 @@ user -> api -> db
             input: input_file.path().to_path_buf(),
             output: Some(output_file.path().to_path_buf()),
             layout: LayoutAlgorithm::Dagre,
+            format: None,
             validate: false,
+            dry_run: false,
             verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
         };
 
         // Run the CLI
@@ -756,6 +1660,804 @@ user -> api -> db
         assert!(output_content.contains("rectangle"));
     }
 
+    #[test]
+    fn test_cli_dump_igr() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\n").unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let dump_file = NamedTempFile::new().unwrap();
+
+        let args = |format| ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: Some(output_file.path().to_path_buf()),
+            layout: LayoutAlgorithm::Dagre,
+            format,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: Some(dump_file.path().to_path_buf()),
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        run_convert(args(None)).unwrap();
+
+        let dump: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dump_file.path()).unwrap()).unwrap();
+        let nodes = dump["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 2);
+        for node in nodes {
+            assert!(node["x"].is_number());
+            assert!(node["width"].as_f64().unwrap() > 0.0);
+        }
+        // Layout separates the two nodes
+        assert_ne!(
+            (nodes[0]["x"].as_f64(), nodes[0]["y"].as_f64()),
+            (nodes[1]["x"].as_f64(), nodes[1]["y"].as_f64())
+        );
+        assert_eq!(dump["edges"][0]["from"], "a");
+        assert_eq!(dump["edges"][0]["to"], "b");
+
+        // Other output formats write the dump too
+        fs::write(dump_file.path(), "").unwrap();
+        run_convert(args(Some(OutputFormat::Svg))).unwrap();
+        let dump: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dump_file.path()).unwrap()).unwrap();
+        assert_eq!(dump["nodes"].as_array().unwrap().len(), 2);
+        assert!(fs::read_to_string(output_file.path())
+            .unwrap()
+            .starts_with("<svg"));
+    }
+
+    #[test]
+    fn test_cli_json_error_format() {
+        let cli = Cli::try_parse_from(["edsl", "convert", "x.edsl", "--error-format", "json"]);
+        assert_eq!(cli.unwrap().error_format, ErrorFormat::Json);
+
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "a[A]\na -> -> b\n").unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let args = |input: PathBuf| ConvertArgs {
+            input,
+            output: Some(output_file.path().to_path_buf()),
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+
+        let err = run_convert(args(input_file.path().to_path_buf())).unwrap_err();
+        let report: serde_json::Value =
+            serde_json::from_str(&format_error(err.as_ref(), ErrorFormat::Json)).unwrap();
+        assert_eq!(report["kind"], "parse");
+        assert_eq!(report["line"], 2);
+        assert!(report["column"].is_number());
+        assert!(report["message"].as_str().is_some_and(|m| !m.is_empty()));
+
+        let err = run_convert(args(PathBuf::from("/nonexistent/diagram.edsl"))).unwrap_err();
+        let report: serde_json::Value =
+            serde_json::from_str(&format_error(err.as_ref(), ErrorFormat::Json)).unwrap();
+        assert_eq!(report["kind"], "io");
+        assert!(report["line"].is_null());
+    }
+
+    #[test]
+    fn test_cli_tex_output() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\n").unwrap();
+        let output_file = tempfile::Builder::new().suffix(".tex").tempfile().unwrap();
+
+        let args = ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: Some(output_file.path().to_path_buf()),
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        run_convert(args).unwrap();
+
+        let tex = fs::read_to_string(output_file.path()).unwrap();
+        assert!(tex.contains("\\begin{tikzpicture}"));
+        assert_eq!(tex.matches("\\node[draw").count(), 2);
+    }
+
+    #[test]
+    fn test_cli_theme_overrides_document() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "---\ntheme: light\n---\na[A]\nb[B]\na -> b\n").unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+
+        let run = |theme| {
+            let args = ConvertArgs {
+                input: input_file.path().to_path_buf(),
+                output: Some(output_file.path().to_path_buf()),
+                layout: LayoutAlgorithm::Dagre,
+                format: None,
+                validate: false,
+                dry_run: false,
+                verbose: false,
+                open: false,
+                dump_igr: None,
+                layout_cache_dir: None,
+                max_nodes: None,
+                max_edges: None,
+                profile: false,
+                theme,
+                stable_ids: false,
+                split_containers: None,
+                elements_only: false,
+                no_cache: false,
+                clear_cache: false,
+                validate_output: false,
+                seed: None,
+                preset: None,
+                layout_direction: None,
+            };
+            run_convert(args).unwrap();
+            let output = fs::read_to_string(output_file.path()).unwrap();
+            serde_json::from_str::<serde_json::Value>(&output).unwrap()
+        };
+
+        let dark = run(ThemeChoice::Dark);
+        assert_eq!(dark["appState"]["viewBackgroundColor"], "#121212");
+        assert!(dark["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|e| e["strokeColor"] != "#000000"));
+
+        let auto = run(ThemeChoice::Auto);
+        assert_eq!(auto["appState"]["viewBackgroundColor"], "#ffffff");
+    }
+
+    #[test]
+    fn test_cli_preset() {
+        let cli = Cli::try_parse_from(["edsl", "convert", "in.edsl", "--preset", "presentation"])
+            .unwrap();
+        match cli.command {
+            Commands::Convert { preset, .. } => assert_eq!(preset.as_deref(), Some("presentation")),
+            _ => panic!("expected convert"),
+        }
+        assert!(Cli::try_parse_from(["edsl", "convert", "in.edsl", "--preset", "nope"]).is_err());
+        // Watch mode compiles without overrides, so it rejects them
+        for flag in [
+            &["--preset", "presentation"][..],
+            &["--theme", "dark"],
+            &["--stable-ids"],
+            &["--seed", "7"],
+            &["--layout-direction", "lr"],
+        ] {
+            let args = [&["edsl", "convert", "in.edsl"][..], flag].concat();
+            assert!(Cli::try_parse_from(&args).is_ok(), "{flag:?}");
+            let args = [&args[..], &["--watch"]].concat();
+            assert!(Cli::try_parse_from(args).is_err(), "{flag:?}");
+        }
+        assert!(Cli::try_parse_from(["edsl", "convert", "in.edsl", "--watch"]).is_ok());
+        assert!(matches!(
+            Cli::try_parse_from(["edsl", "presets"]).unwrap().command,
+            Commands::Presets
+        ));
+        let listing = presets_listing();
+        assert_eq!(listing.lines().count(), DiagramPresets::names().len());
+        assert!(listing.contains("presentation   Roomy layout on a dark canvas, for slides\n"));
+
+        let mut compiler = build_compiler(&ConvertArgs {
+            input: PathBuf::from("in.edsl"),
+            output: None,
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: Some("presentation".to_string()),
+            layout_direction: None,
+        });
+        let output = compiler.compile("a[A]\nb[B]\na -> b\n").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["appState"]["viewBackgroundColor"], "#121212");
+    }
+
+    #[test]
+    fn test_cli_layout_direction() {
+        let offsets = |direction: &str, layout: &str| {
+            let cli = Cli::try_parse_from([
+                "edsl",
+                "convert",
+                "in.edsl",
+                "--layout-direction",
+                direction,
+            ])
+            .unwrap();
+            let Commands::Convert {
+                layout_direction, ..
+            } = cli.command
+            else {
+                panic!("expected convert");
+            };
+            let compiler = build_compiler(&ConvertArgs {
+                input: PathBuf::from("in.edsl"),
+                output: None,
+                layout: LayoutAlgorithm::Dagre,
+                format: None,
+                validate: false,
+                dry_run: false,
+                verbose: false,
+                open: false,
+                dump_igr: None,
+                layout_cache_dir: None,
+                max_nodes: None,
+                max_edges: None,
+                profile: false,
+                theme: ThemeChoice::Auto,
+                stable_ids: false,
+                split_containers: None,
+                elements_only: false,
+                no_cache: false,
+                clear_cache: false,
+                validate_output: false,
+                seed: None,
+                preset: None,
+                layout_direction,
+            });
+            let source = format!("---\nlayout: {layout}\n---\na[A]\nb[B]\nc[C]\na -> b -> c\n");
+            let igr = compiler.get_igr(&source).unwrap();
+            let node = |id: &str| igr.get_node_by_id(id).unwrap().1.clone();
+            let (a, c) = (node("a"), node("c"));
+            (c.x - a.x, c.y - a.y)
+        };
+
+        for layout in ["dagre", "elk"] {
+            let (dx, dy) = offsets("TB", layout);
+            assert!(dy > 0.0 && dx.abs() < dy, "{layout}: ({dx}, {dy})");
+            let (dx, dy) = offsets("LR", layout);
+            assert!(dx > 0.0 && dy.abs() < dx, "{layout}: ({dx}, {dy})");
+            let (dx, dy) = offsets("bt", layout);
+            assert!(dy < 0.0 && dx.abs() < -dy, "{layout}: ({dx}, {dy})");
+        }
+        assert!(
+            Cli::try_parse_from(["edsl", "convert", "in.edsl", "--layout-direction", "up"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_graphml_output() {
+        let input_file = NamedTempFile::new().unwrap();
+        // A cycle, which dagre would reject, since GraphML skips layout
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\nb -> a\n").unwrap();
+        let output_file = tempfile::Builder::new()
+            .suffix(".graphml")
+            .tempfile()
+            .unwrap();
+
+        let args = ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: Some(output_file.path().to_path_buf()),
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        run_convert(args).unwrap();
+
+        let xml = fs::read_to_string(output_file.path()).unwrap();
+        assert!(xml.contains("<graphml"));
+        assert_eq!(xml.matches("<node ").count(), 2);
+        assert!(xml.contains(r#"<edge source="b" target="a"/>"#));
+    }
+
+    #[test]
+    fn test_cli_format_overrides_extension() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\n").unwrap();
+        let output_file = tempfile::Builder::new().suffix(".txt").tempfile().unwrap();
+
+        let args = ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: Some(output_file.path().to_path_buf()),
+            format: Some(OutputFormat::Svg),
+            layout: LayoutAlgorithm::Dagre,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        run_convert(args).unwrap();
+
+        let svg = fs::read_to_string(output_file.path()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">A</tspan>"));
+
+        let cli = Cli::try_parse_from(["edsl", "convert", "in.edsl", "--format", "dot"]).unwrap();
+        match cli.command {
+            Commands::Convert { format, .. } => assert_eq!(format, Some(OutputFormat::Dot)),
+            _ => panic!("Expected convert command"),
+        }
+        assert_eq!(
+            OutputFormat::from_path(std::path::Path::new("out.gv")),
+            OutputFormat::Dot
+        );
+    }
+
+    #[test]
+    fn test_compile_summary() {
+        let mut compiler = EDSLCompiler::new();
+        let (_, stats) = compiler
+            .compile_with_stats("a[A]\nb[B]\nc[C]\na -> b\nb -> c\na -> c\n")
+            .unwrap();
+        assert_eq!((stats.nodes, stats.edges), (3, 3));
+        let summary = compile_summary(&stats);
+        assert!(summary.starts_with("Compiled 3 nodes, 3 edges in "));
+        assert!(summary.ends_with(" ms"));
+
+        let single = CompileStats {
+            nodes: 1,
+            edges: 0,
+            cached: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            compile_summary(&single),
+            "Compiled 1 node, 0 edges from cache"
+        );
+    }
+
+    #[test]
+    fn test_cli_seed_reproduces_output() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(
+            &input_file,
+            "---\nlayout: force\n---\ngroup \"Team\" {\n  a[A]\n  b[B]\n}\nc[C]\na -> b: calls\nb -> c\n",
+        )
+        .unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+
+        let convert = |name: &str, seed: Option<u64>| {
+            let output = output_dir.path().join(name);
+            run_convert(ConvertArgs {
+                input: input_file.path().to_path_buf(),
+                output: Some(output.clone()),
+                format: None,
+                layout: LayoutAlgorithm::Dagre,
+                validate: false,
+                dry_run: false,
+                verbose: false,
+                open: false,
+                dump_igr: None,
+                layout_cache_dir: None,
+                max_nodes: None,
+                max_edges: None,
+                profile: false,
+                theme: ThemeChoice::Auto,
+                stable_ids: false,
+                split_containers: None,
+                elements_only: false,
+                no_cache: true,
+                clear_cache: false,
+                validate_output: false,
+                seed,
+                preset: None,
+                layout_direction: None,
+            })
+            .unwrap();
+            fs::read_to_string(output).unwrap()
+        };
+
+        let first = convert("first.excalidraw", Some(42));
+        assert_eq!(first, convert("again.excalidraw", Some(42)));
+        assert_ne!(first, convert("other.excalidraw", Some(7)));
+        // Unseeded runs stay random
+        assert_ne!(
+            convert("random1.excalidraw", None),
+            convert("random2.excalidraw", None)
+        );
+    }
+
+    #[test]
+    fn test_cli_excalidraw_to_edsl() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("diagram.edsl");
+        fs::write(
+            &source,
+            "container \"Backend\" as backend {\n  api[API]\n  db[Database]\n}\nuser[User]\nuser -> api: calls\napi -> db\n",
+        )
+        .unwrap();
+
+        let convert = |input: PathBuf, output: PathBuf| {
+            run_convert(ConvertArgs {
+                input,
+                output: Some(output),
+                format: None,
+                layout: LayoutAlgorithm::Dagre,
+                validate: false,
+                dry_run: false,
+                verbose: false,
+                open: false,
+                dump_igr: None,
+                layout_cache_dir: None,
+                max_nodes: None,
+                max_edges: None,
+                profile: false,
+                theme: ThemeChoice::Auto,
+                stable_ids: false,
+                split_containers: None,
+                elements_only: false,
+                no_cache: true,
+                clear_cache: false,
+                validate_output: false,
+                seed: None,
+                preset: None,
+                layout_direction: None,
+            })
+        };
+        let drawing = dir.path().join("diagram.excalidraw");
+        convert(source, drawing.clone()).unwrap();
+        let round_trip = dir.path().join("round_trip.edsl");
+        convert(drawing.clone(), round_trip.clone()).unwrap();
+
+        let edsl = fs::read_to_string(&round_trip).unwrap();
+        let igr = EDSLCompiler::new().build_igr(&edsl).unwrap();
+        let nodes = igr
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container)
+            .count();
+        assert_eq!((nodes, igr.graph.edge_count()), (3, 2));
+        assert_eq!(igr.containers[0].children.len(), 2);
+
+        // Excalidraw input can't go anywhere but EDSL
+        assert!(convert(drawing, dir.path().join("copy.excalidraw")).is_err());
+    }
+
+    #[test]
+    fn test_cli_dry_run_catches_generation_errors() {
+        let input_file = NamedTempFile::new().unwrap();
+        // Unknown shapes only fail once elements are generated
+        fs::write(&input_file, "a[A] { shape: blob; }\nb[B]\na -> b\n").unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("out.excalidraw");
+
+        let args = |validate: bool, dry_run: bool| ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: Some(output_path.clone()),
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate,
+            dry_run,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        assert!(run_convert(args(true, false)).is_ok());
+        assert!(run_convert(args(false, true)).is_err());
+
+        // A valid document passes without writing anything
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\n").unwrap();
+        assert!(run_convert(args(false, true)).is_ok());
+        assert!(!output_path.exists());
+
+        let cli = Cli::try_parse_from(["edsl", "convert", "diagram.edsl", "--dry-run"]).unwrap();
+        match cli.command {
+            Commands::Convert { dry_run, .. } => assert!(dry_run),
+            _ => panic!("Expected convert command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_elements_only() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\n").unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("elements.json");
+
+        let args = ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: Some(output_path.clone()),
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: true,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        run_convert(args).unwrap();
+
+        let content = fs::read_to_string(&output_path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let elements = json.as_array().expect("top-level elements array");
+        assert!(!elements.is_empty());
+        assert!(elements.iter().all(|e| e["type"].is_string()));
+        assert_eq!(elements.iter().filter(|e| e["type"] == "arrow").count(), 1);
+
+        let cli =
+            Cli::try_parse_from(["edsl", "convert", "diagram.edsl", "--elements-only"]).unwrap();
+        match cli.command {
+            Commands::Convert { elements_only, .. } => assert!(elements_only),
+            _ => panic!("Expected convert command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_cache_flags() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let mut args = ConvertArgs {
+            input: PathBuf::from("diagram.edsl"),
+            output: None,
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: Some(cache_dir.path().to_path_buf()),
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: None,
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+
+        let mut compiler = build_compiler(&args);
+        assert!(compiler.layout_manager().is_cache_enabled());
+        compiler.compile("a[A]\nb[B]\na -> b\n").unwrap();
+        assert_eq!(fs::read_dir(cache_dir.path()).unwrap().count(), 1);
+
+        args.no_cache = true;
+        assert!(!build_compiler(&args).layout_manager().is_cache_enabled());
+
+        args.clear_cache = true;
+        build_compiler(&args);
+        assert_eq!(fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+
+        let cli = Cli::try_parse_from(["edsl", "convert", "diagram.edsl", "--no-cache"]).unwrap();
+        match cli.command {
+            Commands::Convert { no_cache, .. } => assert!(no_cache),
+            _ => panic!("Expected convert command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_split_containers() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(
+            &input_file,
+            r#"
+container "Frontend" as frontend {
+  web[Web App]
+  mobile[Mobile App]
+  web -> mobile
+}
+container "Back End" {
+  api[API]
+  db[Database]
+  api -> db
+}
+user[User]
+user -> web
+web -> api
+mobile -> api
+"#,
+        )
+        .unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+
+        let args = ConvertArgs {
+            input: input_file.path().to_path_buf(),
+            output: None,
+            layout: LayoutAlgorithm::Dagre,
+            format: None,
+            validate: false,
+            dry_run: false,
+            verbose: false,
+            open: false,
+            dump_igr: None,
+            layout_cache_dir: None,
+            max_nodes: None,
+            max_edges: None,
+            profile: false,
+            theme: ThemeChoice::Auto,
+            stable_ids: false,
+            split_containers: Some(output_dir.path().to_path_buf()),
+            elements_only: false,
+            no_cache: false,
+            clear_cache: false,
+            validate_output: false,
+            seed: None,
+            preset: None,
+            layout_direction: None,
+        };
+        run_convert(args).unwrap();
+
+        let labels = |name: &str| {
+            let content = fs::read_to_string(output_dir.path().join(name)).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+            let mut labels: Vec<String> = json["elements"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|e| e["type"] == "text")
+                .map(|e| e["text"].as_str().unwrap().to_string())
+                .collect();
+            labels.sort();
+            labels
+        };
+        let mut files: Vec<String> = fs::read_dir(output_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                "Back_End.excalidraw",
+                "frontend.excalidraw",
+                "index.excalidraw"
+            ]
+        );
+
+        assert_eq!(
+            labels("frontend.excalidraw"),
+            ["Frontend", "Mobile App", "Web App"]
+        );
+        assert_eq!(
+            labels("Back_End.excalidraw"),
+            ["API", "Back End", "Database"]
+        );
+
+        // The index collapses each container into one node
+        let index = fs::read_to_string(output_dir.path().join("index.excalidraw")).unwrap();
+        let index: serde_json::Value = serde_json::from_str(&index).unwrap();
+        let elements = index["elements"].as_array().unwrap();
+        assert_eq!(labels("index.excalidraw"), ["Back End", "Frontend", "User"]);
+        assert_eq!(elements.iter().filter(|e| e["type"] == "arrow").count(), 2);
+    }
+
+    #[test]
+    fn test_cli_open_flag_parsing() {
+        let cli = Cli::try_parse_from(["edsl", "convert", "diagram.edsl", "--open"]).unwrap();
+        match cli.command {
+            Commands::Convert { open, .. } => assert!(open),
+            _ => panic!("Expected convert command"),
+        }
+
+        let cli = Cli::try_parse_from(["edsl", "convert", "diagram.edsl"]).unwrap();
+        match cli.command {
+            Commands::Convert { open, .. } => assert!(!open),
+            _ => panic!("Expected convert command"),
+        }
+    }
+
     #[test]
     fn test_cli_validation_mode() {
         let edsl_content = r#"
@@ -776,9 +2478,77 @@ user -> api -> db
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_cli_lint_fail_on_warning() {
+        let input_file = NamedTempFile::new().unwrap();
+        fs::write(&input_file, "a[A]\nlonely[Lonely]\n").unwrap();
+
+        let args = |fail_on_warning| LintArgs {
+            input: input_file.path().to_path_buf(),
+            fail_on_warning,
+            disabled_rules: vec![],
+            max_label_length: 40,
+            dag: false,
+        };
+
+        assert!(run_lint(args(false)).is_ok());
+        assert!(run_lint(args(true)).is_err());
+    }
+
     #[test]
     fn test_layout_algorithm_display() {
         assert_eq!(format!("{}", LayoutAlgorithm::Dagre), "dagre");
         assert_eq!(format!("{}", LayoutAlgorithm::Force), "force");
     }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn test_watch_serve_reload_broadcasts() {
+        let input_file = NamedTempFile::new().unwrap();
+        let output_file = NamedTempFile::new().unwrap();
+        let state = excalidraw_dsl::server::AppState::new();
+        let mut updates = state.subscribe_updates();
+
+        // A save with valid content pushes the new diagram and writes the output
+        fs::write(&input_file, "a[A]\nb[B]\na -> b\n").unwrap();
+        reload_preview(
+            &state,
+            &input_file.path().to_path_buf(),
+            &output_file.path().to_path_buf(),
+        );
+        let frame: serde_json::Value = serde_json::from_str(&updates.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["type"], "diagram_update");
+        assert!(frame["data"]["elements"].is_array());
+        assert!(fs::read_to_string(&output_file)
+            .unwrap()
+            .contains("elements"));
+
+        // A broken save pushes an error frame instead
+        fs::write(&input_file, "a -> -> b\n").unwrap();
+        reload_preview(
+            &state,
+            &input_file.path().to_path_buf(),
+            &output_file.path().to_path_buf(),
+        );
+        let frame: serde_json::Value = serde_json::from_str(&updates.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["type"], "diagram_error");
+        assert!(frame["error"].as_str().is_some_and(|e| !e.is_empty()));
+    }
+
+    #[test]
+    fn test_cli_bench_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(run_bench(dir.path()).is_err());
+
+        fs::write(dir.path().join("chain.edsl"), "a[A]\nb[B]\na -> b").unwrap();
+        fs::write(dir.path().join("broken.edsl"), "a -> ").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a diagram").unwrap();
+        run_bench(dir.path()).unwrap();
+
+        let cli = Cli::try_parse_from(["edsl", "bench", "samples"]).unwrap();
+        match cli.command {
+            Commands::Bench { dir } => assert_eq!(dir, PathBuf::from("samples")),
+            _ => panic!("Expected bench command"),
+        }
+    }
 }
@@ -0,0 +1,576 @@
+// src/mermaid.rs
+//! Import Mermaid diagrams into EDSL documents, and export graphs as Mermaid
+//! flowcharts
+//!
+//! Import currently supports Mermaid `erDiagram` sources. Entities become
+//! nodes whose labels list their attributes, and relationships become
+//! undirected edges labeled with the relationship name and cardinality.
+
+use crate::ast::{
+    ArrowType, AttributeValue, DiagramDefinition, DiagramType, EdgeDefinition, GlobalConfig,
+    NodeDefinition, ParsedDocument,
+};
+use crate::error::{ParseError, Result};
+use crate::graphml::unique_names;
+use crate::igr::IntermediateGraph;
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// A parsed ER entity attribute (`type name PK "comment"`)
+#[derive(Debug, Clone)]
+struct ErAttribute {
+    attr_type: String,
+    name: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ErEntity {
+    name: String,
+    attributes: Vec<ErAttribute>,
+}
+
+/// Parse a Mermaid `erDiagram` into a `ParsedDocument`.
+///
+/// Entities with attributes are rendered as rectangles listing one attribute per
+/// line; entities without attributes are rendered as cylinders. Non-identifying
+/// relationships (`..`) produce dashed edges.
+///
+/// # Example
+///
+/// ```rust
+/// use excalidraw_dsl::mermaid::parse_mermaid_er;
+///
+/// let doc = parse_mermaid_er("erDiagram\n  CUSTOMER ||--o{ ORDER : places").unwrap();
+/// assert_eq!(doc.nodes.len(), 2);
+/// assert_eq!(doc.edges.len(), 1);
+/// ```
+pub fn parse_mermaid_er(input: &str) -> Result<ParsedDocument> {
+    let mut entities: Vec<ErEntity> = Vec::new();
+    let mut entity_index: HashMap<String, usize> = HashMap::new();
+    let mut edges = Vec::new();
+    let mut current_entity: Option<usize> = None;
+    let mut seen_header = false;
+
+    let mut ensure_entity = |name: &str, entities: &mut Vec<ErEntity>| -> usize {
+        *entity_index.entry(name.to_string()).or_insert_with(|| {
+            entities.push(ErEntity {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            entities.len() - 1
+        })
+    };
+
+    for (line_idx, raw_line) in input.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with("%%") {
+            continue;
+        }
+
+        if !seen_header {
+            if line != "erDiagram" {
+                return Err(ParseError::Syntax {
+                    line: line_no,
+                    message: format!("Expected 'erDiagram' header, found '{line}'"),
+                }
+                .into());
+            }
+            seen_header = true;
+            continue;
+        }
+
+        // Inside an entity attribute block
+        if let Some(entity_idx) = current_entity {
+            if line == "}" {
+                current_entity = None;
+            } else {
+                let attribute = parse_er_attribute(line, line_no)?;
+                entities[entity_idx].attributes.push(attribute);
+            }
+            continue;
+        }
+
+        // Entity block opening: `NAME {`
+        if let Some(name) = line.strip_suffix('{') {
+            let name = name.trim();
+            if !is_entity_name(name) {
+                return Err(ParseError::Syntax {
+                    line: line_no,
+                    message: format!("Invalid entity name '{name}'"),
+                }
+                .into());
+            }
+            current_entity = Some(ensure_entity(name, &mut entities));
+            continue;
+        }
+
+        if let Some(relationship) = parse_er_relationship(line, line_no)? {
+            ensure_entity(&relationship.from, &mut entities);
+            ensure_entity(&relationship.to, &mut entities);
+            edges.push(relationship.into_edge());
+            continue;
+        }
+
+        // A bare entity name declares an entity without attributes
+        if is_entity_name(line) {
+            ensure_entity(line, &mut entities);
+            continue;
+        }
+
+        return Err(ParseError::Syntax {
+            line: line_no,
+            message: format!("Unrecognized erDiagram statement '{line}'"),
+        }
+        .into());
+    }
+
+    if current_entity.is_some() {
+        return Err(ParseError::Syntax {
+            line: input.lines().count(),
+            message: "Unclosed entity block".to_string(),
+        }
+        .into());
+    }
+
+    let nodes = entities.into_iter().map(entity_to_node).collect();
+
+    Ok(ParsedDocument {
+        config: GlobalConfig::default(),
+        component_types: HashMap::new(),
+        edge_types: HashMap::new(),
+        templates: HashMap::new(),
+        diagram: Some(DiagramDefinition {
+            name: "erDiagram".to_string(),
+            diagram_type: DiagramType::Database,
+            layout: None,
+            template: None,
+        }),
+        nodes,
+        edges,
+        containers: vec![],
+        groups: vec![],
+        connections: vec![],
+        notes: vec![],
+        lines: vec![],
+        hyperedges: vec![],
+    })
+}
+
+struct ErRelationship {
+    from: String,
+    to: String,
+    left: &'static str,
+    right: &'static str,
+    identifying: bool,
+    label: String,
+}
+
+impl ErRelationship {
+    fn into_edge(self) -> EdgeDefinition {
+        let mut attributes = HashMap::new();
+        if !self.identifying {
+            attributes.insert(
+                "strokeStyle".to_string(),
+                AttributeValue::String("dashed".to_string()),
+            );
+        }
+
+        let cardinality = format!("{}:{}", self.left, self.right);
+        let label = if self.label.is_empty() {
+            cardinality
+        } else {
+            format!("{} ({cardinality})", self.label)
+        };
+
+        EdgeDefinition {
+            from: self.from,
+            to: self.to,
+            label: Some(label),
+            arrow_type: ArrowType::Line,
+            attributes,
+            style: None,
+            waypoints: Vec::new(),
+            position: None,
+        }
+    }
+}
+
+/// Parse `A ||--o{ B : label`; returns `None` when the line is not a relationship
+fn parse_er_relationship(line: &str, line_no: usize) -> Result<Option<ErRelationship>> {
+    let mut parts = line.splitn(2, ':');
+    let head = parts.next().unwrap_or_default().trim();
+    let label = parts
+        .next()
+        .map(|l| l.trim().trim_matches('"').to_string())
+        .unwrap_or_default();
+
+    let tokens: Vec<&str> = head.split_whitespace().collect();
+    if tokens.len() != 3 {
+        return Ok(None);
+    }
+
+    let connector = tokens[1];
+    if connector.len() != 6 || !connector.is_ascii() {
+        return Ok(None);
+    }
+    let (left, rest) = connector.split_at(2);
+    let (line_style, right) = rest.split_at(2);
+
+    let identifying = match line_style {
+        "--" => true,
+        ".." => false,
+        _ => return Ok(None),
+    };
+
+    let invalid = || ParseError::Syntax {
+        line: line_no,
+        message: format!("Invalid relationship cardinality '{connector}'"),
+    };
+    let left = left_cardinality(left).ok_or_else(invalid)?;
+    let right = right_cardinality(right).ok_or_else(invalid)?;
+
+    Ok(Some(ErRelationship {
+        from: tokens[0].to_string(),
+        to: tokens[2].to_string(),
+        left,
+        right,
+        identifying,
+        label,
+    }))
+}
+
+fn left_cardinality(token: &str) -> Option<&'static str> {
+    match token {
+        "||" => Some("1"),
+        "|o" => Some("0..1"),
+        "}|" => Some("1..N"),
+        "}o" => Some("0..N"),
+        _ => None,
+    }
+}
+
+fn right_cardinality(token: &str) -> Option<&'static str> {
+    match token {
+        "||" => Some("1"),
+        "o|" => Some("0..1"),
+        "|{" => Some("1..N"),
+        "o{" => Some("0..N"),
+        _ => None,
+    }
+}
+
+fn parse_er_attribute(line: &str, line_no: usize) -> Result<ErAttribute> {
+    // Drop a trailing quoted comment
+    let without_comment = match line.find('"') {
+        Some(pos) => &line[..pos],
+        None => line,
+    };
+
+    let tokens: Vec<&str> = without_comment.split_whitespace().collect();
+    if tokens.len() < 2 {
+        return Err(ParseError::Syntax {
+            line: line_no,
+            message: format!("Expected '<type> <name>' entity attribute, found '{line}'"),
+        }
+        .into());
+    }
+
+    let keys = tokens[2..]
+        .iter()
+        .flat_map(|t| t.split(','))
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+
+    Ok(ErAttribute {
+        attr_type: tokens[0].to_string(),
+        name: tokens[1].to_string(),
+        keys,
+    })
+}
+
+fn is_entity_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn entity_to_node(entity: ErEntity) -> NodeDefinition {
+    let mut attributes = HashMap::new();
+    let shape = if entity.attributes.is_empty() {
+        "cylinder"
+    } else {
+        "rectangle"
+    };
+    attributes.insert(
+        "shape".to_string(),
+        AttributeValue::String(shape.to_string()),
+    );
+
+    let mut label = entity.name.clone();
+    for attribute in &entity.attributes {
+        label.push('\n');
+        label.push_str(&format!("{}: {}", attribute.name, attribute.attr_type));
+        if !attribute.keys.is_empty() {
+            label.push_str(&format!(" {}", attribute.keys.join(",")));
+        }
+    }
+
+    NodeDefinition {
+        id: entity.name,
+        label: Some(label),
+        component_type: None,
+        attributes,
+    }
+}
+
+pub struct MermaidGenerator;
+
+impl MermaidGenerator {
+    /// Generate a Mermaid `flowchart` for a graph; layout is not needed
+    ///
+    /// Containers become nested `subgraph` blocks and ids that Mermaid can't
+    /// parse have their other characters replaced with `_`, with a numeric
+    /// suffix if that clashes with another id.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use excalidraw_dsl::igr::IntermediateGraph;
+    /// use excalidraw_dsl::mermaid::MermaidGenerator;
+    ///
+    /// let document = excalidraw_dsl::parser::parse_edsl("a[A]\nb[B]\na -> b").unwrap();
+    /// let igr = IntermediateGraph::from_ast(document).unwrap();
+    /// let mermaid = MermaidGenerator::generate(&igr).unwrap();
+    /// assert!(mermaid.contains("a --> b"));
+    /// ```
+    pub fn generate(igr: &IntermediateGraph) -> Result<String> {
+        let owners = crate::graphml::GraphmlGenerator::innermost_containers(igr);
+        let names = unique_names(
+            igr.graph
+                .node_weights()
+                .map(|node| node.id.as_str())
+                .chain(igr.containers.iter().filter_map(|c| c.id.as_deref())),
+            mermaid_id,
+        );
+        let mut out = String::from("flowchart TD\n");
+        Self::write_members(&mut out, igr, &owners, &names, None, 1);
+
+        for edge in igr.graph.edge_references() {
+            let connector = match edge.weight().arrow_type {
+                ArrowType::Line => "---",
+                ArrowType::DoubleArrow => "<-->",
+                ArrowType::DashedArrow | ArrowType::DottedArrow => "-.->",
+                ArrowType::ThickArrow => "==>",
+                ArrowType::SingleArrow | ArrowType::WavyArrow => "-->",
+            };
+            let label = edge
+                .weight()
+                .label
+                .as_deref()
+                .map(|label| format!("|{}|", escape_label(label)))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "  {} {connector}{label} {}",
+                names[igr.graph[edge.source()].id.as_str()],
+                names[igr.graph[edge.target()].id.as_str()]
+            );
+        }
+        Ok(out)
+    }
+
+    fn write_members(
+        out: &mut String,
+        igr: &IntermediateGraph,
+        owners: &HashMap<petgraph::graph::NodeIndex, usize>,
+        names: &HashMap<&str, String>,
+        parent: Option<usize>,
+        depth: usize,
+    ) {
+        let indent = "  ".repeat(depth);
+        for (idx, container) in igr.containers.iter().enumerate() {
+            if container.parent_container != parent {
+                continue;
+            }
+            let id = container
+                .id
+                .as_deref()
+                .map(|id| names[id].clone())
+                .unwrap_or_else(|| format!("container_{idx}"));
+            let label = container.label.as_deref().unwrap_or(&id).to_string();
+            let _ = writeln!(out, "{indent}subgraph {id} [\"{}\"]", escape_label(&label));
+            Self::write_members(out, igr, owners, names, Some(idx), depth + 1);
+            let _ = writeln!(out, "{indent}end");
+        }
+
+        for idx in igr.graph.node_indices() {
+            let node = &igr.graph[idx];
+            if node.is_virtual_container || owners.get(&idx).copied() != parent {
+                continue;
+            }
+            let (open, close) = match node.attributes.shape.as_deref() {
+                Some("ellipse") => ("([", "])"),
+                Some("circle") => ("((", "))"),
+                Some("diamond") => ("{", "}"),
+                Some("cylinder") => ("[(", ")]"),
+                Some("hexagon") => ("{{", "}}"),
+                Some("parallelogram") => ("[/", "/]"),
+                _ => ("[", "]"),
+            };
+            let _ = writeln!(
+                out,
+                "{indent}{}{open}\"{}\"{close}",
+                names[node.id.as_str()],
+                escape_label(&node.label)
+            );
+        }
+    }
+}
+
+/// Mermaid ids are limited to word characters
+fn mermaid_id(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Quote-safe label text, with newlines as `<br>`
+fn escape_label(text: &str) -> String {
+    text.replace('"', "#quot;")
+        .replace('|', "#124;")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_two_entity_er_diagram() {
+        let input = r#"
+erDiagram
+    CUSTOMER ||--o{ ORDER : places
+    CUSTOMER {
+        string name
+        string id PK
+    }
+    ORDER {
+        int orderNumber PK
+        string customerId FK "references CUSTOMER"
+    }
+"#;
+
+        let doc = parse_mermaid_er(input).unwrap();
+
+        assert!(matches!(
+            doc.diagram.as_ref().unwrap().diagram_type,
+            DiagramType::Database
+        ));
+        assert_eq!(doc.nodes.len(), 2);
+
+        let customer = &doc.nodes[0];
+        assert_eq!(customer.id, "CUSTOMER");
+        assert_eq!(
+            customer.label.as_deref(),
+            Some("CUSTOMER\nname: string\nid: string PK")
+        );
+        assert_eq!(
+            customer.attributes.get("shape"),
+            Some(&AttributeValue::String("rectangle".to_string()))
+        );
+
+        let order = &doc.nodes[1];
+        assert_eq!(
+            order.label.as_deref(),
+            Some("ORDER\norderNumber: int PK\ncustomerId: string FK")
+        );
+
+        assert_eq!(doc.edges.len(), 1);
+        let edge = &doc.edges[0];
+        assert_eq!(edge.from, "CUSTOMER");
+        assert_eq!(edge.to, "ORDER");
+        assert_eq!(edge.label.as_deref(), Some("places (1:0..N)"));
+        assert_eq!(edge.arrow_type, ArrowType::Line);
+        assert!(!edge.attributes.contains_key("strokeStyle"));
+    }
+
+    #[test]
+    fn test_parse_er_non_identifying_relationship() {
+        let input = "erDiagram\n    PERSON }|..|| ADDRESS : \"lives at\"";
+
+        let doc = parse_mermaid_er(input).unwrap();
+
+        assert_eq!(doc.nodes.len(), 2);
+        assert_eq!(
+            doc.nodes[0].attributes.get("shape"),
+            Some(&AttributeValue::String("cylinder".to_string()))
+        );
+        let edge = &doc.edges[0];
+        assert_eq!(edge.label.as_deref(), Some("lives at (1..N:1)"));
+        assert_eq!(
+            edge.attributes.get("strokeStyle"),
+            Some(&AttributeValue::String("dashed".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_er_errors() {
+        assert!(parse_mermaid_er("flowchart TD").is_err());
+        assert!(parse_mermaid_er("erDiagram\n  A ||--x{ B : bad").is_err());
+        assert!(parse_mermaid_er("erDiagram\n  A {\n    string name").is_err());
+    }
+
+    #[test]
+    fn test_imported_er_diagram_compiles() {
+        let doc = parse_mermaid_er("erDiagram\n  CUSTOMER ||--o{ ORDER : places").unwrap();
+        let igr = crate::igr::IntermediateGraph::from_ast(doc).unwrap();
+
+        assert_eq!(igr.graph.node_count(), 2);
+        assert_eq!(igr.graph.edge_count(), 1);
+    }
+
+    #[test]
+    fn test_generate_mermaid_flowchart() {
+        let input = r#"
+container "Back end" as backend {
+  api[API] { shape: diamond; }
+  db.main[Main DB]
+}
+user[User]
+user -> api: calls
+api -- db.main
+"#;
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let mermaid = MermaidGenerator::generate(&igr).unwrap();
+
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("  subgraph backend [\"Back end\"]\n    api{\"API\"}\n"));
+        assert!(mermaid.contains("db_main[\"Main DB\"]"));
+        assert!(mermaid.contains("  user -->|calls| api\n"));
+        assert!(mermaid.contains("  api --- db_main\n"));
+    }
+
+    #[test]
+    fn test_mermaid_ids_stay_unique() {
+        let input = "svc.api[Dotted]\nsvc_api[Plain]\nsvc.api -> svc_api\n";
+        let document = crate::parser::parse_edsl(input).unwrap();
+        let igr = IntermediateGraph::from_ast(document).unwrap();
+        let mermaid = MermaidGenerator::generate(&igr).unwrap();
+
+        assert!(mermaid.contains("  svc_api_2[\"Dotted\"]\n"));
+        assert!(mermaid.contains("  svc_api[\"Plain\"]\n"));
+        assert!(mermaid.contains("  svc_api_2 --> svc_api\n"));
+    }
+}
@@ -0,0 +1,107 @@
+// src/output_cache.rs
+//! Bounded LRU cache of compiled Excalidraw JSON
+//!
+//! Entries are keyed by the source together with the compiler options that
+//! shape the output, so a hit can skip the whole pipeline. A hash of the two
+//! narrows the search, but a hit needs both to match in full.
+//! Values are whatever the compiler needs to answer a hit, typically the
+//! JSON alongside a summary of the compile.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+/// Cache key for a source compiled with the given options
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OutputKey {
+    /// Compared first, so most mismatches skip the string comparisons
+    hash: u64,
+    source: String,
+    options: String,
+}
+
+pub(crate) fn key(source: &str, options: &str) -> OutputKey {
+    let mut hasher = DefaultHasher::new();
+    (source, options).hash(&mut hasher);
+    OutputKey {
+        hash: hasher.finish(),
+        source: source.to_string(),
+        options: options.to_string(),
+    }
+}
+
+pub(crate) struct OutputCache<V> {
+    capacity: usize,
+    /// Least recently used first
+    entries: VecDeque<(OutputKey, V)>,
+}
+
+impl<V: Clone> OutputCache<V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Look up a compiled output, marking it most recently used
+    pub(crate) fn get(&mut self, key: &OutputKey) -> Option<V> {
+        let position = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(position)?;
+        let value = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(value)
+    }
+
+    /// Store a compiled output, evicting the least recently used entry when full
+    pub(crate) fn insert(&mut self, key: OutputKey, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(k, _)| *k != key);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, value));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let (one, two, three) = (key("a", ""), key("b", ""), key("c", ""));
+        let mut cache = OutputCache::new(2);
+        cache.insert(one.clone(), "one".to_string());
+        cache.insert(two.clone(), "two".to_string());
+        assert_eq!(cache.get(&one).as_deref(), Some("one"));
+
+        // two is now the least recently used
+        cache.insert(three.clone(), "three".to_string());
+        assert_eq!(cache.get(&two), None);
+        assert_eq!(cache.get(&one).as_deref(), Some("one"));
+        assert_eq!(cache.get(&three).as_deref(), Some("three"));
+
+        assert_ne!(key("a[A]", "theme=dark"), key("a[A]", "theme=light"));
+    }
+
+    #[test]
+    fn test_hash_collision_misses() {
+        let mut cache = OutputCache::new(2);
+        let first = key("a[A]", "");
+        cache.insert(first.clone(), "first".to_string());
+
+        // Another source that happens to hash the same isn't served
+        let colliding = OutputKey {
+            source: "b[B]".to_string(),
+            ..first
+        };
+        assert_eq!(cache.get(&colliding), None);
+    }
+}
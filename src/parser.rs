@@ -1,6 +1,7 @@
 // src/parser.rs
 use crate::ast::*;
 use crate::error::{ParseError, Result};
+use pest::iterators::{Pair, Pairs};
 use pest::Parser;
 use pest_derive::Parser;
 use std::collections::HashMap;
@@ -12,28 +13,272 @@ const MAX_NODES: usize = 1000;
 const MAX_EDGES: usize = 5000;
 const MAX_CONTAINERS: usize = 100;
 
+/// Attribute carrying an edge's `@Type` reference until edge types are resolved
+const EDGE_TYPE_ATTRIBUTE: &str = "edgeType";
+
+/// Size limits enforced while parsing
+///
+/// The defaults are safe for untrusted input; trusted local use can raise them
+/// for large diagrams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_input_size: usize,
+    pub max_nodes: usize,
+    pub max_edges: usize,
+    pub max_containers: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_input_size: MAX_INPUT_SIZE,
+            max_nodes: MAX_NODES,
+            max_edges: MAX_EDGES,
+            max_containers: MAX_CONTAINERS,
+        }
+    }
+}
+
 #[derive(Parser)]
 #[grammar = "edsl.pest"]
 pub struct EDSLParser;
 
 pub fn parse_edsl(input: &str) -> Result<ParsedDocument> {
+    parse_edsl_with_limits(input, &ParseLimits::default())
+}
+
+/// Parse EDSL source, rejecting documents that exceed `limits`
+pub fn parse_edsl_with_limits(input: &str, limits: &ParseLimits) -> Result<ParsedDocument> {
     // Validate input size
-    if input.len() > MAX_INPUT_SIZE {
+    if input.len() > limits.max_input_size {
+        return Err(ParseError::ValidationError(format!(
+            "Input size exceeds maximum allowed size of {} bytes",
+            limits.max_input_size
+        ))
+        .into());
+    }
+
+    let pairs =
+        EDSLParser::parse(Rule::file, input).map_err(|e| ParseError::PestError(Box::new(e)))?;
+
+    build_document(pairs, limits)
+}
+
+/// Parse EDSL source, also returning its `#` comments in source order
+///
+/// Each comment is attached to the node, edge or container it annotates: the
+/// statement or style attribute it trails on the same line, or else the
+/// statement or attribute that follows it. `EdslGenerator::generate_with_comments`
+/// uses these to keep comments in place when formatting.
+///
+/// # Examples
+/// ```rust
+/// use excalidraw_dsl::ast::CommentOwner;
+/// use excalidraw_dsl::parser::parse_edsl_lossless;
+///
+/// let (_, comments) =
+///     parse_edsl_lossless("api[API] {\n  strokeColor: \"#f00\"; # brand\n}\n").unwrap();
+/// assert_eq!(comments[0].text, " brand");
+/// assert_eq!(comments[0].owner, Some(CommentOwner::Node("api".to_string())));
+/// assert_eq!(comments[0].attribute.as_deref(), Some("strokeColor"));
+/// ```
+pub fn parse_edsl_lossless(input: &str) -> Result<(ParsedDocument, Vec<Comment>)> {
+    let limits = ParseLimits::default();
+    if input.len() > limits.max_input_size {
         return Err(ParseError::ValidationError(format!(
-            "Input size exceeds maximum allowed size of {MAX_INPUT_SIZE} bytes"
+            "Input size exceeds maximum allowed size of {} bytes",
+            limits.max_input_size
         ))
         .into());
     }
 
     let pairs =
         EDSLParser::parse(Rule::file, input).map_err(|e| ParseError::PestError(Box::new(e)))?;
+    let comments = collect_comments(input, pairs.clone());
+
+    Ok((build_document(pairs, &limits)?, comments))
+}
+
+/// Statement or style attribute a comment can attach to
+struct CommentAnchor {
+    start: usize,
+    /// End of the anchor's last token, before any trailing whitespace or
+    /// comments its span takes in
+    end: usize,
+    owner: CommentOwner,
+    attribute: Option<String>,
+}
+
+/// Find the comments of a parsed file and attach them to statements and
+/// attributes
+fn collect_comments(input: &str, pairs: Pairs<Rule>) -> Vec<Comment> {
+    // Comments are skipped by the grammar, so they are the `#`s outside every
+    // token; the tokens are the innermost pairs, in source order
+    let mut tokens = Vec::new();
+    let mut statements: Vec<(Pair<Rule>, CommentOwner)> = Vec::new();
+    let mut attributes = Vec::new();
+    for pair in pairs.flatten() {
+        if pair.clone().into_inner().next().is_none() {
+            tokens.push((pair.as_span().start(), pair.as_span().end()));
+        }
+        match pair.as_rule() {
+            Rule::attribute => attributes.push(pair),
+            rule => {
+                if let Some(owner) = comment_owner(rule, pair.clone()) {
+                    statements.push((pair, owner));
+                }
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = input[offset..].find('#') {
+        let start = offset + found;
+        let token = tokens.partition_point(|&(token_start, _)| token_start <= start);
+        if token > 0 && start < tokens[token - 1].1 {
+            offset = tokens[token - 1].1;
+            continue;
+        }
+        let end = input[start..]
+            .find(['\n', '\r'])
+            .map_or(input.len(), |len| start + len);
+        ranges.push((start, end));
+        offset = end;
+    }
+
+    // Pairs may end after whitespace and comments skipped past their last token
+    let token_end = |pair: &Pair<Rule>| {
+        let mut end = pair.as_span().end();
+        loop {
+            end = pair.as_span().start() + input[pair.as_span().start()..end].trim_end().len();
+            match ranges.iter().find(|&&(_, comment_end)| comment_end == end) {
+                Some(&(comment_start, _)) if comment_start > pair.as_span().start() => {
+                    end = comment_start
+                }
+                _ => return end,
+            }
+        }
+    };
 
-    build_document(pairs)
+    let mut anchors: Vec<CommentAnchor> = statements
+        .iter()
+        .map(|(pair, owner)| CommentAnchor {
+            start: pair.as_span().start(),
+            end: token_end(pair),
+            owner: owner.clone(),
+            attribute: None,
+        })
+        .collect();
+    for pair in &attributes {
+        let start = pair.as_span().start();
+        // Innermost statement around the attribute: the last one that
+        // contains it, since nested statements come after their parents
+        let Some((_, owner)) = statements.iter().rev().find(|(statement, _)| {
+            statement.as_span().start() < start && start < statement.as_span().end()
+        }) else {
+            continue;
+        };
+        let name = pair
+            .clone()
+            .into_inner()
+            .find(|p| p.as_rule() == Rule::property_name)
+            .map(|p| p.as_str().to_string());
+        anchors.push(CommentAnchor {
+            start,
+            end: token_end(pair),
+            owner: owner.clone(),
+            attribute: name,
+        });
+    }
+    anchors.sort_by_key(|anchor| anchor.start);
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let line_start = input[..start].rfind('\n').map_or(0, |i| i + 1);
+            let trailing = !input[line_start..start].trim().is_empty();
+            let anchor = if trailing {
+                // The closest statement or attribute ending on this line, else
+                // a statement opening on it, such as `container "A" { # ...`
+                anchors
+                    .iter()
+                    .filter(|anchor| line_start <= anchor.end && anchor.end <= start)
+                    .max_by_key(|anchor| (anchor.end, anchor.attribute.is_some()))
+                    .or_else(|| {
+                        anchors.iter().rfind(|anchor| {
+                            anchor.attribute.is_none()
+                                && line_start <= anchor.start
+                                && anchor.start < start
+                        })
+                    })
+            } else {
+                anchors.iter().find(|anchor| anchor.start > start)
+            };
+            Comment {
+                line: input[..start].matches('\n').count() + 1,
+                column: input[line_start..start].chars().count() + 1,
+                text: input[start + 1..end].trim_end().to_string(),
+                trailing,
+                owner: anchor.map(|anchor| anchor.owner.clone()),
+                attribute: anchor.and_then(|anchor| anchor.attribute.clone()),
+            }
+        })
+        .collect()
 }
 
-fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument> {
+/// Node, edge or container a statement pair declares, for attaching comments
+fn comment_owner(rule: Rule, pair: Pair<Rule>) -> Option<CommentOwner> {
+    let node_ref_id = |node_ref: Pair<Rule>| {
+        let inner = node_ref.into_inner().next()?;
+        Some(match inner.as_rule() {
+            Rule::port_ref => inner.into_inner().next()?.as_str().to_string(),
+            _ => inner.as_str().to_string(),
+        })
+    };
+    match rule {
+        Rule::node_def => {
+            let id = pair.into_inner().find(|p| p.as_rule() == Rule::id)?;
+            Some(CommentOwner::Node(id.as_str().to_string()))
+        }
+        Rule::edge_def => {
+            let edge = pair.into_inner().next()?;
+            let mut ends = edge.into_inner().filter_map(|p| match p.as_rule() {
+                Rule::node_ref => node_ref_id(p),
+                Rule::edge_endpoints => p
+                    .into_inner()
+                    .find(|p| p.as_rule() == Rule::node_ref)
+                    .and_then(node_ref_id),
+                _ => None,
+            });
+            Some(CommentOwner::Edge {
+                source: ends.next()?,
+                target: ends.next()?,
+            })
+        }
+        Rule::container_def => {
+            let mut label = None;
+            for inner in pair.into_inner() {
+                match inner.as_rule() {
+                    Rule::id => return Some(CommentOwner::Container(inner.as_str().to_string())),
+                    Rule::string_literal => label = parse_string_literal(inner.as_str()).ok(),
+                    _ => {}
+                }
+            }
+            label.map(CommentOwner::Container)
+        }
+        _ => None,
+    }
+}
+
+fn build_document(
+    pairs: pest::iterators::Pairs<Rule>,
+    limits: &ParseLimits,
+) -> Result<ParsedDocument> {
     let mut config = GlobalConfig::default();
     let mut component_types = HashMap::new();
+    let mut edge_types = HashMap::new();
+    let mut rules = Vec::new();
     let mut templates = HashMap::new();
     let mut diagram = None;
     let mut nodes = Vec::new();
@@ -41,6 +286,9 @@ fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument>
     let mut containers = Vec::new();
     let mut groups = Vec::new();
     let mut connections = Vec::new();
+    let mut notes = Vec::new();
+    let mut lines = Vec::new();
+    let mut hyperedges = Vec::new();
 
     for pair in pairs {
         if pair.as_rule() == Rule::file {
@@ -56,6 +304,13 @@ fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument>
                                     let comp_type = parse_component_type(stmt_pair)?;
                                     component_types.insert(comp_type.name.clone(), comp_type);
                                 }
+                                Rule::edge_type_def => {
+                                    let edge_type = parse_edge_type(stmt_pair)?;
+                                    edge_types.insert(edge_type.name.clone(), edge_type);
+                                }
+                                Rule::rules_def => {
+                                    rules.extend(parse_style_rules(stmt_pair)?);
+                                }
                                 Rule::node_def => {
                                     nodes.push(parse_node_definition(stmt_pair)?);
                                 }
@@ -80,6 +335,15 @@ fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument>
                                     let template = parse_template_definition(stmt_pair)?;
                                     templates.insert(template.name.clone(), template);
                                 }
+                                Rule::note_def => {
+                                    notes.push(parse_note_definition(stmt_pair)?);
+                                }
+                                Rule::line_def => {
+                                    lines.push(parse_line_definition(stmt_pair)?);
+                                }
+                                Rule::hyperedge_def => {
+                                    hyperedges.push(parse_hyperedge_definition(stmt_pair)?);
+                                }
                                 Rule::diagram_def => {
                                     if diagram.is_some() {
                                         return Err(ParseError::ValidationError(
@@ -103,30 +367,50 @@ fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument>
         }
     }
 
+    // Edge types may be declared after the edges that use them
+    resolve_edge_types(&mut edges, &edge_types)?;
+    for container in &mut containers {
+        resolve_statement_edge_types(&mut container.internal_statements, &edge_types)?;
+    }
+    for group in &mut groups {
+        resolve_statement_edge_types(&mut group.internal_statements, &edge_types)?;
+    }
+
+    // Rules apply to every node, wherever it is declared
+    if !rules.is_empty() {
+        apply_style_rules(&mut nodes, &rules);
+        for container in &mut containers {
+            apply_statement_style_rules(&mut container.internal_statements, &rules);
+        }
+        for group in &mut groups {
+            apply_statement_style_rules(&mut group.internal_statements, &rules);
+        }
+    }
+
     // Validate complexity limits
-    if nodes.len() > MAX_NODES {
+    if nodes.len() > limits.max_nodes {
         return Err(ParseError::ValidationError(format!(
             "Number of nodes ({}) exceeds maximum allowed ({})",
             nodes.len(),
-            MAX_NODES
+            limits.max_nodes
         ))
         .into());
     }
 
-    if edges.len() > MAX_EDGES {
+    if edges.len() > limits.max_edges {
         return Err(ParseError::ValidationError(format!(
             "Number of edges ({}) exceeds maximum allowed ({})",
             edges.len(),
-            MAX_EDGES
+            limits.max_edges
         ))
         .into());
     }
 
-    if containers.len() > MAX_CONTAINERS {
+    if containers.len() > limits.max_containers {
         return Err(ParseError::ValidationError(format!(
             "Number of containers ({}) exceeds maximum allowed ({})",
             containers.len(),
-            MAX_CONTAINERS
+            limits.max_containers
         ))
         .into());
     }
@@ -134,6 +418,7 @@ fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument>
     Ok(ParsedDocument {
         config,
         component_types,
+        edge_types,
         templates,
         diagram,
         nodes,
@@ -141,30 +426,66 @@ fn build_document(pairs: pest::iterators::Pairs<Rule>) -> Result<ParsedDocument>
         containers,
         groups,
         connections,
+        notes,
+        lines,
+        hyperedges,
     })
 }
 
 fn parse_config(pair: pest::iterators::Pair<Rule>) -> Result<GlobalConfig> {
-    let yaml_content = pair
+    let Some(yaml_pair) = pair
         .into_inner()
         .find(|p| p.as_rule() == Rule::yaml_content)
-        .map(|p| p.as_str())
-        .unwrap_or("");
+    else {
+        return Ok(GlobalConfig::default());
+    };
+    let yaml_content = yaml_pair.as_str();
 
     if yaml_content.trim().is_empty() {
         return Ok(GlobalConfig::default());
     }
 
-    // Simply trim each line and reconstruct
-    let clean_lines: Vec<String> = yaml_content
+    // Simply trim each line and reconstruct, remembering each kept line's
+    // source line so errors can point at it
+    let first_line = yaml_pair.as_span().start_pos().line_col().0;
+    let (source_lines, clean_lines): (Vec<usize>, Vec<&str>) = yaml_content
         .lines()
-        .map(|line| line.trim().to_string())
-        .filter(|line| !line.is_empty())
-        .collect();
+        .enumerate()
+        .map(|(offset, line)| (first_line + offset, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .unzip();
 
     let clean_yaml = clean_lines.join("\n");
 
-    serde_yaml::from_str(&clean_yaml).map_err(|e| ParseError::InvalidConfig(e.to_string()).into())
+    let config: GlobalConfig = serde_yaml::from_str(&clean_yaml).map_err(|e| {
+        // serde_yaml lines are 1-based within the cleaned YAML
+        let index = e
+            .location()
+            .map(|location| location.line().saturating_sub(1));
+        let key = index
+            .and_then(|index| clean_lines.get(index))
+            .and_then(|line| line.split_once(':'))
+            .map(|(key, _)| key.trim().to_string());
+        // Drop serde_yaml's own position, which counts the cleaned lines
+        let mut message = e.to_string();
+        if let Some(location) = e.location() {
+            let suffix = format!(" at line {} column {}", location.line(), location.column());
+            if message.ends_with(&suffix) {
+                message.truncate(message.len() - suffix.len());
+            }
+        }
+        ParseError::InvalidConfig {
+            line: index.and_then(|index| source_lines.get(index).copied()),
+            key,
+            message,
+        }
+    })?;
+
+    for key in config.extra.keys() {
+        log::warn!("Ignoring unknown front matter key `{key}`");
+    }
+
+    Ok(config)
 }
 
 fn parse_statement(pair: pest::iterators::Pair<Rule>) -> Result<Statement> {
@@ -287,7 +608,7 @@ fn parse_node_definition(pair: pest::iterators::Pair<Rule>) -> Result<NodeDefini
                 let label_text = inner_pair
                     .into_inner()
                     .find(|p| p.as_rule() == Rule::label_text)
-                    .map(|p| p.as_str().to_string())
+                    .map(|p| unescape(p.as_str()))
                     .unwrap_or_else(|| id.clone());
                 label = Some(label_text);
             }
@@ -298,8 +619,30 @@ fn parse_node_definition(pair: pest::iterators::Pair<Rule>) -> Result<NodeDefini
                     }
                 }
             }
+            Rule::node_shorthand => {
+                for value_pair in inner_pair.into_inner() {
+                    if let Some(value) = value_pair.into_inner().next() {
+                        match value.as_rule() {
+                            Rule::color => {
+                                attributes.insert(
+                                    "strokeColor".to_string(),
+                                    AttributeValue::String(value.as_str().to_string()),
+                                );
+                            }
+                            Rule::shape_type => {
+                                attributes.insert(
+                                    "shape".to_string(),
+                                    AttributeValue::String(value.as_str().to_string()),
+                                );
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
             Rule::style_block => {
-                attributes = parse_style_block(inner_pair)?;
+                // Explicit style attributes take precedence over the shorthand
+                attributes.extend(parse_style_block(inner_pair)?);
             }
             _ => {}
         }
@@ -313,44 +656,267 @@ fn parse_node_definition(pair: pest::iterators::Pair<Rule>) -> Result<NodeDefini
     })
 }
 
+fn parse_edge_type(pair: pest::iterators::Pair<Rule>) -> Result<EdgeTypeDefinition> {
+    let mut name = String::new();
+    let mut attributes = HashMap::new();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::id => {
+                name = inner_pair.as_str().to_string();
+            }
+            Rule::style_block => {
+                // Edge types use short names; store them as edge attributes
+                for (key, value) in parse_style_block(inner_pair)? {
+                    let key = match key.as_str() {
+                        "color" => "strokeColor".to_string(),
+                        "width" => "strokeWidth".to_string(),
+                        "type" => "strokeStyle".to_string(),
+                        _ => key,
+                    };
+                    attributes.insert(key, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(EdgeTypeDefinition { name, attributes })
+}
+
+/// Merge each referenced edge type into its edge; explicit attributes win
+fn resolve_edge_types(
+    edges: &mut [EdgeDefinition],
+    edge_types: &HashMap<String, EdgeTypeDefinition>,
+) -> Result<()> {
+    for edge in edges {
+        let Some(AttributeValue::String(name)) = edge.attributes.remove(EDGE_TYPE_ATTRIBUTE) else {
+            continue;
+        };
+        let edge_type = edge_types.get(&name).ok_or_else(|| {
+            ParseError::ValidationError(format!(
+                "Edge {} -> {} references unknown edge type '@{}'",
+                edge.from, edge.to, name
+            ))
+        })?;
+        for (key, value) in &edge_type.attributes {
+            edge.attributes
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+    Ok(())
+}
+
+fn resolve_statement_edge_types(
+    statements: &mut [Statement],
+    edge_types: &HashMap<String, EdgeTypeDefinition>,
+) -> Result<()> {
+    for statement in statements {
+        match statement {
+            Statement::Edge(edge) => {
+                resolve_edge_types(std::slice::from_mut(edge), edge_types)?;
+            }
+            Statement::Container(container) => {
+                resolve_statement_edge_types(&mut container.internal_statements, edge_types)?;
+            }
+            Statement::Group(group) => {
+                resolve_statement_edge_types(&mut group.internal_statements, edge_types)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_style_rules(pair: pest::iterators::Pair<Rule>) -> Result<Vec<StyleRule>> {
+    let mut rules = Vec::new();
+
+    for rule_pair in pair.into_inner() {
+        if rule_pair.as_rule() != Rule::style_rule {
+            continue;
+        }
+        let mut attribute = String::new();
+        let mut operator = RuleOperator::Eq;
+        let mut value = None;
+        let mut attributes = HashMap::new();
+
+        for inner_pair in rule_pair.into_inner() {
+            match inner_pair.as_rule() {
+                Rule::property_name => {
+                    attribute = inner_pair.as_str().to_string();
+                }
+                Rule::rule_operator => {
+                    operator = match inner_pair.as_str() {
+                        "!=" => RuleOperator::Ne,
+                        "<" => RuleOperator::Lt,
+                        "<=" => RuleOperator::Le,
+                        ">" => RuleOperator::Gt,
+                        ">=" => RuleOperator::Ge,
+                        _ => RuleOperator::Eq,
+                    };
+                }
+                Rule::property_value => {
+                    value = Some(parse_property_value(inner_pair)?);
+                }
+                Rule::style_block => {
+                    attributes = parse_style_block(inner_pair)?;
+                }
+                _ => {}
+            }
+        }
+
+        let value = value.ok_or_else(|| ParseError::Syntax {
+            line: 0,
+            message: format!("Expected a value to match '{attribute}' against"),
+        })?;
+        rules.push(StyleRule {
+            attribute,
+            operator,
+            value,
+            attributes,
+        });
+    }
+
+    Ok(rules)
+}
+
+/// Merge the styles of every matching rule into each node. Explicit node
+/// attributes win, then later rules over earlier ones.
+fn apply_style_rules(nodes: &mut [NodeDefinition], rules: &[StyleRule]) {
+    for node in nodes {
+        // Match against the node as written, not styles added by other rules
+        let matching: Vec<&StyleRule> = rules
+            .iter()
+            .rev()
+            .filter(|rule| rule.matches(&node.attributes))
+            .collect();
+        for rule in matching {
+            for (key, value) in &rule.attributes {
+                node.attributes
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+}
+
+fn apply_statement_style_rules(statements: &mut [Statement], rules: &[StyleRule]) {
+    for statement in statements {
+        match statement {
+            Statement::Node(node) => apply_style_rules(std::slice::from_mut(node), rules),
+            Statement::Container(container) => {
+                apply_statement_style_rules(&mut container.internal_statements, rules);
+            }
+            Statement::Group(group) => {
+                apply_statement_style_rules(&mut group.internal_statements, rules);
+            }
+            _ => {}
+        }
+    }
+}
+
 fn parse_edge_definition(pair: pest::iterators::Pair<Rule>) -> Result<Vec<EdgeDefinition>> {
+    let position = pair.as_span().start();
     let inner = pair.into_inner().next().ok_or_else(|| ParseError::Syntax {
         line: 0,
         message: "Expected edge content".to_string(),
     })?;
 
-    match inner.as_rule() {
-        Rule::single_edge => Ok(vec![parse_single_edge(inner)?]),
-        Rule::edge_chain => parse_edge_chain(inner),
-        _ => Err(ParseError::Syntax {
-            line: 0,
-            message: format!("Unexpected rule in edge definition: {:?}", inner.as_rule()),
+    let mut edges = match inner.as_rule() {
+        Rule::single_edge | Rule::waypoint_edge => vec![parse_single_edge(inner)?],
+        Rule::edge_chain => parse_edge_chain(inner)?,
+        Rule::fan_out_edge => parse_fan_out_edge(inner)?,
+        _ => {
+            return Err(ParseError::Syntax {
+                line: 0,
+                message: format!("Unexpected rule in edge definition: {:?}", inner.as_rule()),
+            }
+            .into())
         }
-        .into()),
+    };
+    for edge in &mut edges {
+        edge.position = Some(position);
     }
+    Ok(edges)
 }
 
-fn parse_node_ref(pair: pest::iterators::Pair<Rule>) -> Result<(String, Option<String>)> {
+/// Parse an edge endpoint into its node ID, optional label and optional port side
+fn parse_node_ref(
+    pair: pest::iterators::Pair<Rule>,
+) -> Result<(String, Option<String>, Option<String>)> {
     let mut id = String::new();
     let mut label = None;
+    let mut port = None;
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::id => {
                 id = inner_pair.as_str().to_string();
             }
+            Rule::port_ref => {
+                for port_pair in inner_pair.into_inner() {
+                    match port_pair.as_rule() {
+                        Rule::port_node_id => id = port_pair.as_str().to_string(),
+                        Rule::port => {
+                            port = port_pair
+                                .into_inner()
+                                .find(|p| p.as_rule() == Rule::port_side)
+                                .map(|p| p.as_str().to_string());
+                        }
+                        _ => {}
+                    }
+                }
+            }
             Rule::label => {
                 let label_text = inner_pair
                     .into_inner()
                     .find(|p| p.as_rule() == Rule::label_text)
-                    .map(|p| p.as_str().to_string());
+                    .map(|p| unescape(p.as_str()));
                 label = label_text;
             }
             _ => {}
         }
     }
 
-    Ok((id, label))
+    Ok((id, label, port))
+}
+
+/// Record an `@Type` reference as an edge attribute, resolved once all edge
+/// types are known
+fn apply_edge_type_ref(
+    attributes: &mut HashMap<String, AttributeValue>,
+    edge_type: Option<String>,
+) {
+    if let Some(name) = edge_type {
+        attributes.insert(
+            EDGE_TYPE_ATTRIBUTE.to_string(),
+            AttributeValue::String(name),
+        );
+    }
+}
+
+fn parse_edge_type_ref(pair: pest::iterators::Pair<Rule>) -> Option<String> {
+    pair.into_inner()
+        .find(|p| p.as_rule() == Rule::id)
+        .map(|p| p.as_str().to_string())
+}
+
+/// Record endpoint ports as edge attributes; explicit ports win over style block values
+fn apply_edge_ports(
+    attributes: &mut HashMap<String, AttributeValue>,
+    start_port: Option<&String>,
+    end_port: Option<&String>,
+) {
+    if let Some(port) = start_port {
+        attributes.insert(
+            "startPort".to_string(),
+            AttributeValue::String(port.clone()),
+        );
+    }
+    if let Some(port) = end_port {
+        attributes.insert("endPort".to_string(), AttributeValue::String(port.clone()));
+    }
 }
 
 fn parse_single_edge(pair: pest::iterators::Pair<Rule>) -> Result<EdgeDefinition> {
@@ -359,15 +925,21 @@ fn parse_single_edge(pair: pest::iterators::Pair<Rule>) -> Result<EdgeDefinition
     let mut arrow_type = ArrowType::SingleArrow;
     let mut label = None;
     let mut attributes = HashMap::new();
+    let mut edge_type = None;
+    let mut from_port = None;
+    let mut to_port = None;
+    let mut waypoints = Vec::new();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::node_ref => {
-                let (node_id, _node_label) = parse_node_ref(inner_pair)?;
+                let (node_id, _node_label, port) = parse_node_ref(inner_pair)?;
                 if from.is_empty() {
                     from = node_id;
+                    from_port = port;
                 } else {
                     to = node_id;
+                    to_port = port;
                 }
             }
             Rule::arrow => {
@@ -388,12 +960,23 @@ fn parse_single_edge(pair: pest::iterators::Pair<Rule>) -> Result<EdgeDefinition
                     }
                 }
             }
+            Rule::edge_type_ref => {
+                edge_type = parse_edge_type_ref(inner_pair);
+            }
+            Rule::waypoints => {
+                for waypoint in inner_pair.into_inner() {
+                    waypoints.push(parse_waypoint(waypoint)?);
+                }
+            }
             Rule::style_block => {
                 attributes = parse_style_block(inner_pair)?;
             }
             _ => {}
         }
     }
+    apply_edge_type_ref(&mut attributes, edge_type);
+
+    apply_edge_ports(&mut attributes, from_port.as_ref(), to_port.as_ref());
 
     Ok(EdgeDefinition {
         from,
@@ -402,21 +985,104 @@ fn parse_single_edge(pair: pest::iterators::Pair<Rule>) -> Result<EdgeDefinition
         arrow_type,
         attributes,
         style: None,
+        waypoints,
+        position: None,
     })
 }
 
+/// Expand `{a, b} -> {c, d}` into one edge per source/target pair, all
+/// sharing the arrow, label and style
+fn parse_fan_out_edge(pair: pest::iterators::Pair<Rule>) -> Result<Vec<EdgeDefinition>> {
+    let mut endpoints = Vec::new();
+    let mut arrow_type = ArrowType::SingleArrow;
+    let mut label = None;
+    let mut attributes = HashMap::new();
+    let mut edge_type = None;
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::edge_endpoints => {
+                let refs = inner_pair
+                    .into_inner()
+                    .map(|node_ref| parse_node_ref(node_ref).map(|(id, _, port)| (id, port)))
+                    .collect::<Result<Vec<_>>>()?;
+                endpoints.push(refs);
+            }
+            Rule::arrow => {
+                arrow_type = inner_pair
+                    .as_str()
+                    .parse()
+                    .unwrap_or(ArrowType::SingleArrow);
+            }
+            Rule::edge_label => {
+                for label_part in inner_pair.into_inner() {
+                    if label_part.as_rule() == Rule::edge_label_content {
+                        let content = label_part.as_str();
+                        if content.starts_with('"') && content.ends_with('"') {
+                            label = Some(parse_string_literal(content)?);
+                        } else {
+                            label = Some(content.trim().to_string());
+                        }
+                    }
+                }
+            }
+            Rule::edge_type_ref => {
+                edge_type = parse_edge_type_ref(inner_pair);
+            }
+            Rule::style_block => {
+                attributes = parse_style_block(inner_pair)?;
+            }
+            _ => {}
+        }
+    }
+    apply_edge_type_ref(&mut attributes, edge_type);
+
+    let (sources, targets) = match endpoints.as_slice() {
+        [sources, targets] => (sources, targets),
+        _ => {
+            return Err(ParseError::Syntax {
+                line: 0,
+                message: "Expected source and target endpoints in edge".to_string(),
+            }
+            .into())
+        }
+    };
+
+    let mut edges = Vec::with_capacity(sources.len() * targets.len());
+    for (from, from_port) in sources {
+        for (to, to_port) in targets {
+            let mut edge_attributes = attributes.clone();
+            apply_edge_ports(&mut edge_attributes, from_port.as_ref(), to_port.as_ref());
+            edges.push(EdgeDefinition {
+                from: from.clone(),
+                to: to.clone(),
+                label: label.clone(),
+                arrow_type,
+                attributes: edge_attributes,
+                style: None,
+                waypoints: Vec::new(),
+                position: None,
+            });
+        }
+    }
+    Ok(edges)
+}
+
 fn parse_edge_chain(pair: pest::iterators::Pair<Rule>) -> Result<Vec<EdgeDefinition>> {
     // Parse edge chain and expand into multiple edges
     let mut ids = Vec::new();
+    let mut ports = Vec::new();
     let mut arrow_type = ArrowType::SingleArrow;
     let mut label = None;
     let mut attributes = HashMap::new();
+    let mut edge_type = None;
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::node_ref => {
-                let (node_id, _node_label) = parse_node_ref(inner_pair)?;
+                let (node_id, _node_label, port) = parse_node_ref(inner_pair)?;
                 ids.push(node_id);
+                ports.push(port);
             }
             Rule::arrow => {
                 arrow_type = inner_pair
@@ -436,24 +1102,36 @@ fn parse_edge_chain(pair: pest::iterators::Pair<Rule>) -> Result<Vec<EdgeDefinit
                     }
                 }
             }
+            Rule::edge_type_ref => {
+                edge_type = parse_edge_type_ref(inner_pair);
+            }
             Rule::style_block => {
                 attributes = parse_style_block(inner_pair)?;
             }
             _ => {}
         }
     }
+    apply_edge_type_ref(&mut attributes, edge_type);
 
     if ids.len() >= 2 {
         // Expand the edge chain into individual edges
         let mut edges = Vec::new();
         for i in 0..(ids.len() - 1) {
+            let mut edge_attributes = attributes.clone();
+            apply_edge_ports(
+                &mut edge_attributes,
+                ports[i].as_ref(),
+                ports[i + 1].as_ref(),
+            );
             edges.push(EdgeDefinition {
                 from: ids[i].clone(),
                 to: ids[i + 1].clone(),
                 label: label.clone(),
                 arrow_type,
-                attributes: attributes.clone(),
+                attributes: edge_attributes,
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             });
         }
         Ok(edges)
@@ -586,6 +1264,7 @@ fn parse_group_type(pair: pest::iterators::Pair<Rule>) -> Result<GroupType> {
 }
 
 fn parse_container_definition(pair: pest::iterators::Pair<Rule>) -> Result<ContainerDefinition> {
+    let mut is_lane = false;
     let mut id = None;
     let mut label = None;
     let mut attributes = HashMap::new();
@@ -594,6 +1273,9 @@ fn parse_container_definition(pair: pest::iterators::Pair<Rule>) -> Result<Conta
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
+            Rule::container_keyword => {
+                is_lane = inner_pair.as_str() == "lane";
+            }
             Rule::string_literal => {
                 if label.is_none() {
                     label = Some(parse_string_literal(inner_pair.as_str())?);
@@ -665,6 +1347,10 @@ fn parse_container_definition(pair: pest::iterators::Pair<Rule>) -> Result<Conta
         }
     }
 
+    if is_lane {
+        attributes.insert(LANE_ATTRIBUTE.to_string(), AttributeValue::Boolean(true));
+    }
+
     Ok(ContainerDefinition {
         id,
         label,
@@ -674,33 +1360,152 @@ fn parse_container_definition(pair: pest::iterators::Pair<Rule>) -> Result<Conta
     })
 }
 
+fn parse_note_definition(pair: pest::iterators::Pair<Rule>) -> Result<NoteDefinition> {
+    let mut text = String::new();
+    let mut coordinates = Vec::new();
+    let mut attributes = HashMap::new();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::string_literal => text = parse_string_literal(inner_pair.as_str())?,
+            Rule::signed_number => {
+                let value = inner_pair
+                    .as_str()
+                    .parse::<f64>()
+                    .map_err(|_| ParseError::Syntax {
+                        line: inner_pair.line_col().0,
+                        message: format!("Invalid note coordinate: {}", inner_pair.as_str()),
+                    })?;
+                coordinates.push(value);
+            }
+            Rule::style_block => attributes = parse_style_block(inner_pair)?,
+            _ => {}
+        }
+    }
+
+    Ok(NoteDefinition {
+        text,
+        x: coordinates.first().copied().unwrap_or_default(),
+        y: coordinates.get(1).copied().unwrap_or_default(),
+        attributes,
+    })
+}
+
+/// Parse a `(x, y)` waypoint of a `via` clause
+fn parse_waypoint(pair: pest::iterators::Pair<Rule>) -> Result<(f64, f64)> {
+    let mut coordinates = Vec::with_capacity(2);
+    for number in pair.into_inner() {
+        let value = number
+            .as_str()
+            .parse::<f64>()
+            .map_err(|_| ParseError::Syntax {
+                line: number.line_col().0,
+                message: format!("Invalid waypoint coordinate: {}", number.as_str()),
+            })?;
+        coordinates.push(value);
+    }
+    let coordinate = |i: usize| coordinates.get(i).copied().unwrap_or_default();
+    Ok((coordinate(0), coordinate(1)))
+}
+
+fn parse_line_definition(pair: pest::iterators::Pair<Rule>) -> Result<LineDefinition> {
+    let mut coordinates = Vec::new();
+    let mut attributes = HashMap::new();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::signed_number => {
+                let value = inner_pair
+                    .as_str()
+                    .parse::<f64>()
+                    .map_err(|_| ParseError::Syntax {
+                        line: inner_pair.line_col().0,
+                        message: format!("Invalid line coordinate: {}", inner_pair.as_str()),
+                    })?;
+                coordinates.push(value);
+            }
+            Rule::style_block => attributes = parse_style_block(inner_pair)?,
+            _ => {}
+        }
+    }
+
+    let coordinate = |i: usize| coordinates.get(i).copied().unwrap_or_default();
+    Ok(LineDefinition {
+        from: (coordinate(0), coordinate(1)),
+        to: (coordinate(2), coordinate(3)),
+        attributes,
+    })
+}
+
+fn parse_hyperedge_definition(pair: pest::iterators::Pair<Rule>) -> Result<HyperedgeDefinition> {
+    let mut id = None;
+    let mut members = Vec::new();
+    let mut attributes = HashMap::new();
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::id => id = Some(inner_pair.as_str().to_string()),
+            Rule::hyperedge_members => {
+                members = inner_pair
+                    .into_inner()
+                    .map(|member| member.as_str().to_string())
+                    .collect();
+            }
+            Rule::style_block => attributes = parse_style_block(inner_pair)?,
+            _ => {}
+        }
+    }
+
+    Ok(HyperedgeDefinition {
+        id,
+        members,
+        attributes,
+    })
+}
+
+/// Parse a style block; entries of a nested `meta { ... }` block are keyed
+/// with `META_PREFIX`
 fn parse_style_block(pair: pest::iterators::Pair<Rule>) -> Result<HashMap<String, AttributeValue>> {
     let mut attributes = HashMap::new();
 
     for inner_pair in pair.into_inner() {
-        if inner_pair.as_rule() == Rule::attribute {
-            let mut key = String::new();
-            let mut value = None;
-
-            for attr_pair in inner_pair.into_inner() {
-                match attr_pair.as_rule() {
-                    Rule::property_name => {
-                        key = attr_pair.as_str().to_string();
-                    }
-                    Rule::property_value => {
-                        value = Some(parse_property_value(attr_pair)?);
+        match inner_pair.as_rule() {
+            Rule::attribute => {
+                if let Some((key, value)) = parse_attribute(inner_pair)? {
+                    attributes.insert(key, value);
+                }
+            }
+            Rule::meta_block => {
+                for meta_pair in inner_pair.into_inner() {
+                    if let Some((key, value)) = parse_attribute(meta_pair)? {
+                        attributes.insert(format!("{META_PREFIX}{key}"), value);
                     }
-                    _ => {}
                 }
             }
+            _ => {}
+        }
+    }
+
+    Ok(attributes)
+}
 
-            if let Some(val) = value {
-                attributes.insert(key, val);
+fn parse_attribute(pair: pest::iterators::Pair<Rule>) -> Result<Option<(String, AttributeValue)>> {
+    let mut key = String::new();
+    let mut value = None;
+
+    for attr_pair in pair.into_inner() {
+        match attr_pair.as_rule() {
+            Rule::property_name => {
+                key = attr_pair.as_str().to_string();
+            }
+            Rule::property_value => {
+                value = Some(parse_property_value(attr_pair)?);
             }
+            _ => {}
         }
     }
 
-    Ok(attributes)
+    Ok(value.map(|value| (key, value)))
 }
 
 fn parse_property_value(pair: pest::iterators::Pair<Rule>) -> Result<AttributeValue> {
@@ -726,7 +1531,7 @@ fn parse_property_value(pair: pest::iterators::Pair<Rule>) -> Result<AttributeVa
             let bool_val = inner.as_str() == "true";
             Ok(AttributeValue::Boolean(bool_val))
         }
-        Rule::identifier => Ok(AttributeValue::String(inner.as_str().to_string())),
+        Rule::identifier | Rule::ratio => Ok(AttributeValue::String(inner.as_str().to_string())),
         _ => unreachable!(),
     }
 }
@@ -734,10 +1539,36 @@ fn parse_property_value(pair: pest::iterators::Pair<Rule>) -> Result<AttributeVa
 fn parse_string_literal(s: &str) -> Result<String> {
     // Remove surrounding quotes
     if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
-        Ok(s[1..s.len() - 1].to_string())
+        Ok(unescape(&s[1..s.len() - 1]))
     } else {
-        Ok(s.to_string())
+        Ok(unescape(s))
+    }
+}
+
+/// Resolve backslash escapes in labels and string literals
+///
+/// `\"`, `\\`, `\n`, `\t`, `\[` and `\]` are recognised; any other escaped
+/// character is kept verbatim, backslash included.
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(escaped @ ('"' | '\\' | '[' | ']')) => result.push(escaped),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
     }
+    result
 }
 
 fn parse_connection(pair: pest::iterators::Pair<Rule>) -> Result<ConnectionDefinition> {
@@ -849,6 +1680,14 @@ fn parse_connection_style(pair: pest::iterators::Pair<Rule>) -> Result<EdgeStyle
 
     for attr_pair in pair.into_inner() {
         if attr_pair.as_rule() == Rule::connection_style_attr {
+            // The attribute key is a literal in the grammar, so read it from the source text
+            let key = attr_pair
+                .as_str()
+                .split(':')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
             for inner in attr_pair.into_inner() {
                 match inner.as_rule() {
                     Rule::edge_type => {
@@ -862,11 +1701,10 @@ fn parse_connection_style(pair: pest::iterators::Pair<Rule>) -> Result<EdgeStyle
                     }
                     Rule::string_literal => {
                         let value = parse_string_literal(inner.as_str())?;
-                        // Determine which field based on context
-                        if style.label.is_none() {
-                            style.label = Some(value);
-                        } else if style.color.is_none() {
-                            style.color = Some(value);
+                        match key.as_str() {
+                            "label" => style.label = Some(value),
+                            "color" => style.color = Some(value),
+                            _ => {}
                         }
                     }
                     Rule::number => {
@@ -875,11 +1713,10 @@ fn parse_connection_style(pair: pest::iterators::Pair<Rule>) -> Result<EdgeStyle
                                 line: inner.as_span().start_pos().line_col().0,
                                 message: "Invalid number".to_string(),
                             })?;
-                        // Determine which field based on context
-                        if style.label_position.is_none() {
-                            style.label_position = Some(value);
-                        } else if style.width.is_none() {
-                            style.width = Some(value);
+                        match key.as_str() {
+                            "labelPosition" => style.label_position = Some(value),
+                            "width" => style.width = Some(value),
+                            _ => {}
                         }
                     }
                     Rule::routing_type => {
@@ -1248,6 +2085,87 @@ mod tests {
         assert_eq!(result.edges[0].label, Some("HTTP Request".to_string()));
     }
 
+    #[test]
+    fn test_parse_node_shorthand() {
+        let shorthand = parse_edsl("db[Database]:cylinder:#ff0000").unwrap();
+        let braced =
+            parse_edsl(r##"db[Database] { shape: cylinder; strokeColor: "#ff0000"; }"##).unwrap();
+
+        assert_eq!(shorthand.nodes[0].attributes, braced.nodes[0].attributes);
+
+        let shorthand_igr = crate::igr::IntermediateGraph::from_ast(shorthand).unwrap();
+        let braced_igr = crate::igr::IntermediateGraph::from_ast(braced).unwrap();
+        let (_, shorthand_node) = shorthand_igr.get_node_by_id("db").unwrap();
+        let (_, braced_node) = braced_igr.get_node_by_id("db").unwrap();
+
+        assert_eq!(
+            shorthand_node.attributes.shape,
+            Some("cylinder".to_string())
+        );
+        assert_eq!(
+            shorthand_node.attributes.shape,
+            braced_node.attributes.shape
+        );
+        assert_eq!(
+            shorthand_node.attributes.stroke_color,
+            braced_node.attributes.stroke_color
+        );
+    }
+
+    #[test]
+    fn test_parse_node_shorthand_with_edges() {
+        let input = r##"
+        api[API]:hexagon
+        db:cylinder { strokeColor: "#00ff00"; }
+        api -> db: "Query"
+        "##;
+
+        let result = parse_edsl(input).unwrap();
+
+        assert_eq!(result.nodes.len(), 2);
+        assert_eq!(result.edges.len(), 1);
+        assert_eq!(result.edges[0].label, Some("Query".to_string()));
+        assert_eq!(
+            result.nodes[1].attributes.get("shape"),
+            Some(&AttributeValue::String("cylinder".to_string()))
+        );
+        assert_eq!(
+            result.nodes[1].attributes.get("strokeColor"),
+            Some(&AttributeValue::String("#00ff00".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_edge_ports() {
+        let input = r#"
+        api.v1
+        db
+        api.v1.right -> db.left
+        db -> api.v1
+        "#;
+
+        let result = parse_edsl(input).unwrap();
+
+        assert_eq!(result.nodes[0].id, "api.v1");
+        assert_eq!(result.edges.len(), 2);
+
+        let ported = &result.edges[0];
+        assert_eq!(ported.from, "api.v1");
+        assert_eq!(ported.to, "db");
+        assert_eq!(
+            ported.attributes.get("startPort"),
+            Some(&AttributeValue::String("right".to_string()))
+        );
+        assert_eq!(
+            ported.attributes.get("endPort"),
+            Some(&AttributeValue::String("left".to_string()))
+        );
+
+        let plain = &result.edges[1];
+        assert_eq!(plain.to, "api.v1");
+        assert!(plain.attributes.is_empty());
+    }
+
     #[test]
     fn test_parse_with_config() {
         let input = r#"
@@ -1265,4 +2183,245 @@ mod tests {
         assert_eq!(result.config.theme, Some("dark".to_string()));
         assert_eq!(result.nodes.len(), 1);
     }
+
+    #[test]
+    fn test_config_error_reports_key_and_line() {
+        let input = "---\nlayout: dagre\n\nscale: big\n---\na[A]\n";
+
+        let err = parse_edsl(input).unwrap_err();
+        match err {
+            crate::error::EDSLError::Parse(ParseError::InvalidConfig { line, key, .. }) => {
+                assert_eq!(line, Some(4));
+                assert_eq!(key.as_deref(), Some("scale"));
+            }
+            other => panic!("expected an invalid config error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_config_tolerates_unknown_keys() {
+        let input = "---\nlayout: dagre\nfuture_option: 3\n---\na[A]\n";
+
+        let result = parse_edsl(input).unwrap();
+        assert_eq!(result.config.layout.as_deref(), Some("dagre"));
+        assert_eq!(
+            result.config.extra.get("future_option"),
+            Some(&serde_yaml::Value::from(3))
+        );
+    }
+
+    #[test]
+    fn test_parse_escaped_bracket_labels() {
+        let input = r#"
+quote[He said \"hi\"]
+path[C:\\temp \[draft\]]
+multi[first\nsecond\tindented]
+"#;
+
+        let result = parse_edsl(input).unwrap();
+
+        assert_eq!(result.nodes[0].label.as_deref(), Some("He said \"hi\""));
+        assert_eq!(result.nodes[1].label.as_deref(), Some("C:\\temp [draft]"));
+        assert_eq!(
+            result.nodes[2].label.as_deref(),
+            Some("first\nsecond\tindented")
+        );
+    }
+
+    #[test]
+    fn test_parse_escaped_string_literals() {
+        let input = r#"
+a -> b: "He said \"hi\""
+b -> c: "C:\\temp\\"
+c -> d: "first\nsecond"
+"#;
+
+        let result = parse_edsl(input).unwrap();
+
+        assert_eq!(result.edges[0].label.as_deref(), Some("He said \"hi\""));
+        assert_eq!(result.edges[1].label.as_deref(), Some("C:\\temp\\"));
+        assert_eq!(result.edges[2].label.as_deref(), Some("first\nsecond"));
+    }
+
+    #[test]
+    fn test_parse_limits_can_be_raised() {
+        let input: String = (0..=MAX_NODES).map(|i| format!("n{i}\n")).collect();
+
+        assert!(parse_edsl(&input).is_err());
+
+        let limits = ParseLimits {
+            max_nodes: 2000,
+            ..Default::default()
+        };
+        let result = parse_edsl_with_limits(&input, &limits).unwrap();
+        assert_eq!(result.nodes.len(), MAX_NODES + 1);
+    }
+
+    #[test]
+    fn test_parse_fan_out_edges() {
+        let result = parse_edsl("a -> {b, c}").unwrap();
+        let pairs: Vec<_> = result
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(pairs, [("a", "b"), ("a", "c")]);
+
+        let result = parse_edsl("{a, b} -> {c, d}: syncs { strokeStyle: dashed; }").unwrap();
+        let pairs: Vec<_> = result
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(pairs, [("a", "c"), ("a", "d"), ("b", "c"), ("b", "d")]);
+        for edge in &result.edges {
+            assert_eq!(edge.label.as_deref(), Some("syncs"));
+            assert_eq!(edge.arrow_type, ArrowType::SingleArrow);
+            assert!(edge.attributes.contains_key("strokeStyle"));
+        }
+
+        let result = parse_edsl("{a.right, b} ..> c").unwrap();
+        assert_eq!(result.edges.len(), 2);
+        assert_eq!(result.edges[0].arrow_type, ArrowType::DottedArrow);
+        assert!(result.edges[0].attributes.contains_key("startPort"));
+        assert!(!result.edges[1].attributes.contains_key("startPort"));
+    }
+
+    #[test]
+    fn test_parse_hyperedge() {
+        let input = r##"
+bus [a, b, c]
+bus backbone [c, d] { strokeColor: "#1e40af"; }
+bus[Bus]
+"##;
+        let result = parse_edsl(input).unwrap();
+        assert_eq!(result.hyperedges.len(), 2);
+        assert_eq!(result.hyperedges[0].id, None);
+        assert_eq!(result.hyperedges[0].members, ["a", "b", "c"]);
+        assert_eq!(result.hyperedges[1].id.as_deref(), Some("backbone"));
+        assert_eq!(result.hyperedges[1].members, ["c", "d"]);
+        assert!(result.hyperedges[1].attributes.contains_key("strokeColor"));
+
+        // A bracket label without commas is still a node named `bus`
+        assert_eq!(result.nodes.len(), 1);
+        assert_eq!(result.nodes[0].id, "bus");
+    }
+
+    #[test]
+    fn test_parse_note() {
+        let input = r##"
+note "Needs review" at (400, 50)
+note "Sticky" at (-10.5, 20) { backgroundColor: "#fff3bf"; }
+"##;
+        let result = parse_edsl(input).unwrap();
+        assert!(result.nodes.is_empty());
+        assert_eq!(result.notes.len(), 2);
+        assert_eq!(result.notes[0].text, "Needs review");
+        assert_eq!((result.notes[0].x, result.notes[0].y), (400.0, 50.0));
+        assert_eq!((result.notes[1].x, result.notes[1].y), (-10.5, 20.0));
+        assert!(result.notes[1].attributes.contains_key("backgroundColor"));
+    }
+
+    #[test]
+    fn test_parse_unicode_ids() {
+        let input = r#"
+café_api[Café API]
+服务
+user.v2[User]
+café_api -> 服务: "calls"
+服务.right -> user.v2
+"#;
+        let result = parse_edsl(input).unwrap();
+        let ids: Vec<&str> = result.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["café_api", "服务", "user.v2"]);
+        assert_eq!(result.edges[0].from, "café_api");
+        assert_eq!(result.edges[0].to, "服务");
+        assert_eq!(result.edges[1].from, "服务");
+        assert_eq!(
+            result.edges[1].attributes.get("startPort"),
+            Some(&AttributeValue::String("right".to_string()))
+        );
+
+        let igr = crate::igr::IntermediateGraph::from_ast(result).unwrap();
+        let (source, _) = igr.get_node_by_id("café_api").unwrap();
+        let (target, node) = igr.get_node_by_id("服务").unwrap();
+        assert_eq!(node.label, "服务");
+        assert!(igr.graph.find_edge(source, target).is_some());
+    }
+
+    #[test]
+    fn test_parse_edge_types() {
+        let input = r##"
+edgetype Critical { color: "#f00"; width: 3; type: dashed; }
+a -> b : @Critical
+b -> c : @Critical { strokeColor: "#00f"; }
+c -> d : @Critical : retry
+"##;
+        let result = parse_edsl(input).unwrap();
+        assert_eq!(result.edge_types.len(), 1);
+        assert_eq!(result.edges.len(), 3);
+
+        let color = |i: usize| result.edges[i].attributes.get("strokeColor").cloned();
+        assert_eq!(color(0), Some(AttributeValue::String("#f00".to_string())));
+        assert_eq!(color(1), Some(AttributeValue::String("#00f".to_string())));
+        for edge in &result.edges {
+            assert_eq!(
+                edge.attributes.get("strokeWidth"),
+                Some(&AttributeValue::Number(3.0))
+            );
+            assert_eq!(
+                edge.attributes.get("strokeStyle"),
+                Some(&AttributeValue::String("dashed".to_string()))
+            );
+            assert!(!edge.attributes.contains_key(EDGE_TYPE_ATTRIBUTE));
+        }
+        assert_eq!(result.edges[2].label.as_deref(), Some("retry"));
+
+        let err = parse_edsl("a -> b : @Missing").unwrap_err();
+        assert!(err.to_string().contains("@Missing"));
+    }
+
+    #[test]
+    fn test_parse_style_rules() {
+        let input = r##"
+api[API] { status: up; }
+db[Database] { status: down; }
+cache[Cache] { status: down; strokeColor: "#000000"; }
+container "Workers" {
+  worker[Worker] { status: down; load: 95; }
+}
+
+rules {
+  match status == "down" { strokeColor: "#ff0000"; strokeWidth: 3; }
+  match load >= 90 { backgroundColor: "#ffc9c9"; }
+}
+"##;
+        let result = parse_edsl(input).unwrap();
+        let node = |id: &str| {
+            result
+                .nodes
+                .iter()
+                .find(|node| node.id == id)
+                .expect("node exists")
+        };
+        let red = Some(&AttributeValue::String("#ff0000".to_string()));
+
+        assert_eq!(node("db").attributes.get("strokeColor"), red);
+        assert_eq!(
+            node("db").attributes.get("strokeWidth"),
+            Some(&AttributeValue::Number(3.0))
+        );
+        assert_eq!(node("api").attributes.get("strokeColor"), None);
+        // Explicit styles win over rules
+        assert_eq!(
+            node("cache").attributes.get("strokeColor"),
+            Some(&AttributeValue::String("#000000".to_string()))
+        );
+
+        let Statement::Node(worker) = &result.containers[0].internal_statements[0] else {
+            panic!("Expected worker node");
+        };
+        assert_eq!(worker.attributes.get("strokeColor"), red);
+        assert!(worker.attributes.contains_key("backgroundColor"));
+    }
 }
@@ -1,14 +1,125 @@
 // src/presets.rs
 //! Predefined diagram presets for common use cases
 
-use crate::ast::ArrowType;
+use crate::ast::{ArrowType, GlobalConfig, RoutingType};
+use crate::error::{EDSLError, Result};
 use crate::fluent::DiagramBuilder;
 use std::collections::HashMap;
 
+/// Document settings a named preset supplies
+struct ConfigPreset {
+    name: &'static str,
+    description: &'static str,
+    layout: &'static str,
+    routing: RoutingType,
+    compact_layers: bool,
+    scale: Option<f64>,
+    theme: Option<&'static str>,
+}
+
+const CONFIG_PRESETS: &[ConfigPreset] = &[
+    ConfigPreset {
+        name: "flowchart",
+        description: "Process flows in compact dagre layers with right-angled edges",
+        layout: "dagre",
+        routing: RoutingType::Orthogonal,
+        compact_layers: true,
+        scale: None,
+        theme: None,
+    },
+    ConfigPreset {
+        name: "architecture",
+        description: "Services and containers laid out by ELK",
+        layout: "elk",
+        routing: RoutingType::Orthogonal,
+        compact_layers: false,
+        scale: None,
+        theme: None,
+    },
+    ConfigPreset {
+        name: "state-machine",
+        description: "States spread out by the force engine, curved transitions",
+        layout: "force",
+        routing: RoutingType::Curved,
+        compact_layers: false,
+        scale: None,
+        theme: None,
+    },
+    ConfigPreset {
+        name: "pipeline",
+        description: "Stages in compact dagre layers with straight edges",
+        layout: "dagre",
+        routing: RoutingType::Straight,
+        compact_layers: true,
+        scale: None,
+        theme: None,
+    },
+    ConfigPreset {
+        name: "presentation",
+        description: "Roomy layout on a dark canvas, for slides",
+        layout: "dagre",
+        routing: RoutingType::Orthogonal,
+        compact_layers: false,
+        scale: Some(1.5),
+        theme: Some("dark"),
+    },
+];
+
 /// Common diagram presets
 pub struct DiagramPresets;
 
 impl DiagramPresets {
+    /// Names of the presets `apply` accepts
+    pub fn names() -> Vec<&'static str> {
+        CONFIG_PRESETS.iter().map(|preset| preset.name).collect()
+    }
+
+    /// One-line description of a preset, if it exists
+    pub fn description(name: &str) -> Option<&'static str> {
+        Self::find(name).map(|preset| preset.description)
+    }
+
+    /// Fill in the layout, routing, layer compaction, scale and theme a
+    /// preset supplies
+    ///
+    /// Settings the config already has are kept, so a document's front
+    /// matter wins over the preset.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use excalidraw_dsl::ast::GlobalConfig;
+    /// use excalidraw_dsl::DiagramPresets;
+    ///
+    /// let mut config = GlobalConfig::default();
+    /// DiagramPresets::apply("flowchart", &mut config).unwrap();
+    /// assert_eq!(config.layout.as_deref(), Some("dagre"));
+    /// assert!(DiagramPresets::apply("nope", &mut config).is_err());
+    /// ```
+    pub fn apply(name: &str, config: &mut GlobalConfig) -> Result<()> {
+        let preset = Self::find(name).ok_or_else(|| EDSLError::Validation {
+            message: format!(
+                "Unknown preset '{name}', must be one of: {}",
+                Self::names().join(", ")
+            ),
+        })?;
+        config
+            .layout
+            .get_or_insert_with(|| preset.layout.to_string());
+        config.routing.get_or_insert(preset.routing);
+        config.compact_layers.get_or_insert(preset.compact_layers);
+        if config.scale.is_none() {
+            config.scale = preset.scale;
+        }
+        if config.theme.is_none() {
+            config.theme = preset.theme.map(str::to_string);
+        }
+        Ok(())
+    }
+
+    fn find(name: &str) -> Option<&'static ConfigPreset> {
+        CONFIG_PRESETS.iter().find(|preset| preset.name == name)
+    }
+
     /// Create a basic client-server architecture diagram
     pub fn client_server() -> DiagramBuilder {
         DiagramBuilder::new()
@@ -326,6 +437,34 @@ mod tests {
         assert_eq!(ast.edges.len(), 2);
     }
 
+    #[test]
+    fn test_apply_config_preset() {
+        assert!(DiagramPresets::names().contains(&"presentation"));
+
+        let mut config = GlobalConfig::default();
+        DiagramPresets::apply("presentation", &mut config).unwrap();
+        assert_eq!(config.layout.as_deref(), Some("dagre"));
+        assert_eq!(config.routing, Some(RoutingType::Orthogonal));
+        assert_eq!(config.compact_layers, Some(false));
+        assert_eq!(config.scale, Some(1.5));
+        assert_eq!(config.theme.as_deref(), Some("dark"));
+        config.validate().unwrap();
+
+        // Settings already present are kept
+        let mut config = GlobalConfig {
+            layout: Some("force".to_string()),
+            theme: Some("light".to_string()),
+            ..Default::default()
+        };
+        DiagramPresets::apply("presentation", &mut config).unwrap();
+        assert_eq!(config.layout.as_deref(), Some("force"));
+        assert_eq!(config.theme.as_deref(), Some("light"));
+        assert_eq!(config.scale, Some(1.5));
+
+        let error = DiagramPresets::apply("nope", &mut config).unwrap_err();
+        assert!(error.to_string().contains("Unknown preset 'nope'"));
+    }
+
     #[test]
     fn test_material_colors() {
         let colors = ThemePresets::material_colors();
@@ -1,12 +1,24 @@
 // src/routing.rs
 use crate::ast::RoutingType;
-use crate::igr::NodeData;
+use crate::igr::{BoundingBox, NodeData};
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
 /// Represents a point in 2D space
 pub type Point = (f64, f64);
 
+/// Offset of `point` from `origin` in whole pixels
+///
+/// Arrow points are stored relative to the arrow's `x`/`y`, which is the
+/// rounded start point, so both ends are rounded before subtracting. Rounding
+/// the difference instead can land a point a pixel away from where it aims.
+pub fn relative_point(origin: Point, point: Point) -> [i32; 2] {
+    [
+        (point.0.round() - origin.0.round()) as i32,
+        (point.1.round() - origin.1.round()) as i32,
+    ]
+}
+
 /// A* pathfinding node
 #[derive(Debug, Clone, PartialEq)]
 struct AStarNode {
@@ -46,9 +58,10 @@ struct Obstacle {
 
 impl Obstacle {
     fn new(node: &NodeData) -> Self {
+        // Node positions are centers; obstacles are kept by their top-left corner
         Self {
-            x: node.x.round() as i32,
-            y: node.y.round() as i32,
+            x: (node.x - node.width / 2.0).round() as i32,
+            y: (node.y - node.height / 2.0).round() as i32,
             width: node.width.round() as i32,
             height: node.height.round() as i32,
         }
@@ -146,6 +159,58 @@ impl Obstacle {
     }
 }
 
+/// Gap kept between a route and the container boundaries it crosses
+const CONTAINER_CLEARANCE: f64 = 20.0;
+
+/// Bow of curved edges without a `curvature` attribute
+const DEFAULT_CURVATURE: f64 = 0.3;
+
+/// Container rectangles around an edge, used for boundary-aware routing
+#[derive(Debug, Clone, Default)]
+pub struct ContainerContext {
+    /// Containers holding the source but not the target
+    pub exits: Vec<BoundingBox>,
+    /// Containers holding the target but not the source
+    pub entries: Vec<BoundingBox>,
+    /// Containers holding neither endpoint, which routes try to avoid
+    pub others: Vec<BoundingBox>,
+}
+
+impl ContainerContext {
+    fn crosses_others(&self, path: &[Point]) -> bool {
+        path.windows(2).any(|segment| {
+            self.others
+                .iter()
+                .any(|bounds| segment_crosses_box(segment[0], segment[1], bounds))
+        })
+    }
+}
+
+/// Whether a segment passes through the interior of a box; touching its
+/// boundary doesn't count
+fn segment_crosses_box(a: Point, b: Point, bounds: &BoundingBox) -> bool {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+    // Liang-Barsky clipping against each side of the box
+    for (p, q) in [
+        (-dx, a.0 - bounds.x),
+        (dx, bounds.x + bounds.width - a.0),
+        (-dy, a.1 - bounds.y),
+        (dy, bounds.y + bounds.height - a.1),
+    ] {
+        if p == 0.0 {
+            if q <= 0.0 {
+                return false;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    t0 < t1
+}
+
 /// Routing algorithms for edge connections
 pub struct EdgeRouter;
 
@@ -163,20 +228,203 @@ impl EdgeRouter {
         match routing {
             RoutingType::Straight => Self::straight_route(start, end),
             RoutingType::Orthogonal => Self::orthogonal_route(start, end, source_node, target_node),
-            RoutingType::Curved => Self::curved_route(start, end),
+            RoutingType::Curved => Self::curved_route(start, end, DEFAULT_CURVATURE),
             RoutingType::Auto => Self::auto_route(start, end, source_node, target_node),
         }
     }
 
+    /// Generate route points for an edge, taking the containers around it into
+    /// account
+    ///
+    /// Auto and orthogonal edges that leave or enter containers bend just
+    /// outside each boundary they cross and change direction in the gap
+    /// between containers. Among the orthogonal routes considered, the first
+    /// that doesn't cut through an unrelated container wins. Curved edges bow
+    /// by `curvature`, or a moderate default.
+    pub fn route_edge_with_containers(
+        start: Point,
+        end: Point,
+        source_node: &NodeData,
+        target_node: &NodeData,
+        routing_type: Option<RoutingType>,
+        curvature: Option<f64>,
+        containers: &ContainerContext,
+    ) -> Vec<[i32; 2]> {
+        if routing_type == Some(RoutingType::Curved) {
+            return Self::curved_route(start, end, curvature.unwrap_or(DEFAULT_CURVATURE));
+        }
+        let route = Self::route_edge(start, end, source_node, target_node, routing_type);
+        if !matches!(
+            routing_type.unwrap_or(RoutingType::Auto),
+            RoutingType::Auto | RoutingType::Orthogonal
+        ) {
+            return route;
+        }
+
+        let absolute: Vec<Point> = route
+            .iter()
+            .map(|p| (start.0.round() + p[0] as f64, start.1.round() + p[1] as f64))
+            .collect();
+        let crosses_boundary = !containers.exits.is_empty() || !containers.entries.is_empty();
+        if !crosses_boundary && !containers.crosses_others(&absolute) {
+            return route;
+        }
+
+        // Try the dominant axis first
+        let horizontal_first = (end.0 - start.0).abs() >= (end.1 - start.1).abs();
+        let candidates: Vec<Vec<Point>> = [horizontal_first, !horizontal_first]
+            .into_iter()
+            .flat_map(|horizontal| Self::boundary_routes(start, end, containers, horizontal))
+            .collect();
+
+        match candidates
+            .iter()
+            .find(|path| !containers.crosses_others(path))
+            .or_else(|| candidates.first())
+        {
+            Some(path) => path.iter().map(|&p| relative_point(start, p)).collect(),
+            None => route,
+        }
+    }
+
+    /// Generate route points for an edge that must pass through `waypoints`
+    ///
+    /// Each leg between consecutive stops is a straight segment, or a single
+    /// right-angle bend along the leg's dominant axis first for orthogonal
+    /// edges. Curved edges keep straight legs; the rounded arrow smooths them
+    /// into a curve through the waypoints.
+    pub fn route_through_waypoints(
+        start: Point,
+        end: Point,
+        waypoints: &[Point],
+        routing_type: Option<RoutingType>,
+    ) -> Vec<[i32; 2]> {
+        let orthogonal = routing_type == Some(RoutingType::Orthogonal);
+        let stops: Vec<Point> = std::iter::once(start)
+            .chain(waypoints.iter().copied())
+            .chain(std::iter::once(end))
+            .collect();
+
+        let mut path = vec![start];
+        for leg in stops.windows(2) {
+            let (from, to) = (leg[0], leg[1]);
+            if orthogonal && from.0 != to.0 && from.1 != to.1 {
+                let corner = if (to.0 - from.0).abs() >= (to.1 - from.1).abs() {
+                    (to.0, from.1)
+                } else {
+                    (from.0, to.1)
+                };
+                path.push(corner);
+            }
+            path.push(to);
+        }
+
+        let mut points: Vec<[i32; 2]> = Vec::with_capacity(path.len());
+        for p in path {
+            let point = relative_point(start, p);
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        }
+        points
+    }
+
+    /// Orthogonal routes that travel mainly along one axis, leaving the exit
+    /// containers and entering the entry containers through their facing
+    /// sides. Returns nothing if the containers overlap along that axis.
+    fn boundary_routes(
+        start: Point,
+        end: Point,
+        containers: &ContainerContext,
+        horizontal: bool,
+    ) -> Vec<Vec<Point>> {
+        // Work in (along, across) coordinates so both axes share one implementation
+        let flip = |p: Point| if horizontal { p } else { (p.1, p.0) };
+        let span = |bounds: &BoundingBox| {
+            if horizontal {
+                (bounds.x, bounds.x + bounds.width)
+            } else {
+                (bounds.y, bounds.y + bounds.height)
+            }
+        };
+        let (start, end) = (flip(start), flip(end));
+        let direction = if end.0 >= start.0 { 1.0 } else { -1.0 };
+        let outermost = |a: f64, b: f64| if direction > 0.0 { a.max(b) } else { a.min(b) };
+        let innermost = |a: f64, b: f64| if direction > 0.0 { a.min(b) } else { a.max(b) };
+
+        let exit = containers
+            .exits
+            .iter()
+            .map(span)
+            .map(|(low, high)| {
+                if direction > 0.0 {
+                    high + CONTAINER_CLEARANCE
+                } else {
+                    low - CONTAINER_CLEARANCE
+                }
+            })
+            .reduce(outermost)
+            .unwrap_or(start.0);
+        let entry = containers
+            .entries
+            .iter()
+            .map(span)
+            .map(|(low, high)| {
+                if direction > 0.0 {
+                    low - CONTAINER_CLEARANCE
+                } else {
+                    high + CONTAINER_CLEARANCE
+                }
+            })
+            .reduce(innermost)
+            .unwrap_or(end.0);
+        if (entry - exit) * direction < 0.0 {
+            return Vec::new();
+        }
+
+        // Bend midway first, then next to each boundary and beside each
+        // unrelated container in the gap
+        let (low, high) = (exit.min(entry), exit.max(entry));
+        let mut channels = vec![(exit + entry) / 2.0, exit, entry];
+        for (other_low, other_high) in containers.others.iter().map(span) {
+            for channel in [
+                other_low - CONTAINER_CLEARANCE,
+                other_high + CONTAINER_CLEARANCE,
+            ] {
+                if (low..=high).contains(&channel) {
+                    channels.push(channel);
+                }
+            }
+        }
+
+        channels
+            .into_iter()
+            .map(|channel| {
+                let mut path: Vec<Point> = Vec::new();
+                for point in [
+                    start,
+                    (exit, start.1),
+                    (channel, start.1),
+                    (channel, end.1),
+                    (entry, end.1),
+                    end,
+                ] {
+                    let point = flip(point);
+                    let repeated = path.last().is_some_and(|last: &Point| {
+                        (last.0 - point.0).abs() < 0.5 && (last.1 - point.1).abs() < 0.5
+                    });
+                    if !repeated {
+                        path.push(point);
+                    }
+                }
+                path
+            })
+            .collect()
+    }
+
     /// Simple straight line routing (default)
     fn straight_route(start: Point, end: Point) -> Vec<[i32; 2]> {
-        vec![
-            [0, 0],
-            [
-                (end.0 - start.0).round() as i32,
-                (end.1 - start.1).round() as i32,
-            ],
-        ]
+        vec![[0, 0], relative_point(start, end)]
     }
 
     /// Orthogonal (Manhattan) routing with right angles
@@ -189,16 +437,16 @@ impl EdgeRouter {
         let dx = end.0 - start.0;
         let dy = end.1 - start.1;
 
-        // Calculate bounds from node position and size
-        let source_min_x = source.x;
-        let source_min_y = source.y;
-        let source_max_x = source.x + source.width;
-        let source_max_y = source.y + source.height;
+        // Calculate bounds from node center and size
+        let source_min_x = source.x - source.width / 2.0;
+        let source_min_y = source.y - source.height / 2.0;
+        let source_max_x = source.x + source.width / 2.0;
+        let source_max_y = source.y + source.height / 2.0;
 
-        let target_min_x = target.x;
-        let target_min_y = target.y;
-        let target_max_x = target.x + target.width;
-        let target_max_y = target.y + target.height;
+        let target_min_x = target.x - target.width / 2.0;
+        let target_min_y = target.y - target.height / 2.0;
+        let target_max_x = target.x + target.width / 2.0;
+        let target_max_y = target.y + target.height / 2.0;
 
         // Check if nodes are more horizontally or vertically aligned
         let horizontal_overlap = source_max_x > target_min_x && source_min_x < target_max_x;
@@ -209,67 +457,58 @@ impl EdgeRouter {
 
         if horizontal_overlap && !vertical_overlap {
             // Nodes are horizontally aligned, route vertically first
-            let mid_y = dy / 2.0;
-            points.push([0, mid_y.round() as i32]);
-            points.push([dx.round() as i32, mid_y.round() as i32]);
+            let mid_y = start.1 + dy / 2.0;
+            points.push(relative_point(start, (start.0, mid_y)));
+            points.push(relative_point(start, (end.0, mid_y)));
         } else if vertical_overlap && !horizontal_overlap {
             // Nodes are vertically aligned, route horizontally first
-            let mid_x = dx / 2.0;
-            points.push([mid_x.round() as i32, 0]);
-            points.push([mid_x.round() as i32, dy.round() as i32]);
+            let mid_x = start.0 + dx / 2.0;
+            points.push(relative_point(start, (mid_x, start.1)));
+            points.push(relative_point(start, (mid_x, end.1)));
         } else {
             // Default: horizontal first, then vertical
             let _offset = 30.0; // Minimum offset from nodes
 
             if dx.abs() > dy.abs() {
                 // Mainly horizontal connection
-                let mid_x = dx / 2.0;
-                points.push([mid_x.round() as i32, 0]);
-                points.push([mid_x.round() as i32, dy.round() as i32]);
+                let mid_x = start.0 + dx / 2.0;
+                points.push(relative_point(start, (mid_x, start.1)));
+                points.push(relative_point(start, (mid_x, end.1)));
             } else {
                 // Mainly vertical connection
-                let mid_y = dy / 2.0;
-                points.push([0, mid_y.round() as i32]);
-                points.push([dx.round() as i32, mid_y.round() as i32]);
+                let mid_y = start.1 + dy / 2.0;
+                points.push(relative_point(start, (start.0, mid_y)));
+                points.push(relative_point(start, (end.0, mid_y)));
             }
         }
 
-        points.push([dx.round() as i32, dy.round() as i32]);
+        points.push(relative_point(start, end));
         points
     }
 
-    /// Curved routing using bezier-like control points
-    fn curved_route(start: Point, end: Point) -> Vec<[i32; 2]> {
+    /// Curved routing through one control point, pushed off the midpoint
+    /// perpendicular to the edge by `curvature` times half the edge length
+    fn curved_route(start: Point, end: Point, curvature: f64) -> Vec<[i32; 2]> {
         let dx = end.0 - start.0;
         let dy = end.1 - start.1;
-
-        // Create a simple curved path with one control point
-        let control_offset = dx.abs().min(dy.abs()) * 0.3;
-
-        let mut points = vec![[0, 0]];
-
-        if dx.abs() > dy.abs() {
-            // Horizontal curve
-            let mid_x = dx / 2.0;
-            let control_y = if dy > 0.0 {
-                control_offset
-            } else {
-                -control_offset
-            };
-            points.push([mid_x.round() as i32, control_y.round() as i32]);
-        } else {
-            // Vertical curve
-            let mid_y = dy / 2.0;
-            let control_x = if dx > 0.0 {
-                control_offset
-            } else {
-                -control_offset
-            };
-            points.push([control_x.round() as i32, mid_y.round() as i32]);
+        let length = dx.hypot(dy);
+        let end_point = relative_point(start, end);
+        if length == 0.0 {
+            return vec![[0, 0], end_point];
         }
 
-        points.push([dx.round() as i32, dy.round() as i32]);
-        points
+        // Unit normal to the left of the travel direction
+        let (nx, ny) = (-dy / length, dx / length);
+        let offset = curvature * length / 2.0;
+        let control = relative_point(
+            start,
+            (
+                start.0 + dx / 2.0 + nx * offset,
+                start.1 + dy / 2.0 + ny * offset,
+            ),
+        );
+
+        vec![[0, 0], control, end_point]
     }
 
     /// Automatic routing - chooses the best algorithm based on node arrangement
@@ -313,9 +552,13 @@ impl EdgeRouter {
             return Self::route_edge(start, end, source_node, target_node, routing_type);
         }
 
-        // Use A* pathfinding to find route around obstacles
+        // Use A* pathfinding to find route around obstacles. The search runs
+        // in absolute coordinates; the path is made relative to its start.
         match Self::find_path_astar(start_i, end_i, &obstacle_rects) {
-            Some(path) => path.into_iter().map(|p| [p.0, p.1]).collect(),
+            Some(path) => path
+                .into_iter()
+                .map(|p| [p.0 - start_i.0, p.1 - start_i.1])
+                .collect(),
             None => {
                 // Fallback to basic routing if pathfinding fails
                 Self::route_edge(start, end, source_node, target_node, routing_type)
@@ -466,6 +709,7 @@ mod tests {
             width,
             height,
             is_virtual_container: false,
+            metadata: Default::default(),
         }
     }
 
@@ -495,11 +739,140 @@ mod tests {
         assert_eq!(points[points.len() - 1], [100, 100]);
     }
 
+    #[test]
+    fn test_route_across_container_boundaries() {
+        let bounds = |x, y, width, height| BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        };
+        let source = create_test_node(50.0, 25.0, 100.0, 50.0);
+        let target = create_test_node(450.0, 25.0, 100.0, 50.0);
+        let containers = ContainerContext {
+            exits: vec![bounds(0.0, 0.0, 200.0, 100.0)],
+            entries: vec![bounds(400.0, 0.0, 200.0, 100.0)],
+            others: vec![],
+        };
+
+        let points = EdgeRouter::route_edge_with_containers(
+            (150.0, 50.0),
+            (450.0, 50.0),
+            &source,
+            &target,
+            None,
+            None,
+            &containers,
+        );
+        assert_eq!(points.first(), Some(&[0, 0]));
+        assert_eq!(points.last(), Some(&[300, 0]));
+        // Waypoints just outside the right side of the source container and
+        // the left side of the target container
+        assert!(points.contains(&[70, 0]));
+        assert!(points.contains(&[230, 0]));
+
+        // Straight edges ignore containers
+        let straight = EdgeRouter::route_edge_with_containers(
+            (150.0, 50.0),
+            (450.0, 50.0),
+            &source,
+            &target,
+            Some(RoutingType::Straight),
+            None,
+            &containers,
+        );
+        assert_eq!(straight, vec![[0, 0], [300, 0]]);
+    }
+
+    #[test]
+    fn test_route_avoids_unrelated_containers() {
+        let bounds = |x, y, width, height| BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        };
+        let source = create_test_node(50.0, 25.0, 100.0, 50.0);
+        let target = create_test_node(450.0, 225.0, 100.0, 50.0);
+        let unrelated = bounds(250.0, 100.0, 100.0, 140.0);
+        let containers = ContainerContext {
+            exits: vec![bounds(0.0, 0.0, 200.0, 100.0)],
+            entries: vec![bounds(400.0, 200.0, 200.0, 100.0)],
+            others: vec![unrelated.clone()],
+        };
+
+        let start = (150.0, 50.0);
+        let points = EdgeRouter::route_edge_with_containers(
+            start,
+            (450.0, 250.0),
+            &source,
+            &target,
+            Some(RoutingType::Orthogonal),
+            None,
+            &containers,
+        );
+        assert_eq!(points.last(), Some(&[300, 200]));
+        let absolute: Vec<Point> = points
+            .iter()
+            .map(|p| (start.0 + p[0] as f64, start.1 + p[1] as f64))
+            .collect();
+        assert!(absolute
+            .windows(2)
+            .all(|segment| segment[0].0 == segment[1].0 || segment[0].1 == segment[1].1));
+        assert!(!absolute
+            .windows(2)
+            .any(|segment| segment_crosses_box(segment[0], segment[1], &unrelated)));
+    }
+
+    #[test]
+    fn test_route_points_are_relative_to_rounded_origin() {
+        let start = (10.6, 20.4);
+        let points = EdgeRouter::route_through_waypoints(
+            start,
+            (200.4, 120.6),
+            &[(100.0, 60.0)],
+            Some(RoutingType::Orthogonal),
+        );
+        // The arrow sits at the rounded start point, (11, 20)
+        assert_eq!(
+            points,
+            vec![[0, 0], [89, 0], [89, 40], [189, 40], [189, 101]]
+        );
+        assert_eq!(relative_point(start, (100.0, 60.0)), [89, 40]);
+    }
+
+    #[test]
+    fn test_avoidance_route_is_relative() {
+        let source = create_test_node(0.0, 0.0, 20.0, 20.0);
+        let target = create_test_node(200.0, 0.0, 20.0, 20.0);
+        let obstacle = NodeData {
+            id: "obstacle".to_string(),
+            ..create_test_node(100.0, 0.0, 40.0, 40.0)
+        };
+        let start = (10.0, 0.0);
+
+        let points = EdgeRouter::route_with_avoidance(
+            start,
+            (190.0, 0.0),
+            &source,
+            &target,
+            &[source.clone(), target.clone(), obstacle.clone()],
+            None,
+        );
+        assert!(points.len() > 2);
+        assert_eq!(points.first(), Some(&[0, 0]));
+        assert_eq!(points.last(), Some(&[180, 0]));
+        let blocked = Obstacle::new(&obstacle);
+        assert!(points
+            .iter()
+            .all(|p| !blocked.contains_point(start.0 as i32 + p[0], start.1 as i32 + p[1])));
+    }
+
     #[test]
     fn test_curved_route() {
         let start = (0.0, 0.0);
         let end = (100.0, 50.0);
-        let points = EdgeRouter::curved_route(start, end);
+        let points = EdgeRouter::curved_route(start, end, DEFAULT_CURVATURE);
 
         assert_eq!(points.len(), 3); // Start, control point, end
         assert_eq!(points[0], [0, 0]);
@@ -507,4 +880,28 @@ mod tests {
         // Control point should be offset from the straight line
         assert_ne!(points[1][1], 25); // Not on the straight line
     }
+
+    #[test]
+    fn test_curvature_scales_bow() {
+        let source = create_test_node(0.0, 0.0, 50.0, 50.0);
+        let target = create_test_node(200.0, 0.0, 50.0, 50.0);
+        let bow = |curvature: f64| {
+            let points = EdgeRouter::route_edge_with_containers(
+                (0.0, 0.0),
+                (200.0, 0.0),
+                &source,
+                &target,
+                Some(RoutingType::Curved),
+                Some(curvature),
+                &ContainerContext::default(),
+            );
+            // Distance of the control point from the straight line
+            points[1][1].abs()
+        };
+
+        assert_eq!(bow(0.0), 0);
+        assert!(bow(0.2) < bow(0.6));
+        assert!(bow(0.6) < bow(1.0));
+        assert_eq!(bow(1.0), 100);
+    }
 }
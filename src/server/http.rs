@@ -1,4 +1,8 @@
 // src/server/http.rs
+use crate::error::LayoutError;
+use crate::generator::ExcalidrawFile;
+use crate::server::patch::{diff_files, PatchOperation};
+use crate::server::websocket::WebSocketResponse;
 use crate::{EDSLCompiler, EDSLError, Result};
 use axum::{
     extract::{Path, Query, State, WebSocketUpgrade},
@@ -8,8 +12,11 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
 use tower::ServiceBuilder;
 use tower_http::{cors::CorsLayer, limit::RequestBodyLimitLayer};
 
@@ -27,6 +34,25 @@ pub struct CompileResponse {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompilePatchRequest {
+    pub edsl_content: String,
+    /// Token of the compile the client currently holds
+    pub token: Option<String>,
+}
+
+/// Either a JSON Patch against the file behind the request token, or the full
+/// file when the token is missing or has expired
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompilePatchResponse {
+    pub success: bool,
+    /// Token identifying this compile, to send with the next request
+    pub token: Option<String>,
+    pub patch: Option<Vec<PatchOperation>>,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ValidateRequest {
     pub edsl_content: String,
@@ -89,10 +115,37 @@ pub struct StatesResponse {
     pub directory: String,
 }
 
+/// Number of live-reload frames buffered for slow WebSocket clients
+const UPDATE_CHANNEL_CAPACITY: usize = 16;
+
+/// Number of compiled files kept as bases for `/api/compile/patch`
+const PATCH_SNAPSHOT_CAPACITY: usize = 32;
+
+/// Longest a single layout may run before the request fails with 504
+const LAYOUT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of compiled outputs each compiler keeps for repeated sources
+const COMPILE_CACHE_CAPACITY: usize = 64;
+
+/// HTTP status for a failed compile: layout timeouts are the server's
+/// fault, everything else is a problem with the input
+fn compile_error_status(error: &EDSLError) -> StatusCode {
+    match error {
+        EDSLError::Layout(LayoutError::Timeout { .. }) => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub compiler: Arc<Mutex<EDSLCompiler>>,
+    /// Serialized frames pushed to every connected WebSocket client
+    pub updates: broadcast::Sender<String>,
+    /// Compiler with stable ids, so patches can match elements across compiles
+    pub patch_compiler: Arc<Mutex<EDSLCompiler>>,
+    /// Recent patch compiles by token, oldest first
+    pub patch_snapshots: Arc<Mutex<VecDeque<(String, ExcalidrawFile)>>>,
 }
 
 impl Default for AppState {
@@ -105,7 +158,21 @@ impl AppState {
     pub fn new() -> Self {
         // Use default compiler (LLM optimization disabled by default)
         Self {
-            compiler: Arc::new(Mutex::new(EDSLCompiler::new())),
+            compiler: Arc::new(Mutex::new(
+                EDSLCompiler::builder()
+                    .with_layout_timeout(LAYOUT_TIMEOUT)
+                    .with_output_cache(COMPILE_CACHE_CAPACITY)
+                    .build(),
+            )),
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+            patch_compiler: Arc::new(Mutex::new(
+                EDSLCompiler::builder()
+                    .with_stable_ids(true)
+                    .with_layout_timeout(LAYOUT_TIMEOUT)
+                    .with_output_cache(COMPILE_CACHE_CAPACITY)
+                    .build(),
+            )),
+            patch_snapshots: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
@@ -114,8 +181,92 @@ impl AppState {
         // Note: LLM optimization disabled in server context due to runtime conflicts
         log::warn!("LLM optimization disabled in server context due to runtime conflicts");
         Self {
-            compiler: Arc::new(Mutex::new(EDSLCompiler::new())),
+            compiler: Arc::new(Mutex::new(
+                EDSLCompiler::builder()
+                    .with_layout_timeout(LAYOUT_TIMEOUT)
+                    .with_output_cache(COMPILE_CACHE_CAPACITY)
+                    .build(),
+            )),
+            updates: broadcast::channel(UPDATE_CHANNEL_CAPACITY).0,
+            patch_compiler: Arc::new(Mutex::new(
+                EDSLCompiler::builder()
+                    .with_stable_ids(true)
+                    .with_layout_timeout(LAYOUT_TIMEOUT)
+                    .with_output_cache(COMPILE_CACHE_CAPACITY)
+                    .build(),
+            )),
+            patch_snapshots: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+}
+
+impl AppState {
+    /// Receive the frames broadcast by [`publish_update`](Self::publish_update)
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<String> {
+        self.updates.subscribe()
+    }
+
+    /// Compile `edsl_content` and push the result to all WebSocket clients
+    ///
+    /// Used by `edsl watch --serve` on every save. A failed compile is sent as a
+    /// `diagram_error` frame so clients stay connected and keep the last diagram.
+    pub fn publish_update(&self, source: &str, edsl_content: &str) -> WebSocketResponse {
+        let compiled = self.compiler.lock().unwrap().compile(edsl_content);
+        let response = match compiled.and_then(|json| {
+            serde_json::from_str::<serde_json::Value>(&json).map_err(EDSLError::Json)
+        }) {
+            Ok(data) => WebSocketResponse::DiagramUpdate {
+                source: source.to_string(),
+                data,
+            },
+            Err(e) => WebSocketResponse::DiagramError {
+                source: source.to_string(),
+                error: e.to_string(),
+            },
+        };
+
+        if let Ok(frame) = serde_json::to_string(&response) {
+            // Sending only fails when nobody is connected, which is fine
+            let _ = self.updates.send(frame);
         }
+        response
+    }
+}
+
+impl AppState {
+    /// Compile `edsl_content` and describe the result as a JSON Patch against
+    /// the file compiled under `token`; an unknown token gets the full file
+    pub fn compile_patch(
+        &self,
+        token: Option<&str>,
+        edsl_content: &str,
+    ) -> Result<CompilePatchResponse> {
+        let json = self.patch_compiler.lock().unwrap().compile(edsl_content)?;
+        let file: ExcalidrawFile = serde_json::from_str(&json).map_err(EDSLError::Json)?;
+
+        let mut snapshots = self.patch_snapshots.lock().unwrap();
+        let base = token.and_then(|token| snapshots.iter().find(|(t, _)| t == token));
+        let (patch, data) = match base {
+            Some((_, previous)) => (Some(diff_files(previous, &file)?), None),
+            None => (
+                None,
+                Some(serde_json::to_value(&file).map_err(EDSLError::Json)?),
+            ),
+        };
+
+        let new_token = uuid::Uuid::new_v4().to_string();
+        snapshots.push_back((new_token.clone(), file));
+        if snapshots.len() > PATCH_SNAPSHOT_CAPACITY {
+            snapshots.pop_front();
+        }
+
+        Ok(CompilePatchResponse {
+            success: true,
+            token: Some(new_token),
+            patch,
+            data,
+            error: None,
+        })
     }
 }
 
@@ -138,6 +289,7 @@ pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health_handler))
         .route("/api/compile", post(compile_handler))
+        .route("/api/compile/patch", post(compile_patch_handler))
         .route("/api/validate", post(validate_handler))
         .route("/api/ws", get(websocket_handler))
         .route("/api/files", get(list_files_handler))
@@ -237,7 +389,7 @@ async fn compile_handler(
             }
 
             (
-                StatusCode::BAD_REQUEST,
+                compile_error_status(&e),
                 Json(CompileResponse {
                     success: false,
                     data: None,
@@ -249,6 +401,36 @@ async fn compile_handler(
     }
 }
 
+/// Compile EDSL and return a JSON Patch against the client's previous compile
+async fn compile_patch_handler(
+    State(state): State<AppState>,
+    Json(req): Json<CompilePatchRequest>,
+) -> Response {
+    log::debug!(
+        "Compiling EDSL patch ({} chars, token {:?})",
+        req.edsl_content.len(),
+        req.token
+    );
+
+    match state.compile_patch(req.token.as_deref(), &req.edsl_content) {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            log::warn!("Patch compilation failed: {e}");
+            (
+                compile_error_status(&e),
+                Json(CompilePatchResponse {
+                    success: false,
+                    token: None,
+                    patch: None,
+                    data: None,
+                    error: Some(e.to_string()),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// Validate EDSL syntax
 async fn validate_handler(
     State(state): State<AppState>,
@@ -480,6 +662,7 @@ pub async fn start_server(port: u16, state: AppState) -> Result<()> {
     log::info!("Health check: http://localhost:{port}/health");
     log::info!("API endpoints:");
     log::info!("  POST http://localhost:{port}/api/compile");
+    log::info!("  POST http://localhost:{port}/api/compile/patch");
     log::info!("  POST http://localhost:{port}/api/validate");
     log::info!("  GET  http://localhost:{port}/api/files?path=<directory>");
     log::info!("  GET  http://localhost:{port}/api/file/<filepath>");
@@ -491,3 +674,55 @@ pub async fn start_server(port: u16, state: AppState) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_error_status() {
+        let timeout = EDSLError::Layout(LayoutError::Timeout {
+            engine: "force".to_string(),
+            elapsed: LAYOUT_TIMEOUT,
+        });
+        assert_eq!(compile_error_status(&timeout), StatusCode::GATEWAY_TIMEOUT);
+
+        let unknown = EDSLError::Layout(LayoutError::UnknownEngine("grid".to_string()));
+        assert_eq!(compile_error_status(&unknown), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_compile_patch_tokens() {
+        let state = AppState::new();
+
+        // Without a base the full file comes back
+        let first = state.compile_patch(None, "a[A]\nb[B]\na -> b").unwrap();
+        assert!(first.patch.is_none());
+        let elements = first.data.as_ref().unwrap()["elements"].as_array().unwrap();
+        let label = elements.iter().find(|e| e["text"] == "B").unwrap();
+        let b = elements
+            .iter()
+            .position(|e| e["id"] == label["containerId"])
+            .unwrap();
+
+        let second = state
+            .compile_patch(
+                first.token.as_deref(),
+                "a[A]\nb[B] { strokeColor: \"#e03131\"; }\na -> b",
+            )
+            .unwrap();
+        let patch = second.patch.unwrap();
+        assert!(second.data.is_none());
+        // Only b's shape changed
+        assert!(!patch.is_empty());
+        let prefix = format!("/elements/{b}/");
+        assert!(
+            patch.iter().all(|op| op.path().starts_with(&prefix)),
+            "{patch:?}"
+        );
+        assert_ne!(second.token, first.token);
+
+        let unknown = state.compile_patch(Some("stale"), "a[A]").unwrap();
+        assert!(unknown.patch.is_none() && unknown.data.is_some());
+    }
+}
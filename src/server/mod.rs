@@ -1,5 +1,6 @@
 // src/server/mod.rs
 pub mod http;
+pub mod patch;
 pub mod websocket;
 
 pub use http::{create_router, start_server, AppState};
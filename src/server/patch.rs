@@ -0,0 +1,241 @@
+// src/server/patch.rs
+//! JSON Patch (RFC 6902) deltas between compiled Excalidraw files
+//!
+//! Elements are matched by id, so both files must come from a compiler with
+//! stable ids. Values that change on every render (`seed`, `versionNonce`,
+//! `updated`) are ignored: an element whose content is unchanged produces no
+//! operations and keeps its previous seed on the client.
+
+use crate::generator::ExcalidrawFile;
+use crate::{EDSLError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Element fields that differ between renders of the same diagram
+const VOLATILE_FIELDS: &[&str] = &["seed", "versionNonce", "updated"];
+
+/// A single JSON Patch operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+impl PatchOperation {
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Replace { path, .. } => path,
+        }
+    }
+}
+
+/// Compute the JSON Patch that turns `old` into `new`
+///
+/// Changed elements are patched field by field. If elements present in both
+/// files were reordered, the whole `elements` array is replaced instead.
+pub fn diff_files(old: &ExcalidrawFile, new: &ExcalidrawFile) -> Result<Vec<PatchOperation>> {
+    let old = serde_json::to_value(old).map_err(EDSLError::Json)?;
+    let new = serde_json::to_value(new).map_err(EDSLError::Json)?;
+
+    let mut operations = Vec::new();
+    diff_fields(&mut operations, "", &old, &new, &["elements"]);
+
+    let elements = |file: &Value| file["elements"].as_array().cloned().unwrap_or_default();
+    diff_elements(&mut operations, &elements(&old), &elements(&new));
+    Ok(operations)
+}
+
+/// Diff the fields of two objects, skipping `ignored` keys; anything that
+/// isn't an object is replaced whole
+fn diff_fields(
+    operations: &mut Vec<PatchOperation>,
+    prefix: &str,
+    old: &Value,
+    new: &Value,
+    ignored: &[&str],
+) {
+    let (Some(old_fields), Some(new_fields)) = (old.as_object(), new.as_object()) else {
+        if old != new {
+            operations.push(PatchOperation::Replace {
+                path: prefix.to_string(),
+                value: new.clone(),
+            });
+        }
+        return;
+    };
+
+    let path = |key: &str| format!("{prefix}/{}", escape_pointer(key));
+    let relevant = |fields: &Map<String, Value>| {
+        let mut keys: Vec<String> = fields
+            .keys()
+            .filter(|key| !ignored.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    };
+
+    for key in relevant(old_fields) {
+        if !new_fields.contains_key(&key) {
+            operations.push(PatchOperation::Remove { path: path(&key) });
+        }
+    }
+    for key in relevant(new_fields) {
+        let value = &new_fields[&key];
+        match old_fields.get(&key) {
+            None => operations.push(PatchOperation::Add {
+                path: path(&key),
+                value: value.clone(),
+            }),
+            Some(old_value) if old_value != value => operations.push(PatchOperation::Replace {
+                path: path(&key),
+                value: value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+}
+
+fn diff_elements(operations: &mut Vec<PatchOperation>, old: &[Value], new: &[Value]) {
+    let id = |element: &Value| element["id"].as_str().map(str::to_string);
+    let old_ids: Vec<Option<String>> = old.iter().map(id).collect();
+    let new_ids: Vec<Option<String>> = new.iter().map(id).collect();
+    let old_set: HashSet<&str> = old_ids.iter().flatten().map(String::as_str).collect();
+    let new_set: HashSet<&str> = new_ids.iter().flatten().map(String::as_str).collect();
+
+    // Index-based patching needs unique ids and kept elements in the same order
+    let kept = |ids: &[Option<String>], other: &HashSet<&str>| -> Vec<String> {
+        ids.iter()
+            .flatten()
+            .filter(|id| other.contains(id.as_str()))
+            .cloned()
+            .collect()
+    };
+    let patchable = old_set.len() == old.len()
+        && new_set.len() == new.len()
+        && kept(&old_ids, &new_set) == kept(&new_ids, &old_set);
+    if !patchable {
+        operations.push(PatchOperation::Replace {
+            path: "/elements".to_string(),
+            value: Value::Array(new.to_vec()),
+        });
+        return;
+    }
+
+    // Remove from the back so earlier indices stay valid
+    for (index, element_id) in old_ids.iter().enumerate().rev() {
+        if let Some(element_id) = element_id {
+            if !new_set.contains(element_id.as_str()) {
+                operations.push(PatchOperation::Remove {
+                    path: format!("/elements/{index}"),
+                });
+            }
+        }
+    }
+
+    // After removals, the first `index` elements always match `new`
+    let old_by_id: HashMap<&str, &Value> = old_ids
+        .iter()
+        .zip(old)
+        .filter_map(|(id, element)| Some((id.as_deref()?, element)))
+        .collect();
+    for (index, (element_id, element)) in new_ids.iter().zip(new).enumerate() {
+        let path = format!("/elements/{index}");
+        match element_id.as_deref().and_then(|id| old_by_id.get(id)) {
+            Some(old_element) => {
+                diff_fields(operations, &path, old_element, element, VOLATILE_FIELDS)
+            }
+            None => operations.push(PatchOperation::Add {
+                path,
+                value: element.clone(),
+            }),
+        }
+    }
+}
+
+/// Escape a key for use as a JSON Pointer segment
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EDSLCompiler;
+
+    fn compile(source: &str) -> ExcalidrawFile {
+        let json = EDSLCompiler::builder()
+            .with_stable_ids(true)
+            .build()
+            .compile(source)
+            .unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_diff_touches_only_changed_element() {
+        let before = compile(
+            r##"
+a[A] { backgroundColor: "#a5d8ff"; }
+b[B]
+a -> b
+"##,
+        );
+        let after = compile(
+            r##"
+a[A] { backgroundColor: "#ffc9c9"; }
+b[B]
+a -> b
+"##,
+        );
+
+        assert!(diff_files(&before, &before).unwrap().is_empty());
+
+        let patch = diff_files(&before, &after).unwrap();
+        let index = after
+            .elements
+            .iter()
+            .position(|e| e.background_color == "#ffc9c9")
+            .unwrap();
+        assert_eq!(
+            patch,
+            [PatchOperation::Replace {
+                path: format!("/elements/{index}/backgroundColor"),
+                value: Value::String("#ffc9c9".to_string()),
+            }]
+        );
+
+        let json = serde_json::to_value(&patch).unwrap();
+        assert_eq!(json[0]["op"], "replace");
+    }
+
+    #[test]
+    fn test_diff_adds_and_removes_elements() {
+        let before = compile("a[A]\nb[B]\nc[C]\na -> b\n");
+        let after = compile("a[A]\nb[B]\nd[D]\na -> b\n");
+        let patch = diff_files(&before, &after).unwrap();
+
+        let removed: Vec<_> = patch
+            .iter()
+            .filter(|op| matches!(op, PatchOperation::Remove { .. }))
+            .collect();
+        let added: Vec<_> = patch
+            .iter()
+            .filter(|op| matches!(op, PatchOperation::Add { .. }))
+            .collect();
+        // The node and its label text go; the new pair arrives
+        assert_eq!(removed.len(), 2);
+        assert_eq!(added.len(), 2);
+        assert!(patch.iter().all(|op| op.path().starts_with("/elements/")));
+    }
+
+    #[test]
+    fn test_escape_pointer() {
+        assert_eq!(escape_pointer("a/b~c"), "a~1b~0c");
+    }
+}
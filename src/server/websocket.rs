@@ -4,6 +4,7 @@ use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tokio::time;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,6 +52,15 @@ pub enum WebSocketResponse {
         version: String,
         features: Vec<String>,
     },
+    /// Live-reload push after a watched file recompiled successfully
+    #[serde(rename = "diagram_update")]
+    DiagramUpdate {
+        source: String,
+        data: serde_json::Value,
+    },
+    /// Live-reload push after a watched file failed to compile
+    #[serde(rename = "diagram_error")]
+    DiagramError { source: String, error: String },
 }
 
 /// Handle WebSocket connections for real-time compilation
@@ -78,8 +88,32 @@ pub async fn handle_websocket(socket: WebSocket, state: AppState) {
 
     log::info!("WebSocket client connected");
 
-    // Handle incoming messages
-    while let Some(msg) = receiver.next().await {
+    let mut updates = state.subscribe_updates();
+
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            update = updates.recv() => {
+                match update {
+                    Ok(frame) => {
+                        if sender.send(Message::Text(frame.into())).await.is_err() {
+                            log::warn!("Failed to push diagram update");
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("WebSocket client skipped {skipped} diagram updates");
+                    }
+                    // The state owns the sender, so the channel outlives the connection
+                    Err(broadcast::error::RecvError::Closed) => {}
+                }
+                continue;
+            }
+        };
+
         match msg {
             Ok(Message::Text(text)) => {
                 let start_time = Instant::now();
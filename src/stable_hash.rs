@@ -0,0 +1,97 @@
+// src/stable_hash.rs
+//! Hashing whose output doesn't change between Rust releases
+//!
+//! `DefaultHasher` is free to change its algorithm in any release, which is
+//! fine for in-memory maps but not for hashes written to disk or into
+//! generated files. Those use [`StableHasher`] instead.
+
+use std::hash::{Hash, Hasher};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a, with integers always fed in as little-endian 64-bit values
+/// so the result is the same on every platform
+#[derive(Debug, Clone)]
+pub(crate) struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_isize(&mut self, n: isize) {
+        self.write_u64(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hash `value` with a fresh [`StableHasher`]
+pub(crate) fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hasher_matches_fnv1a() {
+        // Reference FNV-1a 64 values
+        let mut hasher = StableHasher::default();
+        assert_eq!(hasher.finish(), 0xcbf2_9ce4_8422_2325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+
+        let mut hasher = StableHasher::default();
+        hasher.write(b"foobar");
+        assert_eq!(hasher.finish(), 0x8594_4171_f739_67e8);
+
+        // Integer width doesn't change the hash
+        let int = |write: fn(&mut StableHasher)| {
+            let mut hasher = StableHasher::default();
+            write(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(int(|h| h.write_usize(7)), int(|h| h.write_u64(7)));
+        assert_eq!(int(|h| h.write_u32(7)), int(|h| h.write_u64(7)));
+    }
+}
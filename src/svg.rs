@@ -0,0 +1,361 @@
+// src/svg.rs
+//! SVG export for viewing diagrams in browsers and embedding them in docs
+//!
+//! Walks a laid-out `IntermediateGraph` and emits a standalone SVG document.
+//! Shapes are drawn crisply rather than hand-drawn, edges are straight lines
+//! clipped to the node outlines, and the view box fits the whole diagram.
+
+use crate::ast::{ArrowType, ArrowheadType, StrokeStyle};
+use crate::error::{GeneratorError, Result};
+use crate::graphml::escape_xml;
+use crate::igr::{BoundingBox, ExcalidrawAttributes, IntermediateGraph, NodeData};
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+/// Margin around the diagram inside the view box
+const SVG_PADDING: f64 = 20.0;
+
+const DEFAULT_STROKE: &str = "#1e1e1e";
+const DEFAULT_FONT_SIZE: f64 = 16.0;
+
+pub struct SvgGenerator;
+
+impl SvgGenerator {
+    /// Generate an SVG document for a laid-out graph
+    ///
+    /// # Examples
+    /// ```rust
+    /// use excalidraw_dsl::svg::SvgGenerator;
+    ///
+    /// let igr = excalidraw_dsl::parse_and_layout("a[A]\nb[B]\na -> b", "dagre").unwrap();
+    /// let svg = SvgGenerator::generate(&igr).unwrap();
+    /// assert!(svg.starts_with("<svg"));
+    /// ```
+    pub fn generate(igr: &IntermediateGraph) -> Result<String> {
+        let nodes: Vec<&NodeData> = igr
+            .graph
+            .node_weights()
+            .filter(|node| !node.is_virtual_container)
+            .collect();
+        for node in &nodes {
+            if !node.x.is_finite() || !node.y.is_finite() {
+                return Err(GeneratorError::InvalidCoordinate {
+                    x: node.x,
+                    y: node.y,
+                }
+                .into());
+            }
+        }
+
+        let view = Self::view_box(igr);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{:.1} {:.1} {:.1} {:.1}\" width=\"{:.0}\" height=\"{:.0}\">",
+            view.x, view.y, view.width, view.height, view.width, view.height
+        );
+        out.push_str(concat!(
+            "  <defs>\n",
+            "    <marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" ",
+            "markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">\n",
+            "      <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"context-stroke\"/>\n",
+            "    </marker>\n",
+            "  </defs>\n",
+        ));
+
+        // Groups and containers first so they sit behind the nodes
+        for group in &igr.groups {
+            if let Some(ref bounds) = group.bounds {
+                Self::write_cluster(&mut out, bounds, group.label.as_deref(), &group.attributes);
+            }
+        }
+        for container in &igr.containers {
+            if let Some(ref bounds) = container.bounds {
+                Self::write_cluster(
+                    &mut out,
+                    bounds,
+                    container.label.as_deref(),
+                    &container.attributes,
+                );
+            }
+        }
+
+        for edge in igr.graph.edge_references() {
+            let source = &igr.graph[edge.source()];
+            let target = &igr.graph[edge.target()];
+            // Layout fits container and group virtual nodes to their bounds,
+            // so edges to a cluster are clipped to its outline like any node
+            let edge_data = edge.weight();
+            let (x1, y1) = boundary_point(source, target.x, target.y);
+            let (x2, y2) = boundary_point(target, source.x, source.y);
+
+            let mut attributes = edge_data.attributes.clone();
+            attributes.stroke_style = attributes
+                .stroke_style
+                .or_else(|| edge_data.arrow_type.default_stroke_style());
+            attributes.stroke_width = attributes.stroke_width.or_else(|| {
+                edge_data
+                    .arrow_type
+                    .edge_stroke_width(igr.global_config.edge_width)
+            });
+            let (start, end) = Self::arrowheads(&edge_data.arrow_type, &attributes);
+
+            let _ = writeln!(
+                out,
+                "  <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\"{}{}{}/>",
+                Self::stroke(&attributes),
+                if start {
+                    " marker-start=\"url(#arrow)\""
+                } else {
+                    ""
+                },
+                if end {
+                    " marker-end=\"url(#arrow)\""
+                } else {
+                    ""
+                },
+            );
+            if let Some(ref label) = edge_data.label {
+                Self::write_text(
+                    &mut out,
+                    (x1 + x2) / 2.0,
+                    (y1 + y2) / 2.0,
+                    label,
+                    &edge_data.attributes,
+                );
+            }
+        }
+
+        for node in nodes {
+            Self::write_node(&mut out, node);
+        }
+
+        out.push_str("</svg>\n");
+        Ok(out)
+    }
+
+    /// Bounds of the whole diagram, padded
+    fn view_box(igr: &IntermediateGraph) -> BoundingBox {
+        let bounds = igr.bounding_box();
+        BoundingBox {
+            x: bounds.x - SVG_PADDING,
+            y: bounds.y - SVG_PADDING,
+            width: bounds.width + 2.0 * SVG_PADDING,
+            height: bounds.height + 2.0 * SVG_PADDING,
+        }
+    }
+
+    fn write_node(out: &mut String, node: &NodeData) {
+        let attributes = &node.attributes;
+        let (x, y, w, h) = (node.x, node.y, node.width, node.height);
+        let style = format!("{}{}", Self::fill(attributes), Self::stroke(attributes));
+        let rotate = attributes
+            .angle
+            .map(|angle| format!(" transform=\"rotate({angle:.1} {x:.1} {y:.1})\""))
+            .unwrap_or_default();
+
+        match attributes.shape.as_deref() {
+            Some("text") => {}
+            Some("ellipse") | Some("circle") => {
+                let _ = writeln!(
+                    out,
+                    "  <ellipse cx=\"{x:.1}\" cy=\"{y:.1}\" rx=\"{:.1}\" ry=\"{:.1}\"{style}{rotate}/>",
+                    w / 2.0,
+                    h / 2.0
+                );
+            }
+            Some("diamond") => {
+                let _ = writeln!(
+                    out,
+                    "  <polygon points=\"{x:.1},{:.1} {:.1},{y:.1} {x:.1},{:.1} {:.1},{y:.1}\"{style}{rotate}/>",
+                    y - h / 2.0,
+                    x + w / 2.0,
+                    y + h / 2.0,
+                    x - w / 2.0
+                );
+            }
+            _ => {
+                let radius = attributes.rounded.unwrap_or(0.0);
+                let _ = writeln!(
+                    out,
+                    "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{w:.1}\" height=\"{h:.1}\" rx=\"{radius:.1}\"{style}{rotate}/>",
+                    x - w / 2.0,
+                    y - h / 2.0
+                );
+            }
+        }
+        if node.shows_label() {
+            Self::write_text(out, x, y, &node.label, attributes);
+        }
+    }
+
+    fn write_cluster(
+        out: &mut String,
+        bounds: &BoundingBox,
+        label: Option<&str>,
+        attributes: &ExcalidrawAttributes,
+    ) {
+        let _ = writeln!(
+            out,
+            "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"8\"{}{}/>",
+            bounds.x,
+            bounds.y,
+            bounds.width,
+            bounds.height,
+            Self::fill(attributes),
+            Self::stroke(attributes)
+        );
+        if let Some(label) = label {
+            let _ = writeln!(
+                out,
+                "  <text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"14\" fill=\"{}\">{}</text>",
+                bounds.x + 8.0,
+                bounds.y + 18.0,
+                attributes.text_color.as_deref().unwrap_or(DEFAULT_STROKE),
+                escape_xml(label)
+            );
+        }
+    }
+
+    /// Centered, possibly multi-line text
+    fn write_text(out: &mut String, x: f64, y: f64, text: &str, attributes: &ExcalidrawAttributes) {
+        if text.is_empty() {
+            return;
+        }
+        let font_size = attributes.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+        let lines: Vec<&str> = text.lines().collect();
+        let first_y = y - (lines.len() as f64 - 1.0) * font_size * 1.25 / 2.0;
+
+        let _ = write!(
+            out,
+            "  <text x=\"{x:.1}\" y=\"{first_y:.1}\" text-anchor=\"middle\" dominant-baseline=\"middle\" font-family=\"sans-serif\" font-size=\"{font_size:.0}\" fill=\"{}\">",
+            attributes
+                .text_color
+                .as_deref()
+                .or(attributes.stroke_color.as_deref())
+                .unwrap_or(DEFAULT_STROKE)
+        );
+        for (i, line) in lines.iter().enumerate() {
+            let dy = if i == 0 { 0.0 } else { font_size * 1.25 };
+            let _ = write!(
+                out,
+                "<tspan x=\"{x:.1}\" dy=\"{dy:.1}\">{}</tspan>",
+                escape_xml(line)
+            );
+        }
+        out.push_str("</text>\n");
+    }
+
+    fn fill(attributes: &ExcalidrawAttributes) -> String {
+        let fill = attributes
+            .background_color
+            .as_deref()
+            .filter(|color| *color != "transparent")
+            .unwrap_or("none");
+        format!(" fill=\"{}\"", escape_xml(fill))
+    }
+
+    fn stroke(attributes: &ExcalidrawAttributes) -> String {
+        let mut style = format!(
+            " stroke=\"{}\" stroke-width=\"{:.1}\"",
+            escape_xml(attributes.stroke_color.as_deref().unwrap_or(DEFAULT_STROKE)),
+            attributes.stroke_width.unwrap_or(2.0)
+        );
+        match attributes.stroke_style {
+            Some(StrokeStyle::Dashed) => style.push_str(" stroke-dasharray=\"8 6\""),
+            Some(StrokeStyle::Dotted) => style.push_str(" stroke-dasharray=\"2 4\""),
+            _ => {}
+        }
+        style
+    }
+
+    fn arrowheads(arrow_type: &ArrowType, attributes: &ExcalidrawAttributes) -> (bool, bool) {
+        let (default_start, default_end) = match arrow_type {
+            ArrowType::Line => (false, false),
+            ArrowType::DoubleArrow => (true, true),
+            _ => (false, true),
+        };
+        let has_head = |head: &Option<ArrowheadType>, default: bool| match head {
+            Some(ArrowheadType::None) => false,
+            Some(_) => true,
+            None => default,
+        };
+        (
+            has_head(&attributes.start_arrowhead, default_start),
+            has_head(&attributes.end_arrowhead, default_end),
+        )
+    }
+}
+
+/// Where the line from a node's center toward `(x, y)` leaves its box
+fn boundary_point(node: &NodeData, x: f64, y: f64) -> (f64, f64) {
+    let (dx, dy) = (x - node.x, y - node.y);
+    if dx == 0.0 && dy == 0.0 {
+        return (node.x, node.y);
+    }
+    let scale_x = if dx == 0.0 {
+        f64::INFINITY
+    } else {
+        node.width / 2.0 / dx.abs()
+    };
+    let scale_y = if dy == 0.0 {
+        f64::INFINITY
+    } else {
+        node.height / 2.0 / dy.abs()
+    };
+    let scale = scale_x.min(scale_y).min(1.0);
+    (node.x + dx * scale, node.y + dy * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_svg() {
+        let input = r##"
+web[Web Server] { backgroundColor: "#a5d8ff"; }
+db[Database] { shape: ellipse; }
+web -> db: "reads & writes"
+"##;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let svg = SvgGenerator::generate(&igr).unwrap();
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 1);
+        assert_eq!(svg.matches("<ellipse ").count(), 1);
+        assert!(svg.contains("fill=\"#a5d8ff\""));
+        assert!(svg.contains(">Web Server</tspan>"));
+        assert!(svg.contains("marker-end=\"url(#arrow)\""));
+        assert!(svg.contains("reads &amp; writes"));
+    }
+
+    #[test]
+    fn test_edges_to_containers_end_at_the_container() {
+        let input = r#"
+user[User]
+container "Backend" as backend {
+  api[API]
+}
+user -> backend
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let svg = SvgGenerator::generate(&igr).unwrap();
+        let bounds = igr.containers[0].bounds.as_ref().unwrap();
+
+        let line = svg.lines().find(|l| l.contains("<line ")).unwrap();
+        let coord = |name: &str| -> f64 {
+            let start = line.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            let end = start + line[start..].find('"').unwrap();
+            line[start..end].parse().unwrap()
+        };
+        let (x2, y2) = (coord("x2"), coord("y2"));
+        let on_edge = |v: f64, lo: f64, hi: f64| (v - lo).abs() < 0.1 || (v - hi).abs() < 0.1;
+        assert!(
+            on_edge(x2, bounds.x, bounds.x + bounds.width)
+                || on_edge(y2, bounds.y, bounds.y + bounds.height),
+            "({x2}, {y2}) is not on {bounds:?}"
+        );
+    }
+}
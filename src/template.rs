@@ -126,6 +126,8 @@ impl TemplateProcessor {
                             arrow_type: ArrowType::SingleArrow,
                             attributes: HashMap::new(),
                             style: None,
+                            waypoints: Vec::new(),
+                            position: None,
                         };
                         edges.push(edge);
                     }
@@ -161,6 +163,8 @@ impl TemplateProcessor {
                     arrow_type: ArrowType::SingleArrow,
                     attributes: HashMap::new(),
                     style: None,
+                    waypoints: Vec::new(),
+                    position: None,
                 };
                 edges.push(edge);
             }
@@ -183,6 +187,8 @@ impl TemplateProcessor {
                         arrow_type: ArrowType::SingleArrow,
                         attributes: HashMap::new(),
                         style: None,
+                        waypoints: Vec::new(),
+                        position: None,
                     };
                     edges.push(edge);
                 }
@@ -207,6 +213,8 @@ impl TemplateProcessor {
                 arrow_type: ArrowType::SingleArrow,
                 attributes: HashMap::new(),
                 style: None,
+                waypoints: Vec::new(),
+                position: None,
             };
             edges.push(edge);
         }
@@ -14,12 +14,26 @@ fn test_parse_error_display() {
     };
     assert_eq!(err.to_string(), "Syntax error at line 42: Unexpected token");
 
-    let err = ParseError::InvalidConfig("Missing layout field".to_string());
+    let err = ParseError::InvalidConfig {
+        line: None,
+        key: None,
+        message: "Missing layout field".to_string(),
+    };
     assert_eq!(
         err.to_string(),
         "Invalid YAML configuration: Missing layout field"
     );
 
+    let err = ParseError::InvalidConfig {
+        line: Some(3),
+        key: Some("scale".to_string()),
+        message: "invalid type".to_string(),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Invalid YAML configuration at line 3 (`scale`): invalid type"
+    );
+
     let err = ParseError::ValidationError("Invalid node ID".to_string());
     assert_eq!(err.to_string(), "Validation error: Invalid node ID");
 }
@@ -65,8 +79,14 @@ fn test_layout_error_display() {
     let err = LayoutError::InvalidGraph;
     assert_eq!(err.to_string(), "Invalid graph structure for layout");
 
-    let err = LayoutError::Timeout;
-    assert_eq!(err.to_string(), "Layout timeout");
+    let err = LayoutError::Timeout {
+        engine: "force".to_string(),
+        elapsed: std::time::Duration::from_millis(1500),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Layout engine 'force' timed out after 1.5s"
+    );
 }
 
 #[test]
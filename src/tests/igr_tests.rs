@@ -17,6 +17,7 @@ fn create_test_document() -> ParsedDocument {
     ParsedDocument {
         config: GlobalConfig::default(),
         component_types: HashMap::new(),
+        edge_types: HashMap::new(),
         templates: HashMap::new(),
         diagram: None,
         nodes: vec![],
@@ -24,6 +25,9 @@ fn create_test_document() -> ParsedDocument {
         containers: vec![],
         groups: vec![],
         connections: vec![],
+        notes: vec![],
+        lines: vec![],
+        hyperedges: vec![],
     }
 }
 
@@ -49,6 +53,105 @@ fn test_igr_creation_empty() {
     assert!(igr.container_map.is_empty());
 }
 
+#[test]
+fn test_get_node_by_label() {
+    let mut doc = create_test_document();
+    doc.nodes.push(create_test_node("api", "API Gateway"));
+    doc.nodes.push(create_test_node("db", "Database"));
+
+    let igr = IntermediateGraph::from_ast(doc).unwrap();
+
+    let (idx, node) = igr.get_node_by_label("Database").unwrap();
+    assert_eq!(node.id, "db");
+    assert_eq!(idx, igr.node_map["db"]);
+    assert!(igr.get_node_by_label("Missing").is_none());
+}
+
+#[test]
+fn test_get_node_by_label_with_duplicates() {
+    let mut doc = create_test_document();
+    doc.nodes.push(create_test_node("worker1", "Worker"));
+    doc.nodes.push(create_test_node("worker2", "Worker"));
+    doc.nodes.push(create_test_node("queue", "Queue"));
+
+    let igr = IntermediateGraph::from_ast(doc).unwrap();
+
+    // The first node in insertion order wins
+    let (_, node) = igr.get_node_by_label("Worker").unwrap();
+    assert_eq!(node.id, "worker1");
+
+    // All matches are available through nodes_matching
+    let ids: Vec<_> = igr
+        .nodes_matching(|node| node.label == "Worker")
+        .map(|(_, node)| node.id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["worker1", "worker2"]);
+}
+
+#[test]
+fn test_extract_subgraph() {
+    let mut doc = create_test_document();
+    for id in ["a", "b", "c", "d", "e"] {
+        doc.nodes
+            .push(create_test_node(id, &format!("Node {}", id.to_uppercase())));
+    }
+    for (from, to) in [("a", "b"), ("b", "c"), ("a", "c"), ("c", "d"), ("d", "e")] {
+        doc.edges.push(EdgeDefinition {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: None,
+            arrow_type: ArrowType::SingleArrow,
+            attributes: HashMap::new(),
+            style: None,
+            waypoints: Vec::new(),
+            position: None,
+        });
+    }
+    doc.containers.push(ContainerDefinition {
+        id: Some("inside".to_string()),
+        label: Some("Inside".to_string()),
+        children: vec!["a".to_string(), "b".to_string()],
+        attributes: HashMap::new(),
+        internal_statements: vec![],
+    });
+    doc.containers.push(ContainerDefinition {
+        id: Some("partial".to_string()),
+        label: Some("Partial".to_string()),
+        children: vec!["c".to_string(), "d".to_string()],
+        attributes: HashMap::new(),
+        internal_statements: vec![],
+    });
+
+    let igr = IntermediateGraph::from_ast(doc).unwrap();
+    let subgraph = igr.extract_subgraph(&["a", "b", "c"]);
+
+    // Three selected nodes plus the virtual node of the fully-contained container
+    let regular_nodes: Vec<_> = subgraph
+        .nodes_matching(|node| !node.is_virtual_container)
+        .collect();
+    assert_eq!(regular_nodes.len(), 3);
+    assert_eq!(subgraph.graph.edge_count(), 3);
+
+    assert_eq!(subgraph.get_node_by_id("a").unwrap().1.label, "Node A");
+    assert_eq!(subgraph.get_node_by_id("c").unwrap().1.label, "Node C");
+    assert!(subgraph.get_node_by_id("d").is_none());
+
+    // Only the container whose children were all selected is kept
+    assert_eq!(subgraph.containers.len(), 1);
+    assert_eq!(subgraph.container_map.get("inside"), Some(&0));
+    assert!(!subgraph.container_map.contains_key("partial"));
+    for child in &subgraph.containers[0].children {
+        assert!(subgraph.graph.node_weight(*child).is_some());
+    }
+
+    // Naming the container in the selection copies its virtual node once
+    let subgraph = igr.extract_subgraph(&["a", "b", "inside"]);
+    assert_eq!(subgraph.graph.node_count(), 3);
+    let (inside, _) = subgraph.get_node_by_id("inside").unwrap();
+    assert_eq!(subgraph.node_map["inside"], inside);
+    assert!(subgraph.graph[inside].is_virtual_container);
+}
+
 #[test]
 fn test_igr_with_single_node() {
     let mut doc = create_test_document();
@@ -95,6 +198,8 @@ fn test_igr_with_edge() {
         arrow_type: ArrowType::SingleArrow,
         attributes: HashMap::new(),
         style: None,
+        waypoints: Vec::new(),
+        position: None,
     });
 
     let igr = IntermediateGraph::from_ast(doc).unwrap();
@@ -122,6 +227,8 @@ fn test_igr_edge_to_unknown_node() {
         arrow_type: ArrowType::SingleArrow,
         attributes: HashMap::new(),
         style: None,
+        waypoints: Vec::new(),
+        position: None,
     });
 
     let result = IntermediateGraph::from_ast(doc);
@@ -271,6 +378,7 @@ fn test_node_data_creation() {
         width: 150.0,
         height: 75.0,
         is_virtual_container: false,
+        metadata: Default::default(),
     };
 
     assert_eq!(node.id, "test");
@@ -295,6 +403,7 @@ fn test_edge_data_creation() {
         arrow_type: ArrowType::DoubleArrow,
         attributes: attrs,
         routing_type: Some(crate::ast::RoutingType::Curved),
+        waypoints: Vec::new(),
     };
 
     assert_eq!(edge.label, Some("Edge Label".to_string()));
@@ -329,6 +438,7 @@ fn test_container_data_creation() {
             width: 300.0,
             height: 200.0,
         }),
+        is_lane: false,
     };
 
     assert_eq!(container.id, Some("container1".to_string()));
@@ -385,6 +495,8 @@ fn test_virtual_container_nodes() {
         arrow_type: ArrowType::SingleArrow,
         attributes: HashMap::new(),
         style: None,
+        waypoints: Vec::new(),
+        position: None,
     });
     doc.nodes.push(create_test_node("node1", "Node 1"));
 
@@ -575,3 +687,95 @@ fn test_arrowhead_type_parsing() {
         assert_eq!(parsed, expected);
     }
 }
+
+#[test]
+fn test_group_edge_endpoint_gets_virtual_node() {
+    let input = r#"
+group "Team" as team {
+  a[A]
+  b[B]
+}
+group "Other" as other {
+  d[D]
+}
+c[C]
+c -> team
+"#;
+    let document = crate::parser::parse_edsl(input).unwrap();
+    let igr = IntermediateGraph::from_ast(document).unwrap();
+
+    let team = igr.node_map["team"];
+    assert!(igr.graph[team].is_virtual_container);
+    assert_eq!(igr.graph[team].label, "Team");
+    assert!(igr.graph.contains_edge(igr.node_map["c"], team));
+
+    // Groups that no edge references stay out of the graph
+    assert!(!igr.node_map.contains_key("other"));
+}
+
+#[test]
+fn test_hyperedge_creates_junction() {
+    let input = r#"
+a[A]
+b[B]
+c[C]
+d[D]
+bus [a, b, c, d]
+"#;
+    let document = crate::parser::parse_edsl(input).unwrap();
+    let igr = IntermediateGraph::from_ast(document).unwrap();
+
+    assert_eq!(igr.graph.node_count(), 5);
+    let junction = igr.node_map["_bus0"];
+    let node = &igr.graph[junction];
+    assert!(node.label.is_empty());
+    assert_eq!(node.attributes.shape.as_deref(), Some("ellipse"));
+    assert!(node.width < 20.0 && node.height < 20.0);
+
+    assert_eq!(igr.graph.edge_count(), 4);
+    for member in ["a", "b", "c", "d"] {
+        let edge = igr.graph.find_edge(igr.node_map[member], junction).unwrap();
+        assert_eq!(igr.graph[edge].arrow_type, ArrowType::Line);
+    }
+}
+
+#[test]
+fn test_node_width_clamps() {
+    let long_label = "a very long label that keeps going well past any sensible box width";
+    let input = format!(
+        r#"---
+min_node_width: 160
+max_node_width: 240
+---
+short[A]
+wide[{long_label}]
+narrow[{long_label}] {{ maxWidth: 180; }}
+roomy[B] {{ minWidth: 300; }}
+fixed[{long_label}] {{ width: 500; }}
+"#
+    );
+    let document = crate::parser::parse_edsl(&input).unwrap();
+    let igr = IntermediateGraph::from_ast(document).unwrap();
+    let node = |id: &str| &igr.graph[igr.node_map[id]];
+
+    // A one-character label is widened to the minimum
+    assert_eq!(node("short").width, 160.0);
+    assert_eq!(node("short").label, "A");
+    assert_eq!(node("roomy").width, 300.0);
+
+    // A long label is capped at the maximum and wraps onto more lines
+    let wide = node("wide");
+    assert_eq!(wide.width, 240.0);
+    assert!(wide.label.lines().count() > 2);
+    assert_eq!(wide.label.replace('\n', " "), long_label);
+    assert!(wide.height > node("short").height);
+    assert_eq!(node("narrow").width, 180.0);
+    assert!(node("narrow").label.lines().count() > wide.label.lines().count());
+
+    // Explicit widths are left alone
+    assert_eq!(node("fixed").width, 500.0);
+    assert_eq!(node("fixed").label, long_label);
+
+    let document = crate::parser::parse_edsl("a[A] { minWidth: 0; }").unwrap();
+    assert!(IntermediateGraph::from_ast(document).is_err());
+}
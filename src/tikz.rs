@@ -0,0 +1,346 @@
+// src/tikz.rs
+//! TikZ export for embedding diagrams in LaTeX documents
+//!
+//! Walks a laid-out `IntermediateGraph` and emits a `tikzpicture`. Pixel
+//! coordinates are scaled to centimetres and the y axis is flipped, since
+//! TikZ grows upwards. The output needs `\usetikzlibrary{shapes.geometric}`.
+
+use crate::ast::{ArrowType, ArrowheadType, StrokeStyle};
+use crate::error::{GeneratorError, Result};
+use crate::graphml::unique_names;
+use crate::igr::{BoundingBox, ExcalidrawAttributes, IntermediateGraph, NodeData};
+use petgraph::visit::EdgeRef;
+use std::fmt::Write;
+
+/// Centimetres per Excalidraw pixel
+const TIKZ_SCALE: f64 = 0.02;
+
+pub struct TikzGenerator;
+
+impl TikzGenerator {
+    /// Generate a `tikzpicture` environment for a laid-out graph
+    ///
+    /// # Examples
+    /// ```rust
+    /// use excalidraw_dsl::tikz::TikzGenerator;
+    ///
+    /// let igr = excalidraw_dsl::parse_and_layout("a[A]\nb[B]\na -> b", "dagre").unwrap();
+    /// let tex = TikzGenerator::generate(&igr).unwrap();
+    /// assert!(tex.starts_with("\\begin{tikzpicture}"));
+    /// ```
+    pub fn generate(igr: &IntermediateGraph) -> Result<String> {
+        let names = unique_names(
+            igr.graph.node_weights().map(|node| node.id.as_str()),
+            node_name,
+        );
+        let mut out = String::new();
+        out.push_str("\\begin{tikzpicture}\n");
+        out.push_str("  % requires \\usetikzlibrary{shapes.geometric}\n");
+
+        // Groups and containers first so they sit behind the nodes
+        for group in &igr.groups {
+            if let Some(ref bounds) = group.bounds {
+                Self::write_cluster(
+                    &mut out,
+                    bounds,
+                    group.label.as_deref(),
+                    &group.attributes,
+                    "dotted",
+                );
+            }
+        }
+        for container in &igr.containers {
+            if let Some(ref bounds) = container.bounds {
+                Self::write_cluster(
+                    &mut out,
+                    bounds,
+                    container.label.as_deref(),
+                    &container.attributes,
+                    "rounded corners",
+                );
+            }
+        }
+
+        for node in igr.graph.node_weights() {
+            Self::write_node(&mut out, node, &names[node.id.as_str()])?;
+        }
+
+        for edge in igr.graph.edge_references() {
+            let source = &igr.graph[edge.source()];
+            let target = &igr.graph[edge.target()];
+            let edge_data = edge.weight();
+
+            let mut options = vec![Self::arrow_option(
+                &edge_data.arrow_type,
+                &edge_data.attributes,
+            )];
+            // Arrow tokens like `..>` and `==>` imply a stroke when none is set
+            let mut attributes = edge_data.attributes.clone();
+            attributes.stroke_style = attributes
+                .stroke_style
+                .or_else(|| edge_data.arrow_type.default_stroke_style());
+            attributes.stroke_width = attributes.stroke_width.or_else(|| {
+                edge_data
+                    .arrow_type
+                    .edge_stroke_width(igr.global_config.edge_width)
+            });
+            options.extend(Self::stroke_options(&attributes));
+
+            let label = edge_data
+                .label
+                .as_deref()
+                .map(|label| {
+                    format!(
+                        " node[midway, above, sloped, font=\\small] {{{}}}",
+                        escape_latex(label)
+                    )
+                })
+                .unwrap_or_default();
+
+            let _ = writeln!(
+                out,
+                "  \\draw[{}] ({}) --{} ({});",
+                options.join(", "),
+                names[source.id.as_str()],
+                label,
+                names[target.id.as_str()],
+            );
+        }
+
+        out.push_str("\\end{tikzpicture}\n");
+        Ok(out)
+    }
+
+    fn write_node(out: &mut String, node: &NodeData, name: &str) -> Result<()> {
+        if !node.x.is_finite() || !node.y.is_finite() {
+            return Err(GeneratorError::InvalidCoordinate {
+                x: node.x,
+                y: node.y,
+            }
+            .into());
+        }
+
+        // A container or group edges attach to is an invisible node covering
+        // its rectangle, so they end at its border
+        if node.is_virtual_container {
+            let _ = writeln!(
+                out,
+                "  \\node[inner sep=0, minimum width={:.2}cm, minimum height={:.2}cm] ({}) at ({:.2}, {:.2}) {{}};",
+                node.width * TIKZ_SCALE,
+                node.height * TIKZ_SCALE,
+                name,
+                node.x * TIKZ_SCALE,
+                -node.y * TIKZ_SCALE,
+            );
+            return Ok(());
+        }
+
+        let mut options = Vec::new();
+        match node.attributes.shape.as_deref() {
+            Some("text") => {}
+            shape => {
+                options.push("draw".to_string());
+                options.push(Self::shape_option(shape).to_string());
+            }
+        }
+        options.push(format!("minimum width={:.2}cm", node.width * TIKZ_SCALE));
+        options.push(format!("minimum height={:.2}cm", node.height * TIKZ_SCALE));
+        options.push("align=center".to_string());
+        options.extend(Self::stroke_options(&node.attributes));
+
+        let _ = writeln!(
+            out,
+            "  \\node[{}] ({}) at ({:.2}, {:.2}) {{{}}};",
+            options.join(", "),
+            name,
+            node.x * TIKZ_SCALE,
+            -node.y * TIKZ_SCALE,
+            escape_latex(&node.label),
+        );
+        Ok(())
+    }
+
+    fn write_cluster(
+        out: &mut String,
+        bounds: &BoundingBox,
+        label: Option<&str>,
+        attributes: &ExcalidrawAttributes,
+        default_style: &str,
+    ) {
+        let (x1, y1) = (bounds.x * TIKZ_SCALE, -bounds.y * TIKZ_SCALE);
+        let (x2, y2) = (
+            (bounds.x + bounds.width) * TIKZ_SCALE,
+            -(bounds.y + bounds.height) * TIKZ_SCALE,
+        );
+
+        let mut options = vec![default_style.to_string()];
+        options.extend(Self::stroke_options(attributes));
+
+        let _ = writeln!(
+            out,
+            "  \\draw[{}] ({x1:.2}, {y1:.2}) rectangle ({x2:.2}, {y2:.2});",
+            options.join(", ")
+        );
+        if let Some(label) = label {
+            let _ = writeln!(
+                out,
+                "  \\node[anchor=north west, font=\\small] at ({x1:.2}, {y1:.2}) {{{}}};",
+                escape_latex(label)
+            );
+        }
+    }
+
+    fn shape_option(shape: Option<&str>) -> &'static str {
+        match shape {
+            Some("ellipse") => "ellipse",
+            Some("diamond") => "diamond",
+            Some("cylinder") => "cylinder, shape border rotate=90",
+            Some("triangle") => "regular polygon, regular polygon sides=3",
+            Some("hexagon") => "regular polygon, regular polygon sides=6",
+            Some("parallelogram") => {
+                "trapezium, trapezium left angle=70, trapezium right angle=110"
+            }
+            _ => "rectangle",
+        }
+    }
+
+    fn arrow_option(arrow_type: &ArrowType, attributes: &ExcalidrawAttributes) -> String {
+        let (default_start, default_end) = match arrow_type {
+            ArrowType::SingleArrow
+            | ArrowType::WavyArrow
+            | ArrowType::DashedArrow
+            | ArrowType::DottedArrow
+            | ArrowType::ThickArrow => (false, true),
+            ArrowType::Line => (false, false),
+            ArrowType::DoubleArrow => (true, true),
+        };
+        let has_head = |head: &Option<ArrowheadType>, default: bool| match head {
+            Some(ArrowheadType::None) => false,
+            Some(_) => true,
+            None => default,
+        };
+
+        let start = if has_head(&attributes.start_arrowhead, default_start) {
+            "<"
+        } else {
+            ""
+        };
+        let end = if has_head(&attributes.end_arrowhead, default_end) {
+            ">"
+        } else {
+            ""
+        };
+        format!("{start}-{end}")
+    }
+
+    fn stroke_options(attributes: &ExcalidrawAttributes) -> Vec<String> {
+        let mut options = Vec::new();
+        match attributes.stroke_style {
+            Some(StrokeStyle::Dashed) => options.push("dashed".to_string()),
+            Some(StrokeStyle::Dotted) => options.push("dotted".to_string()),
+            _ => {}
+        }
+        if let Some(width) = attributes.stroke_width {
+            options.push(format!("line width={:.1}pt", width * 0.4));
+        }
+        options
+    }
+}
+
+/// TikZ node names may not contain `.` or spaces
+fn node_name(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\n' => escaped.push_str("\\\\"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_tikz_picture() {
+        let input = r#"
+web[Web Server]
+db[Database] { shape: cylinder; }
+cache[Cache]
+web -> db: queries
+web -- cache { strokeStyle: dashed; }
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let tex = TikzGenerator::generate(&igr).unwrap();
+
+        assert!(tex.starts_with("\\begin{tikzpicture}"));
+        assert!(tex.trim_end().ends_with("\\end{tikzpicture}"));
+        assert_eq!(tex.matches("\\node[draw").count(), igr.graph.node_count());
+        assert!(tex.contains("(web) at"));
+        assert!(tex.contains("cylinder"));
+        assert!(tex.contains(
+            "\\draw[->] (web) -- node[midway, above, sloped, font=\\small] {queries} (db);"
+        ));
+        assert!(tex.contains("\\draw[-, dashed] (web) -- (cache);"));
+    }
+
+    #[test]
+    fn test_tikz_containers_and_escaping() {
+        let input = r#"
+web[Web]
+container "R&D" as be {
+  svc.api[API_v2]
+}
+web -> be
+"#;
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let tex = TikzGenerator::generate(&igr).unwrap();
+
+        assert!(tex.contains("rectangle"));
+        assert!(tex.contains("{R\\&D}"));
+        assert!(tex.contains("(svc-api)"));
+        assert!(tex.contains("{API\\_v2}"));
+
+        // Edges to a container end at an invisible node over its rectangle
+        let bounds = igr.containers[0].bounds.as_ref().unwrap();
+        assert!(tex.contains(&format!(
+            "\\node[inner sep=0, minimum width={:.2}cm, minimum height={:.2}cm] (be) at",
+            bounds.width * TIKZ_SCALE,
+            bounds.height * TIKZ_SCALE
+        )));
+        assert!(tex.contains("(web) -- (be);"));
+    }
+
+    #[test]
+    fn test_tikz_node_names_stay_unique() {
+        // `.` and the middle dot both become `-`
+        let input = "svc.api[Dotted]\nsvc·api[Middle]\nsvc.api -> svc·api\n";
+        let igr = crate::parse_and_layout(input, "dagre").unwrap();
+        let tex = TikzGenerator::generate(&igr).unwrap();
+
+        assert!(tex.contains("(svc-api) at"));
+        assert!(tex.contains("(svc-api_2) at"));
+        assert!(tex.contains("(svc-api) -- (svc-api_2);"));
+    }
+}
@@ -17,7 +17,7 @@ pub struct TrainingConfig {
     pub evaluation_config: EvaluationConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TrainingPhase {
     DataGeneration,
     GNNTraining,
@@ -53,7 +53,7 @@ impl TrainingDataGenerator {
     }
 
     pub fn generate_datasets(&mut self, output_dir: &Path) -> Result<DatasetStats> {
-        println!("📊 Generating training datasets...");
+        log::info!("Generating training datasets");
 
         // Create output directories
         let data_dir = output_dir.join("data");
@@ -69,8 +69,8 @@ impl TrainingDataGenerator {
                 let samples_per_type_size = self.config.num_samples
                     / (self.config.graph_types.len() * self.config.graph_sizes.len());
 
-                println!(
-                    "  Generating {graph_type:?} graphs with {size} nodes ({samples_per_type_size} samples)"
+                log::debug!(
+                    "Generating {graph_type:?} graphs with {size} nodes ({samples_per_type_size} samples)"
                 );
 
                 for i in 0..samples_per_type_size {
@@ -92,8 +92,8 @@ impl TrainingDataGenerator {
 
         // Apply data augmentation
         if self.config.augmentation_factor > 1 {
-            println!(
-                "  Applying data augmentation (factor: {})",
+            log::debug!(
+                "Applying data augmentation (factor: {})",
                 self.config.augmentation_factor
             );
             all_samples = self.augment_data(all_samples)?;
@@ -120,10 +120,12 @@ impl TrainingDataGenerator {
         let stats_content = serde_json::to_string_pretty(&stats)?;
         std::fs::write(data_dir.join("stats.json"), stats_content)?;
 
-        println!("✅ Dataset generation completed:");
-        println!("  Training samples: {}", stats.training_samples);
-        println!("  Validation samples: {}", stats.validation_samples);
-        println!("  Test samples: {}", stats.test_samples);
+        log::info!(
+            "Dataset generation completed: {} training, {} validation, {} test samples",
+            stats.training_samples,
+            stats.validation_samples,
+            stats.test_samples
+        );
 
         Ok(stats)
     }
@@ -86,6 +86,7 @@ impl GNNTrainer {
                     std::fs::create_dir_all(parent)?;
                 }
                 model.save(model_save_path)?;
+                model.export_onnx(&model_save_path.with_extension("onnx"))?;
 
                 self.logger
                     .debug(&format!("New best model saved with loss: {best_loss:.6}"));
@@ -17,7 +17,7 @@ pub use data::{DatasetStats, TrainingDataGenerator};
 pub use enhanced::EnhancedTrainer;
 pub use gnn::GNNTrainer;
 pub use rl::RLTrainer;
-pub use utils::{MetricsCollector, TrainingLogger};
+pub use utils::{LogEntry, MetricsCollector, TrainingLogger};
 
 use crate::error::Result;
 use std::path::Path;
@@ -43,7 +43,7 @@ impl TrainingOrchestrator {
 
     /// Run complete training pipeline
     pub fn run_training(&mut self) -> Result<()> {
-        self.logger.info("🚀 Starting ML Layout Training Pipeline");
+        self.logger.info("Starting ML layout training pipeline");
 
         // Phase 1: Data Generation
         if matches!(
@@ -93,15 +93,17 @@ impl TrainingOrchestrator {
             self.run_evaluation()?;
         }
 
-        self.logger
-            .info("✅ Training pipeline completed successfully");
+        self.logger.info("Training pipeline completed");
         self.save_final_metrics()?;
 
         Ok(())
     }
 
     pub fn run_data_generation(&mut self) -> Result<()> {
-        self.logger.info("📊 Phase 1: Generating Training Data");
+        self.logger.phase(
+            TrainingPhase::DataGeneration,
+            "Phase 1: generating training data",
+        );
 
         let mut generator = TrainingDataGenerator::new(&self.config.data_config)?;
         let stats = generator.generate_datasets(&self.config.output_dir)?;
@@ -116,7 +118,8 @@ impl TrainingOrchestrator {
     }
 
     pub fn run_gnn_training(&mut self) -> Result<()> {
-        self.logger.info("🧠 Phase 2: Training GNN Model");
+        self.logger
+            .phase(TrainingPhase::GNNTraining, "Phase 2: training GNN model");
 
         let mut trainer = GNNTrainer::new(&self.config.gnn_config)?;
         let model_path = self.config.output_dir.join("models/gnn_model.bin");
@@ -133,7 +136,8 @@ impl TrainingOrchestrator {
     }
 
     pub fn run_rl_training(&mut self) -> Result<()> {
-        self.logger.info("🎮 Phase 3: Training RL Model");
+        self.logger
+            .phase(TrainingPhase::RLTraining, "Phase 3: training RL model");
 
         let mut trainer = RLTrainer::new(&self.config.rl_config)?;
         let model_path = self.config.output_dir.join("models/rl_model.bin");
@@ -150,7 +154,10 @@ impl TrainingOrchestrator {
     }
 
     pub fn run_constraint_training(&mut self) -> Result<()> {
-        self.logger.info("🧩 Phase 4: Training Constraint Solver");
+        self.logger.phase(
+            TrainingPhase::ConstraintTraining,
+            "Phase 4: training constraint solver",
+        );
 
         let mut trainer = ConstraintTrainer::new(&self.config.constraint_config)?;
         let model_path = self.config.output_dir.join("models/constraint_model.bin");
@@ -167,7 +174,10 @@ impl TrainingOrchestrator {
     }
 
     pub fn run_enhanced_training(&mut self) -> Result<()> {
-        self.logger.info("🔄 Phase 5: Training Enhanced Model");
+        self.logger.phase(
+            TrainingPhase::EnhancedTraining,
+            "Phase 5: training enhanced model",
+        );
 
         let mut trainer = EnhancedTrainer::new(&self.config.enhanced_config)?;
         let model_path = self.config.output_dir.join("models/enhanced_model.bin");
@@ -184,7 +194,8 @@ impl TrainingOrchestrator {
     }
 
     pub fn run_evaluation(&mut self) -> Result<()> {
-        self.logger.info("📊 Phase 6: Model Evaluation");
+        self.logger
+            .phase(TrainingPhase::Evaluation, "Phase 6: evaluating models");
 
         // Load and evaluate all trained models
         let gnn_path = self.config.output_dir.join("models/gnn_model.bin");
@@ -230,4 +241,38 @@ impl TrainingOrchestrator {
     pub fn get_metrics(&self) -> Option<&MetricsCollector> {
         Some(&self.metrics)
     }
+
+    /// Progress logged so far
+    pub fn logger(&self) -> &TrainingLogger {
+        &self.logger
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logger_records_one_entry_per_phase() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = TrainingConfig::quick_test();
+        config.output_dir = dir.path().to_path_buf();
+        config.data_config.num_samples = 0;
+
+        let mut orchestrator = TrainingOrchestrator::new(config).unwrap();
+        orchestrator.run_data_generation().unwrap();
+        orchestrator.run_data_generation().unwrap();
+
+        let phases: Vec<TrainingPhase> = orchestrator
+            .logger()
+            .entries()
+            .iter()
+            .filter_map(|entry| entry.phase)
+            .collect();
+        assert_eq!(
+            phases,
+            [TrainingPhase::DataGeneration, TrainingPhase::DataGeneration]
+        );
+        assert!(dir.path().join("training.log").exists());
+    }
 }
@@ -1,7 +1,7 @@
 // src/training/utils.rs
 //! Training utilities and helpers
 
-use crate::training::config::EvaluationConfig;
+use crate::training::config::{EvaluationConfig, TrainingPhase};
 use crate::training::data::TrainingSample;
 use crate::training::{
     constraints::ConstraintTrainingResults, enhanced::EnhancedTrainingResults,
@@ -12,6 +12,7 @@ use crate::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Mutex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsCollector {
@@ -49,10 +50,23 @@ pub struct QualityMetrics {
     pub overall_quality: f64,
 }
 
+/// A message recorded by a `TrainingLogger`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub level: log::Level,
+    /// Set for the entry that opens a training phase
+    pub phase: Option<TrainingPhase>,
+    pub message: String,
+}
+
+/// Routes training progress through the `log` crate, under the
+/// `edsl::training` target so `RUST_LOG` controls verbosity, and keeps every
+/// entry for the log file and for inspection
 pub struct TrainingLogger {
     prefix: String,
     log_to_file: bool,
     log_file_path: Option<std::path::PathBuf>,
+    entries: Mutex<Vec<LogEntry>>,
 }
 
 impl TrainingLogger {
@@ -67,6 +81,7 @@ impl TrainingLogger {
             prefix: "TRAINING".to_string(),
             log_to_file: true,
             log_file_path: Some(log_file_path),
+            entries: Mutex::new(Vec::new()),
         })
     }
 
@@ -75,36 +90,49 @@ impl TrainingLogger {
             prefix: prefix.to_uppercase(),
             log_to_file: false,
             log_file_path: None,
+            entries: Mutex::new(Vec::new()),
         }
     }
 
+    /// Announce the start of a training phase
+    pub fn phase(&self, phase: TrainingPhase, message: &str) {
+        self.record(log::Level::Info, Some(phase), message);
+    }
+
     pub fn info(&self, message: &str) {
-        let formatted = self.format_message("INFO", message);
-        println!("{formatted}");
-        self.write_to_file(&formatted);
+        self.record(log::Level::Info, None, message);
     }
 
     pub fn debug(&self, message: &str) {
-        let formatted = self.format_message("DEBUG", message);
-        if std::env::var("RUST_LOG")
-            .unwrap_or_default()
-            .contains("debug")
-        {
-            println!("{formatted}");
-        }
-        self.write_to_file(&formatted);
+        self.record(log::Level::Debug, None, message);
     }
 
     pub fn warn(&self, message: &str) {
-        let formatted = self.format_message("WARN", message);
-        eprintln!("{formatted}");
-        self.write_to_file(&formatted);
+        self.record(log::Level::Warn, None, message);
     }
 
     pub fn error(&self, message: &str) {
-        let formatted = self.format_message("ERROR", message);
-        eprintln!("{formatted}");
-        self.write_to_file(&formatted);
+        self.record(log::Level::Error, None, message);
+    }
+
+    /// Everything logged so far, oldest first
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, level: log::Level, phase: Option<TrainingPhase>, message: &str) {
+        log::log!(target: "edsl::training", level, "[{}] {}", self.prefix, message);
+        self.write_to_file(&self.format_message(level.as_str(), message));
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(LogEntry {
+                level,
+                phase,
+                message: message.to_string(),
+            });
+        }
     }
 
     fn format_message(&self, level: &str, message: &str) -> String {
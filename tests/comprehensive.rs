@@ -38,8 +38,8 @@ node1 -> node2: Connection
     assert!(parsed["elements"].is_array());
 
     let elements = parsed["elements"].as_array().unwrap();
-    // Should have 2 nodes + 2 text elements + 1 edge = 5 elements
-    assert_eq!(elements.len(), 5);
+    // Should have 2 nodes + 3 text elements + 1 edge = 6 elements
+    assert_eq!(elements.len(), 6);
 
     // Check that we have the right element types
     let element_types: Vec<&str> = elements
@@ -50,7 +50,7 @@ node1 -> node2: Connection
     assert!(element_types.contains(&"rectangle"));
     assert!(element_types.contains(&"ellipse"));
     assert!(element_types.contains(&"arrow"));
-    assert!(element_types.iter().filter(|&&t| t == "text").count() == 2);
+    assert!(element_types.iter().filter(|&&t| t == "text").count() == 3);
 }
 
 #[test]
@@ -131,7 +131,11 @@ a -> b: Test Label
     // Find the arrow element
     let arrow_element = elements.iter().find(|e| e["type"] == "arrow").unwrap();
 
-    assert_eq!(arrow_element["text"], "Test Label");
+    let label_element = elements
+        .iter()
+        .find(|e| e["type"] == "text" && e["text"] == "Test Label")
+        .unwrap();
+    assert_eq!(label_element["containerId"], arrow_element["id"]);
     assert!(arrow_element["startBinding"].is_object());
     assert!(arrow_element["endBinding"].is_object());
     assert_eq!(arrow_element["endArrowhead"], "arrow");
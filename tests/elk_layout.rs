@@ -41,8 +41,8 @@ process2 -> end
     let json = result.unwrap();
     let elements = json["elements"].as_array().unwrap();
 
-    // 5 nodes + 5 text elements + 5 edges = 15 elements
-    assert_eq!(elements.len(), 15);
+    // 5 nodes + 5 text elements + 2 edge labels + 5 edges = 17 elements
+    assert_eq!(elements.len(), 17);
 
     // Verify layered layout properties
     let nodes: Vec<&Value> = elements
@@ -146,3 +146,85 @@ a -> b
     let points = edges[0]["points"].as_array().unwrap();
     assert!(points.len() >= 2); // At least start and end points
 }
+
+#[test]
+fn test_global_routing_config() {
+    let body = r#"
+a[Node A]
+b[Node B]
+c[Node C]
+
+a -> b
+a -> c
+"#;
+
+    let point_counts = |json: &Value| -> Vec<usize> {
+        json["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["type"] == "arrow")
+            .map(|e| e["points"].as_array().unwrap().len())
+            .collect()
+    };
+
+    let straight = compile_to_json(body).unwrap();
+    assert!(point_counts(&straight).iter().all(|&count| count == 2));
+
+    let orthogonal_edsl = format!("---\nrouting: orthogonal\n---\n{body}");
+    let orthogonal = compile_to_json(&orthogonal_edsl).unwrap();
+    let counts = point_counts(&orthogonal);
+    assert_eq!(counts.len(), 2);
+    assert!(counts.iter().any(|&count| count > 2));
+}
+
+#[test]
+fn test_edge_routing_overrides_global_config() {
+    let edsl = r#"---
+routing: orthogonal
+---
+a[Node A]
+b[Node B]
+c[Node C]
+
+a -> b { routing: straight; }
+a -> c { routing: straight; }
+"#;
+
+    let json = compile_to_json(edsl).unwrap();
+    let elements = json["elements"].as_array().unwrap();
+    for edge in elements.iter().filter(|e| e["type"] == "arrow") {
+        assert_eq!(edge["points"].as_array().unwrap().len(), 2);
+    }
+}
+
+#[test]
+fn test_connection_style_rendered_on_edge() {
+    let edsl = r##"
+a[Node A]
+b[Node B]
+
+connection {
+    from: "a";
+    to: "b";
+    style {
+        type: dashed;
+        color: "#ff0000";
+        width: 3;
+    }
+}
+"##;
+
+    let json = compile_to_json(edsl).unwrap();
+    let elements = json["elements"].as_array().unwrap();
+
+    let edges: Vec<&Value> = elements.iter().filter(|e| e["type"] == "arrow").collect();
+    assert_eq!(edges.len(), 1);
+
+    let edge = edges[0];
+    assert_eq!(edge["strokeStyle"], "dashed");
+    assert_eq!(edge["strokeColor"], "#ff0000");
+    assert_eq!(edge["strokeWidth"], 3);
+    // No label was given, so the color must not be used as one
+    assert!(edge["text"].is_null() || edge["text"] == "");
+}
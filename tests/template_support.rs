@@ -87,3 +87,43 @@ template test {
 
     assert!(result.is_ok(), "Template validation should succeed");
 }
+
+#[test]
+#[cfg(feature = "templates")]
+fn test_mesh_expansion_trips_element_cap() {
+    let edsl = r#"
+---
+layout: force
+---
+
+template everything {
+  layers {
+    "Cluster" {
+      components: ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"]
+    }
+  }
+
+  connections {
+    pattern: mesh
+  }
+}
+
+diagram "Mesh" {
+  type: architecture
+  template: everything
+}
+    "#;
+
+    // 10 nodes + 10 labels + 90 mesh edges
+    let mut compiler = EDSLCompiler::builder().with_max_elements(50).build();
+    let err = compiler.compile(edsl).unwrap_err();
+    match err {
+        excalidraw_dsl::EDSLError::Validation { message } => {
+            assert!(message.contains("limit of 50"), "{message}");
+        }
+        other => panic!("Expected validation error, got {other:?}"),
+    }
+
+    let mut compiler = EDSLCompiler::builder().with_max_elements(500).build();
+    assert!(compiler.compile(edsl).is_ok());
+}
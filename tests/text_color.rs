@@ -258,13 +258,13 @@ node1 -> node2: "Edge Label";
         .expect("Should find Node 2 text");
     assert_eq!(node2_text["strokeColor"], "#00ff00");
 
-    // Edge labels should remain black (arrows with text don't use the node color)
-    let edge = elements
+    // Edge labels should remain black (they don't use the node color)
+    let edge_label = elements
         .iter()
-        .find(|e| e["type"] == "arrow" && e["text"] == "Edge Label")
-        .expect("Should find edge with label");
+        .find(|e| e["type"] == "text" && e["text"] == "Edge Label")
+        .expect("Should find edge label");
     assert_eq!(
-        edge["strokeColor"], "#000000",
+        edge_label["strokeColor"], "#000000",
         "Edge text should remain black"
     );
 }